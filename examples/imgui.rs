@@ -1,4 +1,6 @@
 use bugsyth_engine::prelude::*;
+use bugsyth_engine_imgui_support::logs::LogLevel;
+use bugsyth_engine_imgui_support::widgets::{debug_overlay, drag_vec3, DebugOverlay};
 
 #[derive(Clone, Copy)]
 struct Vertex {
@@ -40,6 +42,7 @@ fn main() -> EngineResult {
     )
     .unwrap();
     let imgui = bugsyth_engine_imgui_support::init(&ctx.window, &ctx.display, |_, _, _| {});
+    imgui.proxy().log(LogLevel::Info, "Example started");
     let game = Game {
         tri: Triangle {
             vbo: VertexBuffer::new(
@@ -67,6 +70,7 @@ fn main() -> EngineResult {
         },
         pos: Vec3::zero(),
         imgui,
+        overlay: DebugOverlay::new(),
     };
     run(game, event_loop, ctx)?;
     Ok(())
@@ -76,6 +80,7 @@ struct Game {
     tri: Triangle<'static>,
     pos: Vec3<f32>,
     imgui: bugsyth_engine_imgui_support::ImGui,
+    overlay: DebugOverlay,
 }
 
 impl GameState for Game {
@@ -98,34 +103,28 @@ impl GameState for Game {
             )
             .unwrap();
 
+        self.overlay
+            .set_frame_stats(self.imgui.renderer.last_frame_stats());
+        let logs_open = self.imgui.inbox.logs_open();
+        let mut show_logs = logs_open;
         self.imgui
-            .platform
-            .prepare_frame(self.imgui.context.io_mut(), &ctx.window)
-            .unwrap();
-        let ui = self.imgui.context.frame();
-
-        ui.window("imgui")
-            .size(
-                [250.0, 150.0],
-                bugsyth_engine_imgui_support::Condition::FirstUseEver,
-            )
-            .build(|| {
-                ui.text_wrapped("Position:");
-
-                ui.slider("x", -1.0, 1.0, &mut self.pos.x);
-                ui.slider("y", -1.0, 1.0, &mut self.pos.y);
-                ui.slider("z", -1.0, 1.0, &mut self.pos.z);
-
-                ui.separator();
-                ui.text(format!("FPS: {}", (1.0 / ctx.dt).floor()));
-            });
-
-        self.imgui.platform.prepare_render(&ui, &ctx.window);
-        let draw_data = self.imgui.context.render();
-        self.imgui
-            .renderer
-            .render(renderer.get_surface_mut(), draw_data)
+            .draw(ctx, renderer, |ui| {
+                ui.window("imgui")
+                    .size(
+                        [250.0, 150.0],
+                        bugsyth_engine_imgui_support::Condition::FirstUseEver,
+                    )
+                    .build(|| {
+                        drag_vec3(ui, "Position", &mut self.pos);
+                        ui.checkbox("Logs", &mut show_logs);
+                    });
+                debug_overlay(ui, &mut self.overlay, ctx);
+            })
             .unwrap();
+        // The inbox draws the log window when the next frame starts
+        if show_logs != logs_open {
+            self.imgui.inbox.set_logs_open(show_logs);
+        }
     }
     fn event(&mut self, ctx: &mut Context, event: &WindowEvent) {
         self.imgui.event(&ctx.window, event);
@@ -139,16 +138,16 @@ struct Triangle<'a> {
 }
 
 impl<'a> Drawable for Triangle<'a> {
-    fn get_vbo(&self) -> impl MultiVerticesSource {
+    fn get_vbo(&self) -> impl MultiVerticesSource<'_> {
         &self.vbo
     }
-    fn get_ibo(&self) -> impl Into<IndicesSource> {
+    fn get_ibo(&self) -> impl Into<IndicesSource<'_>> {
         &self.ibo
     }
     fn get_program(&self) -> String {
         "3d".to_string()
     }
-    fn get_draw_params(&self) -> DrawParameters {
+    fn get_draw_params(&self) -> DrawParameters<'_> {
         self.draw_params.clone()
     }
 }
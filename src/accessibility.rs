@@ -0,0 +1,429 @@
+//! Accessibility presets and flags.
+//!
+//! [`LowVision`] bundles the changes that help players with low vision: a bigger font, a high
+//! contrast theme, thicker borders, opaque backgrounds and bigger hit targets. Apply it with
+//! [`ImGui::set_low_vision`](crate::ImGui::set_low_vision), which also keeps it in the
+//! [`user_session`](crate::ImGui::user_session) so it's back on the next launch. It's a
+//! per-user setting, switching projects with
+//! [`ImGui::set_settings_scope`](crate::ImGui::set_settings_scope) keeps it.
+//!
+//! [`reduced_motion`] is a global flag for players sensitive to motion, the widgets of this
+//! crate skip their animations while it's set. They all go through [`fade_out`] and
+//! [`flashing`], which custom widgets can use too. Set it with
+//! [`ImGui::set_reduced_motion`](crate::ImGui::set_reduced_motion) to also stop the text cursor
+//! from blinking and keep it in the user session.
+//!
+//! [`FocusRing`] makes the item focused with the keyboard or gamepad easy to spot, see
+//! [`FocusOrder`](crate::debug::FocusOrder) for checking the order focus moves in.
+//!
+//! [`InputTimings`] changes how fast double clicks, key repeat and hover tooltips are, for
+//! players who need more time and for tools whose defaults feel wrong. Set it with
+//! [`ImGui::set_input_timings`](crate::ImGui::set_input_timings) to keep it in the user session.
+
+use imgui::{Io, Style, StyleColor, Ui};
+use std::fmt;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+/// User session key the [`LowVision`] preset is saved under
+pub const LOW_VISION: &str = "accessibility/low_vision";
+/// User session key the [`reduced_motion`] flag is saved under
+pub const REDUCED_MOTION: &str = "accessibility/reduced_motion";
+/// User session key the [`InputTimings`] are saved under
+pub const INPUT_TIMINGS: &str = "accessibility/input_timings";
+
+static REDUCED_MOTION_ON: AtomicBool = AtomicBool::new(false);
+
+/// Whether animations, fades, smooth scrolling and slide-ins should be skipped. Custom widgets
+/// should check this too and jump straight to their end state.
+pub fn reduced_motion() -> bool {
+    REDUCED_MOTION_ON.load(Ordering::Relaxed)
+}
+
+/// Sets the flag without touching imgui or the session, see
+/// [`ImGui::set_reduced_motion`](crate::ImGui::set_reduced_motion)
+pub fn set_reduced_motion(on: bool) {
+    REDUCED_MOTION_ON.store(on, Ordering::Relaxed);
+}
+
+/// Opacity of something fading out over the last `fade` of its `remaining` time, e.g. a toast.
+/// Always `1.0` with [reduced motion](reduced_motion), it disappears at once instead.
+pub fn fade_out(remaining: Duration, fade: Duration) -> f32 {
+    if reduced_motion() || remaining >= fade {
+        1.0
+    } else {
+        remaining.as_secs_f32() / fade.as_secs_f32()
+    }
+}
+
+/// Whether a short flash started `elapsed` ago is still showing, e.g. a pressed key. Never
+/// with [reduced motion](reduced_motion).
+pub fn flashing(elapsed: Duration, duration: Duration) -> bool {
+    !reduced_motion() && elapsed < duration
+}
+
+/// Visible indicator around the item focused with the keyboard or gamepad.
+///
+/// [`apply`](Self::apply) colors imgui's own navigation highlight, which is only 2 pixels wide.
+/// For a thicker ring call [`draw_last_item`](Self::draw_last_item) after the items that need it.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct FocusRing {
+    pub color: [f32; 4],
+    pub thickness: f32,
+    /// Gap between the item and the ring
+    pub padding: f32,
+}
+
+impl Default for FocusRing {
+    fn default() -> Self {
+        Self {
+            color: [1.0, 0.9, 0.0, 1.0],
+            thickness: 3.0,
+            padding: 2.0,
+        }
+    }
+}
+
+impl FocusRing {
+    pub fn apply(&self, style: &mut Style) {
+        style[StyleColor::NavHighlight] = self.color;
+    }
+    /// Draws the ring around the last item if it has focus
+    pub fn draw_last_item(&self, ui: &Ui) {
+        if !ui.is_item_focused() {
+            return;
+        }
+        let (min, max) = (ui.item_rect_min(), ui.item_rect_max());
+        let offset = self.padding + self.thickness * 0.5;
+        ui.get_window_draw_list()
+            .add_rect(
+                [min[0] - offset, min[1] - offset],
+                [max[0] + offset, max[1] + offset],
+                self.color,
+            )
+            .rounding(ui.clone_style().frame_rounding + offset)
+            .thickness(self.thickness)
+            .build();
+    }
+}
+
+/// Low-vision preset, see the [module docs](self)
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct LowVision {
+    /// Font size multiplier, the atlas is rebuilt at the bigger size so text stays sharp
+    pub font_scale: f32,
+    /// Multiplier on top of [`ImGui::scale_style`](crate::ImGui::scale_style), makes padding,
+    /// spacing and so hit targets bigger
+    pub style_scale: f32,
+    /// Minimum border width of windows, popups and frames at scale 1
+    pub border_size: f32,
+    /// Replaces the colors with white on black with yellow highlights
+    pub high_contrast: bool,
+}
+
+impl Default for LowVision {
+    fn default() -> Self {
+        Self {
+            font_scale: 1.5,
+            style_scale: 1.25,
+            border_size: 2.0,
+            high_contrast: true,
+        }
+    }
+}
+
+impl LowVision {
+    /// Applies the borders, colors and opacity to a style that's already scaled by `scale`
+    pub fn apply(&self, style: &mut Style, scale: f32) {
+        let border = (self.border_size * scale).round().max(1.0);
+        for size in [
+            &mut style.window_border_size,
+            &mut style.child_border_size,
+            &mut style.popup_border_size,
+            &mut style.frame_border_size,
+            &mut style.tab_border_size,
+        ] {
+            *size = size.max(border);
+        }
+        style.alpha = 1.0;
+        style.disabled_alpha = style.disabled_alpha.max(0.75);
+        if self.high_contrast {
+            high_contrast_colors(style);
+        }
+        // Backgrounds see-through to the game make text hard to read
+        for color in [
+            StyleColor::WindowBg,
+            StyleColor::ChildBg,
+            StyleColor::PopupBg,
+            StyleColor::FrameBg,
+            StyleColor::TitleBg,
+            StyleColor::TitleBgCollapsed,
+            StyleColor::MenuBarBg,
+            StyleColor::ScrollbarBg,
+            StyleColor::TableHeaderBg,
+        ] {
+            style[color][3] = 1.0;
+        }
+    }
+}
+
+fn high_contrast_colors(style: &mut Style) {
+    const BLACK: [f32; 4] = [0.0, 0.0, 0.0, 1.0];
+    const WHITE: [f32; 4] = [1.0, 1.0, 1.0, 1.0];
+    const GREY: [f32; 4] = [0.2, 0.2, 0.2, 1.0];
+    const YELLOW: [f32; 4] = [1.0, 0.9, 0.0, 1.0];
+    const DARK_YELLOW: [f32; 4] = [0.45, 0.4, 0.0, 1.0];
+    const BLUE: [f32; 4] = [0.0, 0.3, 0.8, 1.0];
+    for (color, value) in [
+        (StyleColor::Text, WHITE),
+        (StyleColor::TextDisabled, [0.75, 0.75, 0.75, 1.0]),
+        (StyleColor::WindowBg, BLACK),
+        (StyleColor::ChildBg, BLACK),
+        (StyleColor::PopupBg, BLACK),
+        (StyleColor::Border, WHITE),
+        (StyleColor::BorderShadow, [0.0, 0.0, 0.0, 0.0]),
+        (StyleColor::FrameBg, GREY),
+        (StyleColor::FrameBgHovered, DARK_YELLOW),
+        (StyleColor::FrameBgActive, BLUE),
+        (StyleColor::TitleBg, GREY),
+        (StyleColor::TitleBgActive, BLUE),
+        (StyleColor::TitleBgCollapsed, GREY),
+        (StyleColor::MenuBarBg, GREY),
+        (StyleColor::ScrollbarBg, BLACK),
+        (StyleColor::ScrollbarGrab, WHITE),
+        (StyleColor::ScrollbarGrabHovered, YELLOW),
+        (StyleColor::ScrollbarGrabActive, YELLOW),
+        (StyleColor::CheckMark, YELLOW),
+        (StyleColor::SliderGrab, WHITE),
+        (StyleColor::SliderGrabActive, YELLOW),
+        (StyleColor::Button, GREY),
+        (StyleColor::ButtonHovered, DARK_YELLOW),
+        (StyleColor::ButtonActive, BLUE),
+        (StyleColor::Header, GREY),
+        (StyleColor::HeaderHovered, DARK_YELLOW),
+        (StyleColor::HeaderActive, BLUE),
+        (StyleColor::Separator, WHITE),
+        (StyleColor::SeparatorHovered, YELLOW),
+        (StyleColor::SeparatorActive, YELLOW),
+        (StyleColor::ResizeGrip, WHITE),
+        (StyleColor::ResizeGripHovered, YELLOW),
+        (StyleColor::ResizeGripActive, YELLOW),
+        (StyleColor::Tab, GREY),
+        (StyleColor::TabHovered, DARK_YELLOW),
+        (StyleColor::TabActive, BLUE),
+        (StyleColor::TabUnfocused, GREY),
+        (StyleColor::TabUnfocusedActive, BLUE),
+        (StyleColor::PlotLines, WHITE),
+        (StyleColor::PlotLinesHovered, YELLOW),
+        (StyleColor::PlotHistogram, WHITE),
+        (StyleColor::PlotHistogramHovered, YELLOW),
+        (StyleColor::TableHeaderBg, GREY),
+        (StyleColor::TableBorderStrong, WHITE),
+        (StyleColor::TableBorderLight, [0.6, 0.6, 0.6, 1.0]),
+        (StyleColor::TextSelectedBg, [0.0, 0.3, 0.8, 0.7]),
+        (StyleColor::DragDropTarget, YELLOW),
+        (StyleColor::NavHighlight, YELLOW),
+    ] {
+        style[color] = value;
+    }
+}
+
+/// `font_scale style_scale border_size high_contrast`, as kept in the session
+impl fmt::Display for LowVision {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} {} {} {}",
+            self.font_scale, self.style_scale, self.border_size, self.high_contrast
+        )
+    }
+}
+
+impl FromStr for LowVision {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parts: Vec<&str> = s.split_whitespace().collect();
+        let [font_scale, style_scale, border_size, high_contrast] = parts[..] else {
+            return Err(format!("expected 4 values, got {:?}", s));
+        };
+        let number = |value: &str| -> Result<f32, String> {
+            value
+                .parse::<f32>()
+                .ok()
+                .filter(|value| value.is_finite() && *value > 0.0)
+                .ok_or_else(|| format!("invalid value {:?}", value))
+        };
+        Ok(Self {
+            font_scale: number(font_scale)?,
+            style_scale: number(style_scale)?,
+            border_size: number(border_size)?,
+            high_contrast: high_contrast
+                .parse()
+                .map_err(|_| format!("invalid value {:?}", high_contrast))?,
+        })
+    }
+}
+
+/// Timing of double clicks, key repeat and hover delays, in seconds and pixels. The default
+/// is imgui's.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct InputTimings {
+    /// Longest time between the clicks of a double click
+    pub double_click_time: f32,
+    /// Furthest the mouse may move between the clicks of a double click
+    pub double_click_distance: f32,
+    /// Time a key is held before it repeats
+    pub key_repeat_delay: f32,
+    /// Time between repeats
+    pub key_repeat_rate: f32,
+    /// Hover time before items asking for the normal delay react, e.g. tooltips
+    pub hover_delay: f32,
+    /// Hover time for items asking for the short delay
+    pub hover_delay_short: f32,
+}
+
+impl Default for InputTimings {
+    fn default() -> Self {
+        Self {
+            double_click_time: 0.3,
+            double_click_distance: 6.0,
+            key_repeat_delay: 0.275,
+            key_repeat_rate: 0.05,
+            hover_delay: 0.3,
+            hover_delay_short: 0.1,
+        }
+    }
+}
+
+impl InputTimings {
+    /// Doubles every delay and the double click distance, for players who need more time
+    pub fn relaxed() -> Self {
+        let default = Self::default();
+        Self {
+            double_click_time: default.double_click_time * 2.0,
+            double_click_distance: default.double_click_distance * 2.0,
+            key_repeat_delay: default.key_repeat_delay * 2.0,
+            key_repeat_rate: default.key_repeat_rate * 2.0,
+            hover_delay: default.hover_delay * 2.0,
+            hover_delay_short: default.hover_delay_short * 2.0,
+        }
+    }
+    pub fn from_io(io: &Io) -> Self {
+        Self {
+            double_click_time: io.mouse_double_click_time,
+            double_click_distance: io.mouse_double_click_max_dist,
+            key_repeat_delay: io.key_repeat_delay,
+            key_repeat_rate: io.key_repeat_rate,
+            hover_delay: io.hover_delay_normal,
+            hover_delay_short: io.hover_delay_short,
+        }
+    }
+    pub fn apply(&self, io: &mut Io) {
+        io.mouse_double_click_time = self.double_click_time;
+        io.mouse_double_click_max_dist = self.double_click_distance;
+        io.key_repeat_delay = self.key_repeat_delay;
+        io.key_repeat_rate = self.key_repeat_rate;
+        io.hover_delay_normal = self.hover_delay;
+        io.hover_delay_short = self.hover_delay_short;
+    }
+
+    /// Sliders for a settings panel, returns `true` when a value changed
+    pub fn draw(&mut self, ui: &Ui) -> bool {
+        let mut changed = false;
+        changed |= ui
+            .slider_config("Double click time", 0.1, 1.5)
+            .display_format("%.2f s")
+            .build(&mut self.double_click_time);
+        changed |= ui
+            .slider_config("Double click distance", 1.0, 30.0)
+            .display_format("%.0f px")
+            .build(&mut self.double_click_distance);
+        changed |= ui
+            .slider_config("Key repeat delay", 0.1, 1.5)
+            .display_format("%.2f s")
+            .build(&mut self.key_repeat_delay);
+        changed |= ui
+            .slider_config("Key repeat rate", 0.01, 0.5)
+            .display_format("%.3f s")
+            .build(&mut self.key_repeat_rate);
+        changed |= ui
+            .slider_config("Hover delay", 0.0, 2.0)
+            .display_format("%.2f s")
+            .build(&mut self.hover_delay);
+        changed |= ui
+            .slider_config("Short hover delay", 0.0, 1.0)
+            .display_format("%.2f s")
+            .build(&mut self.hover_delay_short);
+        if ui.button("Reset to defaults") {
+            *self = Self::default();
+            changed = true;
+        }
+        changed
+    }
+}
+
+/// The fields in declaration order separated by spaces, as kept in the session
+impl fmt::Display for InputTimings {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} {} {} {} {} {}",
+            self.double_click_time,
+            self.double_click_distance,
+            self.key_repeat_delay,
+            self.key_repeat_rate,
+            self.hover_delay,
+            self.hover_delay_short
+        )
+    }
+}
+
+impl FromStr for InputTimings {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let values = s
+            .split_whitespace()
+            .map(|value| {
+                value
+                    .parse::<f32>()
+                    .ok()
+                    .filter(|value| value.is_finite() && *value >= 0.0)
+                    .ok_or_else(|| format!("invalid value {:?}", value))
+            })
+            .collect::<Result<Vec<f32>, String>>()?;
+        let [double_click_time, double_click_distance, key_repeat_delay, key_repeat_rate, hover_delay, hover_delay_short] =
+            values[..]
+        else {
+            return Err(format!("expected 6 values, got {:?}", s));
+        };
+        Ok(Self {
+            double_click_time,
+            double_click_distance,
+            key_repeat_delay,
+            key_repeat_rate,
+            hover_delay,
+            hover_delay_short,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reduced_motion_skips_animations() {
+        let fade = Duration::from_millis(500);
+        let flash = Duration::from_millis(120);
+        assert_eq!(fade_out(Duration::from_secs(2), fade), 1.0);
+        assert_eq!(fade_out(Duration::from_millis(250), fade), 0.5);
+        assert!(flashing(Duration::from_millis(60), flash));
+        set_reduced_motion(true);
+        assert_eq!(fade_out(Duration::from_millis(250), fade), 1.0);
+        assert!(!flashing(Duration::from_millis(60), flash));
+        set_reduced_motion(false);
+    }
+}
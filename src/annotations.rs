@@ -0,0 +1,120 @@
+//! Help text attached to widgets without touching the panel drawing code.
+//!
+//! Help is registered per widget ID (usually loaded from a file), panels only call
+//! [`Annotations::annotate`] after the widget. While help mode is on, annotated widgets get a "?"
+//! marker and show their help as a tooltip when hovered.
+
+use crate::{widgets::markdown, Shortcut};
+use imgui::{Key, StyleColor, Ui};
+use std::collections::HashMap;
+
+const MARKER_RADIUS: f32 = 6.0;
+
+/// Registry of help text per widget ID
+#[derive(Debug)]
+pub struct Annotations {
+    help: HashMap<String, String>,
+    help_mode: bool,
+    /// Toggles help mode when pressed, F1 by default
+    pub toggle_shortcut: Option<Shortcut>,
+}
+
+impl Default for Annotations {
+    fn default() -> Self {
+        Self {
+            help: HashMap::new(),
+            help_mode: false,
+            toggle_shortcut: Some(Shortcut::new(Key::F1)),
+        }
+    }
+}
+
+impl Annotations {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Registers markdown help text for the widget `id`, e.g. "Settings/Apply"
+    pub fn register(&mut self, id: impl Into<String>, help: impl Into<String>) {
+        self.help.insert(id.into(), help.into());
+    }
+    pub fn unregister(&mut self, id: &str) {
+        self.help.remove(id);
+    }
+    pub fn help(&self, id: &str) -> Option<&str> {
+        self.help.get(id).map(String::as_str)
+    }
+    /// Registers every section of a help file.
+    ///
+    /// Sections start with a `[widget id]` line, everything until the next section is the help
+    /// text for that widget.
+    pub fn load_str(&mut self, text: &str) {
+        let mut current: Option<(String, String)> = None;
+        for line in text.lines() {
+            let header = line
+                .trim()
+                .strip_prefix('[')
+                .and_then(|line| line.strip_suffix(']'));
+            match (header, current.as_mut()) {
+                (Some(id), _) => {
+                    if let Some((id, help)) = current.take() {
+                        self.register(id, help.trim());
+                    }
+                    current = Some((id.to_string(), String::new()));
+                }
+                (None, Some((_, help))) => {
+                    help.push_str(line);
+                    help.push('\n');
+                }
+                (None, None) => (),
+            }
+        }
+        if let Some((id, help)) = current {
+            self.register(id, help.trim());
+        }
+    }
+
+    pub fn help_mode(&self) -> bool {
+        self.help_mode
+    }
+    pub fn set_help_mode(&mut self, help_mode: bool) {
+        self.help_mode = help_mode;
+    }
+    /// Toggles help mode if the toggle shortcut was pressed, call once per frame
+    pub fn handle_shortcut(&mut self, ui: &Ui) {
+        if self.toggle_shortcut.is_some_and(|s| s.is_pressed(ui)) {
+            self.help_mode = !self.help_mode;
+        }
+    }
+
+    /// Marks the last drawn widget as `id`.
+    ///
+    /// Does nothing outside of help mode or when no help is registered for `id`.
+    pub fn annotate(&self, ui: &Ui, id: &str) {
+        if !self.help_mode {
+            return;
+        }
+        let Some(help) = self.help.get(id) else {
+            return;
+        };
+        let min = ui.item_rect_min();
+        let max = ui.item_rect_max();
+        let center = [max[0] - MARKER_RADIUS, min[1] + MARKER_RADIUS];
+        let draw_list = ui.get_foreground_draw_list();
+        draw_list
+            .add_circle(center, MARKER_RADIUS, ui.style_color(StyleColor::CheckMark))
+            .filled(true)
+            .build();
+        let size = ui.calc_text_size("?");
+        draw_list.add_text(
+            [center[0] - size[0] / 2.0, center[1] - size[1] / 2.0],
+            ui.style_color(StyleColor::WindowBg),
+            "?",
+        );
+        if ui.is_item_hovered() {
+            ui.tooltip(|| {
+                let _wrap = ui.push_text_wrap_pos_with_pos(ui.current_font_size() * 30.0);
+                markdown(ui, help);
+            });
+        }
+    }
+}
@@ -0,0 +1,552 @@
+//! One-click bug reporter for games and tools.
+//!
+//! [`BugReport`] is a dialog where the user describes the problem. The report is packed into a zip
+//! with a screenshot of the frame before the dialog opened, the recent log, the GL driver info and
+//! the UI renderer stats. It's written to a directory or, with the `bug-report-http` feature,
+//! POSTed to an HTTP endpoint. Other transports implement [`ReportUploader`]. The dialog sends
+//! on a background thread, so a slow upload doesn't freeze the game.
+
+use crate::capture::{self, CaptureRegion, CapturedImage};
+use crate::{FrameStats, Renderer};
+use bugsyth_engine::glium::backend::Facade;
+use imgui::{Condition, Ui};
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Lines of log kept in a report
+const MAX_LOG_LINES: usize = 2000;
+
+#[derive(Debug)]
+pub enum BugReportError {
+    Io(io::Error),
+    Png(png::EncodingError),
+    Upload(String),
+}
+
+impl Error for BugReportError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        use self::BugReportError::*;
+        match *self {
+            Io(ref e) => Some(e),
+            Png(ref e) => Some(e),
+            Upload(_) => None,
+        }
+    }
+}
+
+impl fmt::Display for BugReportError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use self::BugReportError::*;
+        match *self {
+            Io(ref e) => write!(f, "Writing the report failed: {}", e),
+            Png(ref e) => write!(f, "Encoding the screenshot failed: {}", e),
+            Upload(ref e) => write!(f, "Uploading the report failed: {}", e),
+        }
+    }
+}
+
+impl From<io::Error> for BugReportError {
+    fn from(e: io::Error) -> BugReportError {
+        BugReportError::Io(e)
+    }
+}
+
+impl From<png::EncodingError> for BugReportError {
+    fn from(e: png::EncodingError) -> BugReportError {
+        BugReportError::Png(e)
+    }
+}
+
+/// Sends a finished report somewhere, e.g. to an issue tracker. Runs on a background thread when
+/// sent from the dialog.
+pub trait ReportUploader: Send {
+    /// `zip` is the whole report, `file_name` a suggested name for it
+    fn upload(&mut self, file_name: &str, zip: &[u8]) -> Result<(), String>;
+}
+
+/// Where submitted reports go
+pub enum ReportDestination {
+    /// Written as `<dir>/bug-report-<unix time>.zip`
+    Directory(PathBuf),
+    Upload(Box<dyn ReportUploader>),
+}
+
+impl fmt::Debug for ReportDestination {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReportDestination::Directory(dir) => f.debug_tuple("Directory").field(dir).finish(),
+            ReportDestination::Upload(_) => f.write_str("Upload"),
+        }
+    }
+}
+
+/// Everything that goes into a report
+#[derive(Clone, Debug, Default)]
+pub struct BugReportData {
+    pub description: String,
+    pub screenshot: Option<CapturedImage>,
+    pub log: Vec<String>,
+    /// Name and value pairs, e.g. the GL renderer string or the game version
+    pub system_info: Vec<(String, String)>,
+    pub renderer_stats: Option<FrameStats>,
+}
+
+impl BugReportData {
+    /// Packs the report into a zip with `description.txt`, `system.txt`, `log.txt` and
+    /// `screenshot.png`
+    pub fn to_zip(&self) -> Result<Vec<u8>, BugReportError> {
+        let mut zip = ZipWriter::default();
+        zip.add("description.txt", self.description.as_bytes());
+        let mut system = String::new();
+        for (name, value) in &self.system_info {
+            system.push_str(&format!("{}: {}\n", name, value));
+        }
+        if let Some(stats) = &self.renderer_stats {
+            system.push_str(&format!(
+                "UI target: {}x{}\nUI draw lists: {}\nUI draw calls: {}\nUI vertices: {}\nUI indices: {}\n",
+                stats.target_dimensions.0,
+                stats.target_dimensions.1,
+                stats.draw_lists,
+                stats.draw_calls,
+                stats.vertices,
+                stats.indices
+            ));
+        }
+        zip.add("system.txt", system.as_bytes());
+        if !self.log.is_empty() {
+            zip.add("log.txt", self.log.join("\n").as_bytes());
+        }
+        if let Some(image) = &self.screenshot {
+            let mut png_data = Vec::new();
+            let mut encoder = png::Encoder::new(&mut png_data, image.width, image.height);
+            encoder.set_color(png::ColorType::Rgba);
+            encoder.set_depth(png::BitDepth::Eight);
+            let mut writer = encoder.write_header()?;
+            writer.write_image_data(&image.pixels)?;
+            writer.finish()?;
+            zip.add("screenshot.png", &png_data);
+        }
+        Ok(zip.finish())
+    }
+}
+
+/// GL driver strings of `facade`, for [`BugReportData::system_info`]
+pub fn gl_info<F: Facade>(facade: &F) -> Vec<(String, String)> {
+    let ctx = facade.get_context();
+    vec![
+        (
+            "GL version".to_string(),
+            ctx.get_opengl_version_string().to_string(),
+        ),
+        (
+            "GL vendor".to_string(),
+            ctx.get_opengl_vendor_string().to_string(),
+        ),
+        (
+            "GL renderer".to_string(),
+            ctx.get_opengl_renderer_string().to_string(),
+        ),
+        ("OS".to_string(), std::env::consts::OS.to_string()),
+        ("Arch".to_string(), std::env::consts::ARCH.to_string()),
+    ]
+}
+
+/// [`ReportDestination`] shared with the thread sending a report
+#[derive(Clone)]
+enum Target {
+    Directory(PathBuf),
+    Upload(Arc<Mutex<Box<dyn ReportUploader>>>),
+}
+
+impl fmt::Debug for Target {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Target::Directory(dir) => f.debug_tuple("Directory").field(dir).finish(),
+            Target::Upload(_) => f.write_str("Upload"),
+        }
+    }
+}
+
+impl Target {
+    /// Packs and writes or uploads the report, returns the message for the user
+    fn deliver(&self, data: &BugReportData) -> Result<String, BugReportError> {
+        let zip = data.to_zip()?;
+        let time = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |time| time.as_secs());
+        let file_name = format!("bug-report-{}.zip", time);
+        match self {
+            Target::Directory(dir) => {
+                fs::create_dir_all(dir)?;
+                let path = dir.join(&file_name);
+                fs::write(&path, zip)?;
+                Ok(format!("Saved to {}", path.display()))
+            }
+            Target::Upload(uploader) => {
+                uploader
+                    .lock()
+                    .unwrap_or_else(|e| e.into_inner())
+                    .upload(&file_name, &zip)
+                    .map_err(BugReportError::Upload)?;
+                Ok("Report sent, thank you!".to_string())
+            }
+        }
+    }
+}
+
+/// Bug report dialog
+#[derive(Debug)]
+pub struct BugReport {
+    target: Target,
+    data: BugReportData,
+    include_screenshot: bool,
+    include_log: bool,
+    opened: bool,
+    /// Result of the last submission
+    status: Option<Result<String, String>>,
+    /// Report being sent by the background thread
+    pending: Option<Receiver<Result<String, String>>>,
+}
+
+impl BugReport {
+    pub fn new(destination: ReportDestination) -> Self {
+        let target = match destination {
+            ReportDestination::Directory(dir) => Target::Directory(dir),
+            ReportDestination::Upload(uploader) => Target::Upload(Arc::new(Mutex::new(uploader))),
+        };
+        Self {
+            target,
+            data: BugReportData::default(),
+            include_screenshot: true,
+            include_log: true,
+            opened: false,
+            status: None,
+            pending: None,
+        }
+    }
+    /// Extra system info added to every report, e.g. the game version
+    pub fn add_system_info(&mut self, name: impl Into<String>, value: impl Into<String>) {
+        self.data.system_info.push((name.into(), value.into()));
+    }
+    /// Recent log lines, oldest first. Only the last lines are kept.
+    pub fn set_log<I, S>(&mut self, lines: I)
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.data.log = lines.into_iter().map(Into::into).collect();
+        let excess = self.data.log.len().saturating_sub(MAX_LOG_LINES);
+        self.data.log.drain(..excess);
+    }
+    pub fn is_open(&self) -> bool {
+        self.opened
+    }
+    /// Whether a report sent from the dialog is still being written or uploaded
+    pub fn is_sending(&self) -> bool {
+        self.pending.is_some()
+    }
+
+    /// Opens the dialog, taking the screenshot and gathering the GL info and renderer stats.
+    ///
+    /// Call before drawing anything of the frame, so the screenshot shows the last presented
+    /// frame without the dialog.
+    pub fn open<F: Facade>(&mut self, facade: &F, renderer: &Renderer) {
+        let full = CaptureRegion {
+            x: 0,
+            y: 0,
+            width: u32::MAX,
+            height: u32::MAX,
+        };
+        self.data.screenshot = capture::read_region(facade, full).ok();
+        self.data
+            .system_info
+            .retain(|(name, _)| !name.starts_with("GL ") && name != "OS" && name != "Arch");
+        self.data.system_info.extend(gl_info(facade));
+        self.data.renderer_stats = Some(renderer.last_frame_stats());
+        self.status = None;
+        self.opened = true;
+    }
+
+    pub fn draw(&mut self, ui: &Ui) {
+        self.poll();
+        if !self.opened {
+            return;
+        }
+        let mut opened = self.opened;
+        ui.window("Report a bug")
+            .opened(&mut opened)
+            .size([420.0, 320.0], Condition::FirstUseEver)
+            .build(|| {
+                ui.text("What happened?");
+                ui.input_text_multiline("##description", &mut self.data.description, [-1.0, 150.0])
+                    .build();
+                let has_screenshot = self.data.screenshot.is_some();
+                {
+                    let _disabled = ui.begin_disabled(!has_screenshot);
+                    ui.checkbox("Include screenshot", &mut self.include_screenshot);
+                }
+                {
+                    let _disabled = ui.begin_disabled(self.data.log.is_empty());
+                    ui.checkbox(
+                        format!("Include log ({} lines)", self.data.log.len()),
+                        &mut self.include_log,
+                    );
+                }
+                {
+                    let _disabled = ui.begin_disabled(
+                        self.data.description.trim().is_empty() || self.is_sending(),
+                    );
+                    if ui.button("Send") {
+                        self.send();
+                    }
+                }
+                match &self.status {
+                    _ if self.is_sending() => ui.text_disabled("Sending..."),
+                    Some(Ok(message)) => ui.text_colored([0.4, 1.0, 0.4, 1.0], message),
+                    Some(Err(message)) => ui.text_colored([1.0, 0.35, 0.35, 1.0], message),
+                    None => (),
+                }
+            });
+        self.opened = opened;
+    }
+
+    /// Packs and sends the report, the description is cleared on success. Blocks until the
+    /// report is written or uploaded, the dialog's Send button uses a background thread instead.
+    pub fn submit(&mut self) -> Result<String, BugReportError> {
+        let message = self.target.deliver(&self.report_data())?;
+        self.data.description.clear();
+        Ok(message)
+    }
+    /// Same as [`submit`](Self::submit) on a background thread, the result is picked up by
+    /// [`draw`](Self::draw)
+    fn send(&mut self) {
+        let data = self.report_data();
+        let target = self.target.clone();
+        let (sender, receiver) = mpsc::channel();
+        std::thread::spawn(move || {
+            let _ = sender.send(target.deliver(&data).map_err(|e| e.to_string()));
+        });
+        self.status = None;
+        self.pending = Some(receiver);
+    }
+    fn poll(&mut self) {
+        let Some(receiver) = &self.pending else {
+            return;
+        };
+        match receiver.try_recv() {
+            Ok(result) => {
+                if result.is_ok() {
+                    self.data.description.clear();
+                }
+                self.status = Some(result);
+                self.pending = None;
+            }
+            Err(TryRecvError::Disconnected) => {
+                self.status = Some(Err("Sending the report failed".to_string()));
+                self.pending = None;
+            }
+            Err(TryRecvError::Empty) => (),
+        }
+    }
+    /// The data with the parts the user left out removed
+    fn report_data(&self) -> BugReportData {
+        let mut data = self.data.clone();
+        if !self.include_screenshot {
+            data.screenshot = None;
+        }
+        if !self.include_log {
+            data.log.clear();
+        }
+        data
+    }
+}
+
+/// POSTs reports as `application/zip` to an `http://` or `https://` URL
+#[cfg(feature = "bug-report-http")]
+#[derive(Clone, Debug)]
+pub struct HttpUploader {
+    pub url: String,
+}
+
+#[cfg(feature = "bug-report-http")]
+impl ReportUploader for HttpUploader {
+    fn upload(&mut self, file_name: &str, zip: &[u8]) -> Result<(), String> {
+        let disposition = format!("attachment; filename=\"{}\"", file_name);
+        crate::http::request(
+            "POST",
+            &self.url,
+            &[
+                ("Content-Type", "application/zip"),
+                ("Content-Disposition", &disposition),
+            ],
+            zip,
+        )
+        .map(|_| ())
+    }
+}
+
+/// Minimal zip writer storing entries uncompressed
+#[derive(Default)]
+struct ZipWriter {
+    data: Vec<u8>,
+    central: Vec<u8>,
+    entries: u16,
+}
+
+impl ZipWriter {
+    fn add(&mut self, name: &str, contents: &[u8]) {
+        let crc = crc32fast::hash(contents);
+        let offset = self.data.len() as u32;
+        let size = contents.len() as u32;
+        // Local file header
+        self.data.extend_from_slice(&0x04034b50u32.to_le_bytes());
+        self.data.extend_from_slice(&20u16.to_le_bytes()); // version needed
+        self.data.extend_from_slice(&0u16.to_le_bytes()); // flags
+        self.data.extend_from_slice(&0u16.to_le_bytes()); // stored
+        self.data.extend_from_slice(&0u16.to_le_bytes()); // time
+        self.data.extend_from_slice(&0x21u16.to_le_bytes()); // date, 1980-01-01
+        self.data.extend_from_slice(&crc.to_le_bytes());
+        self.data.extend_from_slice(&size.to_le_bytes());
+        self.data.extend_from_slice(&size.to_le_bytes());
+        self.data
+            .extend_from_slice(&(name.len() as u16).to_le_bytes());
+        self.data.extend_from_slice(&0u16.to_le_bytes()); // extra length
+        self.data.extend_from_slice(name.as_bytes());
+        self.data.extend_from_slice(contents);
+        // Central directory entry
+        self.central.extend_from_slice(&0x02014b50u32.to_le_bytes());
+        self.central.extend_from_slice(&20u16.to_le_bytes()); // version made by
+        self.central.extend_from_slice(&20u16.to_le_bytes()); // version needed
+        self.central.extend_from_slice(&0u16.to_le_bytes()); // flags
+        self.central.extend_from_slice(&0u16.to_le_bytes()); // stored
+        self.central.extend_from_slice(&0u16.to_le_bytes()); // time
+        self.central.extend_from_slice(&0x21u16.to_le_bytes()); // date
+        self.central.extend_from_slice(&crc.to_le_bytes());
+        self.central.extend_from_slice(&size.to_le_bytes());
+        self.central.extend_from_slice(&size.to_le_bytes());
+        self.central
+            .extend_from_slice(&(name.len() as u16).to_le_bytes());
+        self.central.extend_from_slice(&[0; 12]); // extra, comment, disk, attributes
+        self.central.extend_from_slice(&offset.to_le_bytes());
+        self.central.extend_from_slice(name.as_bytes());
+        self.entries += 1;
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        let central_offset = self.data.len() as u32;
+        let central_size = self.central.len() as u32;
+        self.data.append(&mut self.central);
+        self.data.extend_from_slice(&0x06054b50u32.to_le_bytes());
+        self.data.extend_from_slice(&[0; 4]); // disk numbers
+        self.data.extend_from_slice(&self.entries.to_le_bytes());
+        self.data.extend_from_slice(&self.entries.to_le_bytes());
+        self.data.extend_from_slice(&central_size.to_le_bytes());
+        self.data.extend_from_slice(&central_offset.to_le_bytes());
+        self.data.extend_from_slice(&0u16.to_le_bytes()); // comment length
+        self.data
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn u16_at(bytes: &[u8], offset: usize) -> usize {
+        u16::from_le_bytes([bytes[offset], bytes[offset + 1]]) as usize
+    }
+    fn u32_at(bytes: &[u8], offset: usize) -> u32 {
+        u32::from_le_bytes(bytes[offset..offset + 4].try_into().unwrap())
+    }
+
+    /// Names and contents of the entries, read through the central directory the way unzip
+    /// tools do
+    fn read_zip(zip: &[u8]) -> Vec<(String, Vec<u8>)> {
+        let end = zip.len() - 22;
+        assert_eq!(u32_at(zip, end), 0x06054b50);
+        let entries = u16_at(zip, end + 10);
+        let mut central = u32_at(zip, end + 16) as usize;
+        assert_eq!(central + u32_at(zip, end + 12) as usize, end);
+        let mut files = Vec::new();
+        for _ in 0..entries {
+            assert_eq!(u32_at(zip, central), 0x02014b50);
+            let crc = u32_at(zip, central + 16);
+            let size = u32_at(zip, central + 24) as usize;
+            let name_len = u16_at(zip, central + 28);
+            let name = &zip[central + 46..central + 46 + name_len];
+            let local = u32_at(zip, central + 42) as usize;
+            assert_eq!(u32_at(zip, local), 0x04034b50);
+            assert_eq!(u32_at(zip, local + 14), crc);
+            assert_eq!(&zip[local + 30..local + 30 + name_len], name);
+            let start = local + 30 + name_len + u16_at(zip, local + 28);
+            let contents = zip[start..start + size].to_vec();
+            assert_eq!(crc32fast::hash(&contents), crc);
+            files.push((String::from_utf8(name.to_vec()).unwrap(), contents));
+            central += 46 + name_len;
+        }
+        files
+    }
+
+    #[test]
+    fn zip_entries_read_back() {
+        let files = [
+            ("report.txt", b"Crashed while saving\n".to_vec()),
+            ("logs/empty.log", Vec::new()),
+            ("screenshot.png", (0..=255).collect()),
+        ];
+        let mut zip = ZipWriter::default();
+        for (name, contents) in &files {
+            zip.add(name, contents);
+        }
+        let read = read_zip(&zip.finish());
+        assert_eq!(read.len(), files.len());
+        for ((name, contents), (read_name, read_contents)) in files.iter().zip(&read) {
+            assert_eq!(name, read_name);
+            assert_eq!(contents, read_contents);
+        }
+    }
+
+    #[test]
+    fn empty_zip_is_just_the_end_record() {
+        let zip = ZipWriter::default().finish();
+        assert_eq!(zip.len(), 22);
+        assert!(read_zip(&zip).is_empty());
+    }
+
+    /// Uploader that waits for the test before finishing
+    struct Gated(Receiver<()>);
+
+    impl ReportUploader for Gated {
+        fn upload(&mut self, _: &str, zip: &[u8]) -> Result<(), String> {
+            assert!(!zip.is_empty());
+            self.0.recv().map_err(|e| e.to_string())
+        }
+    }
+
+    #[test]
+    fn sending_doesnt_block() {
+        let (release, gate) = mpsc::channel();
+        let mut report = BugReport::new(ReportDestination::Upload(Box::new(Gated(gate))));
+        report.data.description = "Crashed".to_string();
+        report.send();
+        report.poll();
+        assert!(report.is_sending());
+        assert_eq!(report.data.description, "Crashed");
+
+        release.send(()).unwrap();
+        while report.is_sending() {
+            std::thread::yield_now();
+            report.poll();
+        }
+        assert_eq!(
+            report.status,
+            Some(Ok("Report sent, thank you!".to_string()))
+        );
+        assert!(report.data.description.is_empty());
+    }
+}
@@ -0,0 +1,244 @@
+//! Replaying UI geometry from earlier frames instead of rebuilding it.
+//!
+//! Heavy panels (big tables, plots of long histories) can cost more to build than the rest of the
+//! UI together. The helpers here record the geometry a closure added to the window draw list and
+//! draw that again on frames where the closure is skipped. Skipped frames have no items, so
+//! nothing inside can be interacted with until the next rebuild. Child windows have their own
+//! draw lists and aren't recorded, cache the content inside them instead.
+
+use imgui::{sys, Ui};
+use std::slice;
+
+/// One draw command worth of recorded geometry
+#[derive(Clone, Debug)]
+struct CachedCmd {
+    clip_rect: [f32; 4],
+    texture_id: sys::ImTextureID,
+    vertices: Vec<sys::ImDrawVert>,
+    /// Relative to `vertices`
+    indices: Vec<sys::ImDrawIdx>,
+}
+
+/// Draw list segment recorded from a closure
+#[derive(Clone, Debug, Default)]
+struct DrawCache {
+    cmds: Vec<CachedCmd>,
+    /// Window position when recorded, replays are moved along with the window
+    window_pos: [f32; 2],
+    /// Layout space the content took
+    size: [f32; 2],
+}
+
+impl DrawCache {
+    /// Runs `f` and records what it drew into the current window
+    fn record<R>(&mut self, ui: &Ui, f: impl FnOnce() -> R) -> R {
+        let start_cursor = ui.cursor_pos();
+        let width = ui.content_region_avail()[0];
+        // SAFETY: the window draw list is valid while `ui` is in a window
+        let (idx_start, cmd_start) = unsafe {
+            let draw_list = &*sys::igGetWindowDrawList();
+            (
+                draw_list.IdxBuffer.Size as u32,
+                (draw_list.CmdBuffer.Size - 1).max(0) as usize,
+            )
+        };
+        let result = f();
+        let end_cursor = ui.cursor_pos();
+        self.window_pos = ui.window_pos();
+        self.size = [width, end_cursor[1] - start_cursor[1]];
+        self.cmds.clear();
+        // SAFETY: as above, `f` may have grown the buffers but the draw list is still the same
+        unsafe {
+            let draw_list = &*sys::igGetWindowDrawList();
+            let cmds =
+                slice::from_raw_parts(draw_list.CmdBuffer.Data, draw_list.CmdBuffer.Size as usize);
+            let idx =
+                slice::from_raw_parts(draw_list.IdxBuffer.Data, draw_list.IdxBuffer.Size as usize);
+            let vtx =
+                slice::from_raw_parts(draw_list.VtxBuffer.Data, draw_list.VtxBuffer.Size as usize);
+            for cmd in cmds.iter().skip(cmd_start) {
+                if cmd.UserCallback.is_some() {
+                    continue;
+                }
+                // Commands started before `f` may have been extended by it
+                let first = cmd.IdxOffset.max(idx_start) as usize;
+                let last = (cmd.IdxOffset + cmd.ElemCount) as usize;
+                if first >= last {
+                    continue;
+                }
+                let indices = &idx[first..last];
+                let min = *indices.iter().min().unwrap_or(&0) as usize;
+                let max = *indices.iter().max().unwrap_or(&0) as usize;
+                let vtx_offset = cmd.VtxOffset as usize;
+                self.cmds.push(CachedCmd {
+                    clip_rect: [
+                        cmd.ClipRect.x,
+                        cmd.ClipRect.y,
+                        cmd.ClipRect.z,
+                        cmd.ClipRect.w,
+                    ],
+                    texture_id: cmd.TextureId,
+                    vertices: vtx[vtx_offset + min..=vtx_offset + max].to_vec(),
+                    indices: indices.iter().map(|&i| i - min as u16).collect(),
+                });
+            }
+        }
+        result
+    }
+
+    /// Draws the recorded geometry into the current window and reserves its layout space
+    fn replay(&self, ui: &Ui) {
+        let pos = ui.window_pos();
+        let offset = [pos[0] - self.window_pos[0], pos[1] - self.window_pos[1]];
+        // SAFETY: the window draw list is valid while `ui` is in a window, every command reserves
+        // exactly the vertices and indices it writes
+        unsafe {
+            let draw_list = sys::igGetWindowDrawList();
+            for cmd in &self.cmds {
+                sys::ImDrawList_PushClipRect(
+                    draw_list,
+                    sys::ImVec2::new(cmd.clip_rect[0] + offset[0], cmd.clip_rect[1] + offset[1]),
+                    sys::ImVec2::new(cmd.clip_rect[2] + offset[0], cmd.clip_rect[3] + offset[1]),
+                    true,
+                );
+                sys::ImDrawList_PushTextureID(draw_list, cmd.texture_id);
+                sys::ImDrawList_PrimReserve(
+                    draw_list,
+                    cmd.indices.len() as i32,
+                    cmd.vertices.len() as i32,
+                );
+                // Read after reserving, which may have started a new vertex block
+                let base = (*draw_list)._VtxCurrentIdx as sys::ImDrawIdx;
+                for vertex in &cmd.vertices {
+                    sys::ImDrawList_PrimWriteVtx(
+                        draw_list,
+                        sys::ImVec2::new(vertex.pos.x + offset[0], vertex.pos.y + offset[1]),
+                        vertex.uv,
+                        vertex.col,
+                    );
+                }
+                for &index in &cmd.indices {
+                    sys::ImDrawList_PrimWriteIdx(draw_list, base + index);
+                }
+                sys::ImDrawList_PopTextureID(draw_list);
+                sys::ImDrawList_PopClipRect(draw_list);
+            }
+        }
+        ui.dummy(self.size);
+    }
+}
+
+/// Caps how often a panel's content is rebuilt, e.g. to 30 Hz for an analysis view, while the
+/// rest of the UI keeps running at full rate.
+///
+/// In between rebuilds the last content is drawn again from a cache. The content is rebuilt
+/// every frame while the window is hovered or focused so it stays interactive.
+#[derive(Clone, Debug)]
+pub struct RefreshLimit {
+    interval: f64,
+    last_build: Option<f64>,
+    cache: DrawCache,
+}
+
+impl RefreshLimit {
+    /// Limits rebuilds to `hz` times per second
+    pub fn new(hz: f32) -> Self {
+        Self {
+            interval: 1.0 / hz.max(f32::EPSILON) as f64,
+            last_build: None,
+            cache: DrawCache::default(),
+        }
+    }
+    pub fn set_hz(&mut self, hz: f32) {
+        self.interval = 1.0 / hz.max(f32::EPSILON) as f64;
+    }
+    /// Forces a rebuild on the next frame
+    pub fn invalidate(&mut self) {
+        self.last_build = None;
+    }
+
+    /// Builds the content with `f` or replays it, call inside the window's build closure.
+    ///
+    /// Returns `true` when `f` ran this frame.
+    pub fn build(&mut self, ui: &Ui, f: impl FnOnce()) -> bool {
+        let now = ui.time();
+        let interacting = ui.is_window_hovered_with_flags(imgui::WindowHoveredFlags::CHILD_WINDOWS)
+            || ui.is_window_focused_with_flags(imgui::WindowFocusedFlags::CHILD_WINDOWS);
+        let due = self
+            .last_build
+            .is_none_or(|last| now - last >= self.interval || now < last);
+        if interacting || due {
+            self.last_build = Some(now);
+            self.cache.record(ui, f);
+            true
+        } else {
+            self.cache.replay(ui);
+            false
+        }
+    }
+}
+
+/// Child region whose content is only rebuilt when flagged dirty, e.g. a table with 50k rows
+/// that only changes when the data does.
+///
+/// The content is also rebuilt when the region is resized or scrolled, as the recorded
+/// geometry no longer matches.
+#[derive(Clone, Debug)]
+pub struct RetainedRegion {
+    id: String,
+    dirty: bool,
+    scroll: [f32; 2],
+    size: [f32; 2],
+    cache: DrawCache,
+}
+
+impl RetainedRegion {
+    /// `id` is the child window ID, it only has to be unique within the current ID scope
+    pub fn new(id: impl Into<String>) -> Self {
+        Self {
+            id: id.into(),
+            dirty: true,
+            scroll: [0.0, 0.0],
+            size: [0.0, 0.0],
+            cache: DrawCache::default(),
+        }
+    }
+    /// Rebuilds the content on the next frame
+    pub fn mark_dirty(&mut self) {
+        self.dirty = true;
+    }
+    pub fn is_dirty(&self) -> bool {
+        self.dirty
+    }
+
+    /// Draws the child region, running `f` only if the cached content is out of date.
+    ///
+    /// `size` works like [`ChildWindow::size`](imgui::ChildWindow::size). Returns `true` when
+    /// `f` ran this frame.
+    pub fn build(&mut self, ui: &Ui, size: [f32; 2], f: impl FnOnce()) -> bool {
+        let Self {
+            id,
+            dirty,
+            scroll,
+            size: last_size,
+            cache,
+        } = self;
+        ui.child_window(id.as_str())
+            .size(size)
+            .build(|| {
+                let current_scroll = [ui.scroll_x(), ui.scroll_y()];
+                let current_size = ui.window_size();
+                if *dirty || current_scroll != *scroll || current_size != *last_size {
+                    *dirty = false;
+                    *scroll = current_scroll;
+                    *last_size = current_size;
+                    cache.record(ui, f);
+                    true
+                } else {
+                    cache.replay(ui);
+                    false
+                }
+            })
+            .unwrap_or(false)
+    }
+}
@@ -0,0 +1,223 @@
+//! Reading back what's on screen, e.g. to share bug screenshots from inside the tool.
+//!
+//! [`RegionCapture`] lets the user drag a rectangle over the window, the pixels under it are read
+//! back from the front buffer on the following frame. With the `clipboard-image` feature the result
+//! can be put straight onto the OS clipboard.
+
+use bugsyth_engine::glium::backend::Facade;
+use bugsyth_engine::glium::ReadError;
+use imgui::{Condition, MouseButton, Ui};
+use std::error::Error;
+use std::fmt;
+
+const OVERLAY_COLOR: [f32; 4] = [0.0, 0.0, 0.0, 0.35];
+const SELECTION_COLOR: [f32; 4] = [1.0, 1.0, 1.0, 0.9];
+
+#[derive(Debug)]
+pub enum CaptureError {
+    Read(ReadError),
+    /// The region doesn't overlap the framebuffer
+    EmptyRegion,
+    #[cfg(feature = "clipboard-image")]
+    Clipboard(arboard::Error),
+}
+
+impl Error for CaptureError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        use self::CaptureError::*;
+        match *self {
+            Read(ref e) => Some(e),
+            EmptyRegion => None,
+            #[cfg(feature = "clipboard-image")]
+            Clipboard(ref e) => Some(e),
+        }
+    }
+}
+
+impl fmt::Display for CaptureError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use self::CaptureError::*;
+        match *self {
+            Read(ref e) => write!(f, "Reading the framebuffer failed: {}", e),
+            EmptyRegion => write!(f, "Capture region is empty"),
+            #[cfg(feature = "clipboard-image")]
+            Clipboard(ref e) => write!(f, "Copying to the clipboard failed: {}", e),
+        }
+    }
+}
+
+impl From<ReadError> for CaptureError {
+    fn from(e: ReadError) -> CaptureError {
+        CaptureError::Read(e)
+    }
+}
+
+/// Rectangle in framebuffer pixels, origin at the top left
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct CaptureRegion {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl CaptureRegion {
+    /// Converts a rectangle in UI coordinates to framebuffer pixels
+    pub fn from_ui_rect(ui: &Ui, min: [f32; 2], max: [f32; 2]) -> Self {
+        let scale = ui.io().display_framebuffer_scale;
+        let x0 = (min[0].min(max[0]) * scale[0]).max(0.0);
+        let y0 = (min[1].min(max[1]) * scale[1]).max(0.0);
+        let x1 = (min[0].max(max[0]) * scale[0]).max(0.0);
+        let y1 = (min[1].max(max[1]) * scale[1]).max(0.0);
+        CaptureRegion {
+            x: x0.floor() as u32,
+            y: y0.floor() as u32,
+            width: (x1 - x0).ceil() as u32,
+            height: (y1 - y0).ceil() as u32,
+        }
+    }
+}
+
+/// RGBA8 pixels, rows top to bottom
+#[derive(Clone, Debug)]
+pub struct CapturedImage {
+    pub width: u32,
+    pub height: u32,
+    pub pixels: Vec<u8>,
+}
+
+/// Reads a region of the front buffer, i.e. the last presented frame
+pub fn read_region<F: Facade>(
+    facade: &F,
+    region: CaptureRegion,
+) -> Result<CapturedImage, CaptureError> {
+    // Rows come back bottom to top
+    let rows: Vec<Vec<(u8, u8, u8, u8)>> = facade.get_context().read_front_buffer()?;
+    let fb_height = rows.len() as u32;
+    let fb_width = rows.first().map_or(0, |row| row.len() as u32);
+    let x_end = (region.x + region.width).min(fb_width);
+    let y_end = (region.y + region.height).min(fb_height);
+    if region.x >= x_end || region.y >= y_end {
+        return Err(CaptureError::EmptyRegion);
+    }
+
+    let mut pixels = Vec::with_capacity(((x_end - region.x) * (y_end - region.y) * 4) as usize);
+    for y in region.y..y_end {
+        let row = &rows[(fb_height - 1 - y) as usize];
+        for &(r, g, b, a) in &row[region.x as usize..x_end as usize] {
+            pixels.extend_from_slice(&[r, g, b, a]);
+        }
+    }
+    Ok(CapturedImage {
+        width: x_end - region.x,
+        height: y_end - region.y,
+        pixels,
+    })
+}
+
+/// Puts the image on the OS clipboard
+#[cfg(feature = "clipboard-image")]
+pub fn copy_to_clipboard(image: &CapturedImage) -> Result<(), CaptureError> {
+    let mut clipboard = arboard::Clipboard::new().map_err(CaptureError::Clipboard)?;
+    clipboard
+        .set_image(arboard::ImageData {
+            width: image.width as usize,
+            height: image.height as usize,
+            bytes: std::borrow::Cow::Borrowed(&image.pixels),
+        })
+        .map_err(CaptureError::Clipboard)
+}
+
+/// Drag-a-rectangle capture tool
+#[derive(Debug, Default)]
+pub struct RegionCapture {
+    selecting: bool,
+    start: Option<[f32; 2]>,
+    pending: Option<CaptureRegion>,
+}
+
+impl RegionCapture {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Starts region selection, the next drag over the window picks the region
+    pub fn begin(&mut self) {
+        self.selecting = true;
+        self.start = None;
+    }
+    pub fn cancel(&mut self) {
+        self.selecting = false;
+        self.start = None;
+    }
+    pub fn is_selecting(&self) -> bool {
+        self.selecting
+    }
+
+    /// Draws the selection overlay while selecting, call once per frame after the rest of the UI.
+    ///
+    /// Right click or Escape cancels the selection.
+    pub fn draw(&mut self, ui: &Ui) {
+        if !self.selecting {
+            return;
+        }
+        if ui.is_key_pressed(imgui::Key::Escape) || ui.is_mouse_clicked(MouseButton::Right) {
+            self.cancel();
+            return;
+        }
+        let display_size = ui.io().display_size;
+        // Invisible window covering everything so the drag doesn't reach other windows or the game
+        ui.window("##region_capture")
+            .position([0.0, 0.0], Condition::Always)
+            .size(display_size, Condition::Always)
+            .no_decoration()
+            .draw_background(false)
+            .save_settings(false)
+            .focused(true)
+            .build(|| {
+                ui.invisible_button("##region_capture_area", display_size);
+                let mouse = ui.io().mouse_pos;
+                if ui.is_item_activated() {
+                    self.start = Some(mouse);
+                }
+                if let Some(start) = self.start.filter(|_| ui.is_item_deactivated()) {
+                    // Nothing gets drawn on this frame so the overlay doesn't end up in the capture
+                    self.pending = Some(CaptureRegion::from_ui_rect(ui, start, mouse));
+                    self.cancel();
+                    return;
+                }
+                let draw_list = ui.get_window_draw_list();
+                draw_list
+                    .add_rect([0.0, 0.0], display_size, OVERLAY_COLOR)
+                    .filled(true)
+                    .build();
+                if let Some(start) = self.start {
+                    draw_list
+                        .add_rect(start, mouse, SELECTION_COLOR)
+                        .thickness(1.0)
+                        .build();
+                }
+            });
+    }
+
+    /// Reads back the selected region once the selection finished.
+    ///
+    /// Call at the start of the frame after the selection, before anything is drawn over the
+    /// region. Returns `None` while there's nothing to capture.
+    pub fn take_capture<F: Facade>(
+        &mut self,
+        facade: &F,
+    ) -> Option<Result<CapturedImage, CaptureError>> {
+        let region = self.pending.take()?;
+        Some(read_region(facade, region))
+    }
+
+    /// Same as [`take_capture`](Self::take_capture) but puts the image on the OS clipboard
+    #[cfg(feature = "clipboard-image")]
+    pub fn copy_capture_to_clipboard<F: Facade>(
+        &mut self,
+        facade: &F,
+    ) -> Option<Result<(), CaptureError>> {
+        self.take_capture(facade)
+            .map(|image| image.and_then(|image| copy_to_clipboard(&image)))
+    }
+}
@@ -0,0 +1,594 @@
+//! In-game cheat menu.
+//!
+//! [`CheatMenu`] holds the toggles, actions and value overrides a game registers, grouped into
+//! categories, and shows them once unlocked, see [`CheatUnlock`]. The menu has its own
+//! navigation so a controller works as well as the keyboard or the mouse: up and down select,
+//! Enter or South activates, left and right change the selection's value, Page Up and Page Down
+//! or the bumpers switch categories and Escape or East closes the menu.
+//!
+//! Controller input is read from imgui's gamepad keys, which the game has to feed with
+//! `io.add_key_event` as winit doesn't report controllers. imgui drops them unless
+//! `BackendFlags::HAS_GAMEPAD` is set.
+
+use crate::gamepad::{Button, GamepadUi};
+use crate::Shortcut;
+use imgui::{Condition, Direction, Key, Ui, WindowFlags};
+use std::fmt;
+use std::ops::RangeInclusive;
+
+/// Most seconds between two keys of a [`CheatUnlock::Sequence`]
+pub const SEQUENCE_TIMEOUT: f64 = 1.5;
+/// Category of cheats registered before the first [`CheatMenu::category`]
+const DEFAULT_CATEGORY: &str = "General";
+
+type ToggleFn = Box<dyn FnMut(bool)>;
+type ActionFn = Box<dyn FnMut()>;
+type OverrideFn = Box<dyn FnMut(Option<f32>)>;
+
+/// How the [`CheatMenu`] gets unlocked. Once unlocked its shortcut opens it, see
+/// [`CheatMenu::with_shortcut`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum CheatUnlock {
+    /// Unlocked from the start
+    Always,
+    /// Stays locked unless [`CheatMenu::unlock`] is called, e.g. from a console command
+    Never,
+    /// A key combination, unlocks and opens the menu
+    Shortcut(Shortcut),
+    /// Keys pressed one after another, at most [`SEQUENCE_TIMEOUT`] seconds apart, e.g. a
+    /// Konami code. Controller buttons go in as [`Button::key`]. Unlocks and opens the menu.
+    Sequence(Vec<Key>),
+}
+
+impl Default for CheatUnlock {
+    /// [`Always`](Self::Always) in debug builds and [`Never`](Self::Never) in release builds.
+    /// Gate on a cargo feature of the game with `cfg!(feature = "...")` the same way.
+    fn default() -> Self {
+        if cfg!(debug_assertions) {
+            CheatUnlock::Always
+        } else {
+            CheatUnlock::Never
+        }
+    }
+}
+
+enum CheatKind {
+    Toggle {
+        on: bool,
+        changed: ToggleFn,
+    },
+    Action(ActionFn),
+    Override {
+        enabled: bool,
+        value: f32,
+        range: RangeInclusive<f32>,
+        step: f32,
+        changed: OverrideFn,
+    },
+}
+
+struct Cheat {
+    name: String,
+    kind: CheatKind,
+}
+
+impl Cheat {
+    /// Flips a toggle or override, runs an action
+    fn activate(&mut self) {
+        match &mut self.kind {
+            CheatKind::Toggle { on, changed } => {
+                *on = !*on;
+                changed(*on);
+            }
+            CheatKind::Action(run) => run(),
+            CheatKind::Override {
+                enabled,
+                value,
+                changed,
+                ..
+            } => {
+                *enabled = !*enabled;
+                changed(enabled.then_some(*value));
+            }
+        }
+    }
+    /// Left and right: switches a toggle off or on, moves an override's value by `steps` steps
+    fn adjust(&mut self, steps: f32) {
+        match &mut self.kind {
+            CheatKind::Toggle { on, changed } => {
+                if *on != (steps > 0.0) {
+                    *on = steps > 0.0;
+                    changed(*on);
+                }
+            }
+            CheatKind::Action(_) => (),
+            CheatKind::Override {
+                value, range, step, ..
+            } => {
+                let target = (*value + steps * *step).clamp(*range.start(), *range.end());
+                self.set_override(target);
+            }
+        }
+    }
+    /// Sets and enables an override
+    fn set_override(&mut self, target: f32) {
+        if let CheatKind::Override {
+            enabled,
+            value,
+            changed,
+            ..
+        } = &mut self.kind
+        {
+            if !*enabled || *value != target {
+                *enabled = true;
+                *value = target;
+                changed(Some(target));
+            }
+        }
+    }
+}
+
+struct Category {
+    name: String,
+    cheats: Vec<Cheat>,
+}
+
+/// Cheat menu built from the game's cheats, see the [module docs](self).
+///
+/// ```no_run
+/// # use bugsyth_engine_imgui_support::cheats::{CheatMenu, CheatUnlock};
+/// # use imgui::Key;
+/// # use std::cell::Cell;
+/// # use std::rc::Rc;
+/// let gold = Rc::new(Cell::new(0));
+/// let speed = Rc::new(Cell::new(None));
+/// let cheats = CheatMenu::new()
+///     .with_unlock(CheatUnlock::Sequence(vec![Key::UpArrow, Key::UpArrow, Key::DownArrow]))
+///     .category("Player")
+///     .toggle("God mode", false, |on| println!("God mode {}", on))
+///     .action("Give 1000 gold", {
+///         let gold = Rc::clone(&gold);
+///         move || gold.set(gold.get() + 1000)
+///     })
+///     .value_override("Speed", 1.0, 0.1..=10.0, 0.1, {
+///         let speed = Rc::clone(&speed);
+///         move |value| speed.set(value)
+///     });
+/// ```
+///
+/// Call [`draw`](Self::draw) every frame, also while the menu is closed so the unlock and the
+/// shortcut are noticed.
+pub struct CheatMenu {
+    categories: Vec<Category>,
+    /// Category new cheats go into
+    registering: usize,
+    unlock: CheatUnlock,
+    unlocked: bool,
+    /// Keys of the unlock sequence entered so far, and when the last one was
+    sequence_progress: usize,
+    sequence_time: f64,
+    shortcut: Shortcut,
+    /// Held together, one of them pressed this frame
+    buttons: Vec<Button>,
+    opened: bool,
+    focus: bool,
+    category: usize,
+    selected: usize,
+    scroll_to_selected: bool,
+}
+
+impl fmt::Debug for CheatMenu {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CheatMenu")
+            .field(
+                "categories",
+                &self
+                    .categories
+                    .iter()
+                    .map(|category| &category.name)
+                    .collect::<Vec<_>>(),
+            )
+            .field("unlock", &self.unlock)
+            .field("unlocked", &self.unlocked)
+            .field("opened", &self.opened)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Default for CheatMenu {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CheatMenu {
+    /// Menu with the [default unlock](CheatUnlock::default), opened with F9 or both sticks
+    /// pressed in
+    pub fn new() -> Self {
+        let unlock = CheatUnlock::default();
+        Self {
+            categories: Vec::new(),
+            registering: 0,
+            unlocked: unlock == CheatUnlock::Always,
+            unlock,
+            sequence_progress: 0,
+            sequence_time: 0.0,
+            shortcut: Shortcut::new(Key::F9),
+            buttons: vec![Button::LeftStick, Button::RightStick],
+            opened: false,
+            focus: false,
+            category: 0,
+            selected: 0,
+            scroll_to_selected: false,
+        }
+    }
+    pub fn with_unlock(mut self, unlock: CheatUnlock) -> Self {
+        self.unlocked = unlock == CheatUnlock::Always;
+        self.unlock = unlock;
+        self
+    }
+    /// Opens and closes the unlocked menu
+    pub fn with_shortcut(mut self, shortcut: Shortcut) -> Self {
+        self.shortcut = shortcut;
+        self
+    }
+    /// Controller buttons held together to open and close the unlocked menu, none turns that
+    /// off
+    pub fn with_buttons(mut self, buttons: &[Button]) -> Self {
+        self.buttons = buttons.to_vec();
+        self
+    }
+
+    /// Cheats registered after this go into the category `name`, which is added unless it
+    /// exists
+    pub fn category(mut self, name: impl Into<String>) -> Self {
+        let name = name.into();
+        self.registering = match self.categories.iter().position(|c| c.name == name) {
+            Some(index) => index,
+            None => {
+                self.categories.push(Category {
+                    name,
+                    cheats: Vec::new(),
+                });
+                self.categories.len() - 1
+            }
+        };
+        self
+    }
+    fn register(mut self, name: impl Into<String>, kind: CheatKind) -> Self {
+        if self.categories.is_empty() {
+            self = self.category(DEFAULT_CATEGORY);
+        }
+        let category = &mut self.categories[self.registering];
+        category.cheats.push(Cheat {
+            name: name.into(),
+            kind,
+        });
+        self
+    }
+    /// Switch that starts as `on`, `changed` runs with the new state
+    pub fn toggle<F>(self, name: impl Into<String>, on: bool, changed: F) -> Self
+    where
+        F: FnMut(bool) + 'static,
+    {
+        self.register(
+            name,
+            CheatKind::Toggle {
+                on,
+                changed: Box::new(changed),
+            },
+        )
+    }
+    /// Button running `run`, e.g. to spawn an item or skip a level
+    pub fn action<F>(self, name: impl Into<String>, run: F) -> Self
+    where
+        F: FnMut() + 'static,
+    {
+        self.register(name, CheatKind::Action(Box::new(run)))
+    }
+    /// Value replacing one of the game's while enabled, e.g. the player's speed. Starts
+    /// disabled at `value`, left and right move it by `step`. `changed` gets the value, or
+    /// `None` when the override was turned off and the game's own value applies again.
+    pub fn value_override<F>(
+        self,
+        name: impl Into<String>,
+        value: f32,
+        range: RangeInclusive<f32>,
+        step: f32,
+        changed: F,
+    ) -> Self
+    where
+        F: FnMut(Option<f32>) + 'static,
+    {
+        self.register(
+            name,
+            CheatKind::Override {
+                enabled: false,
+                value,
+                range,
+                step,
+                changed: Box::new(changed),
+            },
+        )
+    }
+
+    fn find(&self, name: &str) -> Option<&CheatKind> {
+        self.categories
+            .iter()
+            .flat_map(|category| &category.cheats)
+            .find(|cheat| cheat.name == name)
+            .map(|cheat| &cheat.kind)
+    }
+    /// State of the toggle `name`, `false` if there's none
+    pub fn is_on(&self, name: &str) -> bool {
+        matches!(self.find(name), Some(CheatKind::Toggle { on: true, .. }))
+    }
+    /// Value of the override `name`, `None` when it's off or there's none
+    pub fn override_value(&self, name: &str) -> Option<f32> {
+        match self.find(name)? {
+            CheatKind::Override { enabled, value, .. } => enabled.then_some(*value),
+            _ => None,
+        }
+    }
+
+    pub fn is_unlocked(&self) -> bool {
+        self.unlocked
+    }
+    /// Unlocks the menu whatever the [`CheatUnlock`] is, e.g. from a console command
+    pub fn unlock(&mut self) {
+        self.unlocked = true;
+    }
+    /// Locks and closes the menu, the unlock has to be entered again
+    pub fn lock(&mut self) {
+        self.unlocked = false;
+        self.opened = false;
+        self.sequence_progress = 0;
+    }
+    pub fn is_open(&self) -> bool {
+        self.opened
+    }
+    /// Opening only works while unlocked
+    pub fn set_open(&mut self, opened: bool) {
+        self.opened = opened && self.unlocked;
+        self.focus = self.opened;
+    }
+
+    /// Returns `true` when the unlock was entered this frame
+    fn check_unlock(&mut self, ui: &Ui) -> bool {
+        match &self.unlock {
+            CheatUnlock::Always | CheatUnlock::Never => false,
+            CheatUnlock::Shortcut(shortcut) => shortcut.is_pressed(ui),
+            CheatUnlock::Sequence(keys) => {
+                let Some(&expected) = keys.get(self.sequence_progress) else {
+                    return false;
+                };
+                let now = ui.time();
+                if self.sequence_progress > 0 && now - self.sequence_time > SEQUENCE_TIMEOUT {
+                    self.sequence_progress = 0;
+                }
+                if ui.is_key_pressed_no_repeat(expected) {
+                    self.sequence_progress += 1;
+                    self.sequence_time = now;
+                } else if Key::VARIANTS
+                    .into_iter()
+                    .any(|key| counts_for_sequence(key) && ui.is_key_pressed_no_repeat(key))
+                {
+                    // A wrong key starts over, or the first step again if it was that
+                    self.sequence_progress = usize::from(ui.is_key_pressed_no_repeat(keys[0]));
+                    self.sequence_time = now;
+                }
+                if self.sequence_progress == keys.len() {
+                    self.sequence_progress = 0;
+                    return true;
+                }
+                false
+            }
+        }
+    }
+
+    fn buttons_pressed(&self, ui: &Ui) -> bool {
+        !self.buttons.is_empty()
+            && self
+                .buttons
+                .iter()
+                .all(|button| ui.is_key_down(button.key()))
+            && self
+                .buttons
+                .iter()
+                .any(|button| ui.is_key_pressed_no_repeat(button.key()))
+    }
+
+    /// Checks the unlock and the shortcut, and draws the menu while it's open
+    pub fn draw(&mut self, ui: &Ui) {
+        if self.check_unlock(ui) {
+            self.unlocked = true;
+            self.set_open(true);
+        } else if self.unlocked && (self.shortcut.is_pressed(ui) || self.buttons_pressed(ui)) {
+            self.set_open(!self.opened);
+        }
+        if !self.opened {
+            return;
+        }
+        let mut opened = true;
+        ui.window("Cheats")
+            .opened(&mut opened)
+            .focused(std::mem::take(&mut self.focus))
+            .size([340.0, 420.0], Condition::FirstUseEver)
+            // The menu navigates by itself, imgui's navigation would move along
+            .flags(WindowFlags::NO_NAV_INPUTS)
+            .build(|| {
+                if ui.is_window_focused() {
+                    self.navigate(ui);
+                }
+                if self.opened {
+                    self.draw_category(ui);
+                }
+            });
+        self.opened &= opened;
+    }
+
+    fn navigate(&mut self, ui: &Ui) {
+        let pressed = |keys: &[Key]| keys.iter().any(|&key| ui.is_key_pressed(key));
+        if pressed(&[Key::Escape, Key::GamepadFaceRight]) {
+            self.opened = false;
+            return;
+        }
+        let count = self.categories.len();
+        if count > 1 {
+            if pressed(&[Key::PageUp, Key::GamepadL1]) {
+                self.switch_category((self.category + count - 1) % count);
+            }
+            if pressed(&[Key::PageDown, Key::GamepadR1]) {
+                self.switch_category((self.category + 1) % count);
+            }
+        }
+        let Some(category) = self.categories.get_mut(self.category) else {
+            return;
+        };
+        let len = category.cheats.len();
+        if len == 0 {
+            return;
+        }
+        if pressed(&[Key::UpArrow, Key::GamepadDpadUp]) {
+            self.selected = (self.selected + len - 1) % len;
+            self.scroll_to_selected = true;
+        }
+        if pressed(&[Key::DownArrow, Key::GamepadDpadDown]) {
+            self.selected = (self.selected + 1) % len;
+            self.scroll_to_selected = true;
+        }
+        let Some(cheat) = category.cheats.get_mut(self.selected) else {
+            return;
+        };
+        if [Key::Enter, Key::KeypadEnter, Key::GamepadFaceDown]
+            .into_iter()
+            .any(|key| ui.is_key_pressed_no_repeat(key))
+        {
+            cheat.activate();
+        }
+        if pressed(&[Key::LeftArrow, Key::GamepadDpadLeft]) {
+            cheat.adjust(-1.0);
+        }
+        if pressed(&[Key::RightArrow, Key::GamepadDpadRight]) {
+            cheat.adjust(1.0);
+        }
+    }
+
+    fn switch_category(&mut self, category: usize) {
+        self.category = category;
+        self.selected = 0;
+    }
+
+    fn draw_category(&mut self, ui: &Ui) {
+        if self.categories.is_empty() {
+            ui.text_disabled("No cheats registered");
+            return;
+        }
+        self.category = self.category.min(self.categories.len() - 1);
+        let count = self.categories.len();
+        if ui.arrow_button("##previous", Direction::Left) {
+            self.switch_category((self.category + count - 1) % count);
+        }
+        ui.same_line();
+        if ui.arrow_button("##next", Direction::Right) {
+            self.switch_category((self.category + 1) % count);
+        }
+        ui.same_line();
+        ui.text(format!(
+            "{} ({}/{})",
+            self.categories[self.category].name,
+            self.category + 1,
+            count
+        ));
+        ui.separator();
+
+        let footer = ui.text_line_height_with_spacing() + ui.clone_style().item_spacing[1];
+        let scroll = std::mem::take(&mut self.scroll_to_selected);
+        ui.child_window("##cheats").size([0.0, -footer]).build(|| {
+            let category = &mut self.categories[self.category];
+            self.selected = self.selected.min(category.cheats.len().saturating_sub(1));
+            for (index, cheat) in category.cheats.iter_mut().enumerate() {
+                let _id = ui.push_id_usize(index);
+                let selected = index == self.selected;
+                let (label, value) = match &cheat.kind {
+                    CheatKind::Toggle { on, .. } => (
+                        cheat.name.clone(),
+                        if *on { "On" } else { "Off" }.to_string(),
+                    ),
+                    CheatKind::Action(_) => (cheat.name.clone(), String::new()),
+                    CheatKind::Override { enabled, value, .. } => (
+                        cheat.name.clone(),
+                        if *enabled {
+                            format!("{:.2}", value)
+                        } else {
+                            "Off".to_string()
+                        },
+                    ),
+                };
+                let row_start = ui.cursor_pos();
+                if ui.selectable_config(&label).selected(selected).build() {
+                    self.selected = index;
+                    cheat.activate();
+                }
+                if selected && scroll {
+                    ui.set_scroll_here_y();
+                }
+                if !value.is_empty() {
+                    let width = ui.calc_text_size(&value)[0];
+                    ui.same_line_with_pos(row_start[0] + ui.content_region_avail()[0] - width);
+                    ui.text_disabled(&value);
+                }
+                // Mouse users get a slider on the selected override
+                if let (true, CheatKind::Override { value, range, .. }) = (selected, &cheat.kind) {
+                    let mut target = *value;
+                    ui.set_next_item_width(-1.0);
+                    if ui.slider("##value", *range.start(), *range.end(), &mut target) {
+                        cheat.set_override(target);
+                    }
+                }
+            }
+        });
+        ui.separator();
+        ui.text_with_buttons("{LeftBumper}/{RightBumper} Category  {South} Select  {East} Close");
+    }
+}
+
+/// Keys that break an unlock sequence when pressed out of turn. Modifiers, the mouse and the
+/// analog sticks, which report presses for small movements, don't.
+fn counts_for_sequence(key: Key) -> bool {
+    !matches!(
+        key,
+        Key::LeftCtrl
+            | Key::LeftShift
+            | Key::LeftAlt
+            | Key::LeftSuper
+            | Key::RightCtrl
+            | Key::RightShift
+            | Key::RightAlt
+            | Key::RightSuper
+            | Key::GamepadLStickLeft
+            | Key::GamepadLStickRight
+            | Key::GamepadLStickUp
+            | Key::GamepadLStickDown
+            | Key::GamepadRStickLeft
+            | Key::GamepadRStickRight
+            | Key::GamepadRStickUp
+            | Key::GamepadRStickDown
+            | Key::MouseLeft
+            | Key::MouseRight
+            | Key::MouseMiddle
+            | Key::MouseX1
+            | Key::MouseX2
+            | Key::MouseWheelX
+            | Key::MouseWheelY
+            | Key::ReservedForModCtrl
+            | Key::ReservedForModShift
+            | Key::ReservedForModAlt
+            | Key::ReservedForModSuper
+            | Key::ModCtrl
+            | Key::ModShift
+            | Key::ModAlt
+            | Key::ModSuper
+            | Key::ModShortcut
+    )
+}
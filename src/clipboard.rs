@@ -0,0 +1,202 @@
+//! Copy/paste of structured values through the clipboard.
+//!
+//! Values are written as a short text snippet, `bugsyth:<kind> <numbers...>`, so they can be
+//! pasted into any widget editing the same kind of value. Copying between separate tool instances
+//! needs an OS clipboard backend set with [`Context::set_clipboard_backend`](imgui::Context::set_clipboard_backend).
+
+use bugsyth_engine::math::{Quaternion, Rgba, Transform, Vec2, Vec3};
+use imgui::Ui;
+use std::error::Error;
+use std::fmt;
+
+const PREFIX: &str = "bugsyth:";
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum ClipboardError {
+    /// The clipboard is empty or unavailable
+    Empty,
+    /// The clipboard doesn't hold a snippet written by this crate
+    NotASnippet,
+    /// The snippet holds another kind of value
+    WrongKind {
+        expected: &'static str,
+        found: String,
+    },
+    /// The snippet has the wrong amount of numbers or numbers that failed to parse
+    Malformed(&'static str),
+    /// The snippet parsed but the value was rejected by the validator
+    Invalid(String),
+}
+
+impl Error for ClipboardError {}
+
+impl fmt::Display for ClipboardError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use self::ClipboardError::*;
+        match self {
+            Empty => write!(f, "Clipboard is empty"),
+            NotASnippet => write!(f, "Clipboard doesn't contain a value"),
+            WrongKind { expected, found } => {
+                write!(
+                    f,
+                    "Expected a {} but the clipboard holds a {}",
+                    expected, found
+                )
+            }
+            Malformed(kind) => write!(f, "Clipboard holds a malformed {}", kind),
+            Invalid(reason) => write!(f, "Invalid value: {}", reason),
+        }
+    }
+}
+
+/// Value that can be copied to and pasted from the clipboard
+pub trait ClipboardValue: Sized {
+    /// Name written into the snippet, pasting only accepts snippets of the same kind
+    const KIND: &'static str;
+    /// Numbers that make up the value
+    fn to_numbers(&self) -> Vec<f32>;
+    /// Rebuilds the value, `None` if the amount of numbers doesn't fit
+    fn from_numbers(numbers: &[f32]) -> Option<Self>;
+}
+
+impl ClipboardValue for Vec2<f32> {
+    const KIND: &'static str = "vec2";
+    fn to_numbers(&self) -> Vec<f32> {
+        self.into_array().to_vec()
+    }
+    fn from_numbers(numbers: &[f32]) -> Option<Self> {
+        (numbers.len() == 2).then(|| Vec2::from_slice(numbers))
+    }
+}
+
+impl ClipboardValue for Vec3<f32> {
+    const KIND: &'static str = "vec3";
+    fn to_numbers(&self) -> Vec<f32> {
+        self.into_array().to_vec()
+    }
+    fn from_numbers(numbers: &[f32]) -> Option<Self> {
+        (numbers.len() == 3).then(|| Vec3::from_slice(numbers))
+    }
+}
+
+impl ClipboardValue for Rgba<f32> {
+    const KIND: &'static str = "color";
+    fn to_numbers(&self) -> Vec<f32> {
+        self.into_array().to_vec()
+    }
+    fn from_numbers(numbers: &[f32]) -> Option<Self> {
+        (numbers.len() == 4).then(|| Rgba::from_slice(numbers))
+    }
+}
+
+impl ClipboardValue for Transform<f32, f32, f32> {
+    const KIND: &'static str = "transform";
+    fn to_numbers(&self) -> Vec<f32> {
+        let mut numbers = self.position.into_array().to_vec();
+        numbers.extend(self.orientation.into_vec4().into_array());
+        numbers.extend(self.scale.into_array());
+        numbers
+    }
+    fn from_numbers(numbers: &[f32]) -> Option<Self> {
+        if numbers.len() != 10 {
+            return None;
+        }
+        Some(Transform {
+            position: Vec3::from_slice(&numbers[0..3]),
+            orientation: Quaternion::from_xyzw(numbers[3], numbers[4], numbers[5], numbers[6]),
+            scale: Vec3::from_slice(&numbers[7..10]),
+        })
+    }
+}
+
+/// Curves are copied as their control points
+impl ClipboardValue for Vec<Vec2<f32>> {
+    const KIND: &'static str = "curve";
+    fn to_numbers(&self) -> Vec<f32> {
+        self.iter().flat_map(|point| point.into_array()).collect()
+    }
+    fn from_numbers(numbers: &[f32]) -> Option<Self> {
+        if !numbers.len().is_multiple_of(2) {
+            return None;
+        }
+        Some(numbers.chunks(2).map(Vec2::from_slice).collect())
+    }
+}
+
+/// Formats a value as a clipboard snippet
+pub fn to_snippet<T: ClipboardValue>(value: &T) -> String {
+    let mut snippet = format!("{}{}", PREFIX, T::KIND);
+    for number in value.to_numbers() {
+        snippet.push(' ');
+        snippet.push_str(&number.to_string());
+    }
+    snippet
+}
+
+/// Parses a snippet written by [`to_snippet`]
+pub fn from_snippet<T: ClipboardValue>(snippet: &str) -> Result<T, ClipboardError> {
+    let mut parts = snippet
+        .trim()
+        .strip_prefix(PREFIX)
+        .ok_or(ClipboardError::NotASnippet)?
+        .split_whitespace();
+    let kind = parts.next().unwrap_or_default();
+    if kind != T::KIND {
+        return Err(ClipboardError::WrongKind {
+            expected: T::KIND,
+            found: kind.to_string(),
+        });
+    }
+    let numbers = parts
+        .map(|part| part.parse::<f32>().ok().filter(|n| n.is_finite()))
+        .collect::<Option<Vec<_>>>()
+        .ok_or(ClipboardError::Malformed(T::KIND))?;
+    T::from_numbers(&numbers).ok_or(ClipboardError::Malformed(T::KIND))
+}
+
+/// Puts the value on the clipboard
+pub fn copy_value<T: ClipboardValue>(ui: &Ui, value: &T) {
+    ui.set_clipboard_text(to_snippet(value));
+}
+
+/// Reads a value of type `T` from the clipboard
+pub fn paste_value<T: ClipboardValue>(ui: &Ui) -> Result<T, ClipboardError> {
+    let text = ui.clipboard_text().ok_or(ClipboardError::Empty)?;
+    from_snippet(&text)
+}
+
+/// Same as [`paste_value`] but also runs `validate` on the parsed value
+pub fn paste_value_with<T, V>(ui: &Ui, validate: V) -> Result<T, ClipboardError>
+where
+    T: ClipboardValue,
+    V: FnOnce(&T) -> Result<(), String>,
+{
+    let value = paste_value(ui)?;
+    validate(&value).map_err(ClipboardError::Invalid)?;
+    Ok(value)
+}
+
+/// Adds a Copy/Paste right-click menu to the last item.
+///
+/// Paste is greyed out when the clipboard doesn't hold a matching value. Returns `true` when the
+/// value was replaced by a paste.
+pub fn copy_paste_menu<T: ClipboardValue>(ui: &Ui, value: &mut T) -> bool {
+    let Some(_popup) = ui.begin_popup_context_item() else {
+        return false;
+    };
+    if ui.menu_item(format!("Copy {}", T::KIND)) {
+        copy_value(ui, value);
+    }
+    let pasted = paste_value::<T>(ui);
+    if ui
+        .menu_item_config(format!("Paste {}", T::KIND))
+        .enabled(pasted.is_ok())
+        .build()
+    {
+        if let Ok(pasted) = pasted {
+            *value = pasted;
+            return true;
+        }
+    }
+    false
+}
@@ -0,0 +1,279 @@
+//! In-game developer console.
+//!
+//! [`Console`] keeps a scrollback of log lines and command output, and runs the commands typed
+//! into its input line. Commands are closures registered with [`Console::register_command`],
+//! they get the arguments split on whitespace (double quotes group words) and return the text to
+//! print or an error. The input has the history and Tab completion of
+//! [`input_text_autocomplete`](crate::widgets::input_text_autocomplete).
+
+use crate::editor::{EditorPanel, EditorSelection};
+use crate::logs::{LogFilter, LogLevel};
+use crate::widgets::{input_text_autocomplete, Autocomplete};
+use imgui::{Condition, ListClipper, Ui};
+use std::collections::{BTreeMap, VecDeque};
+use std::fmt;
+
+/// Lines kept by default, see [`Console::set_max_lines`]
+pub const MAX_LINES: usize = 2000;
+
+const ECHO_COLOR: [f32; 4] = [0.6, 0.8, 1.0, 1.0];
+
+/// Closure run for a command with its arguments, the command name not included
+pub type CommandFn = Box<dyn FnMut(&[&str]) -> Result<String, String>>;
+
+struct Command {
+    help: String,
+    run: CommandFn,
+}
+
+/// One line of the scrollback
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ConsoleLine {
+    /// `None` for the echoed input
+    pub level: Option<LogLevel>,
+    pub text: String,
+}
+
+/// Scrollback, input line and registered commands, see the [module docs](self)
+pub struct Console {
+    lines: VecDeque<ConsoleLine>,
+    max_lines: usize,
+    commands: BTreeMap<String, Command>,
+    input: String,
+    autocomplete: Autocomplete,
+    filter: LogFilter,
+    scroll_to_bottom: bool,
+}
+
+impl fmt::Debug for Console {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Console")
+            .field("lines", &self.lines.len())
+            .field("commands", &self.commands.keys().collect::<Vec<_>>())
+            .field("input", &self.input)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Default for Console {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Console {
+    /// Console with the built-in `help` and `clear` commands
+    pub fn new() -> Self {
+        Self {
+            lines: VecDeque::new(),
+            max_lines: MAX_LINES,
+            commands: BTreeMap::new(),
+            input: String::new(),
+            autocomplete: Autocomplete::new(),
+            filter: LogFilter::new(),
+            scroll_to_bottom: false,
+        }
+    }
+    /// Drops the oldest lines beyond `max_lines`
+    pub fn set_max_lines(&mut self, max_lines: usize) {
+        self.max_lines = max_lines;
+        self.trim();
+    }
+    /// The input's history and completion state, e.g. to load or save the history
+    pub fn autocomplete_mut(&mut self) -> &mut Autocomplete {
+        &mut self.autocomplete
+    }
+
+    /// Adds a command, replacing one with the same name. `help` and `clear` are handled by the
+    /// console itself.
+    pub fn register_command<F>(&mut self, name: impl Into<String>, run: F)
+    where
+        F: FnMut(&[&str]) -> Result<String, String> + 'static,
+    {
+        self.commands.insert(
+            name.into(),
+            Command {
+                help: String::new(),
+                run: Box::new(run),
+            },
+        );
+    }
+    /// Sets the text `help` lists next to a registered command
+    pub fn set_help(&mut self, name: &str, help: impl Into<String>) {
+        if let Some(command) = self.commands.get_mut(name) {
+            command.help = help.into();
+        }
+    }
+    pub fn unregister_command(&mut self, name: &str) {
+        self.commands.remove(name);
+    }
+    pub fn commands(&self) -> impl Iterator<Item = &str> {
+        self.commands.keys().map(String::as_str)
+    }
+
+    pub fn lines(&self) -> impl Iterator<Item = &ConsoleLine> {
+        self.lines.iter()
+    }
+    /// Adds a line to the scrollback, multi-line text is split
+    pub fn log(&mut self, level: LogLevel, text: &str) {
+        for line in text.lines() {
+            self.push(Some(level), line.to_string());
+        }
+    }
+    pub fn clear(&mut self) {
+        self.lines.clear();
+    }
+    fn push(&mut self, level: Option<LogLevel>, text: String) {
+        self.lines.push_back(ConsoleLine { level, text });
+        self.trim();
+        self.scroll_to_bottom = true;
+    }
+    fn trim(&mut self) {
+        let excess = self.lines.len().saturating_sub(self.max_lines);
+        self.lines.drain(..excess);
+    }
+
+    /// Echoes the line and runs the command in it, as if it was typed in
+    pub fn execute(&mut self, line: &str) {
+        let line = line.trim();
+        if line.is_empty() {
+            return;
+        }
+        self.push(None, format!("> {}", line));
+        let args = split_args(line);
+        let Some((name, args)) = args.split_first() else {
+            return;
+        };
+        let args: Vec<&str> = args.iter().map(String::as_str).collect();
+        match name.as_str() {
+            "help" => {
+                let help: Vec<String> = std::iter::once("clear: clears the console".to_string())
+                    .chain(self.commands.iter().map(|(name, command)| {
+                        if command.help.is_empty() {
+                            name.clone()
+                        } else {
+                            format!("{}: {}", name, command.help)
+                        }
+                    }))
+                    .collect();
+                for line in help {
+                    self.push(Some(LogLevel::Info), line);
+                }
+            }
+            "clear" => self.clear(),
+            _ => {
+                let result = match self.commands.get_mut(name) {
+                    Some(command) => (command.run)(&args),
+                    None => Err(format!("Unknown command {:?}, try help", name)),
+                };
+                match result {
+                    Ok(output) => self.log(LogLevel::Info, &output),
+                    Err(error) => self.log(LogLevel::Error, &error),
+                }
+            }
+        }
+    }
+
+    pub fn draw(&mut self, ui: &Ui, opened: &mut bool) {
+        ui.window("Console")
+            .opened(opened)
+            .size([560.0, 360.0], Condition::FirstUseEver)
+            .build(|| self.draw_contents(ui));
+    }
+
+    /// Filter, scrollback and input line, filling the current window
+    fn draw_contents(&mut self, ui: &Ui) {
+        self.filter.draw(ui);
+        ui.separator();
+        let footer = ui.frame_height_with_spacing() + ui.clone_style().item_spacing[1];
+        ui.child_window("##console_lines")
+            .size([0.0, -footer])
+            .horizontal_scrollbar(true)
+            .build(|| {
+                let visible: Vec<&ConsoleLine> = self
+                    .lines
+                    .iter()
+                    .filter(|line| {
+                        line.level.is_none() || self.filter.matches(line.level, &line.text)
+                    })
+                    .collect();
+                let clipper = ListClipper::new(visible.len() as i32).begin(ui);
+                for row in clipper.iter() {
+                    let line = visible[row as usize];
+                    let color = line.level.map_or(ECHO_COLOR, LogLevel::color);
+                    ui.text_colored(color, &line.text);
+                }
+                if self.scroll_to_bottom {
+                    ui.set_scroll_here_y_with_ratio(1.0);
+                }
+            });
+        self.scroll_to_bottom = false;
+        ui.separator();
+        ui.set_next_item_width(-1.0);
+        let names: Vec<&str> = ["clear", "help"]
+            .into_iter()
+            .chain(self.commands.keys().map(String::as_str))
+            .collect();
+        let submitted = input_text_autocomplete(
+            ui,
+            "##console_input",
+            &mut self.input,
+            &mut self.autocomplete,
+            |input| complete(&names, input),
+        );
+        if submitted {
+            let line = std::mem::take(&mut self.input);
+            self.execute(&line);
+            ui.set_keyboard_focus_here_with_offset(imgui::FocusedWidget::Previous);
+        }
+    }
+}
+
+impl EditorPanel for Console {
+    /// The console without its own window, for the [`EditorShell`](crate::editor::EditorShell)
+    fn draw(&mut self, ui: &Ui, _selection: &mut EditorSelection) {
+        self.draw_contents(ui);
+    }
+}
+
+/// Command names starting with the typed word, nothing once arguments are being typed
+fn complete(names: &[&str], input: &str) -> Vec<String> {
+    if input.contains(char::is_whitespace) {
+        return Vec::new();
+    }
+    names
+        .iter()
+        .filter(|name| name.starts_with(input) && **name != input)
+        .map(|name| name.to_string())
+        .collect()
+}
+
+/// Splits on whitespace, double quotes keep the words in them together
+fn split_args(line: &str) -> Vec<String> {
+    let mut args = Vec::new();
+    let mut current = String::new();
+    let mut quoted = false;
+    let mut started = false;
+    for c in line.chars() {
+        match c {
+            '"' => {
+                quoted = !quoted;
+                started = true;
+            }
+            c if c.is_whitespace() && !quoted => {
+                if started {
+                    args.push(std::mem::take(&mut current));
+                    started = false;
+                }
+            }
+            c => {
+                current.push(c);
+                started = true;
+            }
+        }
+    }
+    if started {
+        args.push(current);
+    }
+    args
+}
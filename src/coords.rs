@@ -0,0 +1,166 @@
+//! Conversions between the coordinate spaces around the UI.
+//!
+//! - UI: imgui's coordinates, logical pixels at the platform's DPI factor, which differs from
+//!   winit's when the DPI mode is rounded or locked, offset by `display_pos`
+//! - logical and physical: winit's window coordinates
+//! - framebuffer: pixels of the render target, top left origin, or bottom left for GL calls
+//! - NDC: -1..1 with y up, as seen by shaders
+//! - world: the engine camera's space, see [`Coords::screen_ray`]
+//!
+//! [`Coords`] captures the scales of one frame, get it from [`ImGui::coords`](crate::ImGui::coords).
+
+use crate::WinitPlatform;
+use bugsyth_engine::context::camera::CameraState;
+use bugsyth_engine::glium::winit::dpi::{LogicalPosition, PhysicalPosition};
+use bugsyth_engine::glium::winit::window::Window;
+use bugsyth_engine::math::{Mat4, Vec3, Vec4};
+use imgui::Io;
+
+/// Scales and offsets of the current frame
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Coords {
+    /// Top left of the display in UI coordinates, the draw data's `display_pos`. Always zero
+    /// with a single viewport, set it when rendering draw data with another origin.
+    pub display_pos: [f32; 2],
+    /// Display size in UI coordinates
+    pub display_size: [f32; 2],
+    /// Framebuffer pixels per UI unit
+    pub framebuffer_scale: [f32; 2],
+    /// Physical pixels per UI unit, the platform's DPI factor
+    pub hidpi_factor: f64,
+    /// Physical pixels per winit logical pixel
+    pub scale_factor: f64,
+}
+
+/// Half line through the world, e.g. from the camera through the mouse
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Ray {
+    pub origin: Vec3<f32>,
+    /// Normalized
+    pub direction: Vec3<f32>,
+}
+
+impl Ray {
+    pub fn point_at(&self, distance: f32) -> Vec3<f32> {
+        self.origin + self.direction * distance
+    }
+    /// Where the ray hits the plane through `point` with `normal`, `None` if it's parallel or
+    /// the plane is behind the origin
+    pub fn intersect_plane(&self, point: Vec3<f32>, normal: Vec3<f32>) -> Option<Vec3<f32>> {
+        let denominator = self.direction.dot(normal);
+        if denominator.abs() <= f32::EPSILON {
+            return None;
+        }
+        let distance = (point - self.origin).dot(normal) / denominator;
+        (distance >= 0.0).then(|| self.point_at(distance))
+    }
+}
+
+impl Coords {
+    pub fn new(io: &Io, platform: &WinitPlatform, window: &Window) -> Self {
+        Self {
+            display_pos: [0.0, 0.0],
+            display_size: io.display_size,
+            framebuffer_scale: io.display_framebuffer_scale,
+            hidpi_factor: platform.hidpi_factor(),
+            scale_factor: window.scale_factor(),
+        }
+    }
+
+    /// Framebuffer size in pixels
+    pub fn framebuffer_size(&self) -> [f32; 2] {
+        [
+            self.display_size[0] * self.framebuffer_scale[0],
+            self.display_size[1] * self.framebuffer_scale[1],
+        ]
+    }
+
+    pub fn ui_to_framebuffer(&self, pos: [f32; 2]) -> [f32; 2] {
+        [
+            (pos[0] - self.display_pos[0]) * self.framebuffer_scale[0],
+            (pos[1] - self.display_pos[1]) * self.framebuffer_scale[1],
+        ]
+    }
+    pub fn framebuffer_to_ui(&self, pos: [f32; 2]) -> [f32; 2] {
+        [
+            pos[0] / self.framebuffer_scale[0] + self.display_pos[0],
+            pos[1] / self.framebuffer_scale[1] + self.display_pos[1],
+        ]
+    }
+    /// Framebuffer pixels with the origin at the bottom left, as `glReadPixels` or `glScissor`
+    /// expect
+    pub fn ui_to_framebuffer_gl(&self, pos: [f32; 2]) -> [f32; 2] {
+        let [x, y] = self.ui_to_framebuffer(pos);
+        [x, self.framebuffer_size()[1] - y]
+    }
+    pub fn framebuffer_gl_to_ui(&self, pos: [f32; 2]) -> [f32; 2] {
+        self.framebuffer_to_ui([pos[0], self.framebuffer_size()[1] - pos[1]])
+    }
+
+    pub fn ui_to_physical(&self, pos: [f32; 2]) -> PhysicalPosition<f64> {
+        PhysicalPosition::new(
+            (pos[0] - self.display_pos[0]) as f64 * self.hidpi_factor,
+            (pos[1] - self.display_pos[1]) as f64 * self.hidpi_factor,
+        )
+    }
+    pub fn physical_to_ui(&self, pos: PhysicalPosition<f64>) -> [f32; 2] {
+        [
+            (pos.x / self.hidpi_factor) as f32 + self.display_pos[0],
+            (pos.y / self.hidpi_factor) as f32 + self.display_pos[1],
+        ]
+    }
+    pub fn ui_to_logical(&self, pos: [f32; 2]) -> LogicalPosition<f64> {
+        self.physical_to_logical(self.ui_to_physical(pos))
+    }
+    pub fn logical_to_ui(&self, pos: LogicalPosition<f64>) -> [f32; 2] {
+        self.physical_to_ui(self.logical_to_physical(pos))
+    }
+    pub fn logical_to_physical(&self, pos: LogicalPosition<f64>) -> PhysicalPosition<f64> {
+        pos.to_physical(self.scale_factor)
+    }
+    pub fn physical_to_logical(&self, pos: PhysicalPosition<f64>) -> LogicalPosition<f64> {
+        pos.to_logical(self.scale_factor)
+    }
+
+    pub fn ui_to_ndc(&self, pos: [f32; 2]) -> [f32; 2] {
+        [
+            (pos[0] - self.display_pos[0]) / self.display_size[0] * 2.0 - 1.0,
+            1.0 - (pos[1] - self.display_pos[1]) / self.display_size[1] * 2.0,
+        ]
+    }
+    pub fn ndc_to_ui(&self, ndc: [f32; 2]) -> [f32; 2] {
+        [
+            (ndc[0] + 1.0) * 0.5 * self.display_size[0] + self.display_pos[0],
+            (1.0 - ndc[1]) * 0.5 * self.display_size[1] + self.display_pos[1],
+        ]
+    }
+
+    /// Ray from the camera through a point of the UI, e.g. the mouse position for picking.
+    ///
+    /// Assumes the scene fills the whole display.
+    pub fn screen_ray(&self, camera: &CameraState, pos: [f32; 2]) -> Ray {
+        let inverse = view_projection(camera).inverted();
+        let [x, y] = self.ui_to_ndc(pos);
+        let unproject = |z: f32| {
+            let point = inverse * Vec4::new(x, y, z, 1.0);
+            Vec3::from(point) / point.w
+        };
+        let (near, far) = (unproject(-1.0), unproject(1.0));
+        Ray {
+            origin: near,
+            direction: (far - near).normalized(),
+        }
+    }
+    /// Where a world position shows up in the UI, `None` when it's behind the camera
+    pub fn world_to_ui(&self, camera: &CameraState, pos: Vec3<f32>) -> Option<[f32; 2]> {
+        let clip = view_projection(camera) * Vec4::from_point(pos);
+        if clip.w <= 0.0 {
+            return None;
+        }
+        Some(self.ndc_to_ui([clip.x / clip.w, clip.y / clip.w]))
+    }
+}
+
+fn view_projection(camera: &CameraState) -> Mat4<f32> {
+    Mat4::from_col_arrays(camera.get_perspective()) * Mat4::from_col_arrays(camera.get_view())
+}
@@ -0,0 +1,35 @@
+//! Opt-in debugging aids for UI code
+
+mod behavior_tree;
+mod drawn_windows;
+mod ecs;
+mod focus_order;
+mod id_conflicts;
+mod inspector;
+mod navigation;
+mod profiler;
+mod ruler;
+#[cfg(feature = "serde")]
+mod save_game;
+mod shadows;
+mod skeleton;
+mod texture_inspector;
+mod watchdog;
+mod widget_tree;
+
+pub use behavior_tree::{BehaviorTreePanel, BehaviorTreeProvider, NodeStatus};
+pub use drawn_windows::{drawn_windows, DrawnWindow};
+pub use ecs::{ArchetypeInfo, EcsPanel, EcsStatsProvider};
+pub use focus_order::{FocusItem, FocusOrder, FocusOrderColors};
+pub use id_conflicts::{IdConflict, IdConflictDetector};
+pub use inspector::{InspectedItem, InspectorColors, LayoutInspector};
+pub use navigation::{NavPolygon, NavigationPanel};
+pub use profiler::{ProfileScope, ProfileSpan, Profiler};
+pub use ruler::{Measurement, Ruler};
+#[cfg(feature = "serde")]
+pub use save_game::{SaveGameError, SaveGamePanel};
+pub use shadows::{ShadowCascade, ShadowPanel};
+pub use skeleton::{Bone, Skeleton, SkeletonPanel};
+pub use texture_inspector::{InspectChannel, InspectMode, TextureInspector, HISTOGRAM_BINS};
+pub use watchdog::{FrameWatchdog, WindowTiming, WINDOW_BUDGET};
+pub use widget_tree::{ItemNode, WidgetTree, WindowNode};
@@ -0,0 +1,234 @@
+use imgui::{Condition, MouseButton, StyleColor, TreeNodeFlags, Ui};
+use std::collections::HashSet;
+
+const BREAKPOINT_COLOR: [f32; 4] = [0.9, 0.2, 0.2, 1.0];
+const IDLE_COLOR: [f32; 4] = [0.6, 0.6, 0.6, 1.0];
+
+/// Result of a behavior tree node's last tick
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum NodeStatus {
+    Success,
+    Failure,
+    Running,
+}
+
+impl NodeStatus {
+    pub fn name(self) -> &'static str {
+        match self {
+            NodeStatus::Success => "Success",
+            NodeStatus::Failure => "Failure",
+            NodeStatus::Running => "Running",
+        }
+    }
+    pub fn color(self) -> [f32; 4] {
+        match self {
+            NodeStatus::Success => [0.4, 0.85, 0.4, 1.0],
+            NodeStatus::Failure => [0.9, 0.35, 0.35, 1.0],
+            NodeStatus::Running => [0.95, 0.8, 0.3, 1.0],
+        }
+    }
+}
+
+/// The game's behavior tree as seen by the [`BehaviorTreePanel`]. Node IDs are chosen by the
+/// game and have to stay the same between ticks for breakpoints to stick.
+pub trait BehaviorTreeProvider {
+    fn root(&self) -> Option<u64>;
+    fn children(&self, node: u64) -> Vec<u64>;
+    /// Name shown in the tree, e.g. "Sequence" or "MoveTo(target)"
+    fn label(&self, node: u64) -> String;
+    /// Result of the node's last tick, `None` if it never ran
+    fn status(&self, node: u64) -> Option<NodeStatus>;
+    /// Whether the node ran in the latest tick of the tree
+    fn ran_last_tick(&self, node: u64) -> bool;
+}
+
+/// Live view of a behavior tree with each node colored by its last status, and breakpoints.
+///
+/// Call [`check_breakpoints`](Self::check_breakpoints) after every tick of the tree. When it
+/// returns a node, pause the game, e.g. with
+/// [`ModeController::pause`](crate::editor::ModeController::pause) or by skipping the AI update
+/// while [`is_paused`](Self::is_paused). Continue in the panel resumes, Step breaks again
+/// after the next tick. Click the dot in front of a node, or right click the node, to toggle
+/// its breakpoint.
+#[derive(Clone, Debug, Default)]
+pub struct BehaviorTreePanel {
+    breakpoints: HashSet<u64>,
+    /// Node whose breakpoint paused the tree
+    hit: Option<u64>,
+    paused: bool,
+    /// Break after the next tick whatever runs
+    step: bool,
+}
+
+impl BehaviorTreePanel {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    pub fn toggle_breakpoint(&mut self, node: u64) {
+        if !self.breakpoints.remove(&node) {
+            self.breakpoints.insert(node);
+        }
+    }
+    pub fn has_breakpoint(&self, node: u64) -> bool {
+        self.breakpoints.contains(&node)
+    }
+    pub fn clear_breakpoints(&mut self) {
+        self.breakpoints.clear();
+    }
+    /// A breakpoint hit or a step finished and Continue wasn't pressed yet
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+    pub fn resume(&mut self) {
+        self.paused = false;
+        self.hit = None;
+    }
+
+    /// Looks for breakpoint nodes that ran in the tree's latest tick. Returns the first one found
+    /// depth first, or the root after a step, and pauses until Continue.
+    pub fn check_breakpoints(&mut self, tree: &dyn BehaviorTreeProvider) -> Option<u64> {
+        if self.paused {
+            return None;
+        }
+        let root = tree.root()?;
+        let hit = if std::mem::take(&mut self.step) {
+            Some(root)
+        } else if self.breakpoints.is_empty() {
+            None
+        } else {
+            let mut stack = vec![root];
+            let mut seen = HashSet::new();
+            let mut hit = None;
+            while let Some(node) = stack.pop() {
+                if !seen.insert(node) || !tree.ran_last_tick(node) {
+                    continue;
+                }
+                if self.breakpoints.contains(&node) {
+                    hit = Some(node);
+                    break;
+                }
+                stack.extend(tree.children(node).into_iter().rev());
+            }
+            hit
+        };
+        if hit.is_some() {
+            self.hit = hit;
+            self.paused = true;
+        }
+        hit
+    }
+
+    pub fn draw(&mut self, ui: &Ui, opened: &mut bool, tree: &dyn BehaviorTreeProvider) {
+        ui.window("Behavior tree")
+            .opened(opened)
+            .size([360.0, 480.0], Condition::FirstUseEver)
+            .build(|| {
+                for (index, status) in [
+                    NodeStatus::Success,
+                    NodeStatus::Failure,
+                    NodeStatus::Running,
+                ]
+                .into_iter()
+                .enumerate()
+                {
+                    if index > 0 {
+                        ui.same_line();
+                    }
+                    ui.text_colored(status.color(), status.name());
+                }
+                if self.paused {
+                    let at = self.hit.map(|node| tree.label(node)).unwrap_or_default();
+                    ui.text_colored(BREAKPOINT_COLOR, format!("Paused at {}", at));
+                    if ui.button("Continue") {
+                        self.resume();
+                    }
+                    ui.same_line();
+                    if ui.button("Step") {
+                        self.resume();
+                        self.step = true;
+                    }
+                }
+                ui.same_line();
+                ui.disabled(self.breakpoints.is_empty(), || {
+                    if ui.button("Clear breakpoints") {
+                        self.clear_breakpoints();
+                    }
+                });
+                ui.separator();
+                let Some(root) = tree.root() else {
+                    ui.text_disabled("No tree");
+                    return;
+                };
+                let mut path = Vec::new();
+                self.draw_node(ui, tree, root, &mut path);
+            });
+    }
+
+    /// `path` holds the ancestors, to stop at cycles
+    fn draw_node(
+        &mut self,
+        ui: &Ui,
+        tree: &dyn BehaviorTreeProvider,
+        node: u64,
+        path: &mut Vec<u64>,
+    ) {
+        if path.contains(&node) {
+            return;
+        }
+        let _id = ui.push_id_usize(node as usize);
+        let size = ui.text_line_height();
+        let origin = ui.cursor_screen_pos();
+        if ui.invisible_button("##breakpoint", [size, size]) {
+            self.toggle_breakpoint(node);
+        }
+        let center = [origin[0] + size * 0.5, origin[1] + size * 0.5];
+        let draw_list = ui.get_window_draw_list();
+        if self.has_breakpoint(node) {
+            draw_list
+                .add_circle(center, size * 0.35, BREAKPOINT_COLOR)
+                .filled(true)
+                .build();
+        } else if ui.is_item_hovered() {
+            draw_list
+                .add_circle(center, size * 0.35, BREAKPOINT_COLOR)
+                .build();
+        }
+        drop(draw_list);
+        ui.same_line();
+
+        let children = tree.children(node);
+        let status = tree.status(node);
+        let mut flags = TreeNodeFlags::DEFAULT_OPEN | TreeNodeFlags::OPEN_ON_ARROW;
+        if children.is_empty() {
+            flags |= TreeNodeFlags::LEAF;
+        }
+        let color = status.map_or(IDLE_COLOR, NodeStatus::color);
+        let label = tree.label(node);
+        let token = {
+            let _color = ui.push_style_color(StyleColor::Text, color);
+            ui.tree_node_config(format!("{}##node", label))
+                .flags(flags)
+                .selected(self.hit == Some(node))
+                .push()
+        };
+        if ui.is_item_hovered() {
+            let ran = if tree.ran_last_tick(node) {
+                "ran last tick"
+            } else {
+                "didn't run last tick"
+            };
+            let status = status.map_or("never ran", NodeStatus::name);
+            ui.tooltip_text(format!("{}: {}, {}", label, status, ran));
+        }
+        if ui.is_item_clicked_with_button(MouseButton::Right) {
+            self.toggle_breakpoint(node);
+        }
+        if token.is_some() {
+            path.push(node);
+            for child in children {
+                self.draw_node(ui, tree, child, path);
+            }
+            path.pop();
+        }
+    }
+}
@@ -0,0 +1,142 @@
+use imgui::{sys, DrawData, DrawList};
+use std::ffi::CStr;
+
+/// A window as imgui drew it: a top level window, child region, popup or tooltip.
+///
+/// Found in the draw data after rendering by [`drawn_windows`], so the debug tools see every
+/// window without registering them. [`ImGui::drawn_windows`](crate::ImGui::drawn_windows) keeps
+/// the ones of the last rendered frame.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DrawnWindow {
+    /// imgui's name of the window, including any `##` suffix. Child regions are named
+    /// `<parent>/<child>_<ID>`, or `<parent>/<ID>` when opened by ID as imgui-rs does.
+    pub name: String,
+    pub min: [f32; 2],
+    pub max: [f32; 2],
+}
+
+impl DrawnWindow {
+    /// A child region of another window, named by imgui with the child's ID in 8 hex digits at
+    /// the end
+    pub fn is_child(&self) -> bool {
+        self.child_id().is_some()
+    }
+    /// Last part of a child region's name: `<child>_<ID>` or `<ID>`
+    fn child_id(&self) -> Option<&str> {
+        let (_, last) = self.name.rsplit_once('/')?;
+        let id = last.rsplit_once('_').map_or(last, |(_, id)| id);
+        let hex = |b: u8| b.is_ascii_digit() || (b'A'..=b'F').contains(&b);
+        (id.len() == 8 && id.bytes().all(hex)).then_some(last)
+    }
+    /// Name without the parent and ID of a child region and without the `##` suffix
+    pub fn label(&self) -> &str {
+        let name = match self.child_id() {
+            Some(last) => last.rsplit_once('_').map_or(last, |(name, _)| name),
+            None => &self.name,
+        };
+        name.split("##").next().unwrap_or(name)
+    }
+    pub fn contains(&self, pos: [f32; 2]) -> bool {
+        (self.min[0]..=self.max[0]).contains(&pos[0])
+            && (self.min[1]..=self.max[1]).contains(&pos[1])
+    }
+}
+
+/// Windows drawn in a frame, back to front.
+///
+/// Each window has its own draw list, the bounds are those of the vertices in it. For windows
+/// with a background that's the window's rect plus up to a pixel of anti-aliasing, child regions
+/// without one end at their last item.
+/// Empty windows and the foreground and background draw lists are left out.
+pub fn drawn_windows(draw_data: &DrawData) -> Vec<DrawnWindow> {
+    // The list pointer is null without draw lists, which `draw_lists` can't take
+    if draw_data.draw_lists_count() == 0 {
+        return Vec::new();
+    }
+    draw_data
+        .draw_lists()
+        .filter_map(|draw_list| {
+            // SAFETY: `DrawList` is a transparent wrapper of `ImDrawList`, the owner name points to
+            // the window's name, which lives as long as the context
+            let owner = unsafe {
+                let raw = &*(draw_list as *const DrawList as *const sys::ImDrawList);
+                if raw._OwnerName.is_null() {
+                    return None;
+                }
+                CStr::from_ptr(raw._OwnerName)
+            };
+            let name = owner.to_string_lossy();
+            if name.starts_with("##Foreground") || name.starts_with("##Background") {
+                return None;
+            }
+            let mut vertices = draw_list.vtx_buffer().iter().map(|vertex| vertex.pos);
+            let first = vertices.next()?;
+            let (min, max) = vertices.fold((first, first), |(min, max), pos| {
+                (
+                    [min[0].min(pos[0]), min[1].min(pos[1])],
+                    [max[0].max(pos[0]), max[1].max(pos[1])],
+                )
+            });
+            Some(DrawnWindow {
+                name: name.into_owned(),
+                min,
+                max,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::Harness;
+    use imgui::Condition;
+
+    #[test]
+    fn windows_and_child_regions_are_found() {
+        let mut harness = Harness::new();
+        let build = |ui: &imgui::Ui| {
+            ui.window("Settings##main")
+                .position([10.0, 20.0], Condition::Always)
+                .size([200.0, 150.0], Condition::Always)
+                .build(|| {
+                    ui.text("Volume");
+                    ui.child_window("list")
+                        .size([100.0, 50.0])
+                        .border(true)
+                        .build(|| ui.text("Item"));
+                });
+        };
+        harness.run(2, build);
+        let windows = harness.drawn_windows();
+        let window = windows
+            .iter()
+            .find(|window| window.name == "Settings##main")
+            .unwrap();
+        let near =
+            |a: [f32; 2], b: [f32; 2]| (a[0] - b[0]).abs() <= 1.5 && (a[1] - b[1]).abs() <= 1.5;
+        assert!(near(window.min, [10.0, 20.0]) && near(window.max, [210.0, 170.0]));
+        assert_eq!(window.label(), "Settings");
+        assert!(!window.is_child());
+        let child = windows.iter().find(|window| window.is_child()).unwrap();
+        assert!(child.name.starts_with("Settings##main/"));
+        assert!(window.contains(child.min) && window.contains(child.max));
+        assert!(!windows.iter().any(|window| window.name.starts_with("##")));
+    }
+
+    #[test]
+    fn slashes_in_window_names_arent_children() {
+        let window = |name: &str| DrawnWindow {
+            name: name.to_string(),
+            min: [0.0; 2],
+            max: [0.0; 2],
+        };
+        let log = window("Log: game.log###log_/tmp/game.log");
+        assert!(!log.is_child());
+        assert_eq!(log.label(), "Log: game.log");
+        let child = window("Log: game.log###log_/tmp/game.log/lines_2DCCB6E0");
+        assert!(child.is_child());
+        assert_eq!(child.label(), "lines");
+        assert!(window("Settings/1A2B3C4D").is_child());
+    }
+}
@@ -0,0 +1,200 @@
+use crate::FrameTimer;
+use imgui::{Condition, ProgressBar, Ui};
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+/// Frames of system timings kept for the sparklines
+const SYSTEM_HISTORY: usize = 120;
+
+/// One archetype as reported by an [`EcsStatsProvider`]: the entities sharing a set of components
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ArchetypeInfo {
+    /// Component type names
+    pub components: Vec<String>,
+    pub entities: usize,
+    /// Storage chunks allocated for the archetype, 0 when the ECS doesn't use chunks
+    pub chunks: usize,
+    /// Entities one chunk holds
+    pub chunk_capacity: usize,
+}
+
+impl ArchetypeInfo {
+    /// Share of the allocated chunk slots in use, `None` without chunks
+    pub fn utilization(&self) -> Option<f32> {
+        let slots = self.chunks * self.chunk_capacity;
+        (slots > 0).then(|| self.entities as f32 / slots as f32)
+    }
+}
+
+/// The game's ECS as seen by the [`EcsPanel`]
+pub trait EcsStatsProvider {
+    fn archetypes(&self) -> Vec<ArchetypeInfo>;
+    /// Time each system took in the last frame, in the order they ran
+    fn system_timings(&self) -> Vec<(String, Duration)>;
+}
+
+/// ECS statistics: archetypes with their entity counts and chunk utilization, entity counts per
+/// component and per-system timings with a sparkline of the last frames.
+///
+/// Works with any ECS through [`EcsStatsProvider`]. [`draw`](Self::draw) records the system
+/// timings, call it every frame so the history has no gaps.
+#[derive(Clone, Debug, Default)]
+pub struct EcsPanel {
+    /// Timings per system name, systems missing from a frame are recorded as 0
+    systems: BTreeMap<String, FrameTimer>,
+    /// Shows archetypes and components whose names contain it
+    pub filter: String,
+}
+
+impl EcsPanel {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Timing history of a system, by name
+    pub fn system(&self, name: &str) -> Option<&FrameTimer> {
+        self.systems.get(name)
+    }
+    pub fn clear_history(&mut self) {
+        self.systems.clear();
+    }
+
+    fn record(&mut self, timings: &[(String, Duration)]) {
+        for (system, timer) in &mut self.systems {
+            if !timings.iter().any(|(name, _)| name == system) {
+                timer.record(0.0);
+            }
+        }
+        for (name, time) in timings {
+            self.systems
+                .entry(name.clone())
+                .or_insert_with(|| FrameTimer::with_window(SYSTEM_HISTORY))
+                .record(time.as_secs_f32());
+        }
+    }
+
+    pub fn draw(&mut self, ui: &Ui, opened: &mut bool, ecs: &dyn EcsStatsProvider) {
+        let timings = ecs.system_timings();
+        self.record(&timings);
+        ui.window("ECS")
+            .opened(opened)
+            .size([520.0, 440.0], Condition::FirstUseEver)
+            .build(|| {
+                let archetypes = ecs.archetypes();
+                let entities: usize = archetypes.iter().map(|archetype| archetype.entities).sum();
+                ui.text(format!(
+                    "{} entities in {} archetypes",
+                    entities,
+                    archetypes.len()
+                ));
+                ui.input_text("Filter", &mut self.filter).build();
+                if let Some(_tab_bar) = ui.tab_bar("##ecs") {
+                    if let Some(_tab) = ui.tab_item("Archetypes") {
+                        self.draw_archetypes(ui, &archetypes);
+                    }
+                    if let Some(_tab) = ui.tab_item("Components") {
+                        self.draw_components(ui, &archetypes);
+                    }
+                    if let Some(_tab) = ui.tab_item("Systems") {
+                        self.draw_systems(ui, &timings);
+                    }
+                }
+            });
+    }
+
+    fn matches(&self, name: &str) -> bool {
+        self.filter.is_empty() || name.to_lowercase().contains(&self.filter.to_lowercase())
+    }
+
+    fn draw_archetypes(&self, ui: &Ui, archetypes: &[ArchetypeInfo]) {
+        ui.columns(4, "##archetypes", true);
+        for header in ["Components", "Entities", "Chunks", "Utilization"] {
+            ui.text_disabled(header);
+            ui.next_column();
+        }
+        ui.separator();
+        let mut sorted: Vec<&ArchetypeInfo> = archetypes
+            .iter()
+            .filter(|archetype| archetype.components.iter().any(|name| self.matches(name)))
+            .collect();
+        sorted.sort_by_key(|archetype| std::cmp::Reverse(archetype.entities));
+        for archetype in sorted {
+            ui.text_wrapped(archetype.components.join(", "));
+            ui.next_column();
+            ui.text(archetype.entities.to_string());
+            ui.next_column();
+            ui.text(archetype.chunks.to_string());
+            ui.next_column();
+            match archetype.utilization() {
+                Some(utilization) => ProgressBar::new(utilization)
+                    .overlay_text(format!("{:.0}%", utilization * 100.0))
+                    .size([-1.0, 0.0])
+                    .build(ui),
+                None => ui.text_disabled("-"),
+            }
+            ui.next_column();
+        }
+        ui.columns(1, "##archetypes_end", false);
+    }
+
+    fn draw_components(&self, ui: &Ui, archetypes: &[ArchetypeInfo]) {
+        let mut components: BTreeMap<&str, (usize, usize)> = BTreeMap::new();
+        for archetype in archetypes {
+            for name in &archetype.components {
+                let (entities, count) = components.entry(name.as_str()).or_default();
+                *entities += archetype.entities;
+                *count += 1;
+            }
+        }
+        ui.columns(3, "##components", true);
+        for header in ["Component", "Entities", "Archetypes"] {
+            ui.text_disabled(header);
+            ui.next_column();
+        }
+        ui.separator();
+        for (name, (entities, count)) in components {
+            if !self.matches(name) {
+                continue;
+            }
+            ui.text(name);
+            ui.next_column();
+            ui.text(entities.to_string());
+            ui.next_column();
+            ui.text(count.to_string());
+            ui.next_column();
+        }
+        ui.columns(1, "##components_end", false);
+    }
+
+    fn draw_systems(&self, ui: &Ui, timings: &[(String, Duration)]) {
+        if timings.is_empty() {
+            ui.text_disabled("No systems ran");
+            return;
+        }
+        let total: Duration = timings.iter().map(|(_, time)| *time).sum();
+        ui.text(format!("Total {:.3} ms", total.as_secs_f32() * 1000.0));
+        ui.columns(4, "##systems", true);
+        for header in ["System", "Last", "Average", "History"] {
+            ui.text_disabled(header);
+            ui.next_column();
+        }
+        ui.separator();
+        for (name, time) in timings {
+            let Some(timer) = self.systems.get(name) else {
+                continue;
+            };
+            ui.text(name);
+            ui.next_column();
+            ui.text(format!("{:.3} ms", time.as_secs_f32() * 1000.0));
+            ui.next_column();
+            ui.text(format!("{:.3} ms", timer.average()));
+            ui.next_column();
+            ui.plot_lines(format!("##{}", name), timer.history())
+                .scale_min(0.0)
+                .scale_max(timer.worst().max(f32::EPSILON))
+                .graph_size([-1.0, ui.text_line_height()])
+                .build();
+            ui.next_column();
+        }
+        ui.columns(1, "##systems_end", false);
+    }
+}
@@ -0,0 +1,148 @@
+use imgui::Ui;
+
+/// Colors of the [`FocusOrder`] overlay
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct FocusOrderColors {
+    /// Lines from each item to the next one in order
+    pub path: [f32; 4],
+    /// Background of the order numbers
+    pub badge: [f32; 4],
+    pub text: [f32; 4],
+    pub focused: [f32; 4],
+}
+
+impl Default for FocusOrderColors {
+    fn default() -> Self {
+        Self {
+            path: [0.3, 0.8, 1.0, 0.8],
+            badge: [0.1, 0.3, 0.6, 0.9],
+            text: [1.0, 1.0, 1.0, 1.0],
+            focused: [1.0, 0.9, 0.0, 1.0],
+        }
+    }
+}
+
+/// Item registered with [`FocusOrder::track`]
+#[derive(Clone, Debug, PartialEq)]
+pub struct FocusItem {
+    pub label: String,
+    pub min: [f32; 2],
+    pub max: [f32; 2],
+    pub focused: bool,
+}
+
+/// Shows the order Tab moves focus through the items of each window, for making panels usable
+/// without a mouse. Arrow and gamepad navigation go by position instead.
+///
+/// Items are numbered in the order they're submitted, which is the order Tab cycles through them
+/// in, and joined with lines. The focused item is outlined.
+///
+/// This only covers registered items: imgui 1.89 keeps its Tab order and item list internal, so
+/// each item has to be passed to [`track`](Self::track) right after it's drawn and items that
+/// aren't tracked are missing from the overlay. Items drawn inside
+/// [`Ui::push_allow_keyboard_focus(false)`](Ui::push_allow_keyboard_focus), which Tab skips, are
+/// still numbered.
+#[derive(Debug, Default)]
+pub struct FocusOrder {
+    enabled: bool,
+    pub colors: FocusOrderColors,
+    frame: i32,
+    /// Tracked windows of the current frame with their items in order
+    windows: Vec<(String, Vec<FocusItem>)>,
+}
+
+impl FocusOrder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+        self.windows.clear();
+    }
+    pub fn toggle(&mut self) {
+        self.set_enabled(!self.enabled);
+    }
+    /// Items of each tracked window in the current frame, in focus order
+    pub fn windows(&self) -> &[(String, Vec<FocusItem>)] {
+        &self.windows
+    }
+
+    fn start_frame(&mut self, ui: &Ui) {
+        let frame = ui.frame_count();
+        if frame != self.frame {
+            self.frame = frame;
+            self.windows.clear();
+        }
+    }
+
+    /// Registers the current window, call inside it before its items are tracked
+    pub fn track_window(&mut self, ui: &Ui, name: &str) {
+        if !self.enabled {
+            return;
+        }
+        self.start_frame(ui);
+        self.windows.push((name.to_string(), Vec::new()));
+    }
+
+    /// Registers the last drawn item as the next one in the current window's order
+    pub fn track(&mut self, ui: &Ui, label: &str) {
+        if !self.enabled {
+            return;
+        }
+        self.start_frame(ui);
+        if self.windows.is_empty() {
+            self.windows.push((String::new(), Vec::new()));
+        }
+        let (_, items) = self.windows.last_mut().expect("pushed above");
+        items.push(FocusItem {
+            label: label.to_string(),
+            min: ui.item_rect_min(),
+            max: ui.item_rect_max(),
+            focused: ui.is_item_focused(),
+        });
+    }
+
+    /// Draws the order of the tracked items, call once per frame after the rest of the UI
+    pub fn draw(&mut self, ui: &Ui) {
+        if !self.enabled {
+            return;
+        }
+        self.start_frame(ui);
+        let draw_list = ui.get_foreground_draw_list();
+        let colors = self.colors;
+        for (_, items) in &self.windows {
+            for pair in items.windows(2) {
+                draw_list
+                    .add_line(center(&pair[0]), center(&pair[1]), colors.path)
+                    .thickness(1.5)
+                    .build();
+            }
+            for (index, item) in items.iter().enumerate() {
+                if item.focused {
+                    draw_list
+                        .add_rect(item.min, item.max, colors.focused)
+                        .thickness(2.0)
+                        .build();
+                }
+                let number = (index + 1).to_string();
+                let size = ui.calc_text_size(&number);
+                let badge_max = [item.min[0] + size[0] + 4.0, item.min[1] + size[1]];
+                draw_list
+                    .add_rect(item.min, badge_max, colors.badge)
+                    .filled(true)
+                    .build();
+                draw_list.add_text([item.min[0] + 2.0, item.min[1]], colors.text, number);
+            }
+        }
+    }
+}
+
+fn center(item: &FocusItem) -> [f32; 2] {
+    [
+        (item.min[0] + item.max[0]) * 0.5,
+        (item.min[1] + item.max[1]) * 0.5,
+    ]
+}
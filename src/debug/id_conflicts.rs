@@ -0,0 +1,182 @@
+use imgui::{sys, Ui};
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::rc::Rc;
+
+const HIGHLIGHT_COLOR: [f32; 4] = [1.0, 0.2, 0.2, 1.0];
+
+/// Two items that ended up with the same ID in one frame
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct IdConflict {
+    pub id: u32,
+    pub window: String,
+    pub label: String,
+    /// Window and label of the item that claimed the ID first
+    pub first_window: String,
+    pub first_label: String,
+}
+
+impl fmt::Display for IdConflict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Duplicate widget ID {:#010x}: \"{}\" in \"{}\" collides with \"{}\" in \"{}\", use \"label##unique\" or push an ID",
+            self.id, self.label, self.window, self.first_label, self.first_window
+        )
+    }
+}
+
+/// Finds widgets sharing an ID, e.g. two buttons with the same label, which makes all but the
+/// first one unresponsive.
+///
+/// Only registered widgets are checked: imgui 1.89 has no public hook into ID lookups and doesn't
+/// report collisions, so each widget has to be passed to [`track`](Self::track) right after it's
+/// drawn. For untracked widgets [`show_stack_tool`](Self::show_stack_tool) shows the ID path of
+/// the hovered item. Tracking does nothing while the detector is
+/// disabled, so the calls can stay in release builds.
+///
+/// Clones share their state. Put one in [`ImGui::id_conflicts`](crate::ImGui::id_conflicts) and
+/// new conflicts are reported after every frame, in the [`UiInbox`](crate::proxy::UiInbox) log
+/// and with the `log` feature also as warnings.
+#[derive(Clone, Debug, Default)]
+pub struct IdConflictDetector {
+    state: Rc<RefCell<State>>,
+}
+
+#[derive(Debug, Default)]
+struct State {
+    enabled: bool,
+    frame: i32,
+    window: String,
+    seen: HashMap<u32, (String, String)>,
+    conflicts: Vec<IdConflict>,
+    reported: HashSet<u32>,
+    /// Conflicts found for the first time, until they're taken
+    new: Vec<IdConflict>,
+}
+
+impl IdConflictDetector {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    pub fn is_enabled(&self) -> bool {
+        self.state.borrow().enabled
+    }
+    pub fn set_enabled(&mut self, enabled: bool) {
+        let mut state = self.state.borrow_mut();
+        state.enabled = enabled;
+        if !enabled {
+            state.seen.clear();
+            state.conflicts.clear();
+        }
+    }
+    /// Sets the window name reported for the following tracked items
+    pub fn set_window(&mut self, window: impl Into<String>) {
+        self.state.borrow_mut().window = window.into();
+    }
+
+    /// Registers the last drawn item.
+    ///
+    /// Conflicts are highlighted on screen, and kept for
+    /// [`take_new_conflicts`](Self::take_new_conflicts) the first time each ID collides.
+    pub fn track(&mut self, ui: &Ui, label: &str) {
+        let mut state = self.state.borrow_mut();
+        if !state.enabled {
+            return;
+        }
+        let frame = ui.frame_count();
+        if frame != state.frame {
+            state.frame = frame;
+            state.seen.clear();
+            state.conflicts.clear();
+        }
+        // SAFETY: only reads the last item of the current window, which `ui` guarantees exists
+        let id = unsafe { sys::igGetItemID() };
+        if id == 0 {
+            return;
+        }
+        let Some((first_window, first_label)) = state.seen.get(&id) else {
+            let window = state.window.clone();
+            state.seen.insert(id, (window, label.to_string()));
+            return;
+        };
+        let conflict = IdConflict {
+            id,
+            window: state.window.clone(),
+            label: label.to_string(),
+            first_window: first_window.clone(),
+            first_label: first_label.clone(),
+        };
+        if state.reported.insert(id) {
+            state.new.push(conflict.clone());
+        }
+        state.conflicts.push(conflict);
+        ui.get_foreground_draw_list()
+            .add_rect(ui.item_rect_min(), ui.item_rect_max(), HIGHLIGHT_COLOR)
+            .thickness(2.0)
+            .build();
+    }
+
+    /// Shows imgui's stack tool window, with the ID and the ID stack of the item under the mouse,
+    /// while `opened` is true. Closing the window sets it to false.
+    pub fn show_stack_tool(&self, _ui: &Ui, opened: &mut bool) {
+        if *opened {
+            // SAFETY: `ui` guarantees a frame of the current context is being built
+            unsafe { sys::igShowStackToolWindow(opened) };
+        }
+    }
+
+    /// Conflicts found so far in the current frame
+    pub fn conflicts(&self) -> Vec<IdConflict> {
+        self.state.borrow().conflicts.clone()
+    }
+    /// Conflicts found for the first time since the last call, each ID is only returned once
+    pub fn take_new_conflicts(&self) -> Vec<IdConflict> {
+        std::mem::take(&mut self.state.borrow_mut().new)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::Harness;
+
+    #[test]
+    fn duplicate_labels_are_reported_once() {
+        let mut harness = Harness::new();
+        let mut detector = IdConflictDetector::new();
+        detector.set_enabled(true);
+        let watcher = detector.clone();
+        harness.run(3, |ui| {
+            ui.window("Shop").build(|| {
+                detector.set_window("Shop");
+                for label in ["Buy", "Sell", "Buy"] {
+                    ui.button(label);
+                    detector.track(ui, label);
+                }
+            });
+        });
+        assert_eq!(detector.conflicts().len(), 1);
+        let new = watcher.take_new_conflicts();
+        assert_eq!(new.len(), 1);
+        assert_eq!(
+            (new[0].label.as_str(), new[0].first_label.as_str()),
+            ("Buy", "Buy")
+        );
+        assert!(watcher.take_new_conflicts().is_empty());
+    }
+
+    #[test]
+    fn stack_tool_window_is_shown() {
+        let mut harness = Harness::new();
+        let detector = IdConflictDetector::new();
+        let mut opened = true;
+        harness.run(2, |ui| detector.show_stack_tool(ui, &mut opened));
+        assert!(opened);
+        assert!(harness
+            .drawn_windows()
+            .iter()
+            .any(|window| window.label() == "Dear ImGui Stack Tool"));
+    }
+}
@@ -0,0 +1,276 @@
+use super::DrawnWindow;
+use imgui::{sys, Ui};
+
+/// Outline colors of the [`LayoutInspector`]
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct InspectorColors {
+    pub window: [f32; 4],
+    pub child: [f32; 4],
+    pub item: [f32; 4],
+    pub hovered: [f32; 4],
+    /// Fill of the frame padding around the hovered item's content
+    pub padding: [f32; 4],
+}
+
+impl Default for InspectorColors {
+    fn default() -> Self {
+        Self {
+            window: [1.0, 0.6, 0.1, 0.8],
+            child: [0.3, 0.9, 0.3, 0.8],
+            item: [0.3, 0.6, 1.0, 0.5],
+            hovered: [1.0, 0.2, 0.8, 1.0],
+            padding: [0.5, 0.8, 0.3, 0.3],
+        }
+    }
+}
+
+/// The item under the mouse, as found by [`LayoutInspector::track`]
+#[derive(Clone, Debug, PartialEq)]
+pub struct InspectedItem {
+    pub id: u32,
+    /// Window, child regions containing the item and the item's label
+    pub path: Vec<String>,
+    pub min: [f32; 2],
+    pub max: [f32; 2],
+}
+
+/// "Inspect element" for the UI, outlines windows, child regions and items and shows the layout
+/// values and ID path of the hovered item.
+///
+/// Windows and child regions come from the last rendered frame, pass
+/// [`ImGui::drawn_windows`](crate::ImGui::drawn_windows) to [`set_windows`](Self::set_windows)
+/// every frame and hovering one shows its name and rect. imgui 1.89 has no public way to list
+/// the items in a window, so items show up only when registered with [`track`](Self::track)
+/// right after they're drawn. [`Ui::show_metrics_window`] lists everything imgui knows, but not
+/// over the UI. Registering does nothing while the inspector is disabled.
+#[derive(Debug, Default)]
+pub struct LayoutInspector {
+    enabled: bool,
+    pub colors: InspectorColors,
+    frame: i32,
+    window: String,
+    /// Child regions of the current window, name and rect
+    children: Vec<(String, [f32; 2], [f32; 2])>,
+    hovered: Option<InspectedItem>,
+    drawn: Vec<DrawnWindow>,
+}
+
+impl LayoutInspector {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+        self.hovered = None;
+        self.drawn.clear();
+    }
+    pub fn toggle(&mut self) {
+        self.set_enabled(!self.enabled);
+    }
+    /// The item under the mouse in the current frame
+    pub fn hovered(&self) -> Option<&InspectedItem> {
+        self.hovered.as_ref()
+    }
+    /// Sets the windows to outline, usually those of the last rendered frame
+    pub fn set_windows(&mut self, windows: &[DrawnWindow]) {
+        if self.enabled {
+            self.drawn = windows.to_vec();
+        }
+    }
+    /// The innermost drawn window under `pos`
+    pub fn window_at(&self, pos: [f32; 2]) -> Option<&DrawnWindow> {
+        // Back to front, so the last one is on top
+        self.drawn.iter().rev().find(|window| window.contains(pos))
+    }
+
+    fn start_frame(&mut self, ui: &Ui) {
+        let frame = ui.frame_count();
+        if frame != self.frame {
+            self.frame = frame;
+            self.window.clear();
+            self.children.clear();
+            self.hovered = None;
+        }
+    }
+
+    /// Registers the current window, call inside it before its items are tracked
+    pub fn track_window(&mut self, ui: &Ui, name: &str) {
+        if !self.enabled {
+            return;
+        }
+        self.start_frame(ui);
+        self.window = name.to_string();
+        self.children.clear();
+        let min = ui.window_pos();
+        let size = ui.window_size();
+        ui.get_foreground_draw_list()
+            .add_rect(
+                min,
+                [min[0] + size[0], min[1] + size[1]],
+                self.colors.window,
+            )
+            .build();
+    }
+
+    /// Registers the child region that was just ended
+    pub fn track_child(&mut self, ui: &Ui, name: &str) {
+        if !self.enabled {
+            return;
+        }
+        self.start_frame(ui);
+        let (min, max) = (ui.item_rect_min(), ui.item_rect_max());
+        self.children.push((name.to_string(), min, max));
+        ui.get_foreground_draw_list()
+            .add_rect(min, max, self.colors.child)
+            .build();
+    }
+
+    /// Registers the last drawn item
+    pub fn track(&mut self, ui: &Ui, label: &str) {
+        if !self.enabled {
+            return;
+        }
+        self.start_frame(ui);
+        let (min, max) = (ui.item_rect_min(), ui.item_rect_max());
+        ui.get_foreground_draw_list()
+            .add_rect(min, max, self.colors.item)
+            .build();
+        if !ui.is_item_hovered() {
+            return;
+        }
+        let mut path = vec![self.window.clone()];
+        path.extend(
+            self.children
+                .iter()
+                .filter(|(_, child_min, child_max)| {
+                    child_min[0] <= min[0]
+                        && child_min[1] <= min[1]
+                        && child_max[0] >= max[0]
+                        && child_max[1] >= max[1]
+                })
+                .map(|(name, _, _)| name.clone()),
+        );
+        path.push(label.to_string());
+        self.hovered = Some(InspectedItem {
+            // SAFETY: only reads the last item of the current window, which `ui` guarantees exists
+            id: unsafe { sys::igGetItemID() },
+            path,
+            min,
+            max,
+        });
+    }
+
+    /// Outlines the drawn windows, highlights the hovered item or window and shows its layout
+    /// values, call once per frame after the rest of the UI
+    pub fn draw(&mut self, ui: &Ui) {
+        if !self.enabled {
+            return;
+        }
+        self.start_frame(ui);
+        let style = ui.clone_style();
+        let draw_list = ui.get_foreground_draw_list();
+        for window in &self.drawn {
+            let color = if window.is_child() {
+                self.colors.child
+            } else {
+                self.colors.window
+            };
+            draw_list.add_rect(window.min, window.max, color).build();
+        }
+        let Some(item) = &self.hovered else {
+            if let Some(window) = self.window_at(ui.io().mouse_pos) {
+                draw_list
+                    .add_rect(window.min, window.max, self.colors.hovered)
+                    .thickness(2.0)
+                    .build();
+                ui.tooltip(|| {
+                    ui.text(window.name.replace('/', " > "));
+                    ui.separator();
+                    ui.text(format!(
+                        "Position  {:.0}, {:.0}",
+                        window.min[0], window.min[1]
+                    ));
+                    ui.text(format!(
+                        "Size      {:.0} x {:.0}",
+                        window.max[0] - window.min[0],
+                        window.max[1] - window.min[1]
+                    ));
+                    ui.text(format!(
+                        "Window padding  {:.0}, {:.0}",
+                        style.window_padding[0], style.window_padding[1]
+                    ));
+                });
+            }
+            return;
+        };
+        let padding = style.frame_padding;
+        draw_list
+            .add_rect(item.min, item.max, self.colors.padding)
+            .filled(true)
+            .build();
+        draw_list
+            .add_rect(
+                [item.min[0] + padding[0], item.min[1] + padding[1]],
+                [item.max[0] - padding[0], item.max[1] - padding[1]],
+                self.colors.hovered,
+            )
+            .build();
+        draw_list
+            .add_rect(item.min, item.max, self.colors.hovered)
+            .thickness(2.0)
+            .build();
+        ui.tooltip(|| {
+            ui.text(item.path.join(" > "));
+            ui.text_disabled(format!("ID {:#010x}", item.id));
+            ui.separator();
+            ui.text(format!("Position  {:.0}, {:.0}", item.min[0], item.min[1]));
+            ui.text(format!(
+                "Size      {:.0} x {:.0}",
+                item.max[0] - item.min[0],
+                item.max[1] - item.min[1]
+            ));
+            ui.text(format!(
+                "Frame padding   {:.0}, {:.0}",
+                padding[0], padding[1]
+            ));
+            ui.text(format!(
+                "Item spacing    {:.0}, {:.0}",
+                style.item_spacing[0], style.item_spacing[1]
+            ));
+            ui.text(format!(
+                "Window padding  {:.0}, {:.0}",
+                style.window_padding[0], style.window_padding[1]
+            ));
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::Harness;
+    use imgui::Condition;
+
+    #[test]
+    fn untracked_windows_are_found() {
+        let mut harness = Harness::new();
+        let mut inspector = LayoutInspector::new();
+        inspector.set_enabled(true);
+        harness.move_mouse([50.0, 50.0]);
+        for _ in 0..2 {
+            inspector.set_windows(harness.drawn_windows());
+            harness.frame(|ui| {
+                ui.window("Stats")
+                    .position([20.0, 20.0], Condition::Always)
+                    .size([100.0, 100.0], Condition::Always)
+                    .build(|| ui.text("FPS"));
+                inspector.draw(ui);
+            });
+        }
+        assert_eq!(inspector.window_at([50.0, 50.0]).unwrap().name, "Stats");
+        assert!(inspector.window_at([200.0, 200.0]).is_none());
+    }
+}
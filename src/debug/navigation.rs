@@ -0,0 +1,344 @@
+use crate::coords::Coords;
+use crate::FrameTimer;
+use bugsyth_engine::context::camera::CameraState;
+use bugsyth_engine::math::Vec3;
+use imgui::{Condition, Drag, MouseButton, Ui};
+use std::collections::{BTreeMap, HashSet};
+use std::fmt;
+use std::time::Instant;
+
+/// Colors of area types without one set, by area number, repeated past the sixth
+const AREA_COLORS: [[f32; 4]; 6] = [
+    [0.3, 0.6, 0.9, 1.0],
+    [0.4, 0.8, 0.3, 1.0],
+    [0.9, 0.6, 0.2, 1.0],
+    [0.7, 0.4, 0.9, 1.0],
+    [0.9, 0.3, 0.4, 1.0],
+    [0.3, 0.8, 0.8, 1.0],
+];
+const PATH_COLOR: [f32; 4] = [1.0, 1.0, 0.3, 1.0];
+const START_COLOR: [f32; 4] = [0.3, 0.9, 0.3, 1.0];
+const END_COLOR: [f32; 4] = [0.9, 0.3, 0.3, 1.0];
+const HANDLE_RADIUS: f32 = 7.0;
+/// Path queries kept for the timing stats
+const QUERY_HISTORY: usize = 60;
+
+type PathQuery = Box<dyn FnMut(Vec3<f32>, Vec3<f32>) -> Option<Vec<Vec3<f32>>>>;
+
+/// One polygon of the navmesh, in world space
+#[derive(Clone, Debug, PartialEq)]
+pub struct NavPolygon {
+    /// Corners in order around the polygon. Filled as a convex polygon, which navmesh polygons
+    /// are.
+    pub vertices: Vec<Vec3<f32>>,
+    /// Area type, e.g. ground, water or a door, picks the color
+    pub area: u32,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum Handle {
+    Start,
+    End,
+}
+
+/// Navigation debugging: the navmesh drawn over the game view colored by area type, and a path
+/// query between a start and an end that can be dragged around the view, with timing stats.
+///
+/// The engine has no navigation of its own, fill [`polygons`](Self::polygons) from the game's
+/// navmesh and run the game's pathfinding in [`with_query`](Self::with_query). The query reruns
+/// whenever the start or the end moves.
+pub struct NavigationPanel {
+    pub polygons: Vec<NavPolygon>,
+    /// Names and colors by area type
+    areas: BTreeMap<u32, (String, [f32; 4])>,
+    hidden_areas: HashSet<u32>,
+    pub start: Vec3<f32>,
+    pub end: Vec3<f32>,
+    query: Option<PathQuery>,
+    /// Points of the last query's path, `None` when it found none
+    path: Option<Vec<Vec3<f32>>>,
+    /// Query times in [`FrameTimer`] form, so the same stats apply
+    query_times: FrameTimer,
+    queries: usize,
+    dirty: bool,
+    pub show_mesh: bool,
+    pub show_path: bool,
+    /// Opacity of the polygon fill, edges are drawn opaque
+    pub fill_alpha: f32,
+    dragging: Option<Handle>,
+}
+
+impl fmt::Debug for NavigationPanel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("NavigationPanel")
+            .field("polygons", &self.polygons.len())
+            .field("start", &self.start)
+            .field("end", &self.end)
+            .field("path", &self.path)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Default for NavigationPanel {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl NavigationPanel {
+    pub fn new() -> Self {
+        Self {
+            polygons: Vec::new(),
+            areas: BTreeMap::new(),
+            hidden_areas: HashSet::new(),
+            start: Vec3::zero(),
+            end: Vec3::unit_x(),
+            query: None,
+            path: None,
+            query_times: FrameTimer::with_window(QUERY_HISTORY),
+            queries: 0,
+            dirty: false,
+            show_mesh: true,
+            show_path: true,
+            fill_alpha: 0.25,
+            dragging: None,
+        }
+    }
+    /// Finds a path from the start to the end, `None` when there's none. Gets timed by the panel.
+    pub fn with_query<F>(mut self, query: F) -> Self
+    where
+        F: FnMut(Vec3<f32>, Vec3<f32>) -> Option<Vec<Vec3<f32>>> + 'static,
+    {
+        self.query = Some(Box::new(query));
+        self.dirty = true;
+        self
+    }
+    /// Names an area type and sets its color
+    pub fn set_area(&mut self, area: u32, name: impl Into<String>, color: [f32; 4]) {
+        self.areas.insert(area, (name.into(), color));
+    }
+    fn area_color(&self, area: u32) -> [f32; 4] {
+        self.areas.get(&area).map_or_else(
+            || AREA_COLORS[area as usize % AREA_COLORS.len()],
+            |(_, color)| *color,
+        )
+    }
+
+    /// Moves the query's start and end, the query reruns on the next draw
+    pub fn set_query(&mut self, start: Vec3<f32>, end: Vec3<f32>) {
+        self.start = start;
+        self.end = end;
+        self.dirty = true;
+    }
+    /// Reruns the query on the next draw, e.g. after the navmesh changed
+    pub fn invalidate(&mut self) {
+        self.dirty = true;
+    }
+    /// Points of the last query's path, `None` when it found none
+    pub fn path(&self) -> Option<&[Vec3<f32>]> {
+        self.path.as_deref()
+    }
+    /// Length of the last query's path in world units
+    pub fn path_length(&self) -> Option<f32> {
+        let path = self.path.as_ref()?;
+        Some(path.windows(2).map(|pair| pair[0].distance(pair[1])).sum())
+    }
+    /// Query times in milliseconds
+    pub fn query_times(&self) -> &FrameTimer {
+        &self.query_times
+    }
+
+    fn refresh(&mut self) {
+        if !std::mem::take(&mut self.dirty) {
+            return;
+        }
+        let Some(query) = &mut self.query else {
+            return;
+        };
+        let started = Instant::now();
+        self.path = query(self.start, self.end);
+        self.query_times.record(started.elapsed().as_secs_f32());
+        self.queries += 1;
+    }
+
+    pub fn draw(&mut self, ui: &Ui, opened: &mut bool) {
+        self.refresh();
+        ui.window("Navigation")
+            .opened(opened)
+            .size([340.0, 420.0], Condition::FirstUseEver)
+            .build(|| {
+                ui.text(format!("{} polygons", self.polygons.len()));
+                ui.checkbox("Show mesh", &mut self.show_mesh);
+                ui.same_line();
+                ui.checkbox("Show path", &mut self.show_path);
+                ui.slider("Fill opacity", 0.0, 1.0, &mut self.fill_alpha);
+                self.draw_areas(ui);
+
+                ui.separator();
+                let mut start = self.start.into_array();
+                let mut end = self.end.into_array();
+                let moved = Drag::new("Start").speed(0.05).build_array(ui, &mut start)
+                    | Drag::new("End").speed(0.05).build_array(ui, &mut end);
+                if moved {
+                    self.set_query(start.into(), end.into());
+                }
+                if self.query.is_none() {
+                    ui.text_disabled("No path query set");
+                    return;
+                }
+                if ui.button("Query again") {
+                    self.dirty = true;
+                }
+                match (self.path(), self.path_length()) {
+                    (Some(path), Some(length)) => {
+                        ui.text(format!("{} points, length {:.2}", path.len(), length))
+                    }
+                    _ => ui.text_colored(END_COLOR, "No path"),
+                }
+
+                ui.separator();
+                let times = &self.query_times;
+                ui.text(format!(
+                    "Last {:.3} ms, average {:.3} ms, worst {:.3} ms",
+                    times.last().unwrap_or_default(),
+                    times.average(),
+                    times.worst()
+                ));
+                ui.text_disabled(format!("{} queries", self.queries));
+                ui.plot_lines("##query_times", times.history())
+                    .scale_min(0.0)
+                    .graph_size([0.0, 40.0])
+                    .build();
+            });
+    }
+
+    /// Legend of the area types in the mesh, unticking one hides its polygons
+    fn draw_areas(&mut self, ui: &Ui) {
+        let mut areas: Vec<u32> = self.polygons.iter().map(|polygon| polygon.area).collect();
+        areas.sort_unstable();
+        areas.dedup();
+        for area in areas {
+            let name = self
+                .areas
+                .get(&area)
+                .map_or_else(|| format!("Area {}", area), |(name, _)| name.clone());
+            let mut shown = !self.hidden_areas.contains(&area);
+            ui.color_button(format!("##area{}", area), self.area_color(area));
+            ui.same_line();
+            if ui.checkbox(format!("{}##area{}", name, area), &mut shown) {
+                if shown {
+                    self.hidden_areas.remove(&area);
+                } else {
+                    self.hidden_areas.insert(area);
+                }
+            }
+        }
+    }
+
+    /// Draws the navmesh, the path and the start and end handles over the game view, on the
+    /// background draw list. Call once per frame.
+    ///
+    /// The handles can be dragged where the UI doesn't want the mouse, along the horizontal plane
+    /// they're on. Returns `true` when one moved.
+    pub fn draw_overlay(&mut self, ui: &Ui, coords: &Coords, camera: &CameraState) -> bool {
+        let moved = self.drag_handles(ui, coords, camera);
+        self.refresh();
+        let project = |pos: Vec3<f32>| coords.world_to_ui(camera, pos);
+        let draw_list = ui.get_background_draw_list();
+        if self.show_mesh {
+            for polygon in &self.polygons {
+                if self.hidden_areas.contains(&polygon.area) {
+                    continue;
+                }
+                // Polygons reaching behind the camera would be projected through it
+                let Some(points) = polygon
+                    .vertices
+                    .iter()
+                    .map(|&vertex| project(vertex))
+                    .collect::<Option<Vec<_>>>()
+                else {
+                    continue;
+                };
+                let color = self.area_color(polygon.area);
+                let mut fill = color;
+                fill[3] *= self.fill_alpha;
+                draw_list
+                    .add_polyline(points.clone(), fill)
+                    .filled(true)
+                    .build();
+                let mut outline = points;
+                outline.extend(outline.first().copied());
+                draw_list.add_polyline(outline, color).build();
+            }
+        }
+        if self.show_path {
+            if let Some(path) = &self.path {
+                let points: Vec<Option<[f32; 2]>> = path.iter().map(|&p| project(p)).collect();
+                for pair in points.windows(2) {
+                    if let [Some(a), Some(b)] = *pair {
+                        draw_list.add_line(a, b, PATH_COLOR).thickness(2.5).build();
+                    }
+                }
+                for point in points.into_iter().flatten() {
+                    draw_list
+                        .add_circle(point, 3.0, PATH_COLOR)
+                        .filled(true)
+                        .build();
+                }
+            }
+        }
+        for (pos, color, label) in [(self.start, START_COLOR, "S"), (self.end, END_COLOR, "E")] {
+            if let Some(center) = project(pos) {
+                draw_list
+                    .add_circle(center, HANDLE_RADIUS, color)
+                    .filled(true)
+                    .build();
+                draw_list.add_text(
+                    [center[0] - 3.5, center[1] - 6.5],
+                    [0.0, 0.0, 0.0, 1.0],
+                    label,
+                );
+            }
+        }
+        moved
+    }
+
+    fn drag_handles(&mut self, ui: &Ui, coords: &Coords, camera: &CameraState) -> bool {
+        let mouse = ui.io().mouse_pos;
+        if ui.is_mouse_clicked(MouseButton::Left) && !ui.io().want_capture_mouse {
+            let distance = |pos: Vec3<f32>| {
+                coords
+                    .world_to_ui(camera, pos)
+                    .map_or(f32::INFINITY, |p| (p[0] - mouse[0]).hypot(p[1] - mouse[1]))
+            };
+            self.dragging = [(Handle::Start, self.start), (Handle::End, self.end)]
+                .into_iter()
+                .map(|(handle, pos)| (handle, distance(pos)))
+                .filter(|&(_, distance)| distance <= HANDLE_RADIUS)
+                .min_by(|a, b| a.1.total_cmp(&b.1))
+                .map(|(handle, _)| handle);
+        }
+        if !ui.is_mouse_down(MouseButton::Left) {
+            self.dragging = None;
+        }
+        let Some(handle) = self.dragging else {
+            return false;
+        };
+        let pos = match handle {
+            Handle::Start => &mut self.start,
+            Handle::End => &mut self.end,
+        };
+        let Some(target) = coords
+            .screen_ray(camera, mouse)
+            .intersect_plane(*pos, Vec3::unit_y())
+        else {
+            return false;
+        };
+        if target == *pos {
+            return false;
+        }
+        *pos = target;
+        self.dirty = true;
+        true
+    }
+}
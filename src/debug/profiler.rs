@@ -0,0 +1,335 @@
+use imgui::{Condition, MouseButton, StyleColor, Ui};
+use std::borrow::Cow;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fmt;
+use std::time::{Duration, Instant};
+
+const ROW_HEIGHT_LINES: f32 = 1.4;
+
+/// One timed scope of a frame
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ProfileSpan {
+    pub name: Cow<'static, str>,
+    /// Nesting level, 0 for scopes opened outside of others
+    pub depth: usize,
+    /// Since the frame started
+    pub start: Duration,
+    pub duration: Duration,
+}
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+enum View {
+    #[default]
+    Flame,
+    Bars,
+}
+
+#[derive(Debug)]
+struct Frame {
+    start: Instant,
+    spans: Vec<ProfileSpan>,
+    /// Indices of the open spans, innermost last
+    open: Vec<usize>,
+}
+
+impl Frame {
+    fn new() -> Self {
+        Self {
+            start: Instant::now(),
+            spans: Vec::new(),
+            open: Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Inner {
+    enabled: bool,
+    paused: bool,
+    current: Frame,
+    /// Spans and length of the last finished frame
+    last: Vec<ProfileSpan>,
+    last_length: Duration,
+    view: View,
+}
+
+/// Records nested timing scopes per frame and draws the last frame as a flame graph.
+///
+/// Call [`begin_frame`](Self::begin_frame) once per frame, e.g. at the start of the engine's
+/// update, and open scopes with [`scope`](Self::scope); the scope ends when its guard is
+/// dropped. Scopes do nothing while the profiler is disabled, so they can stay in release
+/// builds. The profiler is meant for the main thread, it isn't `Sync`.
+pub struct Profiler {
+    inner: RefCell<Inner>,
+}
+
+impl fmt::Debug for Profiler {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let inner = self.inner.borrow();
+        f.debug_struct("Profiler")
+            .field("enabled", &inner.enabled)
+            .field("paused", &inner.paused)
+            .field("last_length", &inner.last_length)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Default for Profiler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Ends its scope when dropped, see [`Profiler::scope`]
+#[must_use = "the scope ends when the guard is dropped"]
+pub struct ProfileScope<'a> {
+    profiler: &'a Profiler,
+    /// Start of the frame and index of the span, `None` when the profiler was disabled
+    span: Option<(Instant, usize)>,
+}
+
+impl Drop for ProfileScope<'_> {
+    fn drop(&mut self) {
+        let Some((frame_start, index)) = self.span else {
+            return;
+        };
+        let mut inner = self.profiler.inner.borrow_mut();
+        let frame = &mut inner.current;
+        if frame.start != frame_start {
+            // The frame ended while the scope was open
+            return;
+        }
+        let span = &mut frame.spans[index];
+        span.duration = frame.start.elapsed().saturating_sub(span.start);
+        if let Some(position) = frame.open.iter().rposition(|&open| open == index) {
+            frame.open.truncate(position);
+        }
+    }
+}
+
+impl Profiler {
+    /// Disabled profiler, turn it on with [`set_enabled`](Self::set_enabled) or in its window
+    pub fn new() -> Self {
+        Self {
+            inner: RefCell::new(Inner {
+                enabled: false,
+                paused: false,
+                current: Frame::new(),
+                last: Vec::new(),
+                last_length: Duration::ZERO,
+                view: View::default(),
+            }),
+        }
+    }
+    pub fn is_enabled(&self) -> bool {
+        self.inner.borrow().enabled
+    }
+    pub fn set_enabled(&self, enabled: bool) {
+        self.inner.borrow_mut().enabled = enabled;
+    }
+    /// Keeps showing the current last frame while paused
+    pub fn set_paused(&self, paused: bool) {
+        self.inner.borrow_mut().paused = paused;
+    }
+
+    /// Ends the previous frame and starts a new one
+    pub fn begin_frame(&self) {
+        let mut inner = self.inner.borrow_mut();
+        let finished = std::mem::replace(&mut inner.current, Frame::new());
+        if !inner.enabled || inner.paused {
+            return;
+        }
+        inner.last_length = finished.start.elapsed();
+        inner.last = finished.spans;
+    }
+    /// Times the code until the returned guard is dropped, scopes opened meanwhile nest below it
+    pub fn scope(&self, name: impl Into<Cow<'static, str>>) -> ProfileScope<'_> {
+        let mut inner = self.inner.borrow_mut();
+        if !inner.enabled {
+            return ProfileScope {
+                profiler: self,
+                span: None,
+            };
+        }
+        let frame = &mut inner.current;
+        let index = frame.spans.len();
+        frame.spans.push(ProfileSpan {
+            name: name.into(),
+            depth: frame.open.len(),
+            start: frame.start.elapsed(),
+            duration: Duration::ZERO,
+        });
+        frame.open.push(index);
+        ProfileScope {
+            profiler: self,
+            span: Some((frame.start, index)),
+        }
+    }
+    /// Runs `f` inside a scope
+    pub fn time<R>(&self, name: impl Into<Cow<'static, str>>, f: impl FnOnce() -> R) -> R {
+        let _scope = self.scope(name);
+        f()
+    }
+
+    /// Spans of the last finished frame in the order they were opened
+    pub fn last_frame(&self) -> Vec<ProfileSpan> {
+        self.inner.borrow().last.clone()
+    }
+    /// Time spent in each scope name during the last frame, nested scopes counted in their
+    /// parents too, longest first
+    pub fn totals(&self) -> Vec<(Cow<'static, str>, Duration)> {
+        let inner = self.inner.borrow();
+        let mut totals: HashMap<&Cow<'static, str>, Duration> = HashMap::new();
+        for span in &inner.last {
+            *totals.entry(&span.name).or_default() += span.duration;
+        }
+        let mut totals: Vec<_> = totals
+            .into_iter()
+            .map(|(name, total)| (name.clone(), total))
+            .collect();
+        totals.sort_by_key(|&(_, total)| std::cmp::Reverse(total));
+        totals
+    }
+
+    /// Window with the last frame as a flame graph or as totals per scope name. Right click the
+    /// flame graph to pause or resume.
+    pub fn draw(&self, ui: &Ui, opened: &mut bool) {
+        ui.window("Profiler")
+            .opened(opened)
+            .size([640.0, 280.0], Condition::FirstUseEver)
+            .build(|| {
+                {
+                    let mut inner = self.inner.borrow_mut();
+                    ui.checkbox("Enabled", &mut inner.enabled);
+                    ui.same_line();
+                    ui.checkbox("Pause", &mut inner.paused);
+                    ui.same_line();
+                    ui.radio_button("Flame graph", &mut inner.view, View::Flame);
+                    ui.same_line();
+                    ui.radio_button("Totals", &mut inner.view, View::Bars);
+                    ui.same_line();
+                    ui.text_disabled(format!(
+                        "Frame {:.2} ms",
+                        inner.last_length.as_secs_f64() * 1000.0
+                    ));
+                }
+                ui.separator();
+                let view = self.inner.borrow().view;
+                match view {
+                    View::Flame => self.draw_flame(ui),
+                    View::Bars => self.draw_totals(ui),
+                }
+            });
+    }
+
+    fn draw_flame(&self, ui: &Ui) {
+        let inner = self.inner.borrow();
+        let length = inner.last_length.as_secs_f32().max(f32::EPSILON);
+        let row_height = ui.text_line_height() * ROW_HEIGHT_LINES;
+        let depth = inner
+            .last
+            .iter()
+            .map(|span| span.depth + 1)
+            .max()
+            .unwrap_or(0);
+        let width = ui.content_region_avail()[0];
+        let origin = ui.cursor_screen_pos();
+        ui.invisible_button(
+            "##flame",
+            [width.max(1.0), (depth as f32 * row_height).max(1.0)],
+        );
+        let hovered = ui.is_item_hovered();
+        let mouse = ui.io().mouse_pos;
+        let draw_list = ui.get_window_draw_list();
+        let text_color = ui.style_color(StyleColor::Text);
+        let mut tooltip = None;
+        for span in &inner.last {
+            let min = [
+                origin[0] + span.start.as_secs_f32() / length * width,
+                origin[1] + span.depth as f32 * row_height,
+            ];
+            let max = [
+                (min[0] + span.duration.as_secs_f32() / length * width).max(min[0] + 1.0),
+                min[1] + row_height - 1.0,
+            ];
+            draw_list
+                .add_rect(min, max, span_color(&span.name))
+                .filled(true)
+                .build();
+            let label = format!(
+                "{} {:.2} ms",
+                span.name,
+                span.duration.as_secs_f64() * 1000.0
+            );
+            if ui.calc_text_size(&label)[0] + 4.0 < max[0] - min[0] {
+                draw_list.add_text([min[0] + 2.0, min[1] + 1.0], text_color, &label);
+            }
+            if hovered
+                && (min[0]..max[0]).contains(&mouse[0])
+                && (min[1]..max[1]).contains(&mouse[1])
+            {
+                tooltip = Some(label);
+            }
+        }
+        if let Some(label) = tooltip {
+            ui.tooltip_text(label);
+        }
+        if inner.last.is_empty() {
+            ui.text_disabled(if inner.enabled {
+                "No scopes recorded last frame"
+            } else {
+                "Enable the profiler to record scopes"
+            });
+        }
+        drop(inner);
+        if hovered && ui.is_mouse_clicked(MouseButton::Right) {
+            let mut inner = self.inner.borrow_mut();
+            inner.paused = !inner.paused;
+        }
+    }
+
+    fn draw_totals(&self, ui: &Ui) {
+        let length = self
+            .inner
+            .borrow()
+            .last_length
+            .as_secs_f32()
+            .max(f32::EPSILON);
+        for (name, total) in self.totals() {
+            let ms = total.as_secs_f64() * 1000.0;
+            imgui::ProgressBar::new(total.as_secs_f32() / length)
+                .size([ui.content_region_avail()[0] * 0.5, 0.0])
+                .overlay_text(format!("{:.2} ms", ms))
+                .build(ui);
+            ui.same_line();
+            ui.text(&name);
+        }
+    }
+}
+
+/// Stable color per scope name, so a scope keeps its color between frames
+fn span_color(name: &str) -> [f32; 4] {
+    let hash = name.bytes().fold(2166136261u32, |hash, byte| {
+        (hash ^ byte as u32).wrapping_mul(16777619)
+    });
+    let hue = (hash % 360) as f32 / 360.0;
+    let (r, g, b) = hsv_to_rgb(hue, 0.45, 0.75);
+    [r, g, b, 1.0]
+}
+
+fn hsv_to_rgb(h: f32, s: f32, v: f32) -> (f32, f32, f32) {
+    let i = (h * 6.0).floor();
+    let f = h * 6.0 - i;
+    let p = v * (1.0 - s);
+    let q = v * (1.0 - f * s);
+    let t = v * (1.0 - (1.0 - f) * s);
+    match i as i32 % 6 {
+        0 => (v, t, p),
+        1 => (q, v, p),
+        2 => (p, v, t),
+        3 => (p, q, v),
+        4 => (t, p, v),
+        _ => (v, p, q),
+    }
+}
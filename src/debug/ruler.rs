@@ -0,0 +1,266 @@
+use super::DrawnWindow;
+use imgui::{Condition, Key, MouseButton, MouseCursor, Ui};
+
+const LINE_COLOR: [f32; 4] = [1.0, 0.85, 0.1, 1.0];
+const GUIDE_COLOR: [f32; 4] = [0.2, 0.8, 1.0, 0.6];
+const LABEL_BACKGROUND: [f32; 4] = [0.0, 0.0, 0.0, 0.75];
+/// How close in pixels a point has to be to an edge to snap to it
+const SNAP_DISTANCE: f32 = 6.0;
+
+/// Distance and angle between two points on screen, in imgui's logical pixels
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Measurement {
+    pub start: [f32; 2],
+    pub end: [f32; 2],
+}
+
+impl Measurement {
+    pub fn dx(&self) -> f32 {
+        self.end[0] - self.start[0]
+    }
+    pub fn dy(&self) -> f32 {
+        self.end[1] - self.start[1]
+    }
+    pub fn distance(&self) -> f32 {
+        self.dx().hypot(self.dy())
+    }
+    /// Counterclockwise from the positive x axis in degrees, so up is 90 like on paper
+    pub fn angle(&self) -> f32 {
+        (-self.dy()).atan2(self.dx()).to_degrees()
+    }
+}
+
+/// Overlay measuring distances and angles by dragging across the screen.
+///
+/// Points snap to the edges of the screen and of every window and child region drawn in the
+/// last rendered frame, passed to [`set_windows`](Self::set_windows) each frame. Edges inside a
+/// window, like those of its items, can be added with [`track_window`](Self::track_window) this
+/// frame. Hold Alt while dragging to turn snapping off.
+#[derive(Debug, Default)]
+pub struct Ruler {
+    enabled: bool,
+    frame: i32,
+    edges_x: Vec<f32>,
+    edges_y: Vec<f32>,
+    /// Edges of the drawn windows
+    window_edges: [Vec<f32>; 2],
+    dragging: bool,
+    measurement: Option<Measurement>,
+}
+
+impl Ruler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+        self.dragging = false;
+        if !enabled {
+            self.measurement = None;
+        }
+    }
+    pub fn toggle(&mut self) {
+        self.set_enabled(!self.enabled);
+    }
+    /// The current or last measurement
+    pub fn measurement(&self) -> Option<Measurement> {
+        self.measurement
+    }
+
+    /// Sets the windows whose edges are snapped to, usually those of the last rendered frame
+    pub fn set_windows(&mut self, windows: &[DrawnWindow]) {
+        let [edges_x, edges_y] = &mut self.window_edges;
+        edges_x.clear();
+        edges_y.clear();
+        if !self.enabled {
+            return;
+        }
+        // The ruler's own window covers the screen, whose edges are always snapped to
+        for window in windows.iter().filter(|window| window.name != "##ruler") {
+            edges_x.extend([window.min[0], window.max[0]]);
+            edges_y.extend([window.min[1], window.max[1]]);
+        }
+    }
+
+    /// Registers the edges of the current window as snap guides, call inside the window
+    pub fn track_window(&mut self, ui: &Ui) {
+        if !self.enabled {
+            return;
+        }
+        self.start_frame(ui);
+        let [x, y] = ui.window_pos();
+        let [w, h] = ui.window_size();
+        self.edges_x.extend([x, x + w]);
+        self.edges_y.extend([y, y + h]);
+    }
+
+    fn start_frame(&mut self, ui: &Ui) {
+        let frame = ui.frame_count();
+        if frame != self.frame {
+            self.frame = frame;
+            self.edges_x.clear();
+            self.edges_y.clear();
+        }
+    }
+
+    /// Returns the snapped point and the edges it snapped to
+    fn snap(&self, ui: &Ui, point: [f32; 2]) -> ([f32; 2], [Option<f32>; 2]) {
+        if ui.io().key_alt {
+            return (point, [None, None]);
+        }
+        let display_size = ui.io().display_size;
+        let nearest = |value: f32, edges: &[f32], windows: &[f32], max: f32| {
+            edges
+                .iter()
+                .chain(windows)
+                .chain(&[0.0, max])
+                .copied()
+                .map(|edge| (edge, (edge - value).abs()))
+                .filter(|&(_, distance)| distance <= SNAP_DISTANCE)
+                .min_by(|a, b| a.1.total_cmp(&b.1))
+                .map(|(edge, _)| edge)
+        };
+        let snapped = [
+            nearest(
+                point[0],
+                &self.edges_x,
+                &self.window_edges[0],
+                display_size[0],
+            ),
+            nearest(
+                point[1],
+                &self.edges_y,
+                &self.window_edges[1],
+                display_size[1],
+            ),
+        ];
+        (
+            [
+                snapped[0].unwrap_or(point[0]),
+                snapped[1].unwrap_or(point[1]),
+            ],
+            snapped,
+        )
+    }
+
+    /// Draws the overlay while enabled, call once per frame after the rest of the UI.
+    ///
+    /// Right click clears the measurement, Escape turns the ruler off.
+    pub fn draw(&mut self, ui: &Ui) {
+        if !self.enabled {
+            return;
+        }
+        if ui.is_key_pressed(Key::Escape) {
+            self.set_enabled(false);
+            return;
+        }
+        self.start_frame(ui);
+        let display_size = ui.io().display_size;
+        let (mouse, snapped) = self.snap(ui, ui.io().mouse_pos);
+        // Invisible window covering everything so the drag doesn't reach other windows or the game
+        ui.window("##ruler")
+            .position([0.0, 0.0], Condition::Always)
+            .size(display_size, Condition::Always)
+            .no_decoration()
+            .draw_background(false)
+            .save_settings(false)
+            .focused(true)
+            .build(|| {
+                ui.invisible_button("##ruler_area", display_size);
+                ui.set_mouse_cursor(Some(MouseCursor::Arrow));
+                if ui.is_item_activated() {
+                    self.dragging = true;
+                    self.measurement = Some(Measurement {
+                        start: mouse,
+                        end: mouse,
+                    });
+                }
+                if ui.is_mouse_clicked(MouseButton::Right) {
+                    self.dragging = false;
+                    self.measurement = None;
+                }
+            });
+        if let Some(measurement) = self.measurement.as_mut().filter(|_| self.dragging) {
+            measurement.end = mouse;
+            self.dragging = ui.is_mouse_down(MouseButton::Left);
+        }
+
+        let draw_list = ui.get_foreground_draw_list();
+        if snapped[0].is_some() {
+            draw_list
+                .add_line([mouse[0], 0.0], [mouse[0], display_size[1]], GUIDE_COLOR)
+                .build();
+        }
+        if snapped[1].is_some() {
+            draw_list
+                .add_line([0.0, mouse[1]], [display_size[0], mouse[1]], GUIDE_COLOR)
+                .build();
+        }
+        let Some(measurement) = self.measurement else {
+            return;
+        };
+        let (start, end) = (measurement.start, measurement.end);
+        draw_list
+            .add_line(start, end, LINE_COLOR)
+            .thickness(1.0)
+            .build();
+        for point in [start, end] {
+            draw_list
+                .add_circle(point, 3.0, LINE_COLOR)
+                .filled(true)
+                .build();
+        }
+        let label = format!(
+            "{:.1} px  {:.1}°\ndx {:.1}  dy {:.1}",
+            measurement.distance(),
+            measurement.angle(),
+            measurement.dx(),
+            measurement.dy()
+        );
+        let size = ui.calc_text_size(&label);
+        let position = [
+            (end[0] + 12.0).min(display_size[0] - size[0] - 4.0),
+            (end[1] + 12.0).min(display_size[1] - size[1] - 4.0),
+        ];
+        draw_list
+            .add_rect(
+                [position[0] - 4.0, position[1] - 2.0],
+                [position[0] + size[0] + 4.0, position[1] + size[1] + 2.0],
+                LABEL_BACKGROUND,
+            )
+            .filled(true)
+            .rounding(3.0)
+            .build();
+        draw_list.add_text(position, LINE_COLOR, label);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::Harness;
+
+    #[test]
+    fn snaps_to_drawn_windows() {
+        let mut harness = Harness::new();
+        let mut ruler = Ruler::new();
+        ruler.set_enabled(true);
+        let build = |ui: &Ui| {
+            ui.window("Map")
+                .position([100.0, 80.0], Condition::Always)
+                .size([200.0, 120.0], Condition::Always)
+                .build(|| ui.text("Map"));
+        };
+        harness.run(2, build);
+        ruler.set_windows(harness.drawn_windows());
+        let window = harness.drawn_windows()[0].clone();
+        harness.frame(|ui| {
+            let (point, snapped) = ruler.snap(ui, [window.min[0] + 3.0, 150.0]);
+            assert_eq!(point, [window.min[0], 150.0]);
+            assert_eq!(snapped, [Some(window.min[0]), None]);
+        });
+    }
+}
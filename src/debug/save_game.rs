@@ -0,0 +1,273 @@
+use crate::widgets::value_editor;
+use imgui::{Condition, Ui};
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::Value;
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+const ERROR_COLOR: [f32; 4] = [0.9, 0.35, 0.35, 1.0];
+
+type Decode<T> = Box<dyn Fn(&[u8]) -> Result<T, String>>;
+type Encode<T> = Box<dyn Fn(&T) -> Result<Vec<u8>, String>>;
+type Validate<T> = Box<dyn Fn(&T) -> Result<(), String>>;
+
+/// Why a save couldn't be loaded, checked or written by a [`SaveGamePanel`]
+#[derive(Debug)]
+pub enum SaveGameError {
+    Io(io::Error),
+    /// The file isn't a save of the panel's type
+    Decode(String),
+    Encode(String),
+    Json(serde_json::Error),
+    /// The edits don't fit the save type or the validator rejected them
+    Invalid(String),
+    /// Nothing loaded or no file to save to
+    NoSave,
+}
+
+impl Error for SaveGameError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            SaveGameError::Io(e) => Some(e),
+            SaveGameError::Json(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for SaveGameError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SaveGameError::Io(e) => write!(f, "Save file error: {}", e),
+            SaveGameError::Decode(e) => write!(f, "Not a valid save: {}", e),
+            SaveGameError::Encode(e) => write!(f, "Encoding the save failed: {}", e),
+            SaveGameError::Json(e) => write!(f, "Converting the save failed: {}", e),
+            SaveGameError::Invalid(e) => write!(f, "Invalid save: {}", e),
+            SaveGameError::NoSave => write!(f, "No save loaded"),
+        }
+    }
+}
+
+impl From<io::Error> for SaveGameError {
+    fn from(e: io::Error) -> SaveGameError {
+        SaveGameError::Io(e)
+    }
+}
+
+impl From<serde_json::Error> for SaveGameError {
+    fn from(e: serde_json::Error) -> SaveGameError {
+        SaveGameError::Json(e)
+    }
+}
+
+/// Save game inspector: loads a save file of type `T`, shows it in [`value_editor`] and writes
+/// the edits back.
+///
+/// Saves are JSON by default, [`with_codec`](Self::with_codec) reads and writes any other
+/// format. Edits are checked on every change by deserializing them into `T` and running the
+/// [validator](Self::with_validator), Save stays disabled while they don't pass. Files are
+/// written to a temporary file next to the save first, so a failed write keeps the old save.
+pub struct SaveGamePanel<T> {
+    path: Option<PathBuf>,
+    path_input: String,
+    /// As loaded or last saved
+    saved: Option<Value>,
+    edited: Option<Value>,
+    /// Why the edits can't be saved
+    invalid: Option<String>,
+    /// Result of the last load or save from the panel's buttons
+    status: Option<Result<String, String>>,
+    decode: Decode<T>,
+    encode: Encode<T>,
+    validate: Option<Validate<T>>,
+}
+
+impl<T> fmt::Debug for SaveGamePanel<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SaveGamePanel")
+            .field("path", &self.path)
+            .field("edited", &self.edited)
+            .field("invalid", &self.invalid)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<T: Serialize + DeserializeOwned + 'static> Default for SaveGamePanel<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Serialize + DeserializeOwned + 'static> SaveGamePanel<T> {
+    /// Reads and writes JSON saves
+    pub fn new() -> Self {
+        Self {
+            path: None,
+            path_input: String::new(),
+            saved: None,
+            edited: None,
+            invalid: None,
+            status: None,
+            decode: Box::new(|bytes| serde_json::from_slice(bytes).map_err(|e| e.to_string())),
+            encode: Box::new(|save| serde_json::to_vec_pretty(save).map_err(|e| e.to_string())),
+            validate: None,
+        }
+    }
+    /// Reads and writes saves in another format, e.g. the game's binary one
+    pub fn with_codec<D, E>(mut self, decode: D, encode: E) -> Self
+    where
+        D: Fn(&[u8]) -> Result<T, String> + 'static,
+        E: Fn(&T) -> Result<Vec<u8>, String> + 'static,
+    {
+        self.decode = Box::new(decode);
+        self.encode = Box::new(encode);
+        self
+    }
+    /// Checks edited saves beyond their type, e.g. that health isn't above the maximum
+    pub fn with_validator<F>(mut self, validate: F) -> Self
+    where
+        F: Fn(&T) -> Result<(), String> + 'static,
+    {
+        self.validate = Some(Box::new(validate));
+        self
+    }
+
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+    /// Edits that weren't saved yet
+    pub fn is_dirty(&self) -> bool {
+        self.edited != self.saved
+    }
+    /// Why the edits can't be saved, `None` when they can
+    pub fn invalid(&self) -> Option<&str> {
+        self.invalid.as_deref()
+    }
+
+    pub fn load(&mut self, path: impl AsRef<Path>) -> Result<(), SaveGameError> {
+        let path = path.as_ref();
+        let save = (self.decode)(&fs::read(path)?).map_err(SaveGameError::Decode)?;
+        self.set_value(&save)?;
+        self.path = Some(path.to_path_buf());
+        self.path_input = path.display().to_string();
+        Ok(())
+    }
+    /// Shows a save that isn't from a file, e.g. the game's current state. Saving needs
+    /// [`save_as`](Self::save_as) until a file was loaded.
+    pub fn set_value(&mut self, save: &T) -> Result<(), SaveGameError> {
+        let value = serde_json::to_value(save)?;
+        self.saved = Some(value.clone());
+        self.edited = Some(value);
+        self.invalid = None;
+        Ok(())
+    }
+    /// The edited save, if it deserializes and passes the validator
+    pub fn value(&self) -> Result<T, SaveGameError> {
+        let edited = self.edited.clone().ok_or(SaveGameError::NoSave)?;
+        let save: T =
+            serde_json::from_value(edited).map_err(|e| SaveGameError::Invalid(e.to_string()))?;
+        if let Some(validate) = &self.validate {
+            validate(&save).map_err(SaveGameError::Invalid)?;
+        }
+        Ok(save)
+    }
+    /// Throws the edits away
+    pub fn revert(&mut self) {
+        self.edited = self.saved.clone();
+        self.invalid = None;
+    }
+
+    /// Writes the edits to the loaded file and returns the saved value
+    pub fn save(&mut self) -> Result<T, SaveGameError> {
+        let path = self.path.clone().ok_or(SaveGameError::NoSave)?;
+        self.save_as(path)
+    }
+    /// Writes the edits to `path`, which becomes the panel's file, and returns the saved value
+    pub fn save_as(&mut self, path: impl AsRef<Path>) -> Result<T, SaveGameError> {
+        let path = path.as_ref();
+        let save = self.value()?;
+        let bytes = (self.encode)(&save).map_err(SaveGameError::Encode)?;
+        let mut temporary = path.as_os_str().to_owned();
+        temporary.push(".tmp");
+        fs::write(&temporary, bytes)?;
+        fs::rename(&temporary, path)?;
+        self.saved = self.edited.clone();
+        self.path = Some(path.to_path_buf());
+        self.path_input = path.display().to_string();
+        Ok(save)
+    }
+
+    /// Returns the save when Save was pressed and the file written, e.g. to apply it to the
+    /// running game
+    pub fn draw(&mut self, ui: &Ui, opened: &mut bool) -> Option<T> {
+        let mut saved = None;
+        ui.window("Save game")
+            .opened(opened)
+            .size([460.0, 520.0], Condition::FirstUseEver)
+            .build(|| {
+                ui.input_text("File", &mut self.path_input).build();
+                ui.same_line();
+                if ui.button("Load") {
+                    let path = PathBuf::from(&self.path_input);
+                    self.status = Some(match self.load(&path) {
+                        Ok(()) => Ok(format!("Loaded {}", path.display())),
+                        Err(e) => Err(e.to_string()),
+                    });
+                }
+                if self.edited.is_none() {
+                    ui.text_disabled("No save loaded");
+                    self.draw_status(ui);
+                    return;
+                }
+                let can_save = self.invalid.is_none() && !self.path_input.is_empty();
+                ui.disabled(!can_save, || {
+                    if ui.button("Save") {
+                        let path = PathBuf::from(&self.path_input);
+                        self.status = Some(match self.save_as(&path) {
+                            Ok(save) => {
+                                saved = Some(save);
+                                Ok(format!("Saved {}", path.display()))
+                            }
+                            Err(e) => Err(e.to_string()),
+                        });
+                    }
+                });
+                ui.same_line();
+                ui.disabled(!self.is_dirty(), || {
+                    if ui.button("Revert") {
+                        self.revert();
+                    }
+                });
+                if self.is_dirty() {
+                    ui.same_line();
+                    ui.text_disabled("Modified");
+                }
+                self.draw_status(ui);
+                if let Some(invalid) = &self.invalid {
+                    ui.text_colored(ERROR_COLOR, invalid);
+                }
+                ui.separator();
+                let mut changed = false;
+                ui.child_window("##save").build(|| {
+                    if let Some(edited) = &mut self.edited {
+                        changed = value_editor(ui, "Save", edited);
+                    }
+                });
+                if changed {
+                    self.invalid = self.value().err().map(|e| e.to_string());
+                }
+            });
+        saved
+    }
+
+    fn draw_status(&self, ui: &Ui) {
+        match &self.status {
+            Some(Ok(message)) => ui.text_disabled(message),
+            Some(Err(message)) => ui.text_colored(ERROR_COLOR, message),
+            None => (),
+        }
+    }
+}
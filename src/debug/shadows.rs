@@ -0,0 +1,223 @@
+use crate::coords::Coords;
+use crate::imgui_glium_renderer::{set_image_view, ImageView};
+use bugsyth_engine::context::camera::CameraState;
+use bugsyth_engine::math::{Mat4, Vec3, Vec4};
+use imgui::{Condition, Drag, TextureId, Ui};
+
+/// Colors of the cascades in the chart and the frustum outlines, repeated past the fourth
+const CASCADE_COLORS: [[f32; 4]; 4] = [
+    [0.9, 0.3, 0.3, 1.0],
+    [0.3, 0.8, 0.3, 1.0],
+    [0.3, 0.5, 0.9, 1.0],
+    [0.9, 0.8, 0.2, 1.0],
+];
+
+/// Corners of the NDC cube, near face first
+const CORNERS: [[f32; 3]; 8] = [
+    [-1.0, -1.0, -1.0],
+    [1.0, -1.0, -1.0],
+    [1.0, 1.0, -1.0],
+    [-1.0, 1.0, -1.0],
+    [-1.0, -1.0, 1.0],
+    [1.0, -1.0, 1.0],
+    [1.0, 1.0, 1.0],
+    [-1.0, 1.0, 1.0],
+];
+
+/// Edges of a box between [`CORNERS`]
+const EDGES: [(usize, usize); 12] = [
+    (0, 1),
+    (1, 2),
+    (2, 3),
+    (3, 0),
+    (4, 5),
+    (5, 6),
+    (6, 7),
+    (7, 4),
+    (0, 4),
+    (1, 5),
+    (2, 6),
+    (3, 7),
+];
+
+/// One cascade of a cascaded shadow map, as the game rendered it this frame
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct ShadowCascade {
+    /// The cascade's depth, registered with the renderer as a
+    /// [`TextureSource::Depth`](crate::TextureSource::Depth) or a
+    /// [`TextureSource::DepthArray`](crate::TextureSource::DepthArray) layer
+    pub texture: TextureId,
+    /// Distances from the camera the cascade covers
+    pub split: [f32; 2],
+    /// The light's projection times view for the cascade
+    pub light_view_projection: Mat4<f32>,
+}
+
+impl ShadowCascade {
+    fn color(index: usize) -> [f32; 4] {
+        CASCADE_COLORS[index % CASCADE_COLORS.len()]
+    }
+    /// World positions of the corners of the box the cascade renders, in [`CORNERS`] order
+    fn corners(&self) -> [Vec3<f32>; 8] {
+        let inverse = self.light_view_projection.inverted();
+        CORNERS.map(|[x, y, z]| {
+            let world = inverse * Vec4::new(x, y, z, 1.0);
+            Vec3::from(world) / world.w
+        })
+    }
+}
+
+/// Shadow debugging window: each cascade's depth, a chart of the split distances and the
+/// cascades' light frustums outlined over the game view.
+///
+/// The engine has no shadows of its own, fill [`cascades`](Self::cascades) with what the game
+/// rendered each frame. The depth previews use the renderer's depth view, stretched over
+/// [`depth_range`](Self::depth_range) since shadow depths tend to bunch up near 1.
+#[derive(Clone, Debug)]
+pub struct ShadowPanel {
+    pub cascades: Vec<ShadowCascade>,
+    /// Depth drawn black and white in the previews
+    pub depth_range: [f32; 2],
+    /// Outlines the light frustums in [`draw_frustums`](Self::draw_frustums)
+    pub show_frustums: bool,
+    pub thumbnail_size: f32,
+}
+
+impl Default for ShadowPanel {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ShadowPanel {
+    pub fn new() -> Self {
+        Self {
+            cascades: Vec::new(),
+            depth_range: [0.0, 1.0],
+            show_frustums: true,
+            thumbnail_size: 160.0,
+        }
+    }
+
+    pub fn draw(&mut self, ui: &Ui, opened: &mut bool) {
+        ui.window("Shadows")
+            .opened(opened)
+            .size([560.0, 420.0], Condition::FirstUseEver)
+            .build(|| {
+                if self.cascades.is_empty() {
+                    ui.text_disabled("No cascades");
+                    return;
+                }
+                Drag::new("Depth range")
+                    .speed(0.001)
+                    .range(0.0, 1.0)
+                    .display_format("%.4f")
+                    .build_array(ui, &mut self.depth_range);
+                ui.slider("Size", 64.0, 512.0, &mut self.thumbnail_size);
+                ui.checkbox("Show frustums", &mut self.show_frustums);
+                ui.separator();
+                self.draw_splits(ui);
+                ui.separator();
+                self.draw_depths(ui);
+            });
+    }
+
+    /// Bar from the camera to the last split, one colored segment per cascade
+    fn draw_splits(&self, ui: &Ui) {
+        let far = self
+            .cascades
+            .iter()
+            .map(|cascade| cascade.split[1])
+            .fold(0.0, f32::max)
+            .max(f32::EPSILON);
+        let width = ui.content_region_avail()[0].max(1.0);
+        let height = ui.text_line_height() * 1.5;
+        let origin = ui.cursor_screen_pos();
+        ui.dummy([width, height]);
+        let hovered = ui.is_item_hovered();
+        let mouse = ui.io().mouse_pos;
+        let draw_list = ui.get_window_draw_list();
+        draw_list
+            .add_rect(
+                origin,
+                [origin[0] + width, origin[1] + height],
+                [0.2, 0.2, 0.2, 1.0],
+            )
+            .filled(true)
+            .build();
+        for (index, cascade) in self.cascades.iter().enumerate() {
+            let min = [origin[0] + cascade.split[0] / far * width, origin[1]];
+            let max = [
+                origin[0] + cascade.split[1] / far * width,
+                origin[1] + height,
+            ];
+            draw_list
+                .add_rect(min, max, ShadowCascade::color(index))
+                .filled(true)
+                .build();
+            draw_list.add_text(
+                [min[0] + 2.0, min[1] + height * 0.15],
+                [0.0, 0.0, 0.0, 1.0],
+                index.to_string(),
+            );
+            if hovered && (min[0]..max[0]).contains(&mouse[0]) {
+                ui.tooltip_text(format!(
+                    "Cascade {}: {:.2} to {:.2}",
+                    index, cascade.split[0], cascade.split[1]
+                ));
+            }
+        }
+        drop(draw_list);
+        ui.text_disabled(format!("0 to {:.2}", far));
+    }
+
+    fn draw_depths(&self, ui: &Ui) {
+        let view = ImageView {
+            channel: 0,
+            mip: None,
+            depth_range: self.depth_range,
+        };
+        let size = self.thumbnail_size;
+        let spacing = ui.clone_style().item_spacing[0];
+        let per_row = ((ui.content_region_avail()[0] + spacing) / (size + spacing)).max(1.0);
+        for (index, cascade) in self.cascades.iter().enumerate() {
+            if index % per_row as usize != 0 {
+                ui.same_line();
+            }
+            ui.group(|| {
+                ui.text_colored(ShadowCascade::color(index), format!("Cascade {}", index));
+                let min = ui.cursor_screen_pos();
+                ui.dummy([size, size]);
+                let draw_list = ui.get_window_draw_list();
+                set_image_view(&draw_list, Some(view));
+                draw_list
+                    .add_image(cascade.texture, min, [min[0] + size, min[1] + size])
+                    .build();
+                set_image_view(&draw_list, None);
+            });
+        }
+    }
+
+    /// Outlines each cascade's light frustum over the game view, in the colors of the chart.
+    /// Drawn on the background draw list so windows stay on top, call once per frame.
+    pub fn draw_frustums(&self, ui: &Ui, coords: &Coords, camera: &CameraState) {
+        if !self.show_frustums {
+            return;
+        }
+        let draw_list = ui.get_background_draw_list();
+        for (index, cascade) in self.cascades.iter().enumerate() {
+            let corners = cascade
+                .corners()
+                .map(|corner| coords.world_to_ui(camera, corner));
+            for (a, b) in EDGES {
+                // Edges reaching behind the camera would be projected through it
+                if let (Some(a), Some(b)) = (corners[a], corners[b]) {
+                    draw_list
+                        .add_line(a, b, ShadowCascade::color(index))
+                        .thickness(1.5)
+                        .build();
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,301 @@
+use crate::gizmo::{GizmoCamera, Screen, Transform, AXIS_COLORS, HIGHLIGHT_COLOR};
+use crate::widgets::mat4_table;
+use bugsyth_engine::math::{Mat4, Vec3};
+use imgui::{Condition, MouseButton, TreeNodeFlags, Ui};
+
+const BONE_COLOR: [f32; 4] = [0.8, 0.8, 0.8, 0.8];
+/// How close a click has to be to a joint to select it, in pixels
+const PICK_DISTANCE: f32 = 8.0;
+
+/// One bone of a [`Skeleton`]
+#[derive(Clone, Debug, PartialEq)]
+pub struct Bone {
+    pub name: String,
+    /// Index of the parent bone, `None` for roots
+    pub parent: Option<usize>,
+    /// Transform relative to the parent, e.g. the current animation pose
+    pub local: Transform,
+}
+
+/// Bone hierarchy of an animated model, filled from the game's animation data.
+///
+/// Parents may come after their children, the world transforms are worked out in tree order.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Skeleton {
+    pub bones: Vec<Bone>,
+}
+
+impl Skeleton {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Adds a bone and returns its index
+    pub fn add_bone(
+        &mut self,
+        name: impl Into<String>,
+        parent: Option<usize>,
+        local: Transform,
+    ) -> usize {
+        self.bones.push(Bone {
+            name: name.into(),
+            parent,
+            local,
+        });
+        self.bones.len() - 1
+    }
+    pub fn find(&self, name: &str) -> Option<usize> {
+        self.bones.iter().position(|bone| bone.name == name)
+    }
+    /// Indices of the children of each bone, and of the roots
+    fn children(&self) -> (Vec<Vec<usize>>, Vec<usize>) {
+        let mut children = vec![Vec::new(); self.bones.len()];
+        let mut roots = Vec::new();
+        for (index, bone) in self.bones.iter().enumerate() {
+            match bone.parent.filter(|&parent| parent < self.bones.len()) {
+                Some(parent) => children[parent].push(index),
+                None => roots.push(index),
+            }
+        }
+        (children, roots)
+    }
+    /// Model space matrix of every bone. Bones in a parent cycle are left at the identity.
+    pub fn world_matrices(&self) -> Vec<Mat4<f32>> {
+        let (children, roots) = self.children();
+        let mut world = vec![Mat4::identity(); self.bones.len()];
+        let mut stack: Vec<(usize, Mat4<f32>)> =
+            roots.iter().map(|&root| (root, Mat4::identity())).collect();
+        while let Some((index, parent)) = stack.pop() {
+            world[index] = parent * self.bones[index].local.to_mat4();
+            stack.extend(children[index].iter().map(|&child| (child, world[index])));
+        }
+        world
+    }
+}
+
+/// Skeleton debugging window: the bone tree with the selected bone's local and world transform,
+/// and joints and axes drawn over the game view where bones can be clicked to select them.
+///
+/// Children are only built while their parent is open, so large rigs stay cheap to show.
+/// Selecting a bone in the view opens the tree down to it.
+#[derive(Clone, Debug)]
+pub struct SkeletonPanel {
+    pub selected: Option<usize>,
+    /// Draws the axes of every bone instead of only the selected one's
+    pub show_all_axes: bool,
+    /// Length of the axes in pixels
+    pub axis_length: f32,
+    /// Open the tree down to the selection on the next draw
+    reveal: bool,
+}
+
+impl Default for SkeletonPanel {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SkeletonPanel {
+    pub fn new() -> Self {
+        Self {
+            selected: None,
+            show_all_axes: false,
+            axis_length: 30.0,
+            reveal: false,
+        }
+    }
+
+    pub fn draw(&mut self, ui: &Ui, opened: &mut bool, skeleton: &Skeleton) {
+        ui.window("Skeleton")
+            .opened(opened)
+            .size([380.0, 520.0], Condition::FirstUseEver)
+            .build(|| {
+                if skeleton.bones.is_empty() {
+                    ui.text_disabled("No bones");
+                    return;
+                }
+                ui.checkbox("All axes", &mut self.show_all_axes);
+                ui.same_line();
+                ui.set_next_item_width(120.0);
+                ui.slider("Axis length", 5.0, 100.0, &mut self.axis_length);
+                let (children, roots) = skeleton.children();
+                let path = self.selection_path(skeleton);
+                let tree_height = ui.content_region_avail()[1] * 0.55;
+                ui.child_window("##bones")
+                    .size([0.0, tree_height])
+                    .border(true)
+                    .build(|| {
+                        for &root in &roots {
+                            self.draw_node(ui, skeleton, &children, &path, root);
+                        }
+                    });
+                self.reveal = false;
+                self.draw_selected(ui, skeleton);
+            });
+    }
+
+    /// The selected bone and its ancestors, empty without a selection
+    fn selection_path(&self, skeleton: &Skeleton) -> Vec<usize> {
+        let mut path = Vec::new();
+        let mut next = self.selected.filter(|&index| index < skeleton.bones.len());
+        while let Some(index) = next {
+            if path.contains(&index) {
+                break;
+            }
+            path.push(index);
+            next = skeleton.bones[index].parent;
+        }
+        path
+    }
+
+    fn draw_node(
+        &mut self,
+        ui: &Ui,
+        skeleton: &Skeleton,
+        children: &[Vec<usize>],
+        path: &[usize],
+        index: usize,
+    ) {
+        let selected = self.selected == Some(index);
+        let mut flags = TreeNodeFlags::OPEN_ON_ARROW | TreeNodeFlags::SPAN_AVAIL_WIDTH;
+        if children[index].is_empty() {
+            flags |= TreeNodeFlags::LEAF;
+        }
+        let mut node = ui
+            .tree_node_config(format!("{}##bone{}", skeleton.bones[index].name, index))
+            .flags(flags)
+            .selected(selected);
+        if self.reveal && path.contains(&index) && !selected {
+            node = node.opened(true, Condition::Always);
+        }
+        let token = node.push();
+        if ui.is_item_clicked() && !ui.is_item_toggled_open() {
+            self.selected = Some(index);
+        }
+        if self.reveal && selected {
+            ui.set_scroll_here_y();
+        }
+        if token.is_some() {
+            for &child in &children[index] {
+                self.draw_node(ui, skeleton, children, path, child);
+            }
+        }
+    }
+
+    fn draw_selected(&self, ui: &Ui, skeleton: &Skeleton) {
+        let Some(bone) = self.selected.and_then(|index| skeleton.bones.get(index)) else {
+            ui.text_disabled("Select a bone");
+            return;
+        };
+        ui.text(&bone.name);
+        let parent = bone
+            .parent
+            .and_then(|parent| skeleton.bones.get(parent))
+            .map_or("none", |parent| parent.name.as_str());
+        ui.text_disabled(format!("Parent: {}", parent));
+        let world = skeleton.world_matrices()[self.selected.unwrap_or_default()];
+        for (label, transform) in [
+            ("Local", bone.local),
+            ("World", Transform::from_mat4(world)),
+        ] {
+            ui.separator();
+            ui.text(label);
+            let p = transform.position;
+            let (axis, angle) = axis_angle(transform);
+            let s = transform.scale;
+            ui.text(format!("Position  {:.3}, {:.3}, {:.3}", p.x, p.y, p.z));
+            ui.text(format!(
+                "Rotation  {:.1}\u{b0} around {:.2}, {:.2}, {:.2}",
+                angle.to_degrees(),
+                axis.x,
+                axis.y,
+                axis.z
+            ));
+            ui.text(format!("Scale     {:.3}, {:.3}, {:.3}", s.x, s.y, s.z));
+        }
+        if ui.collapsing_header("World matrix", TreeNodeFlags::empty()) {
+            mat4_table(ui, "##world", &world);
+        }
+    }
+
+    /// Draws the bones as lines from parent to child over the game view, with the axes of the
+    /// selected bone, on the background draw list. `model` places the skeleton in the world.
+    ///
+    /// Clicking a joint where the UI doesn't want the mouse selects its bone. Call once per
+    /// frame, returns `true` when the selection changed.
+    pub fn draw_overlay(
+        &mut self,
+        ui: &Ui,
+        camera: &GizmoCamera,
+        skeleton: &Skeleton,
+        model: Mat4<f32>,
+    ) -> bool {
+        let screen = Screen::new(ui, camera);
+        let world: Vec<Mat4<f32>> = skeleton
+            .world_matrices()
+            .into_iter()
+            .map(|matrix| model * matrix)
+            .collect();
+        let joints: Vec<Option<[f32; 2]>> = world
+            .iter()
+            .map(|matrix| screen.project(Vec3::from(matrix.cols.w)))
+            .collect();
+        let draw_list = ui.get_background_draw_list();
+        for (index, bone) in skeleton.bones.iter().enumerate() {
+            let selected = self.selected == Some(index);
+            let color = if selected {
+                HIGHLIGHT_COLOR
+            } else {
+                BONE_COLOR
+            };
+            let Some(joint) = joints[index] else {
+                continue;
+            };
+            if let Some(Some(parent)) = bone.parent.and_then(|parent| joints.get(parent)) {
+                draw_list
+                    .add_line(*parent, joint, color)
+                    .thickness(2.0)
+                    .build();
+            }
+            draw_list.add_circle(joint, 3.0, color).filled(true).build();
+            if selected || self.show_all_axes {
+                let origin = Vec3::from(world[index].cols.w);
+                let length = screen.world_length(origin, self.axis_length);
+                for (axis, axis_color) in AXIS_COLORS.iter().enumerate() {
+                    let direction = Vec3::from(world[index].cols[axis]).normalized();
+                    if let Some(end) = screen.project(origin + direction * length) {
+                        draw_list.add_line(joint, end, *axis_color).build();
+                    }
+                }
+            }
+        }
+        if !ui.is_mouse_clicked(MouseButton::Left) || ui.io().want_capture_mouse {
+            return false;
+        }
+        let mouse = ui.io().mouse_pos;
+        let distance = |joint: [f32; 2]| (joint[0] - mouse[0]).hypot(joint[1] - mouse[1]);
+        let picked = joints
+            .iter()
+            .enumerate()
+            .filter_map(|(index, joint)| Some((index, distance((*joint)?))))
+            .filter(|&(_, distance)| distance <= PICK_DISTANCE)
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(index, _)| index);
+        if picked.is_none() || picked == self.selected {
+            return false;
+        }
+        self.selected = picked;
+        self.reveal = true;
+        true
+    }
+}
+
+/// Rotation of a transform as a unit axis and an angle in radians
+fn axis_angle(transform: Transform) -> (Vec3<f32>, f32) {
+    let q = transform.rotation.normalized();
+    let angle = 2.0 * q.w.clamp(-1.0, 1.0).acos();
+    let sin = (1.0 - q.w * q.w).max(0.0).sqrt();
+    if sin < 1e-4 {
+        return (Vec3::unit_x(), 0.0);
+    }
+    (Vec3::new(q.x, q.y, q.z) / sin, angle)
+}
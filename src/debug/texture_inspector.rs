@@ -0,0 +1,400 @@
+use crate::imgui_glium_renderer::{read_pixels, PixelPreview};
+use crate::{HdrDisplay, Renderer, RendererError, Tonemap};
+use bugsyth_engine::glium::backend::Facade;
+use bugsyth_engine::glium::texture::Texture2d;
+use imgui::{Condition, Ui};
+use std::borrow::Cow;
+
+/// Buckets of the histograms
+pub const HISTOGRAM_BINS: usize = 64;
+
+const NAN_COLOR: [u8; 4] = [255, 0, 255, 255];
+const INF_COLOR: [u8; 4] = [0, 255, 255, 255];
+
+/// How pixel values are turned into colors
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum InspectMode {
+    /// Exposure and gamma applied, then clamped
+    #[default]
+    Color,
+    /// Values in [`TextureInspector::range`] mapped onto a blue to red ramp
+    FalseColor,
+    /// Luminance spread evenly over the output range with the histogram, shows detail in
+    /// textures whose values are bunched up
+    Equalized,
+}
+
+/// Which part of the pixel is shown
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum InspectChannel {
+    #[default]
+    Rgb,
+    Red,
+    Green,
+    Blue,
+    Alpha,
+}
+
+impl InspectChannel {
+    pub const ALL: [InspectChannel; 5] = [
+        InspectChannel::Rgb,
+        InspectChannel::Red,
+        InspectChannel::Green,
+        InspectChannel::Blue,
+        InspectChannel::Alpha,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            InspectChannel::Rgb => "RGB",
+            InspectChannel::Red => "R",
+            InspectChannel::Green => "G",
+            InspectChannel::Blue => "B",
+            InspectChannel::Alpha => "A",
+        }
+    }
+}
+
+/// Render target debugging view: exposure and gamma, per-channel histograms, NaN and infinity
+/// highlighting and false color and histogram-equalized modes.
+///
+/// The texture is read back to the CPU with [`load`](Self::load), a shaded copy is uploaded by
+/// [`update`](Self::update) when the settings change. Call both outside of a frame, e.g. before
+/// [`ImGui::draw`](crate::ImGui::draw), and draw the window with [`draw`](Self::draw).
+pub struct TextureInspector {
+    pub exposure: f32,
+    pub gamma: f32,
+    pub mode: InspectMode,
+    pub channel: InspectChannel,
+    /// Values mapped to the ends of the false color ramp
+    pub range: [f32; 2],
+    /// Draws NaN pixels magenta and infinite ones cyan
+    pub highlight_invalid: bool,
+    pub zoom: f32,
+    pixels: PixelPreview<[f32; 4]>,
+    /// Smallest and largest finite value over all channels
+    bounds: [f32; 2],
+    histograms: [[f32; HISTOGRAM_BINS]; 4],
+    /// Running sum of the luminance histogram, for the equalized mode
+    luminance_cdf: [f32; HISTOGRAM_BINS],
+    nan_count: usize,
+    inf_count: usize,
+}
+
+impl std::fmt::Debug for TextureInspector {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TextureInspector")
+            .field("mode", &self.mode)
+            .field("channel", &self.channel)
+            .field("size", &self.pixels.size())
+            .field("preview", &self.pixels.texture())
+            .finish_non_exhaustive()
+    }
+}
+
+impl Default for TextureInspector {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TextureInspector {
+    pub fn new() -> Self {
+        Self {
+            exposure: 0.0,
+            gamma: 2.2,
+            mode: InspectMode::default(),
+            channel: InspectChannel::default(),
+            range: [0.0, 1.0],
+            highlight_invalid: true,
+            zoom: 1.0,
+            pixels: PixelPreview::new(),
+            bounds: [0.0, 0.0],
+            histograms: [[0.0; HISTOGRAM_BINS]; 4],
+            luminance_cdf: [0.0; HISTOGRAM_BINS],
+            nan_count: 0,
+            inf_count: 0,
+        }
+    }
+
+    /// Reads the pixels of `texture`, e.g. a float render target
+    pub fn load(&mut self, texture: &Texture2d) {
+        let (width, height, pixels) = read_pixels::<(f32, f32, f32, f32)>(texture);
+        let pixels = pixels
+            .into_iter()
+            .map(|(r, g, b, a)| [r, g, b, a])
+            .collect();
+        self.set_pixels(width, height, pixels);
+    }
+    /// Inspects pixels from elsewhere, rows top to bottom.
+    ///
+    /// Panics if there aren't `width * height` pixels.
+    pub fn set_pixels(&mut self, width: u32, height: u32, pixels: Vec<[f32; 4]>) {
+        self.pixels.set_pixels(width, height, pixels);
+        self.analyze();
+    }
+    pub fn size(&self) -> [u32; 2] {
+        self.pixels.size()
+    }
+    /// Pixel at `x`, `y` from the top left
+    pub fn pixel(&self, x: u32, y: u32) -> Option<[f32; 4]> {
+        self.pixels.pixel(x, y).copied()
+    }
+    /// The exposure and gamma as shader settings, to show the live texture with the same look
+    /// through [`Renderer::register_hdr_texture`] or [`Renderer::set_hdr_display`]
+    pub fn hdr_display(&self) -> HdrDisplay {
+        HdrDisplay {
+            exposure: self.exposure,
+            tonemap: Tonemap::Clamp,
+            gamma: self.gamma,
+        }
+    }
+    /// NaN and infinite values over all channels
+    pub fn invalid_counts(&self) -> (usize, usize) {
+        (self.nan_count, self.inf_count)
+    }
+
+    fn analyze(&mut self) {
+        self.nan_count = 0;
+        self.inf_count = 0;
+        let mut bounds = [f32::MAX, f32::MIN];
+        for value in self.pixels.pixels().iter().flatten() {
+            if value.is_nan() {
+                self.nan_count += 1;
+            } else if value.is_infinite() {
+                self.inf_count += 1;
+            } else {
+                bounds = [bounds[0].min(*value), bounds[1].max(*value)];
+            }
+        }
+        if bounds[0] > bounds[1] {
+            bounds = [0.0, 0.0];
+        }
+        self.bounds = bounds;
+        self.histograms = [[0.0; HISTOGRAM_BINS]; 4];
+        let mut luminance = [0.0; HISTOGRAM_BINS];
+        for pixel in self.pixels.pixels() {
+            for (channel, value) in pixel.iter().enumerate() {
+                if let Some(bin) = self.bin(*value) {
+                    self.histograms[channel][bin] += 1.0;
+                }
+            }
+            if let Some(bin) = self.bin(luma(pixel)) {
+                luminance[bin] += 1.0;
+            }
+        }
+        let total: f32 = luminance.iter().sum::<f32>().max(1.0);
+        let mut sum = 0.0;
+        for (cdf, count) in self.luminance_cdf.iter_mut().zip(luminance) {
+            sum += count;
+            *cdf = sum / total;
+        }
+    }
+    /// Histogram bucket of a finite value
+    fn bin(&self, value: f32) -> Option<usize> {
+        if !value.is_finite() {
+            return None;
+        }
+        let [min, max] = self.bounds;
+        let t = if max > min {
+            (value - min) / (max - min)
+        } else {
+            0.0
+        };
+        Some(((t * HISTOGRAM_BINS as f32) as usize).min(HISTOGRAM_BINS - 1))
+    }
+
+    /// Display color of a pixel with the current settings
+    fn shade(&self, pixel: &[f32; 4]) -> [u8; 4] {
+        let value = match self.channel {
+            InspectChannel::Rgb => [pixel[0], pixel[1], pixel[2]],
+            InspectChannel::Red => [pixel[0]; 3],
+            InspectChannel::Green => [pixel[1]; 3],
+            InspectChannel::Blue => [pixel[2]; 3],
+            InspectChannel::Alpha => [pixel[3]; 3],
+        };
+        if self.highlight_invalid {
+            if value.iter().any(|v| v.is_nan()) {
+                return NAN_COLOR;
+            }
+            if value.iter().any(|v| v.is_infinite()) {
+                return INF_COLOR;
+            }
+        }
+        let to_u8 = |v: f32| (v.clamp(0.0, 1.0) * 255.0).round() as u8;
+        match self.mode {
+            InspectMode::Color => {
+                let scale = self.exposure.exp2();
+                let [r, g, b] =
+                    value.map(|v| to_u8((v * scale).max(0.0).powf(1.0 / self.gamma.max(0.01))));
+                [r, g, b, 255]
+            }
+            InspectMode::FalseColor => {
+                let v = if self.channel == InspectChannel::Rgb {
+                    luma(pixel)
+                } else {
+                    value[0]
+                };
+                let [min, max] = self.range;
+                let t = if max > min {
+                    (v - min) / (max - min)
+                } else {
+                    0.0
+                };
+                let [r, g, b] = ramp(t.clamp(0.0, 1.0)).map(to_u8);
+                [r, g, b, 255]
+            }
+            InspectMode::Equalized => {
+                let v = if self.channel == InspectChannel::Rgb {
+                    luma(pixel)
+                } else {
+                    value[0]
+                };
+                let t = self.bin(v).map_or(0.0, |bin| self.luminance_cdf[bin]);
+                let [r, g, b] = [t; 3].map(to_u8);
+                [r, g, b, 255]
+            }
+        }
+    }
+
+    /// Uploads the shaded copy if the pixels or settings changed since the last call
+    pub fn update<F: Facade>(
+        &mut self,
+        facade: &F,
+        renderer: &mut Renderer,
+    ) -> Result<(), RendererError> {
+        let mut pixels = std::mem::take(&mut self.pixels);
+        let result = pixels.update(facade, renderer, |pixel| self.shade(pixel));
+        self.pixels = pixels;
+        result
+    }
+    /// Removes the preview texture from the renderer
+    pub fn release(&mut self, renderer: &mut Renderer) {
+        self.pixels.release(renderer);
+    }
+
+    pub fn draw(&mut self, ui: &Ui, opened: &mut bool) {
+        ui.window("Texture inspector")
+            .opened(opened)
+            .size([520.0, 560.0], Condition::FirstUseEver)
+            .build(|| {
+                if self.pixels.pixels().is_empty() {
+                    ui.text_disabled("No texture loaded");
+                    return;
+                }
+                self.draw_controls(ui);
+                ui.separator();
+                self.draw_histograms(ui);
+                ui.separator();
+                self.draw_preview(ui);
+            });
+    }
+
+    fn draw_controls(&mut self, ui: &Ui) {
+        let mut changed = false;
+        let modes = [
+            (InspectMode::Color, "Color"),
+            (InspectMode::FalseColor, "False color"),
+            (InspectMode::Equalized, "Equalized"),
+        ];
+        for (i, (mode, name)) in modes.into_iter().enumerate() {
+            if i > 0 {
+                ui.same_line();
+            }
+            changed |= ui.radio_button(name, &mut self.mode, mode);
+        }
+        let mut channel = InspectChannel::ALL
+            .iter()
+            .position(|&channel| channel == self.channel)
+            .unwrap_or(0);
+        ui.set_next_item_width(80.0);
+        if ui.combo("Channel", &mut channel, &InspectChannel::ALL, |channel| {
+            Cow::Borrowed(channel.name())
+        }) {
+            self.channel = InspectChannel::ALL[channel];
+            changed = true;
+        }
+        ui.same_line();
+        changed |= ui.checkbox("Highlight NaN/Inf", &mut self.highlight_invalid);
+        match self.mode {
+            InspectMode::Color => {
+                changed |= ui.slider("Exposure", -10.0, 10.0, &mut self.exposure);
+                changed |= ui.slider("Gamma", 0.2, 4.0, &mut self.gamma);
+            }
+            InspectMode::FalseColor => {
+                changed |= ui.input_float2("Range", &mut self.range).build();
+                ui.same_line();
+                if ui.button("Fit") {
+                    self.range = self.bounds;
+                    changed = true;
+                }
+            }
+            InspectMode::Equalized => (),
+        }
+        if changed {
+            self.pixels.mark_dirty();
+        }
+    }
+
+    fn draw_histograms(&self, ui: &Ui) {
+        let [width, height] = self.pixels.size();
+        ui.text(format!(
+            "{}x{}, values {} to {}",
+            width, height, self.bounds[0], self.bounds[1]
+        ));
+        if self.nan_count > 0 || self.inf_count > 0 {
+            ui.same_line();
+            ui.text_colored(
+                [1.0, 0.35, 0.35, 1.0],
+                format!("{} NaN, {} Inf", self.nan_count, self.inf_count),
+            );
+        }
+        let width = (ui.content_region_avail()[0] - ui.clone_style().item_spacing[0] * 3.0) / 4.0;
+        for (channel, name) in ["R", "G", "B", "A"].into_iter().enumerate() {
+            if channel > 0 {
+                ui.same_line();
+            }
+            ui.plot_histogram(format!("##histogram_{}", name), &self.histograms[channel])
+                .overlay_text(name)
+                .scale_min(0.0)
+                .graph_size([width.max(20.0), 50.0])
+                .build();
+        }
+    }
+
+    fn draw_preview(&mut self, ui: &Ui) {
+        ui.set_next_item_width(120.0);
+        ui.slider("Zoom", 0.1, 16.0, &mut self.zoom);
+        if self.pixels.texture().is_none() {
+            ui.text_disabled("Call TextureInspector::update to upload the preview");
+            return;
+        }
+        self.pixels.draw(
+            ui,
+            "##texture_preview",
+            self.zoom,
+            |[x, y], &[r, g, b, a]| {
+                ui.text(format!("{}, {}\nR {}\nG {}\nB {}\nA {}", x, y, r, g, b, a));
+            },
+        );
+    }
+}
+
+fn luma(pixel: &[f32; 4]) -> f32 {
+    0.2126 * pixel[0] + 0.7152 * pixel[1] + 0.0722 * pixel[2]
+}
+
+/// Blue, cyan, green, yellow, red over 0 to 1
+fn ramp(t: f32) -> [f32; 3] {
+    const STOPS: [[f32; 3]; 5] = [
+        [0.0, 0.0, 1.0],
+        [0.0, 1.0, 1.0],
+        [0.0, 1.0, 0.0],
+        [1.0, 1.0, 0.0],
+        [1.0, 0.0, 0.0],
+    ];
+    let scaled = t * (STOPS.len() - 1) as f32;
+    let i = (scaled as usize).min(STOPS.len() - 2);
+    let f = scaled - i as f32;
+    [0, 1, 2].map(|c| STOPS[i][c] + (STOPS[i + 1][c] - STOPS[i][c]) * f)
+}
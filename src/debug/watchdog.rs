@@ -0,0 +1,248 @@
+use crate::logs::LogLevel;
+use crate::proxy::UiProxy;
+use imgui::{Condition, Ui};
+use std::collections::HashMap;
+use std::fmt;
+use std::time::{Duration, Instant};
+
+/// Default for [`FrameWatchdog::budget`], a fifth of a 60 FPS frame
+pub const WINDOW_BUDGET: Duration = Duration::from_micros(3300);
+
+const OVER_BUDGET_COLOR: [f32; 4] = [1.0, 0.35, 0.35, 1.0];
+
+type Reporter = Box<dyn FnMut(&str, Duration)>;
+
+/// Build times of one window
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct WindowTiming {
+    pub last: Duration,
+    /// Exponential moving average over roughly the last 30 frames
+    pub average: Duration,
+    pub worst: Duration,
+    /// Frames that went over the budget
+    pub over_budget: u32,
+}
+
+/// Measures how long each window takes to build and flags the ones over a budget.
+///
+/// imgui has no timing hooks, so the window's code is wrapped with [`measure`](Self::measure).
+/// Measuring does nothing while the watchdog is disabled, so the calls can stay in release
+/// builds. A window going over the budget is reported once until it's back under it: to the
+/// [reporter](Self::with_reporter) when one is set, otherwise as a toast when a [`UiProxy`] is set
+/// and logged with the `log` feature.
+pub struct FrameWatchdog {
+    enabled: bool,
+    pub budget: Duration,
+    proxy: Option<UiProxy>,
+    reporter: Option<Reporter>,
+    timings: HashMap<String, WindowTiming>,
+    /// Windows over the budget in their last measured frame
+    over: HashMap<String, bool>,
+}
+
+impl fmt::Debug for FrameWatchdog {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FrameWatchdog")
+            .field("enabled", &self.enabled)
+            .field("budget", &self.budget)
+            .field("proxy", &self.proxy)
+            .field("timings", &self.timings)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Default for FrameWatchdog {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            budget: WINDOW_BUDGET,
+            proxy: None,
+            reporter: None,
+            timings: HashMap::new(),
+            over: HashMap::new(),
+        }
+    }
+}
+
+impl FrameWatchdog {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Reports windows over the budget as toasts, see [`ImGui::proxy`](crate::ImGui::proxy)
+    pub fn with_proxy(mut self, proxy: UiProxy) -> Self {
+        self.proxy = Some(proxy);
+        self
+    }
+    /// Called with the window and its build time instead of the toast or log when a window goes
+    /// over the budget, e.g. to send it to the game's own logging
+    pub fn with_reporter<F: FnMut(&str, Duration) + 'static>(mut self, reporter: F) -> Self {
+        self.reporter = Some(Box::new(reporter));
+        self
+    }
+    pub fn with_budget(mut self, budget: Duration) -> Self {
+        self.budget = budget;
+        self
+    }
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+    }
+    pub fn reset(&mut self) {
+        self.timings.clear();
+        self.over.clear();
+    }
+
+    /// Runs `build` and records how long it took under `window`, wrap the window's
+    /// `ui.window(..).build(..)` call with it
+    pub fn measure<R>(&mut self, window: &str, build: impl FnOnce() -> R) -> R {
+        if !self.enabled {
+            return build();
+        }
+        let start = Instant::now();
+        let result = build();
+        self.record(window, start.elapsed());
+        result
+    }
+    /// Adds a build time measured elsewhere
+    pub fn record(&mut self, window: &str, elapsed: Duration) {
+        let timing = self.timings.entry(window.to_string()).or_default();
+        timing.average = if timing.average.is_zero() {
+            elapsed
+        } else {
+            timing.average.mul_f32(29.0 / 30.0) + elapsed.mul_f32(1.0 / 30.0)
+        };
+        timing.last = elapsed;
+        timing.worst = timing.worst.max(elapsed);
+        let over = elapsed > self.budget;
+        if over {
+            timing.over_budget += 1;
+        }
+        let was_over = self.over.insert(window.to_string(), over).unwrap_or(false);
+        if over && !was_over {
+            if let Some(reporter) = &mut self.reporter {
+                reporter(window, elapsed);
+                return;
+            }
+            let message = format!(
+                "UI window \"{}\" took {:.2} ms, over the {:.2} ms budget",
+                window,
+                elapsed.as_secs_f64() * 1000.0,
+                self.budget.as_secs_f64() * 1000.0
+            );
+            if !self
+                .proxy
+                .as_ref()
+                .is_some_and(|proxy| proxy.toast(LogLevel::Warn, &message))
+            {
+                log!(warn, "{}", message);
+            }
+        }
+    }
+
+    pub fn timing(&self, window: &str) -> Option<WindowTiming> {
+        self.timings.get(window).copied()
+    }
+    /// Windows by average build time, slowest first
+    pub fn worst_offenders(&self) -> Vec<(&str, WindowTiming)> {
+        let mut offenders: Vec<_> = self
+            .timings
+            .iter()
+            .map(|(window, timing)| (window.as_str(), *timing))
+            .collect();
+        offenders.sort_by_key(|&(_, timing)| std::cmp::Reverse(timing.average));
+        offenders
+    }
+
+    /// Panel with the budget and the windows by average build time
+    pub fn draw(&mut self, ui: &Ui, opened: &mut bool) {
+        ui.window("UI watchdog")
+            .opened(opened)
+            .size([420.0, 300.0], Condition::FirstUseEver)
+            .build(|| {
+                ui.checkbox("Enabled", &mut self.enabled);
+                ui.same_line();
+                if ui.button("Reset") {
+                    self.reset();
+                }
+                let mut budget_ms = self.budget.as_secs_f32() * 1000.0;
+                if ui
+                    .input_float("Budget (ms)", &mut budget_ms)
+                    .step(0.5)
+                    .build()
+                {
+                    if let Some(budget) = budget_from_ms(budget_ms) {
+                        self.budget = budget;
+                    }
+                }
+                ui.separator();
+                let ms = |duration: Duration| format!("{:.2}", duration.as_secs_f64() * 1000.0);
+                ui.columns(5, "##watchdog_columns", true);
+                for header in ["Window", "Last ms", "Avg ms", "Worst ms", "Over"] {
+                    ui.text_disabled(header);
+                    ui.next_column();
+                }
+                ui.separator();
+                for (window, timing) in self.worst_offenders() {
+                    let _color = (timing.average > self.budget)
+                        .then(|| ui.push_style_color(imgui::StyleColor::Text, OVER_BUDGET_COLOR));
+                    ui.text(window);
+                    ui.next_column();
+                    ui.text(ms(timing.last));
+                    ui.next_column();
+                    ui.text(ms(timing.average));
+                    ui.next_column();
+                    ui.text(ms(timing.worst));
+                    ui.next_column();
+                    ui.text(timing.over_budget.to_string());
+                    ui.next_column();
+                }
+                ui.columns(1, "##watchdog_columns", false);
+            });
+    }
+}
+
+/// Budget typed into the window, `None` for values a `Duration` can't hold like `inf`
+fn budget_from_ms(ms: f32) -> Option<Duration> {
+    Duration::try_from_secs_f32(ms.max(0.0) / 1000.0).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    #[test]
+    fn going_over_budget_is_reported_once() {
+        let reports = Rc::new(RefCell::new(Vec::new()));
+        let sink = reports.clone();
+        let mut watchdog = FrameWatchdog::new()
+            .with_budget(Duration::from_millis(2))
+            .with_reporter(move |window, elapsed| {
+                sink.borrow_mut().push((window.to_string(), elapsed))
+            });
+        let ms = Duration::from_millis;
+        for elapsed in [1, 5, 6, 1, 3] {
+            watchdog.record("Inventory", ms(elapsed));
+        }
+        assert_eq!(
+            *reports.borrow(),
+            [
+                ("Inventory".to_string(), ms(5)),
+                ("Inventory".to_string(), ms(3))
+            ]
+        );
+        assert_eq!(watchdog.timing("Inventory").unwrap().over_budget, 3);
+        assert_eq!(watchdog.timing("Inventory").unwrap().worst, ms(6));
+    }
+
+    #[test]
+    fn typed_budget_is_checked() {
+        assert_eq!(budget_from_ms(2.5), Some(Duration::from_micros(2500)));
+        assert_eq!(budget_from_ms(-1.0), Some(Duration::ZERO));
+        assert_eq!(budget_from_ms(f32::INFINITY), None);
+        assert_eq!(budget_from_ms(f32::MAX), None);
+    }
+}
@@ -0,0 +1,226 @@
+use super::DrawnWindow;
+use imgui::{sys, Ui};
+
+/// A window registered with [`WidgetTree::track_window`] or drawn without being registered
+#[derive(Clone, Debug, PartialEq)]
+pub struct WindowNode {
+    pub name: String,
+    pub min: [f32; 2],
+    pub max: [f32; 2],
+    /// Always false for windows that weren't registered
+    pub focused: bool,
+    /// Always false for windows that weren't registered
+    pub collapsed: bool,
+    /// Registered items, empty for windows that weren't registered
+    pub items: Vec<ItemNode>,
+}
+
+/// An item registered with [`WidgetTree::track`]
+#[derive(Clone, Debug, PartialEq)]
+pub struct ItemNode {
+    pub id: u32,
+    /// Label as passed to the widget, including any `##` suffix
+    pub label: String,
+    pub min: [f32; 2],
+    pub max: [f32; 2],
+    pub hovered: bool,
+    pub active: bool,
+    /// Not clipped away, e.g. scrolled out of its window
+    pub visible: bool,
+}
+
+impl ItemNode {
+    /// Center of the item, where a simulated click should go
+    pub fn center(&self) -> [f32; 2] {
+        [
+            (self.min[0] + self.max[0]) * 0.5,
+            (self.min[1] + self.max[1]) * 0.5,
+        ]
+    }
+}
+
+/// Structured dump of the windows and items drawn in a frame, for UI automation and scripting.
+///
+/// Every drawn window is in the dump once [`end_frame`](Self::end_frame) is called after each
+/// render with [`ImGui::drawn_windows`](crate::ImGui::drawn_windows). imgui 1.89 can't list the
+/// items of a window, so items, and the focus and collapse state of windows, are only known when
+/// registered with [`track`](Self::track) and [`track_window`](Self::track_window) right after
+/// they're drawn. Registering does nothing while the tree is disabled. The dump and queries
+/// describe the last complete frame.
+#[derive(Debug, Default)]
+pub struct WidgetTree {
+    enabled: bool,
+    building: Vec<WindowNode>,
+    windows: Vec<WindowNode>,
+}
+
+impl WidgetTree {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+        if !enabled {
+            self.building.clear();
+            self.windows.clear();
+        }
+    }
+
+    /// Completes the dump of the frame that was just rendered, adding the drawn windows that
+    /// weren't registered. Call once per frame after rendering, even when nothing was tracked.
+    pub fn end_frame(&mut self, drawn: &[DrawnWindow]) {
+        if !self.enabled {
+            return;
+        }
+        let mut windows = std::mem::take(&mut self.building);
+        for window in drawn {
+            let tracked = windows.iter().any(|node| {
+                node.name == window.name || (!window.is_child() && node.name == window.label())
+            });
+            if !tracked {
+                windows.push(WindowNode {
+                    name: window.name.clone(),
+                    min: window.min,
+                    max: window.max,
+                    focused: false,
+                    collapsed: false,
+                    items: Vec::new(),
+                });
+            }
+        }
+        self.windows = windows;
+    }
+
+    /// Registers the current window, call inside it before its items are tracked
+    pub fn track_window(&mut self, ui: &Ui, name: &str) {
+        if !self.enabled {
+            return;
+        }
+        let min = ui.window_pos();
+        let size = ui.window_size();
+        self.building.push(WindowNode {
+            name: name.to_string(),
+            min,
+            max: [min[0] + size[0], min[1] + size[1]],
+            focused: ui.is_window_focused(),
+            collapsed: ui.is_window_collapsed(),
+            items: Vec::new(),
+        });
+    }
+
+    /// Registers the last drawn item in the last tracked window
+    pub fn track(&mut self, ui: &Ui, label: &str) {
+        if !self.enabled {
+            return;
+        }
+        let Some(window) = self.building.last_mut() else {
+            return;
+        };
+        window.items.push(ItemNode {
+            // SAFETY: only reads the last item of the current window, which `ui` guarantees exists
+            id: unsafe { sys::igGetItemID() },
+            label: label.to_string(),
+            min: ui.item_rect_min(),
+            max: ui.item_rect_max(),
+            hovered: ui.is_item_hovered(),
+            active: ui.is_item_active(),
+            visible: ui.is_item_visible(),
+        });
+    }
+
+    /// Windows and items of the last complete frame
+    pub fn windows(&self) -> &[WindowNode] {
+        &self.windows
+    }
+
+    /// Finds an item by `"<window>/<label>"`.
+    ///
+    /// Both parts match either the full label or the part shown before `##`, so
+    /// `"Settings/Apply"` finds an `Apply##settings` button in the `Settings` window.
+    pub fn find_item(&self, path: &str) -> Option<(&WindowNode, &ItemNode)> {
+        let (window_name, label) = path.split_once('/')?;
+        self.windows
+            .iter()
+            .filter(|window| label_matches(&window.name, window_name))
+            .find_map(|window| {
+                window
+                    .items
+                    .iter()
+                    .find(|item| label_matches(&item.label, label))
+                    .map(|item| (window, item))
+            })
+    }
+
+    /// Finds an item by its ID
+    pub fn find_id(&self, id: u32) -> Option<(&WindowNode, &ItemNode)> {
+        self.windows.iter().find_map(|window| {
+            window
+                .items
+                .iter()
+                .find(|item| item.id == id)
+                .map(|item| (window, item))
+        })
+    }
+
+    /// All items of the last frame with their `"<window>/<label>"` paths
+    pub fn items(&self) -> impl Iterator<Item = (String, &ItemNode)> {
+        self.windows.iter().flat_map(|window| {
+            window
+                .items
+                .iter()
+                .map(move |item| (format!("{}/{}", window.name, item.label), item))
+        })
+    }
+}
+
+fn label_matches(label: &str, query: &str) -> bool {
+    label == query || label.split("##").next() == Some(query)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::Harness;
+    use imgui::Condition;
+
+    #[test]
+    fn dump_follows_every_frame() {
+        let mut harness = Harness::new();
+        let mut tree = WidgetTree::new();
+        tree.set_enabled(true);
+        let frame = |harness: &mut Harness, tree: &mut WidgetTree, track: bool| {
+            harness.frame(|ui| {
+                ui.window("Settings##main")
+                    .position([10.0, 10.0], Condition::Always)
+                    .size([100.0, 100.0], Condition::Always)
+                    .build(|| {
+                        if track {
+                            tree.track_window(ui, "Settings");
+                        }
+                        ui.button("Apply");
+                        if track {
+                            tree.track(ui, "Apply");
+                        }
+                    });
+                ui.window("Log")
+                    .position([150.0, 10.0], Condition::Always)
+                    .size([100.0, 100.0], Condition::Always)
+                    .build(|| ui.text("Started"));
+            });
+            tree.end_frame(harness.drawn_windows());
+        };
+        frame(&mut harness, &mut tree, true);
+        frame(&mut harness, &mut tree, true);
+        let names: Vec<_> = tree.windows().iter().map(|window| &window.name).collect();
+        assert_eq!(names, ["Settings", "Log"]);
+        assert!(tree.find_item("Settings/Apply").is_some());
+
+        frame(&mut harness, &mut tree, false);
+        assert!(tree.find_item("Settings/Apply").is_none());
+        let names: Vec<_> = tree.windows().iter().map(|window| &window.name).collect();
+        assert_eq!(names, ["Settings##main", "Log"]);
+    }
+}
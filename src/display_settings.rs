@@ -0,0 +1,201 @@
+//! Stock panel for window mode, resolution, monitor and vsync.
+//!
+//! The engine side implements [`DisplayBackend`], [`DisplaySettings`] draws the panel and asks the
+//! user to confirm applied changes, reverting them automatically when nobody answers (e.g. the
+//! monitor can't show the new mode).
+
+use imgui::Ui;
+use std::borrow::Cow;
+
+/// Seconds until applied settings are reverted without confirmation
+pub const REVERT_SECONDS: f64 = 15.0;
+
+const CONFIRM_POPUP: &str = "Keep display settings?";
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum WindowMode {
+    Windowed,
+    /// Fullscreen window at the monitor's current resolution
+    Borderless,
+    /// Exclusive fullscreen at the chosen resolution
+    Fullscreen,
+}
+
+impl WindowMode {
+    pub const ALL: [WindowMode; 3] = [
+        WindowMode::Windowed,
+        WindowMode::Borderless,
+        WindowMode::Fullscreen,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            WindowMode::Windowed => "Windowed",
+            WindowMode::Borderless => "Borderless",
+            WindowMode::Fullscreen => "Fullscreen",
+        }
+    }
+}
+
+/// Everything the panel can change
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DisplayMode {
+    pub window_mode: WindowMode,
+    /// Index into [`DisplayBackend::monitors`]
+    pub monitor: usize,
+    /// Window size in windowed mode, video mode in fullscreen, ignored for borderless
+    pub resolution: [u32; 2],
+    pub vsync: bool,
+}
+
+/// Implemented by the engine to query and change the display configuration
+pub trait DisplayBackend {
+    /// Names of the connected monitors
+    fn monitors(&self) -> Vec<String>;
+    /// Resolutions supported by `monitor`, largest first
+    fn resolutions(&self, monitor: usize) -> Vec<[u32; 2]>;
+    /// Configuration in use right now
+    fn current(&self) -> DisplayMode;
+    /// Switches to `mode`, the error message is shown in the panel
+    fn apply(&mut self, mode: &DisplayMode) -> Result<(), String>;
+}
+
+/// Display settings panel state
+#[derive(Clone, Debug, Default)]
+pub struct DisplaySettings {
+    /// Mode being edited, `None` until the panel is first drawn
+    edited: Option<DisplayMode>,
+    /// Mode to go back to and the time the revert happens at
+    revert: Option<(DisplayMode, f64)>,
+    error: Option<String>,
+}
+
+impl DisplaySettings {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Whether applied settings are waiting for confirmation
+    pub fn is_confirming(&self) -> bool {
+        self.revert.is_some()
+    }
+
+    /// Draws the panel and the confirmation popup.
+    ///
+    /// Keep calling this while a change awaits confirmation even if the panel got closed,
+    /// otherwise the revert timer can't fire.
+    pub fn draw<B: DisplayBackend>(&mut self, ui: &Ui, opened: &mut bool, backend: &mut B) {
+        if *opened {
+            ui.window("Display").opened(opened).build(|| {
+                self.draw_settings(ui, backend);
+            });
+        }
+        self.draw_confirmation(ui, backend);
+    }
+
+    fn draw_settings<B: DisplayBackend>(&mut self, ui: &Ui, backend: &mut B) {
+        let current = backend.current();
+        let edited = self.edited.get_or_insert_with(|| current.clone());
+
+        let mut mode_index = WindowMode::ALL
+            .iter()
+            .position(|&mode| mode == edited.window_mode)
+            .unwrap_or(0);
+        if ui.combo("Mode", &mut mode_index, &WindowMode::ALL, |mode| {
+            Cow::Borrowed(mode.name())
+        }) {
+            edited.window_mode = WindowMode::ALL[mode_index];
+        }
+
+        let monitors = backend.monitors();
+        if !monitors.is_empty() {
+            edited.monitor = edited.monitor.min(monitors.len() - 1);
+            ui.combo_simple_string("Monitor", &mut edited.monitor, &monitors);
+        }
+
+        {
+            let _disabled = ui.begin_disabled(edited.window_mode == WindowMode::Borderless);
+            let resolutions = backend.resolutions(edited.monitor);
+            let preview = format!("{}x{}", edited.resolution[0], edited.resolution[1]);
+            if let Some(_combo) = ui.begin_combo("Resolution", preview) {
+                for resolution in resolutions {
+                    let selected = resolution == edited.resolution;
+                    if ui
+                        .selectable_config(format!("{}x{}", resolution[0], resolution[1]))
+                        .selected(selected)
+                        .build()
+                    {
+                        edited.resolution = resolution;
+                    }
+                }
+            }
+        }
+
+        ui.checkbox("VSync", &mut edited.vsync);
+
+        ui.separator();
+        let changed = *edited != current;
+        {
+            let _disabled = ui.begin_disabled(!changed || self.revert.is_some());
+            if ui.button("Apply") {
+                let edited = edited.clone();
+                match backend.apply(&edited) {
+                    Ok(()) => {
+                        self.error = None;
+                        self.revert = Some((current.clone(), ui.time() + REVERT_SECONDS));
+                    }
+                    Err(e) => self.error = Some(e),
+                }
+            }
+            ui.same_line();
+            if ui.button("Reset") {
+                self.edited = Some(current);
+            }
+        }
+        if let Some(error) = &self.error {
+            ui.text_colored([1.0, 0.3, 0.3, 1.0], error);
+        }
+    }
+
+    fn draw_confirmation<B: DisplayBackend>(&mut self, ui: &Ui, backend: &mut B) {
+        let Some((previous, deadline)) = self.revert.clone() else {
+            return;
+        };
+        // Opened here every frame rather than by Apply so the popup ID isn't scoped to the panel
+        // window, reopening an open popup is a no-op
+        ui.open_popup(CONFIRM_POPUP);
+        let remaining = deadline - ui.time();
+        let mut keep = None;
+        if remaining <= 0.0 {
+            keep = Some(false);
+        }
+        ui.modal_popup_config(CONFIRM_POPUP)
+            .always_auto_resize(true)
+            .build(|| {
+                ui.text(format!(
+                    "Reverting to the previous settings in {} seconds",
+                    remaining.max(0.0).ceil()
+                ));
+                if ui.button("Keep") {
+                    keep = Some(true);
+                }
+                ui.same_line();
+                if ui.button("Revert") {
+                    keep = Some(false);
+                }
+                if keep.is_some() {
+                    ui.close_current_popup();
+                }
+            });
+        match keep {
+            Some(true) => self.revert = None,
+            Some(false) => {
+                self.revert = None;
+                if let Err(e) = backend.apply(&previous) {
+                    self.error = Some(e);
+                }
+                self.edited = Some(previous);
+            }
+            None => (),
+        }
+    }
+}
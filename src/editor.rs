@@ -0,0 +1,515 @@
+//! Scaffold for engine tooling.
+//!
+//! [`EditorShell`] lays out the usual editor panels around the game view: scene tree on the left,
+//! properties on the right, assets and console along the bottom and the viewport in the middle,
+//! under a main menu bar whose View menu toggles them. The panels' content comes from
+//! [`EditorPanel`]s set with [`EditorShell::set_panel`], and they share an [`EditorSelection`] so
+//! picking an entity in the scene tree shows it in the properties.
+//!
+//! [`EditorShell::standard`] fills the panels with this crate's tools: the [`PrefabPanel`] as
+//! properties, an [`AssetBrowser`] and the [`Console`](crate::console::Console). Only the scene
+//! tree is left to the game, which knows its entities.
+//!
+//! The imgui bindings used here are built without docking, so the panels are snapped to fixed
+//! places instead of a dockspace. Their sizes are the fractions in [`ShellLayout`].
+//!
+//! The [`ModeController`] switches between editing and playing. Its toolbar sits in the menu
+//! bar, editing panels are disabled while playing and the selection is restored on stop.
+//!
+//! [`PrefabPanel`] edits reusable entity templates and applies or reverts the changes of
+//! entities made from them.
+//!
+//! [`PickingPanel`] shows the engine's object ID buffer and selects the entity clicked in it.
+//!
+//! [`VcsProvider`] reports version control status for asset views, [`vcs_badge`] and
+//! [`vcs_menu_items`] show it next to file names and in their context menus. [`GitCli`] uses
+//! the `git` command.
+
+mod assets;
+mod picking;
+mod prefab;
+mod vcs;
+
+pub use assets::AssetBrowser;
+pub use picking::PickingPanel;
+pub use prefab::{
+    ComponentTemplate, EntityTemplate, FieldValue, PrefabPanel, TemplateInstance, TemplateLibrary,
+};
+pub use vcs::{vcs_badge, vcs_menu_items, GitCli, VcsProvider, VcsStatus};
+
+use crate::console::Console;
+use crate::Condition;
+use imgui::{DisabledToken, Ui, WindowFlags};
+use std::any::Any;
+use std::path::PathBuf;
+
+/// Shared selection of the editor panels, entries are IDs chosen by the game, e.g. entity IDs
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct EditorSelection {
+    items: Vec<String>,
+}
+
+impl EditorSelection {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Selects only `id`
+    pub fn select(&mut self, id: impl Into<String>) {
+        self.items.clear();
+        self.items.push(id.into());
+    }
+    /// Adds `id` or removes it if it's selected, for ctrl+click
+    pub fn toggle(&mut self, id: &str) {
+        match self.items.iter().position(|item| item == id) {
+            Some(index) => {
+                self.items.remove(index);
+            }
+            None => self.items.push(id.to_string()),
+        }
+    }
+    pub fn clear(&mut self) {
+        self.items.clear();
+    }
+    pub fn is_selected(&self, id: &str) -> bool {
+        self.items.iter().any(|item| item == id)
+    }
+    /// The last selected item, the one the properties panel shows
+    pub fn primary(&self) -> Option<&str> {
+        self.items.last().map(String::as_str)
+    }
+    /// Selected items in the order they were selected
+    pub fn items(&self) -> &[String] {
+        &self.items
+    }
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+}
+
+/// Content of one editor panel, drawn inside the panel's window
+pub trait EditorPanel: Any {
+    fn draw(&mut self, ui: &Ui, selection: &mut EditorSelection);
+    /// Adds items to the main menu bar, called every frame while the bar is open
+    fn menu_bar(&mut self, _ui: &Ui, _selection: &mut EditorSelection) {}
+    /// Play mode was entered from edit mode, e.g. to save what the panel shows
+    fn on_play(&mut self) {}
+    /// Play mode was stopped, e.g. to restore what [`on_play`](Self::on_play) saved
+    fn on_stop(&mut self) {}
+}
+
+/// Whether the game is being edited or runs, see [`ModeController`]
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub enum EditorMode {
+    #[default]
+    Edit,
+    Play,
+    Paused,
+}
+
+type ModeCallback = Box<dyn FnMut(EditorMode, EditorMode)>;
+
+/// Edit / Play / Paused switch with a toolbar.
+///
+/// Bind the engine to it with [`on_change`](Self::on_change), e.g. snapshot the scene and start
+/// the simulation when play mode is entered and restore the snapshot on stop.
+#[derive(Default)]
+pub struct ModeController {
+    mode: EditorMode,
+    callbacks: Vec<ModeCallback>,
+}
+
+impl std::fmt::Debug for ModeController {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ModeController")
+            .field("mode", &self.mode)
+            .finish_non_exhaustive()
+    }
+}
+
+impl ModeController {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    pub fn mode(&self) -> EditorMode {
+        self.mode
+    }
+    /// Playing or paused, editing widgets should be disabled
+    pub fn is_playing(&self) -> bool {
+        self.mode != EditorMode::Edit
+    }
+    /// Adds a callback that runs with the old and new mode on every switch
+    pub fn on_change<F>(&mut self, callback: F)
+    where
+        F: FnMut(EditorMode, EditorMode) + 'static,
+    {
+        self.callbacks.push(Box::new(callback));
+    }
+    /// Switches the mode and runs the callbacks, does nothing if it's already in `mode`.
+    /// Pausing in edit mode is ignored.
+    pub fn set_mode(&mut self, mode: EditorMode) {
+        let from = self.mode;
+        if mode == from || (from == EditorMode::Edit && mode == EditorMode::Paused) {
+            return;
+        }
+        self.mode = mode;
+        for callback in &mut self.callbacks {
+            callback(from, mode);
+        }
+    }
+    /// Enters play mode or resumes when paused
+    pub fn play(&mut self) {
+        self.set_mode(EditorMode::Play);
+    }
+    pub fn pause(&mut self) {
+        self.set_mode(EditorMode::Paused);
+    }
+    /// Back to edit mode
+    pub fn stop(&mut self) {
+        self.set_mode(EditorMode::Edit);
+    }
+
+    /// Draws Play/Pause/Stop buttons, fits into a menu bar
+    pub fn toolbar(&mut self, ui: &Ui) {
+        match self.mode {
+            EditorMode::Edit => {
+                if ui.button("Play") {
+                    self.play();
+                }
+            }
+            EditorMode::Play => {
+                if ui.button("Pause") {
+                    self.pause();
+                }
+            }
+            EditorMode::Paused => {
+                if ui.button("Resume") {
+                    self.play();
+                }
+            }
+        }
+        ui.same_line();
+        ui.disabled(!self.is_playing(), || {
+            if ui.button("Stop") {
+                self.stop();
+            }
+        });
+    }
+    /// Disables the widgets until the token is dropped while playing, for editing widgets
+    pub fn disable_editing<'ui>(&self, ui: &'ui Ui) -> DisabledToken<'ui> {
+        ui.begin_disabled(self.is_playing())
+    }
+}
+
+/// Where a panel is snapped to
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum PanelSlot {
+    /// Scene tree, left
+    Scene,
+    /// Property inspector, right
+    Properties,
+    /// Asset browser, bottom left
+    Assets,
+    /// Console, bottom right
+    Console,
+    /// Game view, the middle. Its window has no background so the game shows through.
+    Viewport,
+}
+
+impl PanelSlot {
+    pub const ALL: [PanelSlot; 5] = [
+        PanelSlot::Scene,
+        PanelSlot::Properties,
+        PanelSlot::Assets,
+        PanelSlot::Console,
+        PanelSlot::Viewport,
+    ];
+
+    pub fn title(self) -> &'static str {
+        match self {
+            PanelSlot::Scene => "Scene",
+            PanelSlot::Properties => "Properties",
+            PanelSlot::Assets => "Assets",
+            PanelSlot::Console => "Console",
+            PanelSlot::Viewport => "Viewport",
+        }
+    }
+}
+
+/// Sizes of the snapped panels as fractions of the display
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct ShellLayout {
+    pub left_width: f32,
+    pub right_width: f32,
+    pub bottom_height: f32,
+    /// Width of the assets panel as a fraction of the bottom row, the console gets the rest
+    pub assets_width: f32,
+}
+
+impl Default for ShellLayout {
+    fn default() -> Self {
+        Self {
+            left_width: 0.2,
+            right_width: 0.25,
+            bottom_height: 0.28,
+            assets_width: 0.5,
+        }
+    }
+}
+
+struct ShellPanel {
+    slot: PanelSlot,
+    open: bool,
+    content: Option<Box<dyn EditorPanel>>,
+}
+
+/// Main menu bar and snapped editor panels, see the [module docs](self)
+pub struct EditorShell {
+    panels: Vec<ShellPanel>,
+    pub selection: EditorSelection,
+    pub layout: ShellLayout,
+    pub mode: ModeController,
+    /// Mode in the last frame, to notice switches
+    last_mode: EditorMode,
+    /// Selection when play mode was entered
+    saved_selection: Option<EditorSelection>,
+    /// Rect of the viewport panel's content in the last frame, UI coordinates
+    viewport: Option<([f32; 2], [f32; 2])>,
+}
+
+impl std::fmt::Debug for EditorShell {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("EditorShell")
+            .field("selection", &self.selection)
+            .field("layout", &self.layout)
+            .field("mode", &self.mode)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Default for EditorShell {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl EditorShell {
+    /// All five panels open without content, set it with [`set_panel`](Self::set_panel)
+    pub fn new() -> Self {
+        Self {
+            panels: PanelSlot::ALL
+                .into_iter()
+                .map(|slot| ShellPanel {
+                    slot,
+                    open: true,
+                    content: None,
+                })
+                .collect(),
+            selection: EditorSelection::new(),
+            layout: ShellLayout::default(),
+            mode: ModeController::new(),
+            last_mode: EditorMode::Edit,
+            saved_selection: None,
+            viewport: None,
+        }
+    }
+    /// All five panels open with the crate's tools in them: a [`PrefabPanel`] with an empty
+    /// library as properties, an [`AssetBrowser`] of `assets` and a [`Console`]. The scene tree
+    /// shows a placeholder until the game sets it, the viewport stays empty for the game view.
+    ///
+    /// Reach the panels with [`panel_mut`](Self::panel_mut), e.g. to load the template library
+    /// or register console commands.
+    pub fn standard(assets: impl Into<PathBuf>) -> Self {
+        let mut shell = Self::new();
+        shell.set_panel(PanelSlot::Properties, PrefabPanel::default());
+        shell.set_panel(PanelSlot::Assets, AssetBrowser::new(assets));
+        shell.set_panel(PanelSlot::Console, Console::new());
+        shell
+    }
+    pub fn with_layout(mut self, layout: ShellLayout) -> Self {
+        self.layout = layout;
+        self
+    }
+    /// Sets what's drawn in a panel, replacing the previous content
+    pub fn set_panel<P: EditorPanel + 'static>(&mut self, slot: PanelSlot, panel: P) {
+        self.shell_panel_mut(slot).content = Some(Box::new(panel));
+    }
+    /// The content of a panel if it's a `P`
+    pub fn panel_mut<P: EditorPanel>(&mut self, slot: PanelSlot) -> Option<&mut P> {
+        let content: &mut dyn Any = self.shell_panel_mut(slot).content.as_deref_mut()?;
+        content.downcast_mut()
+    }
+    pub fn is_open(&self, slot: PanelSlot) -> bool {
+        self.panels
+            .iter()
+            .any(|panel| panel.slot == slot && panel.open)
+    }
+    pub fn set_open(&mut self, slot: PanelSlot, open: bool) {
+        self.shell_panel_mut(slot).open = open;
+    }
+    /// Where the game view is, for rendering the scene or picking into it. `None` while the
+    /// viewport panel is closed.
+    pub fn viewport_rect(&self) -> Option<([f32; 2], [f32; 2])> {
+        self.viewport
+    }
+
+    fn shell_panel_mut(&mut self, slot: PanelSlot) -> &mut ShellPanel {
+        self.panels
+            .iter_mut()
+            .find(|panel| panel.slot == slot)
+            .expect("every slot has a panel")
+    }
+
+    /// Saves the selection when play mode is entered and restores it on stop, and tells the
+    /// panels
+    fn track_mode(&mut self) {
+        let mode = self.mode.mode();
+        match (self.last_mode, mode) {
+            (EditorMode::Edit, EditorMode::Play | EditorMode::Paused) => {
+                self.saved_selection = Some(self.selection.clone());
+                for content in self
+                    .panels
+                    .iter_mut()
+                    .filter_map(|panel| panel.content.as_mut())
+                {
+                    content.on_play();
+                }
+            }
+            (EditorMode::Play | EditorMode::Paused, EditorMode::Edit) => {
+                if let Some(selection) = self.saved_selection.take() {
+                    self.selection = selection;
+                }
+                for content in self
+                    .panels
+                    .iter_mut()
+                    .filter_map(|panel| panel.content.as_mut())
+                {
+                    content.on_stop();
+                }
+            }
+            _ => (),
+        }
+        self.last_mode = mode;
+    }
+
+    /// Draws the menu bar and the open panels, call once per frame.
+    ///
+    /// The scene, properties and assets panels are disabled while playing.
+    pub fn draw(&mut self, ui: &Ui) {
+        self.track_mode();
+        let mut menu_height = 0.0;
+        if let Some(_bar) = ui.begin_main_menu_bar() {
+            menu_height = ui.window_size()[1];
+            if let Some(_menu) = ui.begin_menu("View") {
+                for panel in &mut self.panels {
+                    ui.menu_item_config(panel.slot.title())
+                        .build_with_ref(&mut panel.open);
+                }
+            }
+            for panel in &mut self.panels {
+                if let Some(content) = &mut panel.content {
+                    content.menu_bar(ui, &mut self.selection);
+                }
+            }
+            self.mode.toolbar(ui);
+        }
+        self.track_mode();
+
+        let [width, height] = ui.io().display_size;
+        let height = height - menu_height;
+        let layout = self.layout;
+        let left = width * layout.left_width;
+        let right = width * layout.right_width;
+        let bottom = height * layout.bottom_height;
+        let top = menu_height;
+        let middle_height = height - bottom;
+        let assets = width * layout.assets_width;
+        self.viewport = None;
+        for panel in &mut self.panels {
+            if !panel.open {
+                continue;
+            }
+            let (pos, size) = match panel.slot {
+                PanelSlot::Scene => ([0.0, top], [left, middle_height]),
+                PanelSlot::Properties => ([width - right, top], [right, middle_height]),
+                PanelSlot::Assets => ([0.0, top + middle_height], [assets, bottom]),
+                PanelSlot::Console => ([assets, top + middle_height], [width - assets, bottom]),
+                PanelSlot::Viewport => ([left, top], [width - left - right, middle_height]),
+            };
+            let mut flags =
+                WindowFlags::NO_MOVE | WindowFlags::NO_RESIZE | WindowFlags::NO_COLLAPSE;
+            if panel.slot == PanelSlot::Viewport {
+                flags |= WindowFlags::NO_BACKGROUND | WindowFlags::NO_BRING_TO_FRONT_ON_FOCUS;
+            }
+            let selection = &mut self.selection;
+            let viewport = &mut self.viewport;
+            let editing = matches!(
+                panel.slot,
+                PanelSlot::Scene | PanelSlot::Properties | PanelSlot::Assets
+            );
+            let mode = &self.mode;
+            ui.window(panel.slot.title())
+                .position(pos, Condition::Always)
+                .size(size, Condition::Always)
+                .flags(flags)
+                .opened(&mut panel.open)
+                .build(|| {
+                    if panel.slot == PanelSlot::Viewport {
+                        let min = ui.cursor_screen_pos();
+                        let available = ui.content_region_avail();
+                        *viewport = Some((min, [min[0] + available[0], min[1] + available[1]]));
+                    }
+                    let _disabled = editing.then(|| mode.disable_editing(ui));
+                    match &mut panel.content {
+                        Some(content) => content.draw(ui, selection),
+                        None if panel.slot != PanelSlot::Viewport => {
+                            ui.text_disabled("Nothing here yet, see EditorShell::set_panel")
+                        }
+                        None => (),
+                    }
+                });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::Harness;
+    use std::fs;
+
+    #[test]
+    fn standard_shell_has_the_crates_panels() {
+        let dir = std::env::temp_dir().join(format!("imgui_support_shell_{}", std::process::id()));
+        fs::create_dir_all(dir.join("textures")).unwrap();
+        fs::write(dir.join("level.map"), "map").unwrap();
+        let mut shell = EditorShell::standard(&dir);
+        shell
+            .panel_mut::<Console>(PanelSlot::Console)
+            .unwrap()
+            .register_command("spawn", |_| Ok("spawned".to_string()));
+        assert!(shell
+            .panel_mut::<PrefabPanel>(PanelSlot::Properties)
+            .is_some());
+        assert!(shell.panel_mut::<AssetBrowser>(PanelSlot::Assets).is_some());
+        assert!(shell.panel_mut::<Console>(PanelSlot::Assets).is_none());
+        assert!(shell.panel_mut::<Console>(PanelSlot::Scene).is_none());
+
+        let mut harness = Harness::new();
+        harness.run(2, |ui| shell.draw(ui));
+        let titles: Vec<_> = harness
+            .drawn_windows()
+            .iter()
+            .filter(|window| !window.is_child())
+            .map(|window| window.label().to_string())
+            .collect();
+        for title in ["Scene", "Properties", "Assets", "Console"] {
+            assert!(
+                titles.iter().any(|t| t == title),
+                "{} in {:?}",
+                title,
+                titles
+            );
+        }
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,141 @@
+use super::{vcs_badge, vcs_menu_items, EditorPanel, EditorSelection, VcsProvider};
+use imgui::Ui;
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Editor panel listing the files under an asset directory.
+///
+/// Directories are read when they're first expanded and kept until Refresh is clicked. Clicking
+/// a file selects its path in the [`EditorSelection`], ctrl+click toggles it. With a
+/// [`VcsProvider`] files show [`vcs_badge`]s and have Stage and Revert in their context menu.
+pub struct AssetBrowser {
+    root: PathBuf,
+    vcs: Option<Box<dyn VcsProvider>>,
+    /// Directories read so far with their entries, directories first
+    entries: HashMap<PathBuf, Vec<(PathBuf, bool)>>,
+    error: Option<String>,
+}
+
+impl std::fmt::Debug for AssetBrowser {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AssetBrowser")
+            .field("root", &self.root)
+            .field("vcs", &self.vcs.is_some())
+            .finish_non_exhaustive()
+    }
+}
+
+impl AssetBrowser {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self {
+            root: root.into(),
+            vcs: None,
+            entries: HashMap::new(),
+            error: None,
+        }
+    }
+    /// Shows the version control status of the files, e.g. with [`GitCli`](super::GitCli)
+    pub fn with_vcs<V: VcsProvider + 'static>(mut self, vcs: V) -> Self {
+        self.vcs = Some(Box::new(vcs));
+        self
+    }
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+    /// Forgets the directories read so far and the version control statuses
+    pub fn refresh(&mut self) {
+        self.entries.clear();
+        self.error = None;
+        if let Some(vcs) = &mut self.vcs {
+            self.error = vcs.refresh().err().map(|e| e.to_string());
+        }
+    }
+
+    /// Entries of `dir`, read on first use. Hidden files are left out.
+    fn entries(&mut self, dir: &Path) -> &[(PathBuf, bool)] {
+        if !self.entries.contains_key(dir) {
+            let entries = match read_dir(dir) {
+                Ok(entries) => entries,
+                Err(e) => {
+                    self.error = Some(format!("{}: {}", dir.display(), e));
+                    Vec::new()
+                }
+            };
+            self.entries.insert(dir.to_path_buf(), entries);
+        }
+        &self.entries[dir]
+    }
+
+    fn draw_dir(&mut self, ui: &Ui, dir: &Path, selection: &mut EditorSelection) {
+        for (path, is_dir) in self.entries(dir).to_vec() {
+            let name = path.file_name().unwrap_or_default().to_string_lossy();
+            let _id = ui.push_id(path.to_string_lossy().as_ref());
+            if is_dir {
+                let node = ui.tree_node_config(name.as_ref()).push();
+                self.draw_status(ui, &path);
+                if let Some(_node) = node {
+                    self.draw_dir(ui, &path, selection);
+                }
+                continue;
+            }
+            let id = path.to_string_lossy();
+            if ui
+                .selectable_config(name.as_ref())
+                .selected(selection.is_selected(&id))
+                .build()
+            {
+                if ui.io().key_ctrl {
+                    selection.toggle(&id);
+                } else {
+                    selection.select(id.as_ref());
+                }
+            }
+            self.draw_status(ui, &path);
+        }
+    }
+
+    /// Badge and context menu of the last drawn entry
+    fn draw_status(&mut self, ui: &Ui, path: &Path) {
+        let Some(vcs) = &mut self.vcs else {
+            return;
+        };
+        if let Some(_popup) = ui.begin_popup_context_item() {
+            if let Err(e) = vcs_menu_items(ui, vcs.as_mut(), path) {
+                self.error = Some(e.to_string());
+            }
+        }
+        vcs_badge(ui, vcs.as_ref(), path);
+    }
+}
+
+impl EditorPanel for AssetBrowser {
+    fn draw(&mut self, ui: &Ui, selection: &mut EditorSelection) {
+        if ui.button("Refresh") {
+            self.refresh();
+        }
+        ui.same_line();
+        ui.text_disabled(self.root.display().to_string());
+        if let Some(error) = &self.error {
+            ui.text_colored([1.0, 0.35, 0.35, 1.0], error);
+        }
+        ui.separator();
+        let root = self.root.clone();
+        self.draw_dir(ui, &root, selection);
+    }
+}
+
+/// Directories first, then files, each sorted by name
+fn read_dir(dir: &Path) -> io::Result<Vec<(PathBuf, bool)>> {
+    let mut entries = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        if entry.file_name().to_string_lossy().starts_with('.') {
+            continue;
+        }
+        entries.push((entry.path(), entry.file_type()?.is_dir()));
+    }
+    entries.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    Ok(entries)
+}
@@ -0,0 +1,153 @@
+use super::{EditorPanel, EditorSelection};
+use crate::imgui_glium_renderer::{read_pixels, PixelPreview};
+use crate::{Renderer, RendererError};
+use bugsyth_engine::glium::backend::Facade;
+use bugsyth_engine::glium::texture::UnsignedTexture2d;
+use imgui::{MouseButton, Ui};
+use std::collections::HashSet;
+
+type EntityFn = Box<dyn Fn(u32) -> Option<String>>;
+
+/// Color of an object ID in the preview. Neighbouring IDs get far apart hues, 0 is black.
+fn id_color(id: u32) -> [u8; 4] {
+    if id == 0 {
+        return [0, 0, 0, 255];
+    }
+    // Golden ratio steps spread consecutive IDs around the hue circle
+    let hue = (id as f32 * 0.618_034).fract() * 6.0;
+    // Every other run of 7 IDs is darker, so IDs with similar hues differ in brightness
+    let value = if (id / 7) & 1 == 0 { 1.0 } else { 0.7 };
+    let x = 1.0 - (hue % 2.0 - 1.0).abs();
+    let [r, g, b] = match hue as u32 {
+        0 => [1.0, x, 0.0],
+        1 => [x, 1.0, 0.0],
+        2 => [0.0, 1.0, x],
+        3 => [0.0, x, 1.0],
+        4 => [x, 0.0, 1.0],
+        _ => [1.0, 0.0, x],
+    }
+    .map(|c: f32| ((c * 0.8 + 0.2) * value * 255.0).round() as u8);
+    [r, g, b, 255]
+}
+
+/// Editor panel showing the engine's picking buffer, the render target holding the object ID
+/// drawn at each pixel, with each ID in its own color.
+///
+/// Hovering shows the ID under the mouse and the entity it belongs to, clicking selects that
+/// entity in the [`EditorSelection`] and ctrl+click toggles it. ID 0 is the background, clicking
+/// it clears the selection.
+///
+/// The buffer is read back to the CPU with [`load`](Self::load) and a colored copy uploaded by
+/// [`update`](Self::update), call both outside of a frame, e.g. after the picking pass.
+pub struct PickingPanel {
+    pub zoom: f32,
+    entity: EntityFn,
+    ids: PixelPreview<u32>,
+    distinct: usize,
+}
+
+impl std::fmt::Debug for PickingPanel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PickingPanel")
+            .field("zoom", &self.zoom)
+            .field("size", &self.ids.size())
+            .field("preview", &self.ids.texture())
+            .finish_non_exhaustive()
+    }
+}
+
+impl Default for PickingPanel {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PickingPanel {
+    pub fn new() -> Self {
+        Self {
+            zoom: 1.0,
+            entity: Box::new(|id| (id != 0).then(|| id.to_string())),
+            ids: PixelPreview::new(),
+            distinct: 0,
+        }
+    }
+    /// Maps object IDs to the entity IDs used in the [`EditorSelection`], `None` for IDs that
+    /// aren't an entity. By default every ID but 0 is selected as its decimal number.
+    pub fn with_entity_ids(mut self, entity: impl Fn(u32) -> Option<String> + 'static) -> Self {
+        self.entity = Box::new(entity);
+        self
+    }
+
+    /// Reads the IDs of an unsigned integer picking target, e.g. an `R32UI` color attachment
+    pub fn load(&mut self, texture: &UnsignedTexture2d) {
+        let (width, height, ids) = read_pixels(texture);
+        self.set_ids(width, height, ids);
+    }
+    /// Shows IDs from elsewhere, e.g. decoded from a color target, rows top to bottom.
+    ///
+    /// Panics if there aren't `width * height` IDs.
+    pub fn set_ids(&mut self, width: u32, height: u32, ids: Vec<u32>) {
+        self.distinct = ids.iter().collect::<HashSet<_>>().len();
+        self.ids.set_pixels(width, height, ids);
+    }
+    pub fn size(&self) -> [u32; 2] {
+        self.ids.size()
+    }
+    /// ID at `x`, `y` from the top left
+    pub fn id(&self, x: u32, y: u32) -> Option<u32> {
+        self.ids.pixel(x, y).copied()
+    }
+
+    /// Uploads the colored copy if the IDs changed since the last call
+    pub fn update<F: Facade>(
+        &mut self,
+        facade: &F,
+        renderer: &mut Renderer,
+    ) -> Result<(), RendererError> {
+        self.ids.update(facade, renderer, |&id| id_color(id))
+    }
+    /// Removes the preview texture from the renderer
+    pub fn release(&mut self, renderer: &mut Renderer) {
+        self.ids.release(renderer);
+    }
+}
+
+impl EditorPanel for PickingPanel {
+    fn draw(&mut self, ui: &Ui, selection: &mut EditorSelection) {
+        if self.ids.texture().is_none() {
+            ui.text_disabled("No picking buffer loaded");
+            return;
+        }
+        let [width, height] = self.ids.size();
+        ui.text(format!(
+            "{}x{}, {} distinct IDs",
+            width, height, self.distinct
+        ));
+        ui.slider("Zoom", 0.1, 8.0, &mut self.zoom);
+        let entity_of = &self.entity;
+        let mut hovered = None;
+        self.ids.draw(ui, "##picking", self.zoom, |_, &id| {
+            let entity = entity_of(id);
+            let [r, g, b, _] = id_color(id).map(|c| c as f32 / 255.0);
+            ui.color_button("##id", [r, g, b, 1.0]);
+            ui.same_line();
+            ui.text(format!("ID {}", id));
+            match &entity {
+                Some(entity) if selection.is_selected(entity) => {
+                    ui.text(format!("{} (selected)", entity))
+                }
+                Some(entity) => ui.text(entity),
+                None => ui.text_disabled("Background"),
+            }
+            hovered = Some(entity);
+        });
+        if let Some(entity) = hovered.filter(|_| ui.is_mouse_clicked(MouseButton::Left)) {
+            match entity {
+                Some(entity) if ui.io().key_ctrl => selection.toggle(&entity),
+                Some(entity) => selection.select(entity),
+                None if !ui.io().key_ctrl => selection.clear(),
+                None => (),
+            }
+        }
+    }
+}
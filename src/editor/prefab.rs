@@ -0,0 +1,463 @@
+use super::{EditorPanel, EditorSelection};
+use imgui::Ui;
+use std::collections::{BTreeMap, HashMap};
+
+/// Value of a component field
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum FieldValue {
+    Bool(bool),
+    Int(i64),
+    Float(f32),
+    Text(String),
+    Vec3([f32; 3]),
+    Color([f32; 4]),
+}
+
+impl FieldValue {
+    /// Names of the kinds, offered when adding a field
+    pub const KINDS: [&'static str; 6] = ["Bool", "Int", "Float", "Text", "Vec3", "Color"];
+
+    pub fn kind(&self) -> &'static str {
+        match self {
+            FieldValue::Bool(_) => "Bool",
+            FieldValue::Int(_) => "Int",
+            FieldValue::Float(_) => "Float",
+            FieldValue::Text(_) => "Text",
+            FieldValue::Vec3(_) => "Vec3",
+            FieldValue::Color(_) => "Color",
+        }
+    }
+    /// Default value of a kind from [`KINDS`](Self::KINDS)
+    pub fn default_of(kind: &str) -> Option<FieldValue> {
+        Some(match kind {
+            "Bool" => FieldValue::Bool(false),
+            "Int" => FieldValue::Int(0),
+            "Float" => FieldValue::Float(0.0),
+            "Text" => FieldValue::Text(String::new()),
+            "Vec3" => FieldValue::Vec3([0.0; 3]),
+            "Color" => FieldValue::Color([1.0; 4]),
+            _ => return None,
+        })
+    }
+    /// Draws an editor for the value, returns `true` when it changed
+    pub fn edit(&mut self, ui: &Ui, label: &str) -> bool {
+        match self {
+            FieldValue::Bool(value) => ui.checkbox(label, value),
+            FieldValue::Int(value) => {
+                let mut int = (*value).clamp(i32::MIN as i64, i32::MAX as i64) as i32;
+                let changed = ui.input_int(label, &mut int).build();
+                if changed {
+                    *value = int as i64;
+                }
+                changed
+            }
+            FieldValue::Float(value) => ui.input_float(label, value).build(),
+            FieldValue::Text(value) => ui.input_text(label, value).build(),
+            FieldValue::Vec3(value) => ui.input_float3(label, value).build(),
+            FieldValue::Color(value) => ui.color_edit4(label, value),
+        }
+    }
+}
+
+/// Component of an [`EntityTemplate`] with the default values of its fields
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ComponentTemplate {
+    pub name: String,
+    pub fields: Vec<(String, FieldValue)>,
+}
+
+impl ComponentTemplate {
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            fields: Vec::new(),
+        }
+    }
+    pub fn with_field(mut self, name: impl Into<String>, value: FieldValue) -> Self {
+        self.fields.push((name.into(), value));
+        self
+    }
+    pub fn field(&self, name: &str) -> Option<&FieldValue> {
+        self.fields
+            .iter()
+            .find(|(field, _)| field == name)
+            .map(|(_, value)| value)
+    }
+}
+
+/// Reusable entity definition, a list of components with default values
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct EntityTemplate {
+    pub name: String,
+    pub components: Vec<ComponentTemplate>,
+}
+
+impl EntityTemplate {
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            components: Vec::new(),
+        }
+    }
+    pub fn with_component(mut self, component: ComponentTemplate) -> Self {
+        self.components.push(component);
+        self
+    }
+    pub fn component(&self, name: &str) -> Option<&ComponentTemplate> {
+        self.components
+            .iter()
+            .find(|component| component.name == name)
+    }
+}
+
+/// Entity created from a template, keeps the fields it changed from the template's defaults
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TemplateInstance {
+    pub template: String,
+    /// Changed fields by `component.field`
+    pub overrides: BTreeMap<String, FieldValue>,
+}
+
+impl TemplateInstance {
+    pub fn new(template: impl Into<String>) -> Self {
+        Self {
+            template: template.into(),
+            overrides: BTreeMap::new(),
+        }
+    }
+    fn key(component: &str, field: &str) -> String {
+        format!("{}.{}", component, field)
+    }
+    /// The overridden value or the template's default
+    pub fn value<'a>(
+        &'a self,
+        template: &'a EntityTemplate,
+        component: &str,
+        field: &str,
+    ) -> Option<&'a FieldValue> {
+        self.overrides
+            .get(&Self::key(component, field))
+            .or_else(|| template.component(component)?.field(field))
+    }
+    pub fn set(&mut self, component: &str, field: &str, value: FieldValue) {
+        self.overrides.insert(Self::key(component, field), value);
+    }
+    /// Goes back to the template's values
+    pub fn revert(&mut self) {
+        self.overrides.clear();
+    }
+    /// Makes the overrides the template's defaults, so every instance gets them
+    pub fn apply_to(&mut self, template: &mut EntityTemplate) {
+        for component in &mut template.components {
+            for (field, value) in &mut component.fields {
+                if let Some(new) = self.overrides.remove(&Self::key(&component.name, field)) {
+                    *value = new;
+                }
+            }
+        }
+        self.overrides.clear();
+    }
+}
+
+/// Templates and the entities created from them, the part of the [`PrefabPanel`] to persist
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TemplateLibrary {
+    pub templates: Vec<EntityTemplate>,
+    /// Instances by entity ID, the IDs used in the [`EditorSelection`]
+    pub instances: HashMap<String, TemplateInstance>,
+}
+
+impl TemplateLibrary {
+    pub fn template(&self, name: &str) -> Option<&EntityTemplate> {
+        self.templates.iter().find(|template| template.name == name)
+    }
+    pub fn template_mut(&mut self, name: &str) -> Option<&mut EntityTemplate> {
+        self.templates
+            .iter_mut()
+            .find(|template| template.name == name)
+    }
+    /// Entity IDs of the instances of a template
+    pub fn instances_of<'a>(&'a self, template: &'a str) -> impl Iterator<Item = &'a str> + 'a {
+        self.instances
+            .iter()
+            .filter(move |(_, instance)| instance.template == template)
+            .map(|(entity, _)| entity.as_str())
+    }
+    /// Renames a template and its instances' references to it. Returns `false` and changes
+    /// nothing when `new` is empty or taken by another template.
+    pub fn rename_template(&mut self, old: &str, new: &str) -> bool {
+        if new.is_empty() || (new != old && self.template(new).is_some()) {
+            return false;
+        }
+        let Some(template) = self.template_mut(old) else {
+            return false;
+        };
+        template.name = new.to_string();
+        for instance in self.instances.values_mut() {
+            if instance.template == old {
+                instance.template = new.to_string();
+            }
+        }
+        true
+    }
+    /// `Template <n>` with the first `n` no template uses
+    fn unused_name(&self) -> String {
+        (self.templates.len() + 1..)
+            .map(|n| format!("Template {}", n))
+            .find(|name| self.template(name).is_none())
+            .expect("unbounded range")
+    }
+}
+
+/// Editor panel for defining entity templates and applying or reverting the changes of the
+/// selected entity.
+///
+/// Put it into the [`EditorShell`](super::EditorShell) with
+/// [`set_panel`](super::EditorShell::set_panel). The [`library`](Self::library) derives serde's
+/// traits with the `serde` feature.
+#[derive(Debug, Default)]
+pub struct PrefabPanel {
+    pub library: TemplateLibrary,
+    /// Index of the template being edited
+    current: Option<usize>,
+    /// Name field of the current template, applied when editing it ends
+    rename: String,
+    renaming: bool,
+    new_name: String,
+    /// Name and kind index of the field being added, by component
+    new_fields: HashMap<String, (String, usize)>,
+}
+
+impl PrefabPanel {
+    pub fn new(library: TemplateLibrary) -> Self {
+        Self {
+            library,
+            ..Self::default()
+        }
+    }
+
+    fn draw_instance(&mut self, ui: &Ui, entity: &str) {
+        let Some(instance) = self.library.instances.get_mut(entity) else {
+            return;
+        };
+        let Some(template) = self
+            .library
+            .templates
+            .iter_mut()
+            .find(|template| template.name == instance.template)
+        else {
+            ui.text_disabled(format!("Template {} is missing", instance.template));
+            return;
+        };
+        ui.text(format!("{} is an instance of {}", entity, template.name));
+        ui.disabled(instance.overrides.is_empty(), || {
+            if ui.button("Apply to template") {
+                instance.apply_to(template);
+            }
+            ui.same_line();
+            if ui.button("Revert to template") {
+                instance.revert();
+            }
+        });
+        for component in &template.components {
+            ui.text(&component.name);
+            for (field, _) in &component.fields {
+                let Some(mut value) = instance.value(template, &component.name, field).cloned()
+                else {
+                    continue;
+                };
+                let overridden = instance
+                    .overrides
+                    .contains_key(&TemplateInstance::key(&component.name, field));
+                let label = format!(
+                    "{}{}##{}",
+                    field,
+                    if overridden { " *" } else { "" },
+                    component.name
+                );
+                if value.edit(ui, &label) {
+                    instance.set(&component.name, field, value);
+                }
+            }
+        }
+        ui.separator();
+    }
+
+    fn draw_template(&mut self, ui: &Ui, index: usize) {
+        let template = &mut self.library.templates[index];
+        let mut remove_component = None;
+        for (component_index, component) in template.components.iter_mut().enumerate() {
+            let _id = ui.push_id_usize(component_index);
+            let open = ui.collapsing_header(&component.name, imgui::TreeNodeFlags::DEFAULT_OPEN);
+            if ui.is_item_clicked_with_button(imgui::MouseButton::Right) {
+                remove_component = Some(component_index);
+            }
+            if !open {
+                continue;
+            }
+            let mut remove_field = None;
+            for (field_index, (field, value)) in component.fields.iter_mut().enumerate() {
+                let _id = ui.push_id_usize(field_index);
+                value.edit(ui, field);
+                ui.same_line();
+                if ui.small_button("x") {
+                    remove_field = Some(field_index);
+                }
+            }
+            if let Some(field_index) = remove_field {
+                component.fields.remove(field_index);
+            }
+            let (new_field, new_kind) = self.new_fields.entry(component.name.clone()).or_default();
+            ui.set_next_item_width(120.0);
+            ui.input_text("##field_name", new_field).build();
+            ui.same_line();
+            ui.set_next_item_width(80.0);
+            ui.combo_simple_string("##field_kind", new_kind, &FieldValue::KINDS);
+            ui.same_line();
+            let taken = component.field(new_field).is_some();
+            ui.disabled(new_field.is_empty() || taken, || {
+                if ui.button("Add field") {
+                    let value = FieldValue::default_of(FieldValue::KINDS[*new_kind])
+                        .expect("kind from KINDS");
+                    component.fields.push((std::mem::take(new_field), value));
+                }
+            });
+        }
+        if let Some(component_index) = remove_component {
+            template.components.remove(component_index);
+        }
+        ui.input_text("##component_name", &mut self.new_name)
+            .hint("Component")
+            .build();
+        ui.same_line();
+        let taken = template.component(&self.new_name).is_some();
+        ui.disabled(self.new_name.is_empty() || taken, || {
+            if ui.button("Add component") {
+                template
+                    .components
+                    .push(ComponentTemplate::new(std::mem::take(&mut self.new_name)));
+            }
+        });
+    }
+}
+
+impl EditorPanel for PrefabPanel {
+    /// The selected entity's instance, if it has one, then the template list and editor.
+    /// Right click a component header to remove it. Renaming a template renames it in its
+    /// instances too, templates with instances can't be deleted.
+    fn draw(&mut self, ui: &Ui, selection: &mut EditorSelection) {
+        if let Some(entity) = selection.primary() {
+            self.draw_instance(ui, entity);
+        }
+        let names: Vec<&str> = self
+            .library
+            .templates
+            .iter()
+            .map(|template| template.name.as_str())
+            .collect();
+        let mut current = self.current.unwrap_or(usize::MAX);
+        // The library is public and may have been replaced by a shorter one
+        let preview = self
+            .current
+            .and_then(|index| names.get(index).copied())
+            .unwrap_or_default();
+        if let Some(_combo) = ui.begin_combo("Template", preview) {
+            for (index, name) in names.iter().enumerate() {
+                if ui
+                    .selectable_config(name)
+                    .selected(index == current)
+                    .build()
+                {
+                    current = index;
+                }
+            }
+        }
+        if (current < names.len()).then_some(current) != self.current {
+            self.new_fields.clear();
+        }
+        self.current = (current < names.len()).then_some(current);
+        if ui.button("New template") {
+            let name = self.library.unused_name();
+            self.library.templates.push(EntityTemplate::new(name));
+            self.current = Some(self.library.templates.len() - 1);
+            self.new_fields.clear();
+        }
+        if let Some(index) = self.current {
+            let name = self.library.templates[index].name.clone();
+            let used = self.library.instances_of(&name).count();
+            ui.same_line();
+            ui.disabled(used > 0, || {
+                if ui.button("Delete template") {
+                    self.library.templates.remove(index);
+                    self.current = None;
+                }
+            });
+            if used > 0
+                && ui.is_item_hovered_with_flags(imgui::ItemHoveredFlags::ALLOW_WHEN_DISABLED)
+            {
+                ui.tooltip_text(format!("Used by {} entities", used));
+            }
+            if self.current.is_none() {
+                return;
+            }
+            if !self.renaming {
+                self.rename = name.clone();
+            }
+            ui.input_text("Name", &mut self.rename).build();
+            self.renaming = ui.is_item_active();
+            if ui.is_item_deactivated_after_edit()
+                && !self.library.rename_template(&name, &self.rename)
+            {
+                self.rename = name;
+            }
+            self.draw_template(ui, index);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::Harness;
+
+    fn library() -> TemplateLibrary {
+        let mut library = TemplateLibrary::default();
+        library.templates.push(EntityTemplate::new("Crate"));
+        library.templates.push(EntityTemplate::new("Template 3"));
+        library
+            .instances
+            .insert("crate_1".to_string(), TemplateInstance::new("Crate"));
+        library
+    }
+
+    #[test]
+    fn renaming_keeps_instances() {
+        let mut library = library();
+        assert!(!library.rename_template("Crate", "Template 3"));
+        assert!(!library.rename_template("Crate", ""));
+        assert!(library.rename_template("Crate", "Barrel"));
+        assert_eq!(library.instances["crate_1"].template, "Barrel");
+        assert_eq!(
+            library.instances_of("Barrel").collect::<Vec<_>>(),
+            ["crate_1"]
+        );
+        assert_eq!(library.unused_name(), "Template 4");
+    }
+
+    #[test]
+    fn replaced_library_doesnt_panic() {
+        let mut panel = PrefabPanel::new(library());
+        panel.current = Some(1);
+        let mut selection = EditorSelection::default();
+        let mut harness = Harness::new();
+        harness.frame(|ui| panel.draw(ui, &mut selection));
+        panel.library = TemplateLibrary::default();
+        harness.frame(|ui| panel.draw(ui, &mut selection));
+        assert_eq!(panel.current, None);
+    }
+}
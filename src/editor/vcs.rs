@@ -0,0 +1,284 @@
+use imgui::Ui;
+use std::collections::HashMap;
+use std::ffi::OsStr;
+use std::io;
+use std::path::{Component, Path, PathBuf};
+use std::process::Command;
+
+/// Version control state of a file, or of the files in a directory
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub enum VcsStatus {
+    #[default]
+    Unmodified,
+    Modified,
+    Added,
+    Deleted,
+    Renamed,
+    Untracked,
+    Ignored,
+    Conflicted,
+}
+
+impl VcsStatus {
+    /// Letter shown next to the file name, empty when unmodified
+    pub fn badge(self) -> &'static str {
+        match self {
+            VcsStatus::Unmodified => "",
+            VcsStatus::Modified => "M",
+            VcsStatus::Added => "A",
+            VcsStatus::Deleted => "D",
+            VcsStatus::Renamed => "R",
+            VcsStatus::Untracked => "?",
+            VcsStatus::Ignored => "!",
+            VcsStatus::Conflicted => "U",
+        }
+    }
+    pub fn color(self) -> [f32; 4] {
+        match self {
+            VcsStatus::Unmodified | VcsStatus::Ignored => [0.5, 0.5, 0.5, 1.0],
+            VcsStatus::Modified | VcsStatus::Renamed => [0.9, 0.7, 0.2, 1.0],
+            VcsStatus::Added | VcsStatus::Untracked => [0.4, 0.8, 0.4, 1.0],
+            VcsStatus::Deleted | VcsStatus::Conflicted => [0.9, 0.3, 0.3, 1.0],
+        }
+    }
+}
+
+/// Version control backend of the asset views, see [`GitCli`] for git
+pub trait VcsProvider {
+    /// Status of a file, directories report the most notable status of the files in them
+    fn status(&self, path: &Path) -> VcsStatus;
+    /// Adds the file's changes to the next commit
+    fn stage(&mut self, path: &Path) -> io::Result<()>;
+    /// Throws away the file's changes
+    fn revert(&mut self, path: &Path) -> io::Result<()>;
+    /// Reads the statuses again, e.g. after files were saved
+    fn refresh(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// [`VcsProvider`] running the `git` command line tool.
+///
+/// Statuses are read once with `git status` and cached until [`refresh`](VcsProvider::refresh).
+/// Paths can be absolute or relative to the working directory.
+#[derive(Clone, Debug)]
+pub struct GitCli {
+    /// Top level of the work tree, status paths are relative to it
+    root: PathBuf,
+    statuses: HashMap<PathBuf, VcsStatus>,
+}
+
+impl GitCli {
+    /// Opens the repository containing `dir`
+    pub fn open(dir: impl AsRef<Path>) -> io::Result<Self> {
+        let output = git(dir.as_ref(), ["rev-parse", "--show-toplevel"])?;
+        let root = PathBuf::from(String::from_utf8_lossy(&output).trim());
+        let mut git = Self {
+            root,
+            statuses: HashMap::new(),
+        };
+        git.refresh()?;
+        Ok(git)
+    }
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Path relative to the work tree, the way `git status` reports it. Relative paths are
+    /// taken from the working directory, paths outside of the work tree stay as they are.
+    fn relative(&self, path: &Path) -> PathBuf {
+        let absolute = match std::env::current_dir() {
+            Ok(dir) => normalize(&dir.join(path)),
+            Err(_) => path.to_path_buf(),
+        };
+        if let Ok(relative) = absolute.strip_prefix(&self.root) {
+            return relative.to_path_buf();
+        }
+        // The top level from git has symlinks resolved, resolve them in the path too. Deleted
+        // files don't exist anymore, their directory does.
+        let canonical = absolute.canonicalize().ok().or_else(|| {
+            let dir = absolute.parent()?.canonicalize().ok()?;
+            Some(dir.join(absolute.file_name()?))
+        });
+        canonical
+            .and_then(|path| Some(path.strip_prefix(&self.root).ok()?.to_path_buf()))
+            .unwrap_or_else(|| path.to_path_buf())
+    }
+}
+
+fn git<I, S>(dir: &Path, args: I) -> io::Result<Vec<u8>>
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<OsStr>,
+{
+    let output = Command::new("git").arg("-C").arg(dir).args(args).output()?;
+    if !output.status.success() {
+        return Err(io::Error::other(
+            String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        ));
+    }
+    Ok(output.stdout)
+}
+
+/// Removes `.` and `..` components without touching the file system
+fn normalize(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => (),
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            component => normalized.push(component),
+        }
+    }
+    normalized
+}
+
+/// Status from the two letters of `git status --porcelain`
+fn parse_status(index: u8, worktree: u8) -> VcsStatus {
+    match (index, worktree) {
+        (b'?', b'?') => VcsStatus::Untracked,
+        (b'!', b'!') => VcsStatus::Ignored,
+        (b'U', _) | (_, b'U') | (b'D', b'D') | (b'A', b'A') => VcsStatus::Conflicted,
+        (b'R' | b'C', _) => VcsStatus::Renamed,
+        (b'A', _) => VcsStatus::Added,
+        (b'D', _) | (_, b'D') => VcsStatus::Deleted,
+        _ => VcsStatus::Modified,
+    }
+}
+
+/// Which status a directory shows when its files differ
+fn rank(status: VcsStatus) -> u8 {
+    match status {
+        VcsStatus::Unmodified | VcsStatus::Ignored => 0,
+        VcsStatus::Untracked => 1,
+        VcsStatus::Added | VcsStatus::Renamed | VcsStatus::Deleted => 2,
+        VcsStatus::Modified => 3,
+        VcsStatus::Conflicted => 4,
+    }
+}
+
+impl VcsProvider for GitCli {
+    fn status(&self, path: &Path) -> VcsStatus {
+        let path = self.relative(path);
+        if let Some(&status) = self.statuses.get(&path) {
+            return status;
+        }
+        self.statuses
+            .iter()
+            .filter(|(file, _)| file.starts_with(&path))
+            .map(|(_, &status)| status)
+            .max_by_key(|&status| rank(status))
+            .unwrap_or_default()
+    }
+    fn stage(&mut self, path: &Path) -> io::Result<()> {
+        let path = self.relative(path);
+        git(
+            &self.root,
+            [OsStr::new("add"), OsStr::new("--"), path.as_os_str()],
+        )?;
+        self.refresh()
+    }
+    fn revert(&mut self, path: &Path) -> io::Result<()> {
+        if self.status(path) == VcsStatus::Untracked {
+            return Err(io::Error::other(
+                "untracked files aren't known to git, delete them instead",
+            ));
+        }
+        let path = self.relative(path);
+        let args = ["restore", "--source=HEAD", "--staged", "--worktree", "--"];
+        git(
+            &self.root,
+            args.map(OsStr::new).into_iter().chain([path.as_os_str()]),
+        )?;
+        self.refresh()
+    }
+    fn refresh(&mut self) -> io::Result<()> {
+        // Files in new directories are listed one by one instead of as the directory
+        let output = git(
+            &self.root,
+            ["status", "--porcelain=v1", "-z", "--untracked-files=all"],
+        )?;
+        self.statuses.clear();
+        let mut entries = output.split(|&b| b == 0);
+        while let Some(entry) = entries.next() {
+            if entry.len() < 4 {
+                continue;
+            }
+            let status = parse_status(entry[0], entry[1]);
+            let path = PathBuf::from(String::from_utf8_lossy(&entry[3..]).into_owned());
+            if status == VcsStatus::Renamed {
+                // The original path follows a rename
+                entries.next();
+            }
+            self.statuses.insert(path, status);
+        }
+        Ok(())
+    }
+}
+
+/// Draws the status letter of a path on the same line as the last item, nothing when it's
+/// unmodified. Use it after file names in asset views and file dialogs.
+pub fn vcs_badge(ui: &Ui, provider: &dyn VcsProvider, path: &Path) {
+    let status = provider.status(path);
+    if status == VcsStatus::Unmodified {
+        return;
+    }
+    ui.same_line();
+    ui.text_colored(status.color(), status.badge());
+}
+
+/// Stage and Revert entries for a file's context menu, call inside the open popup. Returns the
+/// error of a failed action so it can be shown.
+pub fn vcs_menu_items(ui: &Ui, provider: &mut dyn VcsProvider, path: &Path) -> io::Result<()> {
+    let status = provider.status(path);
+    let changed = !matches!(status, VcsStatus::Unmodified | VcsStatus::Ignored);
+    if ui
+        .menu_item_config("Stage")
+        .enabled(changed && status != VcsStatus::Conflicted)
+        .build()
+    {
+        provider.stage(path)?;
+    }
+    if ui
+        .menu_item_config("Revert")
+        .enabled(changed && status != VcsStatus::Untracked)
+        .build()
+    {
+        provider.revert(path)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn files_in_untracked_directories_get_badges() {
+        let dir = std::env::temp_dir().join(format!("imgui_support_vcs_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join("assets/new/deep")).unwrap();
+        git(&dir, ["init", "-q"]).unwrap();
+        fs::write(dir.join("assets/new/deep/tree.png"), "png").unwrap();
+
+        // From a subdirectory the paths are still matched against the top level
+        let git = GitCli::open(dir.join("assets")).unwrap();
+        let file = dir.join("assets/new/deep/tree.png");
+        assert_eq!(git.status(&file), VcsStatus::Untracked);
+        assert_eq!(git.status(&dir.join("assets/new")), VcsStatus::Untracked);
+        assert_eq!(
+            git.status(&dir.join("assets/./new/../new/deep/tree.png")),
+            VcsStatus::Untracked
+        );
+        let cwd = std::env::current_dir().unwrap();
+        let up: PathBuf = cwd.components().skip(1).map(|_| "..").collect();
+        assert_eq!(
+            git.status(&up.join(file.strip_prefix("/").unwrap_or(&file))),
+            VcsStatus::Untracked
+        );
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
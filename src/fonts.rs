@@ -0,0 +1,447 @@
+//! Font atlas configuration.
+//!
+//! [`FontBuilder`] describes the fonts merged into the UI font and how the atlas texture is built.
+//! Large glyph ranges (e.g. full CJK) multiplied by oversampling can produce an atlas bigger than
+//! the GPU supports, so the builder lowers the oversampling until the atlas fits.
+
+use crate::FONT_SIZE;
+use imgui::{sys, Context, Font, FontConfig, FontGlyphRanges, FontSource, Image, Ui};
+use std::borrow::Cow;
+
+const MIB: f32 = 1024.0 * 1024.0;
+
+#[derive(Clone, Debug)]
+struct FontEntry {
+    data: Cow<'static, [u8]>,
+    glyph_ranges: FontGlyphRanges,
+    rasterizer_multiply: f32,
+}
+
+impl FontEntry {
+    fn source(&self, size: f32, oversample: [i32; 2]) -> FontSource<'_> {
+        FontSource::TtfData {
+            data: &self.data,
+            size_pixels: size,
+            config: Some(FontConfig {
+                rasterizer_multiply: self.rasterizer_multiply,
+                oversample_h: oversample[0],
+                oversample_v: oversample[1],
+                glyph_ranges: self.glyph_ranges.clone(),
+                ..FontConfig::default()
+            }),
+        }
+    }
+}
+
+/// Describes the UI font and its atlas, pass it to [`init_with_fonts`](crate::init_with_fonts)
+#[derive(Clone, Debug)]
+pub struct FontBuilder {
+    size: f32,
+    oversample_h: i32,
+    oversample_v: i32,
+    tex_desired_width: i32,
+    max_texture_size: Option<u32>,
+    fonts: Vec<FontEntry>,
+    /// Separate fonts after the UI font, name and size
+    named: Vec<(String, f32, FontEntry)>,
+}
+
+/// What the atlas ended up as after [`FontBuilder::build`]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct FontAtlasInfo {
+    pub width: u32,
+    pub height: u32,
+    pub oversample_h: i32,
+    pub oversample_v: i32,
+    /// The oversampling or texture width had to be changed to stay under the max texture size
+    pub downgraded: bool,
+    /// Still bigger than the max texture size at 1x1 oversampling and the widest texture, the
+    /// glyph ranges need reducing
+    pub oversized: bool,
+    /// Limit the atlas was built for, the smaller of the GPU's and
+    /// [`FontBuilder::max_texture_size`]
+    pub max_texture_size: u32,
+}
+
+impl Default for FontBuilder {
+    /// Roboto with the Japanese glyphs of M+ 1p merged in, 4x4 oversampling.
+    ///
+    /// Each font is left out without its cargo feature, `default-fonts` and `japanese-font`.
+    /// Without either imgui's built-in font is used.
+    fn default() -> Self {
+        let fonts = Self::empty();
+        #[cfg(feature = "default-fonts")]
+        let fonts = fonts.add_font(
+            &include_bytes!("../resources/Roboto-Regular.ttf")[..],
+            FontGlyphRanges::default(),
+        );
+        #[cfg(feature = "japanese-font")]
+        let fonts = fonts.add_font(
+            &include_bytes!("../resources/mplus-1p-regular.ttf")[..],
+            FontGlyphRanges::japanese(),
+        );
+        fonts
+    }
+}
+
+impl FontBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Builder without any fonts, the first added font is the main one. imgui's built-in font
+    /// is used if none is added.
+    pub fn empty() -> Self {
+        Self {
+            size: FONT_SIZE,
+            oversample_h: 4,
+            oversample_v: 4,
+            tex_desired_width: 0,
+            max_texture_size: None,
+            fonts: Vec::new(),
+            named: Vec::new(),
+        }
+    }
+    /// Font size in logical pixels
+    pub fn size(mut self, size: f32) -> Self {
+        self.size = size;
+        self
+    }
+    pub fn font_size(&self) -> f32 {
+        self.size
+    }
+    /// Copy with the UI font and every named font `factor` times bigger
+    pub fn scaled(&self, factor: f32) -> Self {
+        let mut scaled = self.clone();
+        scaled.size *= factor;
+        for (_, size, _) in &mut scaled.named {
+            *size *= factor;
+        }
+        scaled
+    }
+    /// Oversampling helps improve text rendering at expense of larger font atlas texture
+    pub fn oversample(mut self, h: i32, v: i32) -> Self {
+        self.oversample_h = h.max(1);
+        self.oversample_v = v.max(1);
+        self
+    }
+    /// Atlas texture width, rounded up to a power of two. 0 lets imgui pick one from the glyph
+    /// count
+    pub fn tex_desired_width(mut self, width: u32) -> Self {
+        self.tex_desired_width = match width {
+            0 => 0,
+            width => width.next_power_of_two() as i32,
+        };
+        self
+    }
+    /// Lower limit than the GPU's max texture size for the atlas
+    pub fn max_texture_size(mut self, size: u32) -> Self {
+        self.max_texture_size = Some(size);
+        self
+    }
+    /// Merges a font into the UI font, only glyphs in `glyph_ranges` are rasterized
+    pub fn add_font(
+        self,
+        data: impl Into<Cow<'static, [u8]>>,
+        glyph_ranges: FontGlyphRanges,
+    ) -> Self {
+        self.add_font_with(data, glyph_ranges, 1.0)
+    }
+    /// Same as [`add_font`](Self::add_font) with a rasterizer multiplier, >1.0 makes the glyphs
+    /// heavier
+    pub fn add_font_with(
+        mut self,
+        data: impl Into<Cow<'static, [u8]>>,
+        glyph_ranges: FontGlyphRanges,
+        rasterizer_multiply: f32,
+    ) -> Self {
+        self.fonts.push(FontEntry {
+            data: data.into(),
+            glyph_ranges,
+            rasterizer_multiply,
+        });
+        self
+    }
+
+    /// Adds a separate font, e.g. a big title font, used with `ui.push_font`. Its ID is found by
+    /// name after the build, see [`ImGui::font`](crate::ImGui::font). A font with the same name
+    /// is replaced.
+    pub fn add_named_font(
+        mut self,
+        name: impl Into<String>,
+        data: impl Into<Cow<'static, [u8]>>,
+        size: f32,
+        glyph_ranges: FontGlyphRanges,
+    ) -> Self {
+        let name = name.into();
+        let entry = FontEntry {
+            data: data.into(),
+            glyph_ranges,
+            rasterizer_multiply: 1.0,
+        };
+        match self.named.iter_mut().find(|(named, _, _)| *named == name) {
+            Some(named) => *named = (name, size, entry),
+            None => self.named.push((name, size, entry)),
+        }
+        self
+    }
+    /// Names of the fonts added with [`add_named_font`](Self::add_named_font), their IDs follow
+    /// the UI font's in the atlas in this order
+    pub fn named_fonts(&self) -> impl Iterator<Item = &str> {
+        self.named.iter().map(|(name, _, _)| name.as_str())
+    }
+
+    /// Replaces the fonts of `ctx` and builds the atlas.
+    ///
+    /// `gpu_max_texture_size` is the limit reported by the GPU. When the atlas is bigger than
+    /// that or [`max_texture_size`](Self::max_texture_size) the oversampling is halved, and then
+    /// the texture width raised, until it fits. The returned info tells when that happened or it
+    /// still doesn't fit, which is also logged with the `log` feature.
+    pub fn build(&self, ctx: &mut Context, gpu_max_texture_size: u32) -> FontAtlasInfo {
+        let max_size = self
+            .max_texture_size
+            .map_or(gpu_max_texture_size, |size| size.min(gpu_max_texture_size));
+        let mut oversample = [self.oversample_h, self.oversample_v];
+        let mut tex_desired_width = self.tex_desired_width;
+        // Widest power of two texture that still fits
+        let widest = 1 << (31 - max_size.max(1).leading_zeros());
+        let mut downgraded = false;
+        loop {
+            let (width, height) = self.build_atlas(ctx, oversample, tex_desired_width);
+            let fits = width <= max_size && height <= max_size;
+            if !fits && oversample.iter().any(|&o| o > 1) {
+                oversample = oversample.map(|o| (o / 2).max(1));
+            } else if !fits && tex_desired_width < widest {
+                // Wider and shorter
+                tex_desired_width = widest;
+            } else {
+                if !fits {
+                    log!(
+                        warn,
+                        "Font atlas is {}x{} which exceeds the max texture size of {}, reduce the glyph ranges",
+                        width, height, max_size
+                    );
+                } else if downgraded {
+                    log!(
+                        warn,
+                        "Font atlas exceeded the max texture size of {}, built with {}x{} oversampling and a {}x{} texture instead",
+                        max_size, oversample[0], oversample[1], width, height
+                    );
+                }
+                return FontAtlasInfo {
+                    width,
+                    height,
+                    oversample_h: oversample[0],
+                    oversample_v: oversample[1],
+                    downgraded,
+                    oversized: !fits,
+                    max_texture_size: max_size,
+                };
+            }
+            downgraded = true;
+        }
+    }
+
+    fn build_atlas(
+        &self,
+        ctx: &mut Context,
+        oversample: [i32; 2],
+        tex_desired_width: i32,
+    ) -> (u32, u32) {
+        let fonts = ctx.fonts();
+        fonts.clear();
+        fonts.tex_desired_width = tex_desired_width;
+        let sources: Vec<_> = self
+            .fonts
+            .iter()
+            .map(|font| font.source(self.size, oversample))
+            .collect();
+        if sources.is_empty() {
+            fonts.add_font(&[FontSource::DefaultFontData {
+                config: Some(FontConfig {
+                    size_pixels: self.size,
+                    oversample_h: oversample[0],
+                    oversample_v: oversample[1],
+                    ..FontConfig::default()
+                }),
+            }]);
+        } else {
+            fonts.add_font(&sources);
+        }
+        for (_, size, font) in &self.named {
+            fonts.add_font(&[font.source(*size, oversample)]);
+        }
+        let texture = fonts.build_rgba32_texture();
+        (texture.width, texture.height)
+    }
+}
+
+/// Glyph ranges covering the characters of `text`, e.g. every translation of a language, to merge
+/// a font for its script with [`FontBuilder::add_font`].
+///
+/// imgui needs glyph ranges for as long as the program runs, so each call leaks them. Call it
+/// when the fonts are rebuilt, not every frame.
+pub fn glyph_ranges_for(text: &str) -> FontGlyphRanges {
+    let mut chars: Vec<u32> = text
+        .chars()
+        .filter(|c| !c.is_control())
+        .map(u32::from)
+        .collect();
+    chars.sort_unstable();
+    chars.dedup();
+    let mut ranges: Vec<u32> = Vec::new();
+    for c in chars {
+        match ranges.last_mut() {
+            Some(end) if *end + 1 == c => *end = c,
+            _ => ranges.extend([c, c]),
+        }
+    }
+    ranges.push(0);
+    FontGlyphRanges::from_slice(Box::leak(ranges.into_boxed_slice()))
+}
+
+/// Characters of `text` the current font has no glyph for, each once. Whitespace and control
+/// characters are skipped.
+pub fn missing_glyphs(ui: &Ui, text: &str) -> Vec<char> {
+    let font = ui.current_font() as *const Font as *mut sys::ImFont;
+    let mut missing: Vec<char> = text
+        .chars()
+        .filter(|c| !c.is_whitespace() && !c.is_control())
+        // SAFETY: `Font` is a transparent wrapper of `ImFont`, the lookup doesn't modify it
+        .filter(|&c| unsafe { sys::ImFont_FindGlyphNoFallback(font, c as sys::ImWchar).is_null() })
+        .collect();
+    missing.sort_unstable();
+    missing.dedup();
+    missing
+}
+
+/// Debug panel showing the font atlas texture, glyph counts, occupancy and memory.
+///
+/// The oversampling and texture width can be edited in the panel, they're applied to the builder
+/// with [`apply`](Self::apply) when [`draw`](Self::draw) reports a rebuild.
+#[derive(Clone, Debug)]
+pub struct AtlasDiagnostics {
+    zoom: f32,
+    oversample: [i32; 2],
+    tex_desired_width: i32,
+}
+
+impl AtlasDiagnostics {
+    /// Starts with the settings of `fonts`
+    pub fn new(fonts: &FontBuilder) -> Self {
+        Self {
+            zoom: 1.0,
+            oversample: [fonts.oversample_h, fonts.oversample_v],
+            tex_desired_width: fonts.tex_desired_width,
+        }
+    }
+    /// Copies the settings edited in the panel into `fonts`
+    pub fn apply(&self, fonts: FontBuilder) -> FontBuilder {
+        fonts
+            .oversample(self.oversample[0], self.oversample[1])
+            .tex_desired_width(self.tex_desired_width as u32)
+    }
+
+    /// Draws the panel, returns `true` when Rebuild was pressed.
+    ///
+    /// The atlas can't be rebuilt while a frame is in progress, call
+    /// [`ImGui::rebuild_fonts`](crate::ImGui::rebuild_fonts) after rendering.
+    pub fn draw(&mut self, ui: &Ui, opened: &mut bool, info: &FontAtlasInfo) -> bool {
+        let mut rebuild = false;
+        ui.window("Font atlas").opened(opened).build(|| {
+            let atlas = ui.fonts();
+            let surface: i64 = atlas
+                .fonts()
+                .iter()
+                .filter_map(|&id| atlas.get_font(id))
+                .map(|font| font.metrics_total_surface as i64)
+                .sum();
+            let area = (info.width as i64 * info.height as i64).max(1);
+            ui.text(format!("Texture: {}x{}", info.width, info.height));
+            ui.text(format!(
+                "Occupancy: {:.1}%",
+                surface as f32 / area as f32 * 100.0
+            ));
+            // RGBA32 on the GPU plus the alpha8 and RGBA32 copies kept by imgui
+            ui.text(format!(
+                "Memory: {:.2} MiB GPU, {:.2} MiB CPU",
+                (area * 4) as f32 / MIB,
+                (area * 5) as f32 / MIB
+            ));
+            if info.oversized {
+                ui.text_colored(
+                    [1.0, 0.4, 0.4, 1.0],
+                    format!(
+                        "Exceeds the max texture size of {}, reduce the glyph ranges",
+                        info.max_texture_size
+                    ),
+                );
+            } else if info.downgraded {
+                ui.text_colored(
+                    [1.0, 0.7, 0.2, 1.0],
+                    format!(
+                        "Downgraded to {}x{} oversampling to fit the max texture size of {}",
+                        info.oversample_h, info.oversample_v, info.max_texture_size
+                    ),
+                );
+            }
+
+            ui.separator();
+            for (i, &id) in atlas.fonts().iter().enumerate() {
+                let Some(font) = atlas.get_font(id) else {
+                    continue;
+                };
+                // SAFETY: `Font` is a transparent wrapper of `ImFont`
+                let glyphs = unsafe { (*(font as *const Font as *const sys::ImFont)).Glyphs.Size };
+                ui.text(format!(
+                    "Font {}: {}px, {} sources, {} glyphs",
+                    i, font.font_size, font.config_data_count, glyphs
+                ));
+            }
+
+            ui.separator();
+            ui.input_int2("Oversample", &mut self.oversample).build();
+            self.oversample = self.oversample.map(|o| o.clamp(1, 8));
+            ui.input_int("Texture width", &mut self.tex_desired_width)
+                .step(256)
+                .build();
+            self.tex_desired_width = self.tex_desired_width.max(0);
+            rebuild = ui.button("Rebuild");
+
+            ui.separator();
+            ui.slider("Zoom", 0.25, 4.0, &mut self.zoom);
+            ui.child_window("##atlas_texture")
+                .horizontal_scrollbar(true)
+                .build(|| {
+                    let size = [
+                        info.width as f32 * self.zoom,
+                        info.height as f32 * self.zoom,
+                    ];
+                    Image::new(atlas.tex_id, size)
+                        .border_col([1.0, 1.0, 1.0, 0.5])
+                        .build(ui);
+                });
+        });
+        rebuild
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::Harness;
+
+    #[test]
+    fn atlas_over_the_limit_is_reported() {
+        let mut harness = Harness::new();
+        let fonts = FontBuilder::empty().oversample(4, 4);
+        let roomy = fonts.build(harness.context(), 4096);
+        assert!(!roomy.downgraded && !roomy.oversized);
+        let tight = fonts.build(harness.context(), roomy.height / 2);
+        assert!(tight.downgraded && !tight.oversized);
+        assert!(tight.oversample_h < 4);
+        // With a much smaller limit imgui fails to pack its mouse cursors
+        let tiny = fonts.size(40.0).build(harness.context(), 256);
+        assert!(tiny.oversized);
+        assert_eq!(tiny.max_texture_size, 256);
+    }
+}
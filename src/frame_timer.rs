@@ -0,0 +1,117 @@
+/// Frames kept by [`FrameTimer::new`], two seconds at 60 FPS
+pub const FRAME_TIMER_WINDOW: usize = 120;
+
+/// Rolling frame time statistics over the last frames, fed with the engine's `dt`.
+///
+/// [`ImGui::update_dt`](crate::ImGui::update_dt) feeds
+/// [`ImGui::frame_timer`](crate::ImGui::frame_timer), other timers are fed with
+/// [`record`](Self::record). [`history`](Self::history) is always [`window`](Self::window) long,
+/// oldest first, so it can go straight into `ui.plot_lines`.
+#[derive(Clone, Debug)]
+pub struct FrameTimer {
+    /// Frame times in milliseconds, oldest first, zeros before the first frames
+    history: Vec<f32>,
+    /// Frames recorded, up to the window
+    recorded: usize,
+}
+
+impl Default for FrameTimer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FrameTimer {
+    pub fn new() -> Self {
+        Self::with_window(FRAME_TIMER_WINDOW)
+    }
+    /// Keeps the last `frames` frame times, at least one
+    pub fn with_window(frames: usize) -> Self {
+        Self {
+            history: vec![0.0; frames.max(1)],
+            recorded: 0,
+        }
+    }
+    pub fn window(&self) -> usize {
+        self.history.len()
+    }
+    /// Changes how many frames are kept, keeping the newest ones
+    pub fn set_window(&mut self, frames: usize) {
+        let frames = frames.max(1);
+        let len = self.history.len();
+        if frames < len {
+            self.history.drain(..len - frames);
+        } else {
+            self.history
+                .splice(0..0, std::iter::repeat_n(0.0, frames - len));
+        }
+        self.recorded = self.recorded.min(frames);
+    }
+    pub fn clear(&mut self) {
+        self.history.fill(0.0);
+        self.recorded = 0;
+    }
+
+    /// Adds a frame time in seconds
+    pub fn record(&mut self, dt: f32) {
+        self.history.copy_within(1.., 0);
+        if let Some(last) = self.history.last_mut() {
+            *last = dt * 1000.0;
+        }
+        self.recorded = (self.recorded + 1).min(self.history.len());
+    }
+    /// Frame times in milliseconds, oldest first. Always [`window`](Self::window) long, with
+    /// zeros before the first frames.
+    pub fn history(&self) -> &[f32] {
+        &self.history
+    }
+    /// Frame times recorded so far in the window, oldest first
+    pub fn recorded(&self) -> &[f32] {
+        &self.history[self.history.len() - self.recorded..]
+    }
+    /// Newest frame time in milliseconds
+    pub fn last(&self) -> Option<f32> {
+        self.recorded().last().copied()
+    }
+
+    /// Average frame time in milliseconds, 0 before the first frame
+    pub fn average(&self) -> f32 {
+        let recorded = self.recorded();
+        if recorded.is_empty() {
+            return 0.0;
+        }
+        recorded.iter().sum::<f32>() / recorded.len() as f32
+    }
+    /// Longest frame time in milliseconds
+    pub fn worst(&self) -> f32 {
+        self.recorded().iter().copied().fold(0.0, f32::max)
+    }
+    /// Frames per second from the average frame time
+    pub fn fps(&self) -> f32 {
+        to_fps(self.average())
+    }
+    /// The "1% low": frames per second over the slowest 1% of the frames, at least one frame.
+    /// Shows stutter that the average hides.
+    pub fn one_percent_low(&self) -> f32 {
+        self.percent_low(1.0)
+    }
+    /// Frames per second over the slowest `percent` of the frames, at least one frame
+    pub fn percent_low(&self, percent: f32) -> f32 {
+        let mut sorted = self.recorded().to_vec();
+        if sorted.is_empty() {
+            return 0.0;
+        }
+        sorted.sort_by(|a, b| b.total_cmp(a));
+        let count =
+            ((sorted.len() as f32 * percent / 100.0).ceil() as usize).clamp(1, sorted.len());
+        to_fps(sorted[..count].iter().sum::<f32>() / count as f32)
+    }
+}
+
+fn to_fps(ms: f32) -> f32 {
+    if ms > 0.0 {
+        1000.0 / ms
+    } else {
+        0.0
+    }
+}
@@ -0,0 +1,278 @@
+//! Gamepad button prompts.
+//!
+//! [`ButtonPrompts`] maps buttons to what's shown for them in the controller family's style,
+//! either a glyph of an icon font merged with [`FontBuilder::add_font`](crate::fonts::FontBuilder::add_font)
+//! or a text label as the fallback. Install it with [`set_prompts`] and use the [`GamepadUi`]
+//! helpers to show prompts, e.g. `ui.text_with_buttons("Press {South} to jump")`.
+
+use imgui::{Key, Ui};
+use std::cell::RefCell;
+use std::collections::HashMap;
+
+thread_local! {
+    static PROMPTS: RefCell<ButtonPrompts> = RefCell::new(ButtonPrompts::default());
+}
+
+/// Buttons by position, so South is A on Xbox, Cross on PlayStation and B on Switch
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Button {
+    South,
+    East,
+    West,
+    North,
+    LeftBumper,
+    RightBumper,
+    LeftTrigger,
+    RightTrigger,
+    LeftStick,
+    RightStick,
+    Start,
+    Select,
+    DPadUp,
+    DPadDown,
+    DPadLeft,
+    DPadRight,
+}
+
+impl Button {
+    pub const ALL: [Button; 16] = [
+        Button::South,
+        Button::East,
+        Button::West,
+        Button::North,
+        Button::LeftBumper,
+        Button::RightBumper,
+        Button::LeftTrigger,
+        Button::RightTrigger,
+        Button::LeftStick,
+        Button::RightStick,
+        Button::Start,
+        Button::Select,
+        Button::DPadUp,
+        Button::DPadDown,
+        Button::DPadLeft,
+        Button::DPadRight,
+    ];
+
+    /// Name used in [`GamepadUi::text_with_buttons`] placeholders
+    pub fn name(self) -> &'static str {
+        match self {
+            Button::South => "South",
+            Button::East => "East",
+            Button::West => "West",
+            Button::North => "North",
+            Button::LeftBumper => "LeftBumper",
+            Button::RightBumper => "RightBumper",
+            Button::LeftTrigger => "LeftTrigger",
+            Button::RightTrigger => "RightTrigger",
+            Button::LeftStick => "LeftStick",
+            Button::RightStick => "RightStick",
+            Button::Start => "Start",
+            Button::Select => "Select",
+            Button::DPadUp => "DPadUp",
+            Button::DPadDown => "DPadDown",
+            Button::DPadLeft => "DPadLeft",
+            Button::DPadRight => "DPadRight",
+        }
+    }
+    pub fn from_name(name: &str) -> Option<Button> {
+        Button::ALL.into_iter().find(|button| button.name() == name)
+    }
+    /// imgui's key for the button, for checking it with `ui.is_key_pressed`
+    pub fn key(self) -> Key {
+        match self {
+            Button::South => Key::GamepadFaceDown,
+            Button::East => Key::GamepadFaceRight,
+            Button::West => Key::GamepadFaceLeft,
+            Button::North => Key::GamepadFaceUp,
+            Button::LeftBumper => Key::GamepadL1,
+            Button::RightBumper => Key::GamepadR1,
+            Button::LeftTrigger => Key::GamepadL2,
+            Button::RightTrigger => Key::GamepadR2,
+            Button::LeftStick => Key::GamepadL3,
+            Button::RightStick => Key::GamepadR3,
+            Button::Start => Key::GamepadStart,
+            Button::Select => Key::GamepadBack,
+            Button::DPadUp => Key::GamepadDpadUp,
+            Button::DPadDown => Key::GamepadDpadDown,
+            Button::DPadLeft => Key::GamepadDpadLeft,
+            Button::DPadRight => Key::GamepadDpadRight,
+        }
+    }
+}
+
+/// Controller family the prompts are shown for
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub enum GamepadStyle {
+    #[default]
+    Xbox,
+    PlayStation,
+    Switch,
+}
+
+impl GamepadStyle {
+    /// Guesses the family from the name the OS reports for a controller, Xbox when unknown
+    pub fn from_controller_name(name: &str) -> GamepadStyle {
+        let name = name.to_lowercase();
+        let matches = |words: &[&str]| words.iter().any(|word| name.contains(word));
+        if matches(&[
+            "playstation",
+            "dualshock",
+            "dualsense",
+            "ps3",
+            "ps4",
+            "ps5",
+            "sony",
+        ]) {
+            GamepadStyle::PlayStation
+        } else if matches(&["nintendo", "switch", "joy-con", "joycon", "pro controller"]) {
+            GamepadStyle::Switch
+        } else {
+            GamepadStyle::Xbox
+        }
+    }
+
+    /// Text shown for a button without a glyph
+    pub fn label(self, button: Button) -> &'static str {
+        use self::Button::*;
+        match (self, button) {
+            (GamepadStyle::Xbox, South) => "A",
+            (GamepadStyle::Xbox, East) => "B",
+            (GamepadStyle::Xbox, West) => "X",
+            (GamepadStyle::Xbox, North) => "Y",
+            (GamepadStyle::Xbox, LeftBumper) => "LB",
+            (GamepadStyle::Xbox, RightBumper) => "RB",
+            (GamepadStyle::Xbox, LeftTrigger) => "LT",
+            (GamepadStyle::Xbox, RightTrigger) => "RT",
+            (GamepadStyle::Xbox, LeftStick) => "LS",
+            (GamepadStyle::Xbox, RightStick) => "RS",
+            (GamepadStyle::Xbox, Start) => "Menu",
+            (GamepadStyle::Xbox, Select) => "View",
+            (GamepadStyle::PlayStation, South) => "Cross",
+            (GamepadStyle::PlayStation, East) => "Circle",
+            (GamepadStyle::PlayStation, West) => "Square",
+            (GamepadStyle::PlayStation, North) => "Triangle",
+            (GamepadStyle::PlayStation, LeftBumper) => "L1",
+            (GamepadStyle::PlayStation, RightBumper) => "R1",
+            (GamepadStyle::PlayStation, LeftTrigger) => "L2",
+            (GamepadStyle::PlayStation, RightTrigger) => "R2",
+            (GamepadStyle::PlayStation, LeftStick) => "L3",
+            (GamepadStyle::PlayStation, RightStick) => "R3",
+            (GamepadStyle::PlayStation, Start) => "Options",
+            (GamepadStyle::PlayStation, Select) => "Share",
+            (GamepadStyle::Switch, South) => "B",
+            (GamepadStyle::Switch, East) => "A",
+            (GamepadStyle::Switch, West) => "Y",
+            (GamepadStyle::Switch, North) => "X",
+            (GamepadStyle::Switch, LeftBumper) => "L",
+            (GamepadStyle::Switch, RightBumper) => "R",
+            (GamepadStyle::Switch, LeftTrigger) => "ZL",
+            (GamepadStyle::Switch, RightTrigger) => "ZR",
+            (GamepadStyle::Switch, LeftStick) => "LS",
+            (GamepadStyle::Switch, RightStick) => "RS",
+            (GamepadStyle::Switch, Start) => "+",
+            (GamepadStyle::Switch, Select) => "-",
+            (_, DPadUp) => "Up",
+            (_, DPadDown) => "Down",
+            (_, DPadLeft) => "Left",
+            (_, DPadRight) => "Right",
+        }
+    }
+}
+
+/// What's shown for each button, see the [module docs](self)
+#[derive(Clone, Debug, Default)]
+pub struct ButtonPrompts {
+    pub style: GamepadStyle,
+    glyphs: HashMap<(GamepadStyle, Button), char>,
+}
+
+impl ButtonPrompts {
+    pub fn new(style: GamepadStyle) -> Self {
+        Self {
+            style,
+            glyphs: HashMap::new(),
+        }
+    }
+    /// Uses `glyph` of an icon font for `button` in `style`
+    pub fn with_glyph(mut self, style: GamepadStyle, button: Button, glyph: char) -> Self {
+        self.glyphs.insert((style, button), glyph);
+        self
+    }
+    /// Same as [`with_glyph`](Self::with_glyph) for every button of a style, in the order of
+    /// [`Button::ALL`]
+    pub fn with_glyphs(mut self, style: GamepadStyle, glyphs: [char; 16]) -> Self {
+        for (button, glyph) in Button::ALL.into_iter().zip(glyphs) {
+            self.glyphs.insert((style, button), glyph);
+        }
+        self
+    }
+    /// The glyph of the button in the current style, or its text label
+    pub fn label(&self, button: Button) -> String {
+        match self.glyphs.get(&(self.style, button)) {
+            Some(glyph) => glyph.to_string(),
+            None => self.style.label(button).to_string(),
+        }
+    }
+    /// Replaces `{Button}` placeholders, e.g. `{South}`, with the button labels. Unknown
+    /// placeholders stay as they are.
+    pub fn format(&self, text: &str) -> String {
+        let mut formatted = String::with_capacity(text.len());
+        let mut rest = text;
+        while let Some(start) = rest.find('{') {
+            formatted.push_str(&rest[..start]);
+            let placeholder = &rest[start..];
+            let button = placeholder
+                .find('}')
+                .and_then(|end| Some((end, Button::from_name(&placeholder[1..end])?)));
+            match button {
+                Some((end, button)) => {
+                    formatted.push_str(&self.label(button));
+                    rest = &placeholder[end + 1..];
+                }
+                None => {
+                    formatted.push('{');
+                    rest = &placeholder[1..];
+                }
+            }
+        }
+        formatted.push_str(rest);
+        formatted
+    }
+}
+
+/// Replaces the prompts used by the [`GamepadUi`] helpers on this thread
+pub fn set_prompts(prompts: ButtonPrompts) {
+    PROMPTS.with(|current| *current.borrow_mut() = prompts);
+}
+
+/// Switches the style of the installed prompts, e.g. when another controller was used
+pub fn set_style(style: GamepadStyle) {
+    PROMPTS.with(|current| current.borrow_mut().style = style);
+}
+
+pub fn style() -> GamepadStyle {
+    PROMPTS.with(|current| current.borrow().style)
+}
+
+/// Button prompt helpers on [`Ui`] using the prompts installed with [`set_prompts`]
+pub trait GamepadUi {
+    /// The glyph or text label of a button
+    fn gamepad_button_label(&self, button: Button) -> String;
+    /// Draws the prompt of a button as text
+    fn gamepad_button(&self, button: Button);
+    /// Draws text with `{Button}` placeholders replaced, see [`ButtonPrompts::format`]
+    fn text_with_buttons(&self, text: &str);
+}
+
+impl GamepadUi for Ui {
+    fn gamepad_button_label(&self, button: Button) -> String {
+        PROMPTS.with(|prompts| prompts.borrow().label(button))
+    }
+    fn gamepad_button(&self, button: Button) {
+        self.text(self.gamepad_button_label(button));
+    }
+    fn text_with_buttons(&self, text: &str) {
+        self.text(PROMPTS.with(|prompts| prompts.borrow().format(text)));
+    }
+}
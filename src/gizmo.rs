@@ -0,0 +1,623 @@
+//! Translate, rotate and scale manipulators drawn over the 3D scene, like ImGuizmo.
+//!
+//! A [`Gizmo`] draws its handles on imgui's background draw list, so they sit above the scene
+//! and below the UI windows, and edits a [`Transform`] or a matrix while a handle is dragged.
+//! The camera comes in as view and projection matrices, see [`GizmoCamera`]. Like
+//! [`Coords::screen_ray`](crate::coords::Coords::screen_ray) it assumes the scene fills the
+//! whole display.
+
+use crate::coords::Ray;
+use bugsyth_engine::context::camera::CameraState;
+use bugsyth_engine::math::{Mat4, Quaternion, Vec3, Vec4};
+use imgui::{MouseButton, Ui};
+
+pub(crate) const AXIS_COLORS: [[f32; 4]; 3] = [
+    [0.9, 0.25, 0.25, 1.0],
+    [0.3, 0.85, 0.3, 1.0],
+    [0.3, 0.45, 0.95, 1.0],
+];
+const CENTER_COLOR: [f32; 4] = [0.9, 0.9, 0.9, 1.0];
+pub(crate) const HIGHLIGHT_COLOR: [f32; 4] = [1.0, 0.85, 0.2, 1.0];
+/// How close the mouse has to be to a handle, in pixels
+const PICK_DISTANCE: f32 = 7.0;
+const RING_SEGMENTS: usize = 48;
+
+/// What dragging the handles does
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum GizmoMode {
+    /// Arrows along the axes, the center moves on the plane facing the camera
+    #[default]
+    Translate,
+    /// A ring around each axis
+    Rotate,
+    /// Boxes along the local axes, the center scales uniformly when dragged sideways
+    Scale,
+}
+
+/// Whether the translate and rotate handles follow the object's rotation. Scale always uses the
+/// object's own axes.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum GizmoSpace {
+    #[default]
+    World,
+    Local,
+}
+
+/// Position, rotation and scale of an object, applied as scale, then rotation, then translation
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Transform {
+    pub position: Vec3<f32>,
+    pub rotation: Quaternion<f32>,
+    pub scale: Vec3<f32>,
+}
+
+impl Default for Transform {
+    fn default() -> Self {
+        Self {
+            position: Vec3::zero(),
+            rotation: Quaternion::identity(),
+            scale: Vec3::one(),
+        }
+    }
+}
+
+impl Transform {
+    pub fn to_mat4(&self) -> Mat4<f32> {
+        Mat4::<f32>::translation_3d(self.position)
+            * Mat4::from(self.rotation)
+            * Mat4::<f32>::scaling_3d(self.scale)
+    }
+    /// Splits a matrix built from a translation, rotation and scale. Shear and mirroring are
+    /// lost, the scale comes out positive.
+    pub fn from_mat4(matrix: Mat4<f32>) -> Self {
+        let columns = [matrix.cols.x, matrix.cols.y, matrix.cols.z].map(Vec3::from);
+        let scale = columns.map(|column| column.magnitude());
+        let axes = [0, 1, 2].map(|i| {
+            if scale[i] > f32::EPSILON {
+                columns[i] / scale[i]
+            } else {
+                Vec3::zero()
+            }
+        });
+        Self {
+            position: Vec3::from(matrix.cols.w),
+            rotation: rotation_from_axes(axes),
+            scale: Vec3::from(scale),
+        }
+    }
+}
+
+/// View and projection matrices of the camera the scene is drawn with
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct GizmoCamera {
+    pub view: Mat4<f32>,
+    pub projection: Mat4<f32>,
+}
+
+impl From<&CameraState> for GizmoCamera {
+    fn from(camera: &CameraState) -> Self {
+        Self {
+            view: Mat4::from_col_arrays(camera.get_view()),
+            projection: Mat4::from_col_arrays(camera.get_perspective()),
+        }
+    }
+}
+
+/// A part of the gizmo the mouse can grab
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum Handle {
+    Axis(usize),
+    Center,
+}
+
+#[derive(Copy, Clone, Debug)]
+struct DragState {
+    handle: Handle,
+    start: Transform,
+    /// Axis or plane normal in world space
+    direction: Vec3<f32>,
+    /// Position along the axis where the drag started
+    start_along: f32,
+    /// Plane hit where the drag started, relative to the position for rotations
+    start_hit: Vec3<f32>,
+    start_mouse: [f32; 2],
+}
+
+/// Projection between the world and the display for one frame
+pub(crate) struct Screen {
+    size: [f32; 2],
+    view_projection: Mat4<f32>,
+    inverse: Mat4<f32>,
+    projection: Mat4<f32>,
+    /// Direction the camera looks in
+    forward: Vec3<f32>,
+}
+
+impl Screen {
+    pub(crate) fn new(ui: &Ui, camera: &GizmoCamera) -> Self {
+        let view_projection = camera.projection * camera.view;
+        let view_inverse = camera.view.inverted();
+        Self {
+            size: ui.io().display_size,
+            view_projection,
+            inverse: view_projection.inverted(),
+            projection: camera.projection,
+            forward: -Vec3::from(view_inverse.cols.z).normalized(),
+        }
+    }
+    /// UI position of a world position, `None` when it's behind the camera
+    pub(crate) fn project(&self, pos: Vec3<f32>) -> Option<[f32; 2]> {
+        let clip = self.view_projection * Vec4::from_point(pos);
+        if clip.w <= 0.0 {
+            return None;
+        }
+        Some([
+            (clip.x / clip.w + 1.0) * 0.5 * self.size[0],
+            (1.0 - clip.y / clip.w) * 0.5 * self.size[1],
+        ])
+    }
+    fn ray(&self, pos: [f32; 2]) -> Ray {
+        let x = pos[0] / self.size[0] * 2.0 - 1.0;
+        let y = 1.0 - pos[1] / self.size[1] * 2.0;
+        let unproject = |z: f32| {
+            let point = self.inverse * Vec4::new(x, y, z, 1.0);
+            Vec3::from(point) / point.w
+        };
+        let (near, far) = (unproject(-1.0), unproject(1.0));
+        Ray {
+            origin: near,
+            direction: (far - near).normalized(),
+        }
+    }
+    /// World length that shows up as `pixels` long at `pos`
+    pub(crate) fn world_length(&self, pos: Vec3<f32>, pixels: f32) -> f32 {
+        let w = (self.view_projection * Vec4::from_point(pos)).w;
+        pixels * 2.0 * w / (self.projection.cols.y.y * self.size[1])
+    }
+}
+
+/// Translate, rotate and scale manipulator, see the [module docs](self).
+///
+/// Call [`manipulate`](Self::manipulate) every frame the object is selected, between
+/// [`ImGui::frame`](crate::ImGui::frame) and rendering. Check [`is_active`](Self::is_active)
+/// before handling scene clicks so grabbing a handle doesn't also pick what's behind it.
+#[derive(Clone, Debug)]
+pub struct Gizmo {
+    pub mode: GizmoMode,
+    pub space: GizmoSpace,
+    /// Length of the handles in pixels
+    pub size: f32,
+    /// Translation step in world units
+    pub translate_snap: Option<f32>,
+    /// Rotation step in degrees
+    pub rotate_snap: Option<f32>,
+    /// Scale step
+    pub scale_snap: Option<f32>,
+    hovered: Option<Handle>,
+    drag: Option<DragState>,
+}
+
+impl Default for Gizmo {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Gizmo {
+    pub fn new() -> Self {
+        Self {
+            mode: GizmoMode::default(),
+            space: GizmoSpace::default(),
+            size: 90.0,
+            translate_snap: None,
+            rotate_snap: None,
+            scale_snap: None,
+            hovered: None,
+            drag: None,
+        }
+    }
+    /// The mouse is over a handle
+    pub fn is_hovered(&self) -> bool {
+        self.hovered.is_some()
+    }
+    /// A handle is being dragged
+    pub fn is_active(&self) -> bool {
+        self.drag.is_some()
+    }
+
+    /// Radio buttons for the mode and space, e.g. for a viewport toolbar
+    pub fn toolbar(&mut self, ui: &Ui) {
+        ui.radio_button("Move", &mut self.mode, GizmoMode::Translate);
+        ui.same_line();
+        ui.radio_button("Rotate", &mut self.mode, GizmoMode::Rotate);
+        ui.same_line();
+        ui.radio_button("Scale", &mut self.mode, GizmoMode::Scale);
+        ui.same_line();
+        ui.text_disabled("|");
+        ui.same_line();
+        ui.radio_button("World", &mut self.space, GizmoSpace::World);
+        ui.same_line();
+        ui.radio_button("Local", &mut self.space, GizmoSpace::Local);
+    }
+
+    /// Draws the handles at `transform` and applies dragging them. Returns `true` when the
+    /// transform changed.
+    pub fn manipulate(&mut self, ui: &Ui, camera: &GizmoCamera, transform: &mut Transform) -> bool {
+        let screen = Screen::new(ui, camera);
+        let Some(center) = screen.project(transform.position) else {
+            self.hovered = None;
+            self.drag = None;
+            return false;
+        };
+        let mouse = ui.io().mouse_pos;
+        let ray = screen.ray(mouse);
+        let mut changed = false;
+        if let Some(drag) = self.drag {
+            if ui.is_mouse_down(MouseButton::Left) {
+                changed = self.drag_to(&drag, &ray, mouse, transform);
+            } else {
+                self.drag = None;
+            }
+        }
+        let length = screen.world_length(transform.position, self.size);
+        let axes = self.axes(transform);
+        if self.drag.is_none() {
+            self.hovered = if ui.io().want_capture_mouse {
+                None
+            } else {
+                self.pick(&screen, center, length, &axes, transform.position, mouse)
+            };
+            if let Some(handle) = self.hovered {
+                if ui.is_mouse_clicked(MouseButton::Left) {
+                    self.drag = self.start_drag(handle, &screen, &ray, &axes, transform, mouse);
+                }
+            }
+        }
+        self.draw(ui, &screen, center, length, &axes, transform.position);
+        changed
+    }
+    /// [`manipulate`](Self::manipulate) for a model matrix, see [`Transform::from_mat4`] for
+    /// what survives the round trip
+    pub fn manipulate_matrix(
+        &mut self,
+        ui: &Ui,
+        camera: &GizmoCamera,
+        matrix: &mut Mat4<f32>,
+    ) -> bool {
+        let mut transform = Transform::from_mat4(*matrix);
+        let changed = self.manipulate(ui, camera, &mut transform);
+        if changed {
+            *matrix = transform.to_mat4();
+        }
+        changed
+    }
+
+    /// World directions of the handles
+    fn axes(&self, transform: &Transform) -> [Vec3<f32>; 3] {
+        let local = self.space == GizmoSpace::Local || self.mode == GizmoMode::Scale;
+        [Vec3::unit_x(), Vec3::unit_y(), Vec3::unit_z()].map(|axis| {
+            if local {
+                (transform.rotation * axis).normalized()
+            } else {
+                axis
+            }
+        })
+    }
+
+    fn pick(
+        &self,
+        screen: &Screen,
+        center: [f32; 2],
+        length: f32,
+        axes: &[Vec3<f32>; 3],
+        position: Vec3<f32>,
+        mouse: [f32; 2],
+    ) -> Option<Handle> {
+        let mut best = None;
+        let mut best_distance = PICK_DISTANCE;
+        let mut consider = |handle: Handle, distance: f32| {
+            if distance < best_distance {
+                best = Some(handle);
+                best_distance = distance;
+            }
+        };
+        if self.mode != GizmoMode::Rotate {
+            consider(
+                Handle::Center,
+                distance(mouse, center) - PICK_DISTANCE * 0.5,
+            );
+        }
+        for (i, axis) in axes.iter().enumerate() {
+            let distance = match self.mode {
+                GizmoMode::Translate | GizmoMode::Scale => {
+                    let Some(end) = screen.project(position + *axis * length) else {
+                        continue;
+                    };
+                    // Axes pointing at the camera can't be dragged sensibly
+                    if distance(center, end) < PICK_DISTANCE {
+                        continue;
+                    }
+                    segment_distance(mouse, center, end)
+                }
+                GizmoMode::Rotate => ring(screen, position, *axis, length)
+                    .windows(2)
+                    .map(|segment| segment_distance(mouse, segment[0], segment[1]))
+                    .fold(f32::MAX, f32::min),
+            };
+            consider(Handle::Axis(i), distance);
+        }
+        best
+    }
+
+    fn start_drag(
+        &self,
+        handle: Handle,
+        screen: &Screen,
+        ray: &Ray,
+        axes: &[Vec3<f32>; 3],
+        transform: &Transform,
+        mouse: [f32; 2],
+    ) -> Option<DragState> {
+        let mut drag = DragState {
+            handle,
+            start: *transform,
+            direction: screen.forward,
+            start_along: 0.0,
+            start_hit: transform.position,
+            start_mouse: mouse,
+        };
+        match (self.mode, handle) {
+            (GizmoMode::Translate | GizmoMode::Scale, Handle::Axis(i)) => {
+                drag.direction = axes[i];
+                drag.start_along = along_axis(ray, transform.position, axes[i])?;
+            }
+            (GizmoMode::Translate, Handle::Center) => {
+                drag.start_hit = ray.intersect_plane(transform.position, screen.forward)?;
+            }
+            (GizmoMode::Rotate, Handle::Axis(i)) => {
+                drag.direction = axes[i];
+                drag.start_hit =
+                    ray.intersect_plane(transform.position, axes[i])? - transform.position;
+            }
+            (GizmoMode::Rotate, Handle::Center) | (GizmoMode::Scale, Handle::Center) => (),
+        }
+        Some(drag)
+    }
+
+    fn drag_to(
+        &self,
+        drag: &DragState,
+        ray: &Ray,
+        mouse: [f32; 2],
+        transform: &mut Transform,
+    ) -> bool {
+        let start = &drag.start;
+        let new = match (self.mode, drag.handle) {
+            (GizmoMode::Translate, Handle::Axis(_)) => {
+                let Some(along) = along_axis(ray, start.position, drag.direction) else {
+                    return false;
+                };
+                let delta = snap(along - drag.start_along, self.translate_snap);
+                Transform {
+                    position: start.position + drag.direction * delta,
+                    ..*transform
+                }
+            }
+            (GizmoMode::Translate, Handle::Center) => {
+                let Some(hit) = ray.intersect_plane(start.position, drag.direction) else {
+                    return false;
+                };
+                let delta = (hit - drag.start_hit).map(|v| snap(v, self.translate_snap));
+                Transform {
+                    position: start.position + delta,
+                    ..*transform
+                }
+            }
+            (GizmoMode::Rotate, Handle::Axis(_)) => {
+                let Some(hit) = ray.intersect_plane(start.position, drag.direction) else {
+                    return false;
+                };
+                let (from, to) = (drag.start_hit, hit - start.position);
+                let angle = drag.direction.dot(from.cross(to)).atan2(from.dot(to));
+                let angle = snap(angle.to_degrees(), self.rotate_snap).to_radians();
+                Transform {
+                    rotation: (Quaternion::rotation_3d(angle, drag.direction) * start.rotation)
+                        .normalized(),
+                    ..*transform
+                }
+            }
+            (GizmoMode::Rotate, Handle::Center) => return false,
+            (GizmoMode::Scale, Handle::Axis(i)) => {
+                let Some(along) = along_axis(ray, start.position, drag.direction) else {
+                    return false;
+                };
+                if drag.start_along.abs() <= f32::EPSILON {
+                    return false;
+                }
+                let mut scale = start.scale;
+                scale[i] = snap(start.scale[i] * along / drag.start_along, self.scale_snap);
+                Transform {
+                    scale,
+                    ..*transform
+                }
+            }
+            (GizmoMode::Scale, Handle::Center) => {
+                let factor = (1.0 + (mouse[0] - drag.start_mouse[0]) / self.size).max(0.01);
+                Transform {
+                    scale: start.scale.map(|v| snap(v * factor, self.scale_snap)),
+                    ..*transform
+                }
+            }
+        };
+        if new == *transform {
+            return false;
+        }
+        *transform = new;
+        true
+    }
+
+    fn draw(
+        &self,
+        ui: &Ui,
+        screen: &Screen,
+        center: [f32; 2],
+        length: f32,
+        axes: &[Vec3<f32>; 3],
+        position: Vec3<f32>,
+    ) {
+        let draw_list = ui.get_background_draw_list();
+        let focused = self.drag.map(|drag| drag.handle).or(self.hovered);
+        let color = |handle: Handle, normal: [f32; 4]| {
+            if focused == Some(handle) {
+                HIGHLIGHT_COLOR
+            } else {
+                normal
+            }
+        };
+        for (i, axis) in axes.iter().enumerate() {
+            let axis_color = color(Handle::Axis(i), AXIS_COLORS[i]);
+            if self.mode == GizmoMode::Rotate {
+                draw_list
+                    .add_polyline(ring(screen, position, *axis, length), axis_color)
+                    .thickness(2.5)
+                    .build();
+                continue;
+            }
+            let Some(end) = screen.project(position + *axis * length) else {
+                continue;
+            };
+            draw_list
+                .add_line(center, end, axis_color)
+                .thickness(3.0)
+                .build();
+            if self.mode == GizmoMode::Scale {
+                draw_list
+                    .add_rect(
+                        [end[0] - 5.0, end[1] - 5.0],
+                        [end[0] + 5.0, end[1] + 5.0],
+                        axis_color,
+                    )
+                    .filled(true)
+                    .build();
+                continue;
+            }
+            let along = [end[0] - center[0], end[1] - center[1]];
+            let screen_length = distance(center, end);
+            if screen_length < 1.0 {
+                continue;
+            }
+            let along = [along[0] / screen_length, along[1] / screen_length];
+            let side = [-along[1] * 5.0, along[0] * 5.0];
+            draw_list
+                .add_triangle(
+                    [end[0] + along[0] * 12.0, end[1] + along[1] * 12.0],
+                    [end[0] + side[0], end[1] + side[1]],
+                    [end[0] - side[0], end[1] - side[1]],
+                    axis_color,
+                )
+                .filled(true)
+                .build();
+        }
+        let center_color = color(Handle::Center, CENTER_COLOR);
+        match self.mode {
+            GizmoMode::Translate => {
+                draw_list
+                    .add_circle(center, PICK_DISTANCE, center_color)
+                    .thickness(2.0)
+                    .build();
+            }
+            GizmoMode::Scale => {
+                draw_list
+                    .add_rect(
+                        [center[0] - 6.0, center[1] - 6.0],
+                        [center[0] + 6.0, center[1] + 6.0],
+                        center_color,
+                    )
+                    .filled(true)
+                    .build();
+            }
+            GizmoMode::Rotate => {
+                draw_list
+                    .add_circle(center, 2.5, center_color)
+                    .filled(true)
+                    .build();
+            }
+        }
+    }
+}
+
+/// Quaternion of the rotation matrix with the columns `axes`
+fn rotation_from_axes([x, y, z]: [Vec3<f32>; 3]) -> Quaternion<f32> {
+    let trace = x.x + y.y + z.z;
+    let (qx, qy, qz, qw) = if trace > 0.0 {
+        let s = (trace + 1.0).sqrt() * 2.0;
+        ((y.z - z.y) / s, (z.x - x.z) / s, (x.y - y.x) / s, 0.25 * s)
+    } else if x.x > y.y && x.x > z.z {
+        let s = (1.0 + x.x - y.y - z.z).sqrt() * 2.0;
+        (0.25 * s, (y.x + x.y) / s, (z.x + x.z) / s, (y.z - z.y) / s)
+    } else if y.y > z.z {
+        let s = (1.0 + y.y - x.x - z.z).sqrt() * 2.0;
+        ((y.x + x.y) / s, 0.25 * s, (z.y + y.z) / s, (z.x - x.z) / s)
+    } else {
+        let s = (1.0 + z.z - x.x - y.y).sqrt() * 2.0;
+        ((z.x + x.z) / s, (z.y + y.z) / s, 0.25 * s, (x.y - y.x) / s)
+    };
+    let rotation = Quaternion::from_xyzw(qx, qy, qz, qw);
+    if rotation.magnitude_squared() > f32::EPSILON {
+        rotation.normalized()
+    } else {
+        Quaternion::identity()
+    }
+}
+
+/// Screen points of a circle around `axis`, closed
+fn ring(screen: &Screen, position: Vec3<f32>, axis: Vec3<f32>, radius: f32) -> Vec<[f32; 2]> {
+    let helper = if axis.x.abs() < 0.9 {
+        Vec3::unit_x()
+    } else {
+        Vec3::unit_y()
+    };
+    let u = axis.cross(helper).normalized();
+    let v = axis.cross(u);
+    (0..=RING_SEGMENTS)
+        .filter_map(|i| {
+            let angle = i as f32 / RING_SEGMENTS as f32 * std::f32::consts::TAU;
+            screen.project(position + (u * angle.cos() + v * angle.sin()) * radius)
+        })
+        .collect()
+}
+
+/// Position along the line through `origin` in `direction` closest to the ray, `None` when they
+/// are parallel
+fn along_axis(ray: &Ray, origin: Vec3<f32>, direction: Vec3<f32>) -> Option<f32> {
+    let offset = origin - ray.origin;
+    let cos = direction.dot(ray.direction);
+    let denominator = 1.0 - cos * cos;
+    if denominator <= 1e-6 {
+        return None;
+    }
+    Some((cos * ray.direction.dot(offset) - direction.dot(offset)) / denominator)
+}
+
+fn snap(value: f32, step: Option<f32>) -> f32 {
+    match step {
+        Some(step) if step > 0.0 => (value / step).round() * step,
+        _ => value,
+    }
+}
+
+fn distance(a: [f32; 2], b: [f32; 2]) -> f32 {
+    ((a[0] - b[0]).powi(2) + (a[1] - b[1]).powi(2)).sqrt()
+}
+
+/// Distance from `point` to the segment from `a` to `b`
+fn segment_distance(point: [f32; 2], a: [f32; 2], b: [f32; 2]) -> f32 {
+    let ab = [b[0] - a[0], b[1] - a[1]];
+    let length_squared = ab[0] * ab[0] + ab[1] * ab[1];
+    if length_squared <= f32::EPSILON {
+        return distance(point, a);
+    }
+    let t =
+        (((point[0] - a[0]) * ab[0] + (point[1] - a[1]) * ab[1]) / length_squared).clamp(0.0, 1.0);
+    distance(point, [a[0] + ab[0] * t, a[1] + ab[1] * t])
+}
@@ -0,0 +1,73 @@
+//! Blocking HTTP(S) client for uploading bug reports and fetching update manifests, a thin
+//! wrapper over `ureq` with rustls.
+
+use std::io::Read;
+use std::time::Duration;
+
+const TIMEOUT: Duration = Duration::from_secs(30);
+/// Largest response read, update manifests and upload replies are small
+const MAX_RESPONSE: u64 = 16 * 1024 * 1024;
+
+/// Sends a request to an `http://` or `https://` URL and returns the response body if the status
+/// is 2xx
+pub(crate) fn request(
+    method: &str,
+    url: &str,
+    headers: &[(&str, &str)],
+    body: &[u8],
+) -> Result<Vec<u8>, String> {
+    let mut request = ureq::request(method, url).timeout(TIMEOUT);
+    for (name, value) in headers {
+        request = request.set(name, value);
+    }
+    let response = match request.send_bytes(body) {
+        Ok(response) => response,
+        Err(ureq::Error::Status(code, response)) => {
+            return Err(format!(
+                "Server responded with \"{} {}\"",
+                code,
+                response.status_text()
+            ))
+        }
+        Err(e) => return Err(e.to_string()),
+    };
+    let mut body = Vec::new();
+    response
+        .into_reader()
+        .take(MAX_RESPONSE)
+        .read_to_end(&mut body)
+        .map_err(|e| e.to_string())?;
+    Ok(body)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{BufRead, BufReader, Write};
+    use std::net::TcpListener;
+
+    /// Serves one request with `response` and returns the URL to send it to
+    fn serve_once(response: &'static str) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/manifest", listener.local_addr().unwrap());
+        std::thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream);
+            let mut line = String::new();
+            while reader.read_line(&mut line).unwrap() > 2 {
+                line.clear();
+            }
+            reader.get_mut().write_all(response.as_bytes()).unwrap();
+        });
+        url
+    }
+
+    #[test]
+    fn returns_the_body_of_successful_responses() {
+        let url = serve_once("HTTP/1.1 200 OK\r\nContent-Length: 5\r\n\r\n1.2.3");
+        assert_eq!(request("GET", &url, &[], &[]).unwrap(), b"1.2.3");
+        let url = serve_once("HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\n\r\n");
+        let error = request("GET", &url, &[], &[]).unwrap_err();
+        assert!(error.contains("404"), "{}", error);
+    }
+}
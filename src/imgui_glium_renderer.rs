@@ -1,24 +1,42 @@
 use bugsyth_engine::glium::backend::{Context, Facade};
+use bugsyth_engine::glium::draw_parameters::{Stencil, StencilOperation, StencilTest};
+use bugsyth_engine::glium::framebuffer::{SimpleFrameBuffer, ValidationError};
 use bugsyth_engine::glium::index::{self, PrimitiveType};
 use bugsyth_engine::glium::program::ProgramChooserCreationError;
+#[cfg(feature = "dds")]
+use bugsyth_engine::glium::texture::CompressedMipmapsOption;
 use bugsyth_engine::glium::texture::{
-    ClientFormat, MipmapsOption, RawImage2d, TextureCreationError,
+    ClientFormat, CompressedTexture2d, CubeLayer, Cubemap, DepthTexture2d, DepthTexture2dArray,
+    MipmapsOption, RawImage2d, SrgbTexture2d, Texture2dArray, TextureCreationError,
 };
 use bugsyth_engine::glium::uniforms::{
-    MagnifySamplerFilter, MinifySamplerFilter, Sampler, SamplerBehavior, SamplerWrapFunction,
+    MagnifySamplerFilter, MinifySamplerFilter, SamplerBehavior, SamplerWrapFunction, UniformValue,
+    Uniforms,
 };
 use bugsyth_engine::glium::{
-    program, uniform, vertex, Blend, BlendingFunction, DrawError, DrawParameters, IndexBuffer,
-    LinearBlendingFactor, Program, Rect, Surface, Texture2d, VertexBuffer,
+    program, vertex, Blend, BlendingFunction, Depth, DepthTest, DrawError, DrawParameters,
+    IndexBuffer, LinearBlendingFactor, Program, Rect, Surface, Texture2d, VertexBuffer,
 };
+use bugsyth_engine::math::Mat4;
 
 use imgui::internal::RawWrapper;
-use imgui::{BackendFlags, DrawCmd, DrawCmdParams, DrawData, TextureId, Textures};
+use imgui::{
+    BackendFlags, DrawCmd, DrawCmdParams, DrawData, DrawIdx, DrawListMut, TextureId, Textures,
+};
 use std::borrow::Cow;
+use std::cell::Cell;
+use std::collections::HashMap;
 use std::error::Error;
 use std::fmt;
+use std::ops::BitOr;
 use std::rc::Rc;
 
+#[cfg(feature = "dds")]
+mod dds;
+mod pixel_preview;
+
+pub(crate) use pixel_preview::{read_pixels, PixelPreview};
+
 #[derive(Clone, Debug)]
 pub enum RendererError {
     Vertex(vertex::BufferCreationError),
@@ -26,7 +44,14 @@ pub enum RendererError {
     Program(ProgramChooserCreationError),
     Texture(TextureCreationError),
     Draw(DrawError),
+    Framebuffer(ValidationError),
     BadTexture(TextureId),
+    /// Decoding an image for [`Renderer::load_texture_from_bytes`] failed
+    #[cfg(feature = "image")]
+    Image(std::sync::Arc<image::ImageError>),
+    /// Reading or decompressing a DDS file for [`Renderer::load_texture_from_bytes`] failed
+    #[cfg(feature = "dds")]
+    Dds(&'static str),
 }
 
 impl Error for RendererError {
@@ -38,7 +63,12 @@ impl Error for RendererError {
             Program(ref e) => Some(e),
             Texture(ref e) => Some(e),
             Draw(ref e) => Some(e),
+            Framebuffer(ref e) => Some(e),
             BadTexture(_) => None,
+            #[cfg(feature = "image")]
+            Image(ref e) => Some(&**e),
+            #[cfg(feature = "dds")]
+            Dds(_) => None,
         }
     }
 }
@@ -52,7 +82,12 @@ impl fmt::Display for RendererError {
             Program(ref e) => write!(f, "Program creation failed: {}", e),
             Texture(_) => write!(f, "Texture creation failed"),
             Draw(ref e) => write!(f, "Drawing failed: {}", e),
+            Framebuffer(ref e) => write!(f, "Framebuffer creation failed: {}", e),
             BadTexture(ref t) => write!(f, "Bad texture ID: {}", t.id()),
+            #[cfg(feature = "image")]
+            Image(ref e) => write!(f, "Image decoding failed: {}", e),
+            #[cfg(feature = "dds")]
+            Dds(e) => write!(f, "DDS loading failed: {}", e),
         }
     }
 }
@@ -87,16 +122,529 @@ impl From<DrawError> for RendererError {
     }
 }
 
+impl From<ValidationError> for RendererError {
+    fn from(e: ValidationError) -> RendererError {
+        RendererError::Framebuffer(e)
+    }
+}
+
+#[cfg(feature = "image")]
+impl From<image::ImageError> for RendererError {
+    fn from(e: image::ImageError) -> RendererError {
+        RendererError::Image(std::sync::Arc::new(e))
+    }
+}
+
+/// A texture registered with the [`Renderer`], drawn with `ui.image()` through its `TextureId`
 pub struct Texture {
-    pub texture: Rc<Texture2d>,
+    pub source: TextureSource,
     pub sampler: SamplerBehavior,
+    /// Exposure and tonemap for floating point textures, `None` draws the values as they are
+    pub hdr: Option<HdrDisplay>,
+    /// Draws this texture's images instead of the UI program, e.g. a palette shader for a
+    /// minimap. Gets what [`Renderer::set_program`] lists, with `tex` matching the source.
+    /// Images in a channel view still use the built-in shaders.
+    pub program: Option<Rc<Program>>,
+}
+
+/// What a registered [`Texture`] samples from
+#[derive(Clone, Debug)]
+pub enum TextureSource {
+    Texture2d(Rc<Texture2d>),
+    /// An sRGB encoded texture, decoded to linear when sampled. For [`ColorMode::Linear`], which
+    /// expects linear texels.
+    Srgb(Rc<SrgbTexture2d>),
+    /// A block compressed texture, e.g. BC1 to BC7 from a DDS file
+    Compressed(Rc<CompressedTexture2d>),
+    /// One face of a cube map, or the whole map on a sphere, e.g. an environment map
+    Cubemap {
+        texture: Rc<Cubemap>,
+        view: CubemapView,
+    },
+    /// One layer of an array texture, e.g. a shadow cascade. Needs GLSL 1.30 or ES 3.00, older
+    /// contexts draw it magenta.
+    Array {
+        texture: Rc<Texture2dArray>,
+        layer: u32,
+    },
+    /// A depth texture, e.g. a shadow map, drawn as grey from 0 black to 1 white. The sampler
+    /// must not compare depths.
+    Depth(Rc<DepthTexture2d>),
+    /// One layer of a depth array texture, e.g. a shadow cascade, see [`Array`](Self::Array)
+    /// and [`Depth`](Self::Depth)
+    DepthArray {
+        texture: Rc<DepthTexture2dArray>,
+        layer: u32,
+    },
+}
+
+impl From<Rc<Texture2d>> for TextureSource {
+    fn from(texture: Rc<Texture2d>) -> Self {
+        TextureSource::Texture2d(texture)
+    }
+}
+
+impl TextureSource {
+    fn features(&self) -> ShaderFeatures {
+        match self {
+            TextureSource::Texture2d(_) | TextureSource::Srgb(_) | TextureSource::Compressed(_) => {
+                ShaderFeatures::NONE
+            }
+            TextureSource::Cubemap { .. } => ShaderFeatures::CUBEMAP,
+            TextureSource::Array { .. } => ShaderFeatures::ARRAY,
+            TextureSource::Depth(_) => ShaderFeatures::DEPTH,
+            TextureSource::DepthArray { .. } => ShaderFeatures::ARRAY | ShaderFeatures::DEPTH,
+        }
+    }
+}
+
+/// What a cube map [`TextureSource`] shows in the image's square
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum CubemapView {
+    Face(CubeLayer),
+    /// A mirror ball reflecting the map, the outside of the circle is transparent
+    Sphere,
+}
+
+impl CubemapView {
+    fn uniform(self) -> i32 {
+        match self {
+            CubemapView::Face(CubeLayer::PositiveX) => 0,
+            CubemapView::Face(CubeLayer::NegativeX) => 1,
+            CubemapView::Face(CubeLayer::PositiveY) => 2,
+            CubemapView::Face(CubeLayer::NegativeY) => 3,
+            CubemapView::Face(CubeLayer::PositiveZ) => 4,
+            CubemapView::Face(CubeLayer::NegativeZ) => 5,
+            CubemapView::Sphere => 6,
+        }
+    }
+}
+
+impl Texture {
+    fn features(&self) -> ShaderFeatures {
+        let mut features = self.source.features();
+        if self.hdr.is_some() {
+            features = features | ShaderFeatures::HDR;
+        }
+        features
+    }
+}
+
+/// Channel and mip level overrides for the images drawn between two [`set_image_view`] calls.
+/// Images with a view are magnified without filtering so single texels can be told apart.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub(crate) struct ImageView {
+    /// 0 shows all channels, 1 to 4 one of R, G, B, A as grey
+    pub channel: i32,
+    /// Mip level to sample, `None` for the sampler's usual filtering
+    pub mip: Option<u32>,
+    /// Depth drawn black and white for depth textures, to stretch the few values in use
+    pub depth_range: [f32; 2],
+}
+
+thread_local! {
+    /// View set by the last [`set_image_view`] callback run by the renderer
+    static IMAGE_VIEW: Cell<Option<ImageView>> = const { Cell::new(None) };
+}
+
+/// Queues a callback on `draw_list` that applies `view` to the images drawn after it, until the
+/// next call. `None` goes back to drawing images as they are.
+pub(crate) fn set_image_view(draw_list: &DrawListMut, view: Option<ImageView>) {
+    draw_list
+        .add_callback(move || IMAGE_VIEW.with(|current| current.set(view)))
+        .build();
+}
+
+/// Uniforms of one draw command
+struct DrawUniforms<'a> {
+    matrix: [[f32; 4]; 4],
+    texture: &'a Texture,
+    view: Option<ImageView>,
+    /// Set with [`Renderer::set_program_uniform`], only passed to custom programs
+    extra: &'a [(String, UniformValue<'static>)],
+}
+
+impl Uniforms for DrawUniforms<'_> {
+    fn visit_values<'a, F: FnMut(&str, UniformValue<'a>)>(&'a self, mut output: F) {
+        output("matrix", UniformValue::Mat4(self.matrix));
+        let mut sampler = self.texture.sampler;
+        if let Some(view) = self.view {
+            sampler.magnify_filter = MagnifySamplerFilter::Nearest;
+            if view.mip.is_some() {
+                sampler.minify_filter = MinifySamplerFilter::NearestMipmapNearest;
+            }
+        }
+        let sampler = Some(sampler);
+        match &self.texture.source {
+            TextureSource::Texture2d(texture) => {
+                output("tex", UniformValue::Texture2d(texture, sampler));
+            }
+            TextureSource::Srgb(texture) => {
+                output("tex", UniformValue::SrgbTexture2d(texture, sampler));
+            }
+            TextureSource::Compressed(texture) => {
+                output("tex", UniformValue::CompressedTexture2d(texture, sampler));
+            }
+            TextureSource::Cubemap { texture, view } => {
+                output("tex", UniformValue::Cubemap(texture, sampler));
+                output("cube_face", UniformValue::SignedInt(view.uniform()));
+            }
+            TextureSource::Array { texture, layer } => {
+                output("tex", UniformValue::Texture2dArray(texture, sampler));
+                output("layer", UniformValue::Float(*layer as f32));
+            }
+            TextureSource::Depth(texture) => {
+                output("tex", UniformValue::DepthTexture2d(texture, sampler));
+            }
+            TextureSource::DepthArray { texture, layer } => {
+                output("tex", UniformValue::DepthTexture2dArray(texture, sampler));
+                output("layer", UniformValue::Float(*layer as f32));
+            }
+        }
+        if matches!(
+            self.texture.source,
+            TextureSource::Depth(_) | TextureSource::DepthArray { .. }
+        ) {
+            let range = self.view.map_or([0.0, 1.0], |view| view.depth_range);
+            output("depth_range", UniformValue::Vec2(range));
+        }
+        if let Some(hdr) = self.texture.hdr {
+            output("exposure", UniformValue::Float(hdr.exposure.exp2()));
+            output("tonemap", UniformValue::SignedInt(hdr.tonemap.uniform()));
+            output("inv_gamma", UniformValue::Float(1.0 / hdr.gamma.max(0.01)));
+        }
+        if let Some(view) = self.view {
+            output("view_channel", UniformValue::SignedInt(view.channel));
+            let lod = view.mip.map_or(-1.0, |mip| mip as f32);
+            output("view_lod", UniformValue::Float(lod));
+        }
+        for (name, value) in self.extra {
+            output(name, *value);
+        }
+    }
+}
+
+/// Curve mapping HDR values into the displayable range, see [`HdrDisplay`]
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum Tonemap {
+    /// Values above 1 are clipped
+    #[default]
+    Clamp,
+    Reinhard,
+    /// Narkowicz's fit of the ACES filmic curve
+    Aces,
+}
+
+impl Tonemap {
+    fn uniform(self) -> i32 {
+        match self {
+            Tonemap::Clamp => 0,
+            Tonemap::Reinhard => 1,
+            Tonemap::Aces => 2,
+        }
+    }
+}
+
+/// How a floating point texture (e.g. an RGBA16F or RGBA32F render target) is shown, see
+/// [`Renderer::register_hdr_texture`]. Applied in the shader, so it can change every frame
+/// without touching the texture.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct HdrDisplay {
+    /// In stops, the values are multiplied by `2^exposure`
+    pub exposure: f32,
+    pub tonemap: Tonemap,
+    /// Applied after the tonemap, 2.2 shows linear values about right on an sRGB display
+    pub gamma: f32,
 }
 
+impl Default for HdrDisplay {
+    fn default() -> Self {
+        Self {
+            exposure: 0.0,
+            tonemap: Tonemap::default(),
+            gamma: 2.2,
+        }
+    }
+}
+
+/// How color and alpha are combined when blending the UI onto the target.
+///
+/// In both modes the alpha channel of the target accumulates coverage correctly, so a UI drawn
+/// into a transparent render target holds premultiplied color and composites with
+/// `One`/`OneMinusSourceAlpha`.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum AlphaMode {
+    /// Textures hold straight (non-premultiplied) alpha, the default
+    #[default]
+    Straight,
+    /// Textures hold premultiplied alpha, e.g. render targets the UI or the engine rendered into
+    /// with premultiplied output. The font atlas is premultiplied on upload in this mode.
+    Premultiplied,
+}
+
+/// How the UI's colors relate to the color space of the target.
+///
+/// imgui's colors, including the style and those passed to widgets, are sRGB values.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum ColorMode {
+    /// Colors are written to the target as they are, with the framebuffer's sRGB conversion
+    /// off, and blended as sRGB values. This is what the reference imgui backends do, so the UI
+    /// looks the same as theirs on sRGB and linear framebuffers alike. The default.
+    #[default]
+    Srgb,
+    /// Colors are decoded to linear in the shader and encoded back by an sRGB framebuffer, so
+    /// blending happens in linear space like the rest of a gamma-correct pipeline.
+    ///
+    /// The target must be sRGB capable, otherwise the UI comes out too dark. Textures are
+    /// expected to hold linear values: images loaded by the renderer are uploaded as
+    /// [`TextureSource::Srgb`] in this mode, register sRGB images of your own the same way. HDR
+    /// textures skip their [gamma](HdrDisplay::gamma), the framebuffer applies it. Anti-aliased
+    /// edges and text look thinner with linear blending, a higher rasterizer multiply on the
+    /// fonts (see [`FontBuilder::add_font_with`](crate::fonts::FontBuilder::add_font_with))
+    /// makes up for it.
+    Linear,
+}
+
+/// How the UI pass uses the stencil buffer of the target
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum StencilMode {
+    /// The stencil buffer is ignored, the default
+    #[default]
+    Disabled,
+    /// UI triangles write `reference` into the stencil bits in `mask`, so later passes (e.g.
+    /// post effects) can exclude UI pixels. Whole triangles are written, including transparent
+    /// parts of window backgrounds and glyph quads.
+    Write { reference: i32, mask: u32 },
+    /// The UI isn't drawn where the stencil bits in `mask` equal `reference`, so the engine can
+    /// punch holes into the UI, e.g. for a viewport panel it fills directly
+    SkipWhereEqual { reference: i32, mask: u32 },
+}
+
+impl StencilMode {
+    fn to_stencil(self) -> Stencil {
+        let (test, reference, write_mask, pass_operation) = match self {
+            StencilMode::Disabled => return Stencil::default(),
+            StencilMode::Write { reference, mask } => (
+                StencilTest::AlwaysPass,
+                reference,
+                mask,
+                StencilOperation::Replace,
+            ),
+            StencilMode::SkipWhereEqual { reference, mask } => (
+                StencilTest::IfNotEqual { mask },
+                reference,
+                0,
+                StencilOperation::Keep,
+            ),
+        };
+        // imgui doesn't keep a consistent winding, both faces get the same settings
+        Stencil {
+            test_clockwise: test,
+            reference_value_clockwise: reference,
+            write_mask_clockwise: write_mask,
+            depth_pass_operation_clockwise: pass_operation,
+            test_counter_clockwise: test,
+            reference_value_counter_clockwise: reference,
+            write_mask_counter_clockwise: write_mask,
+            depth_pass_operation_counter_clockwise: pass_operation,
+            ..Stencil::default()
+        }
+    }
+}
+
+/// Placement of the UI for [`Renderer::render_world`]
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct WorldSpace {
+    /// Maps UI coordinates to clip space
+    pub transform: Mat4<f32>,
+    /// Test against the target's depth buffer so scene geometry occludes the UI
+    pub depth_test: bool,
+}
+
+/// Size of a UI pass, passed to the render hooks
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct FrameStats {
+    /// Target size in pixels
+    pub target_dimensions: (u32, u32),
+    pub draw_lists: usize,
+    pub draw_calls: usize,
+    pub vertices: usize,
+    pub indices: usize,
+}
+
+impl FrameStats {
+    pub(crate) fn new(draw_data: &DrawData, target_dimensions: (u32, u32)) -> Self {
+        // The list pointer is null without draw lists, which `draw_lists` can't take
+        if draw_data.draw_lists_count() == 0 {
+            return Self {
+                target_dimensions,
+                ..Self::default()
+            };
+        }
+        Self {
+            target_dimensions,
+            draw_lists: draw_data.draw_lists_count(),
+            draw_calls: draw_data
+                .draw_lists()
+                .flat_map(|draw_list| draw_list.commands())
+                .filter(|cmd| matches!(cmd, DrawCmd::Elements { .. }))
+                .count(),
+            vertices: draw_data.total_vtx_count as usize,
+            indices: draw_data.total_idx_count as usize,
+        }
+    }
+}
+
+/// Closure run right before or after the UI pass, see [`Renderer::set_pre_render_hook`]
+pub type RenderHook = Box<dyn FnMut(&Rc<Context>, &FrameStats)>;
+
 pub struct Renderer {
+    /// `None` while suspended
+    gl: Option<GlResources>,
+    textures: Textures<Texture>,
+    alpha_mode: AlphaMode,
+    color_mode: ColorMode,
+    stencil_mode: StencilMode,
+    projection_override: Option<Mat4<f32>>,
+    pre_render_hook: Option<RenderHook>,
+    post_render_hook: Option<RenderHook>,
+    last_frame_stats: FrameStats,
+    program_uniforms: Vec<(String, UniformValue<'static>)>,
+    /// Reused across frames, one pair per draw list
+    buffers: Vec<DrawListBuffers>,
+}
+
+/// Dynamic buffers sized to powers of two, reallocated only when a draw list outgrows them
+struct DrawListBuffers {
+    vertices: VertexBuffer<GliumDrawVert>,
+    indices: IndexBuffer<DrawIdx>,
+}
+
+impl DrawListBuffers {
+    /// Uploads a draw list, growing the buffers first if needed
+    fn upload(
+        buffers: &mut Vec<DrawListBuffers>,
+        index: usize,
+        ctx: &Rc<Context>,
+        vertices: &[GliumDrawVert],
+        indices: &[DrawIdx],
+    ) -> Result<(), RendererError> {
+        let allocate_vertices =
+            |len: usize| VertexBuffer::empty_dynamic(ctx, len.max(1).next_power_of_two());
+        let allocate_indices = |len: usize| {
+            IndexBuffer::empty_dynamic(
+                ctx,
+                PrimitiveType::TrianglesList,
+                len.max(1).next_power_of_two(),
+            )
+        };
+        if index == buffers.len() {
+            buffers.push(DrawListBuffers {
+                vertices: allocate_vertices(vertices.len())?,
+                indices: allocate_indices(indices.len())?,
+            });
+        }
+        let buffer = &mut buffers[index];
+        if buffer.vertices.len() < vertices.len() {
+            buffer.vertices = allocate_vertices(vertices.len())?;
+        }
+        if buffer.indices.len() < indices.len() {
+            buffer.indices = allocate_indices(indices.len())?;
+        }
+        if let Some(slice) = buffer.vertices.slice(..vertices.len()) {
+            slice.write(vertices);
+        }
+        if let Some(slice) = buffer.indices.slice(..indices.len()) {
+            slice.write(indices);
+        }
+        Ok(())
+    }
+}
+
+/// Everything tied to the GL context
+struct GlResources {
     ctx: Rc<Context>,
-    program: Program,
+    /// Compiled programs per feature set, the default one is compiled up front
+    programs: HashMap<ShaderFeatures, Program>,
+    /// Replaces the built-in program for regular UI draws, see [`Renderer::set_program`]
+    custom_program: Option<Program>,
     font_texture: Texture,
-    textures: Textures<Texture>,
+}
+
+/// Switches compiled into the UI shaders as `#define`s, each set gets its own program
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+struct ShaderFeatures(u32);
+
+impl ShaderFeatures {
+    const NONE: ShaderFeatures = ShaderFeatures(0);
+    /// Multiplies the vertex color by its alpha, for [`AlphaMode::Premultiplied`]
+    const PREMULTIPLY: ShaderFeatures = ShaderFeatures(1 << 0);
+    /// Exposure, tonemap and gamma on the texture, for textures with [`HdrDisplay`]
+    const HDR: ShaderFeatures = ShaderFeatures(1 << 1);
+    /// `tex` is a cube map, see [`TextureSource::Cubemap`]
+    const CUBEMAP: ShaderFeatures = ShaderFeatures(1 << 2);
+    /// `tex` is an array texture, see [`TextureSource::Array`]
+    const ARRAY: ShaderFeatures = ShaderFeatures(1 << 3);
+    /// Channel isolation and mip selection, while an [`ImageView`] is set
+    const VIEW: ShaderFeatures = ShaderFeatures(1 << 4);
+    /// `tex` holds depth, drawn as grey, see [`TextureSource::Depth`]
+    const DEPTH: ShaderFeatures = ShaderFeatures(1 << 5);
+    /// Vertex colors decoded to linear and sRGB output by the framebuffer, for
+    /// [`ColorMode::Linear`]
+    const LINEAR: ShaderFeatures = ShaderFeatures(1 << 6);
+
+    const DEFINES: [(ShaderFeatures, &'static str); 7] = [
+        (Self::PREMULTIPLY, "PREMULTIPLY"),
+        (Self::HDR, "HDR"),
+        (Self::CUBEMAP, "CUBEMAP"),
+        (Self::ARRAY, "ARRAY"),
+        (Self::VIEW, "VIEW"),
+        (Self::DEPTH, "DEPTH"),
+        (Self::LINEAR, "LINEAR"),
+    ];
+
+    fn contains(self, other: ShaderFeatures) -> bool {
+        self.0 & other.0 == other.0
+    }
+    /// Inserts the defines after the `#version` line of `source`
+    fn apply(self, source: &str) -> String {
+        let (version, body) = source.split_once('\n').unwrap_or((source, ""));
+        let mut result = format!("{}\n", version);
+        for (feature, define) in Self::DEFINES {
+            if self.contains(feature) {
+                result.push_str("#define ");
+                result.push_str(define);
+                result.push('\n');
+            }
+        }
+        result.push_str(body);
+        result
+    }
+}
+
+impl BitOr for ShaderFeatures {
+    type Output = ShaderFeatures;
+    fn bitor(self, rhs: ShaderFeatures) -> ShaderFeatures {
+        ShaderFeatures(self.0 | rhs.0)
+    }
+}
+
+impl GlResources {
+    fn new<F: Facade>(
+        ctx: &mut imgui::Context,
+        facade: &F,
+        alpha_mode: AlphaMode,
+    ) -> Result<GlResources, RendererError> {
+        Ok(GlResources {
+            ctx: Rc::clone(facade.get_context()),
+            programs: HashMap::from([(
+                ShaderFeatures::NONE,
+                compile_program(facade, ShaderFeatures::NONE)?,
+            )]),
+            custom_program: None,
+            font_texture: upload_font_texture(ctx.fonts(), facade.get_context(), alpha_mode)?,
+        })
+    }
 }
 
 #[repr(C)]
@@ -141,8 +689,7 @@ impl bugsyth_engine::glium::vertex::Vertex for GliumDrawVert {
 impl Renderer {
     /// Creates a new [`Renderer`].
     pub fn new<F: Facade>(ctx: &mut imgui::Context, facade: &F) -> Result<Renderer, RendererError> {
-        let program = compile_default_program(facade)?;
-        let font_texture = upload_font_texture(ctx.fonts(), facade.get_context())?;
+        let gl = GlResources::new(ctx, facade, AlphaMode::default())?;
         ctx.set_renderer_name(Some(format!(
             "imgui-glium-renderer {}",
             env!("CARGO_PKG_VERSION")
@@ -151,12 +698,30 @@ impl Renderer {
             .backend_flags
             .insert(BackendFlags::RENDERER_HAS_VTX_OFFSET);
         Ok(Renderer {
-            ctx: Rc::clone(facade.get_context()),
-            program,
-            font_texture,
+            gl: Some(gl),
             textures: Textures::new(),
+            alpha_mode: AlphaMode::default(),
+            color_mode: ColorMode::default(),
+            stencil_mode: StencilMode::default(),
+            projection_override: None,
+            pre_render_hook: None,
+            post_render_hook: None,
+            last_frame_stats: FrameStats::default(),
+            program_uniforms: Vec::new(),
+            buffers: Vec::new(),
         })
     }
+    /// Creates a new [`Renderer`] drawing the UI with `program`, see
+    /// [`set_program`](Self::set_program)
+    pub fn with_program<F: Facade>(
+        ctx: &mut imgui::Context,
+        facade: &F,
+        program: Program,
+    ) -> Result<Renderer, RendererError> {
+        let mut renderer = Self::new(ctx, facade)?;
+        renderer.set_program(Some(program));
+        Ok(renderer)
+    }
 
     /// Creates a new [`Renderer`]
     #[deprecated(since = "0.13.0", note = "use `new` instead")]
@@ -168,15 +733,338 @@ impl Renderer {
     }
 
     pub fn reload_font_texture(&mut self, ctx: &mut imgui::Context) -> Result<(), RendererError> {
-        self.font_texture = upload_font_texture(ctx.fonts(), &self.ctx)?;
+        // While suspended the font texture gets uploaded on resume
+        if let Some(gl) = &mut self.gl {
+            gl.font_texture = upload_font_texture(ctx.fonts(), &gl.ctx, self.alpha_mode)?;
+        }
+        Ok(())
+    }
+    /// Releases all GL resources, e.g. when the app gets suspended on Android.
+    ///
+    /// User textures are released too, their IDs are invalid after resuming. Rendering does
+    /// nothing until [`resume`](Self::resume) is called.
+    pub fn suspend(&mut self) {
+        self.gl = None;
+        self.textures = Textures::new();
+        self.buffers.clear();
+    }
+    /// Recreates the program and font texture on the new context
+    pub fn resume<F: Facade>(
+        &mut self,
+        ctx: &mut imgui::Context,
+        facade: &F,
+    ) -> Result<(), RendererError> {
+        self.gl = Some(GlResources::new(ctx, facade, self.alpha_mode)?);
+        Ok(())
+    }
+    /// Recompiles the UI shaders, with the `shader-hot-reload` feature they're read from disk.
+    ///
+    /// The old programs stay in use if compiling fails.
+    pub fn reload_shaders(&mut self) -> Result<(), RendererError> {
+        let Some(gl) = &mut self.gl else {
+            return Ok(());
+        };
+        let programs = gl
+            .programs
+            .keys()
+            .map(|&features| Ok((features, compile_program(&gl.ctx, features)?)))
+            .collect::<Result<HashMap<_, _>, RendererError>>()?;
+        gl.programs = programs;
+        Ok(())
+    }
+    /// Replaces the built-in shader for regular UI draws: the font atlas and 2D textures. Cube
+    /// maps, arrays, depth and HDR textures and channel views keep the built-in shaders. `None`
+    /// goes back to the built-in one.
+    ///
+    /// The program gets the vertex attributes `pos` (`vec2` in UI pixels), `uv` (`vec2`) and `col`
+    /// (`vec4` from 0 to 255), and the uniforms `matrix` (`mat4` from UI pixels to clip space),
+    /// `tex` (`sampler2D`) and those set with [`set_program_uniform`](Self::set_program_uniform).
+    /// See `src/shader` for the built-in ones to start from.
+    ///
+    /// - Blending follows the [`AlphaMode`]: with [`AlphaMode::Premultiplied`] the shader has
+    ///   to premultiply the vertex color
+    /// - With the default [`ColorMode::Srgb`] the built-in programs output sRGB, create the
+    ///   program with `outputs_srgb: true` too or glium converts the colors a second time. With
+    ///   [`ColorMode::Linear`] they decode `col` to linear and are created with `false`.
+    /// - The program belongs to the GL context, it's dropped on [`suspend`](Self::suspend) and
+    ///   has to be set again after resuming
+    pub fn set_program(&mut self, program: Option<Program>) {
+        if let Some(gl) = &mut self.gl {
+            gl.custom_program = program;
+        }
+    }
+    pub fn has_custom_program(&self) -> bool {
+        self.gl
+            .as_ref()
+            .is_some_and(|gl| gl.custom_program.is_some())
+    }
+    /// Sets a uniform of the [custom program](Self::set_program) and the textures' own
+    /// [programs](Texture::program), e.g. a tint color or the time for an animated effect.
+    /// Replaces the value of the same name.
+    pub fn set_program_uniform(&mut self, name: impl Into<String>, value: UniformValue<'static>) {
+        let name = name.into();
+        match self.program_uniforms.iter_mut().find(|(n, _)| *n == name) {
+            Some((_, current)) => *current = value,
+            None => self.program_uniforms.push((name, value)),
+        }
+    }
+    pub fn clear_program_uniforms(&mut self) {
+        self.program_uniforms.clear();
+    }
+    pub fn is_suspended(&self) -> bool {
+        self.gl.is_none()
+    }
+    pub fn alpha_mode(&self) -> AlphaMode {
+        self.alpha_mode
+    }
+    pub fn color_mode(&self) -> ColorMode {
+        self.color_mode
+    }
+    /// Takes effect from the next frame, images loaded afterwards are uploaded to match.
+    /// Textures loaded before keep their format.
+    pub fn set_color_mode(&mut self, color_mode: ColorMode) {
+        self.color_mode = color_mode;
+    }
+    pub fn stencil_mode(&self) -> StencilMode {
+        self.stencil_mode
+    }
+    /// The target needs a stencil buffer for anything but [`StencilMode::Disabled`]
+    pub fn set_stencil_mode(&mut self, stencil_mode: StencilMode) {
+        self.stencil_mode = stencil_mode;
+    }
+    pub fn projection_override(&self) -> Option<Mat4<f32>> {
+        self.projection_override
+    }
+    /// Transforms the whole UI in clip space, e.g. to rotate it for a rotated mobile screen or
+    /// skew it for a CRT look. The transform is applied after the UI projection.
+    ///
+    /// Clip rects can't follow arbitrary transforms and are disabled while an override is set.
+    /// Mouse input isn't transformed either, the platform side has to map it back.
+    pub fn set_projection_override(&mut self, transform: Option<Mat4<f32>>) {
+        self.projection_override = transform;
+    }
+    /// Runs `hook` before every UI pass, e.g. to start a timestamp query.
+    ///
+    /// glium surfaces can't be passed as trait objects, so the hook gets the GL context instead of
+    /// the target. Raw GL work can be done through [`Context::exec_in_context`], glium binds
+    /// framebuffers lazily so bind the target yourself if the work depends on it.
+    pub fn set_pre_render_hook<F>(&mut self, hook: F)
+    where
+        F: FnMut(&Rc<Context>, &FrameStats) + 'static,
+    {
+        self.pre_render_hook = Some(Box::new(hook));
+    }
+    /// Runs `hook` after every UI pass, e.g. to resolve a multisampled target or draw a watermark
+    /// over the UI. See [`set_pre_render_hook`](Self::set_pre_render_hook).
+    pub fn set_post_render_hook<F>(&mut self, hook: F)
+    where
+        F: FnMut(&Rc<Context>, &FrameStats) + 'static,
+    {
+        self.post_render_hook = Some(Box::new(hook));
+    }
+    /// Stats of the last UI pass
+    pub fn last_frame_stats(&self) -> FrameStats {
+        self.last_frame_stats
+    }
+    pub fn clear_render_hooks(&mut self) {
+        self.pre_render_hook = None;
+        self.post_render_hook = None;
+    }
+    /// Switches the alpha mode, the font texture is uploaded again to match
+    pub fn set_alpha_mode(
+        &mut self,
+        ctx: &mut imgui::Context,
+        alpha_mode: AlphaMode,
+    ) -> Result<(), RendererError> {
+        if self.alpha_mode != alpha_mode {
+            self.alpha_mode = alpha_mode;
+            self.reload_font_texture(ctx)?;
+        }
         Ok(())
     }
     pub fn textures(&mut self) -> &mut Textures<Texture> {
         &mut self.textures
     }
-    fn lookup_texture(&self, texture_id: TextureId) -> Result<&Texture, RendererError> {
+    /// Makes `texture` drawable with `ui.image()`, e.g. an engine render target or sprite.
+    ///
+    /// The renderer keeps a reference until [`unregister_texture`](Self::unregister_texture) or
+    /// [`suspend`](Self::suspend).
+    pub fn register_texture(
+        &mut self,
+        texture: Rc<Texture2d>,
+        sampler: SamplerBehavior,
+    ) -> TextureId {
+        self.register_texture_source(TextureSource::Texture2d(texture), sampler)
+    }
+    /// Registers a cube map face or array layer, see [`TextureSource`]. The preview widgets in
+    /// [`widgets`](crate::widgets) register every face or layer this way.
+    pub fn register_texture_source(
+        &mut self,
+        source: TextureSource,
+        sampler: SamplerBehavior,
+    ) -> TextureId {
+        self.textures.insert(Texture {
+            source,
+            sampler,
+            hdr: None,
+            program: None,
+        })
+    }
+    /// Like [`register_texture`](Self::register_texture) for a floating point texture, its
+    /// values go through the exposure and tonemap in `hdr` so HDR buffers can be looked at
+    pub fn register_hdr_texture(
+        &mut self,
+        texture: Rc<Texture2d>,
+        sampler: SamplerBehavior,
+        hdr: HdrDisplay,
+    ) -> TextureId {
+        self.textures.insert(Texture {
+            source: TextureSource::Texture2d(texture),
+            sampler,
+            hdr: Some(hdr),
+            program: None,
+        })
+    }
+    /// Draws a registered texture with its own program, see [`Texture::program`]. `None` goes
+    /// back to the UI program. Returns `false` if the ID isn't registered.
+    pub fn set_texture_program(
+        &mut self,
+        texture_id: TextureId,
+        program: Option<Rc<Program>>,
+    ) -> bool {
+        match self.textures.get_mut(texture_id) {
+            Some(texture) => {
+                texture.program = program;
+                true
+            }
+            None => false,
+        }
+    }
+    /// Changes how a registered texture is shown, e.g. from an exposure slider. Returns `false`
+    /// if the ID isn't registered.
+    pub fn set_hdr_display(&mut self, texture_id: TextureId, hdr: Option<HdrDisplay>) -> bool {
+        match self.textures.get_mut(texture_id) {
+            Some(texture) => {
+                texture.hdr = hdr;
+                true
+            }
+            None => false,
+        }
+    }
+    /// Decodes a PNG or JPEG image, uploads it and registers it for `ui.image()`.
+    ///
+    /// With the `dds` feature BC1 to BC7 compressed DDS files are uploaded as they are when the
+    /// context supports the format, and decompressed on the CPU otherwise. Only the top mip
+    /// level is used, DXT2 and DXT4 files with premultiplied alpha are rejected.
+    ///
+    /// The image is premultiplied when the renderer is in [`AlphaMode::Premultiplied`] and
+    /// uploaded as [`TextureSource::Srgb`] in [`ColorMode::Linear`], both always decompress DDS
+    /// files. Returns the ID and the size in pixels.
+    #[cfg(feature = "image")]
+    pub fn load_texture_from_bytes<F: Facade>(
+        &mut self,
+        facade: &F,
+        bytes: &[u8],
+    ) -> Result<(TextureId, [u32; 2]), RendererError> {
+        #[cfg(feature = "dds")]
+        if bytes.starts_with(dds::MAGIC) {
+            return self.load_dds(facade, bytes);
+        }
+        let image = image::load_from_memory(bytes)?.into_rgba8();
+        let (width, height) = image.dimensions();
+        self.upload_rgba(facade, image.into_raw(), width, height)
+    }
+    #[cfg(feature = "dds")]
+    fn load_dds<F: Facade>(
+        &mut self,
+        facade: &F,
+        bytes: &[u8],
+    ) -> Result<(TextureId, [u32; 2]), RendererError> {
+        let image = dds::DdsImage::parse(bytes).map_err(RendererError::Dds)?;
+        let format = image.format.compressed_format();
+        if self.alpha_mode == AlphaMode::Straight
+            && self.color_mode == ColorMode::Srgb
+            && format.is_supported(facade.get_context())
+        {
+            let texture = CompressedTexture2d::with_compressed_data(
+                facade,
+                image.data,
+                image.width,
+                image.height,
+                format,
+                CompressedMipmapsOption::NoMipmap,
+            )?;
+            let sampler = SamplerBehavior {
+                minify_filter: MinifySamplerFilter::Linear,
+                ..image_sampler()
+            };
+            let id =
+                self.register_texture_source(TextureSource::Compressed(Rc::new(texture)), sampler);
+            return Ok((id, [image.width, image.height]));
+        }
+        let rgba = image.decode().map_err(RendererError::Dds)?;
+        self.upload_rgba(facade, rgba, image.width, image.height)
+    }
+    /// Uploads straight alpha RGBA8 rows with mipmaps, premultiplying them first if needed.
+    /// Premultiplying the sRGB values before decoding them is close but not exact for
+    /// [`ColorMode::Linear`].
+    #[cfg(feature = "image")]
+    fn upload_rgba<F: Facade>(
+        &mut self,
+        facade: &F,
+        rgba: Vec<u8>,
+        width: u32,
+        height: u32,
+    ) -> Result<(TextureId, [u32; 2]), RendererError> {
+        let data = match self.alpha_mode {
+            AlphaMode::Straight => rgba,
+            AlphaMode::Premultiplied => premultiply_rgba(&rgba),
+        };
+        let raw = RawImage2d {
+            data: Cow::Owned(data),
+            width,
+            height,
+            format: ClientFormat::U8U8U8U8,
+        };
+        let source = match self.color_mode {
+            ColorMode::Srgb => TextureSource::Texture2d(Rc::new(Texture2d::new(facade, raw)?)),
+            ColorMode::Linear => TextureSource::Srgb(Rc::new(SrgbTexture2d::new(facade, raw)?)),
+        };
+        let id = self.register_texture_source(source, image_sampler());
+        Ok((id, [width, height]))
+    }
+    /// Same as [`load_texture_from_bytes`](Self::load_texture_from_bytes) with an image file
+    #[cfg(feature = "image")]
+    pub fn load_texture_from_file<F: Facade>(
+        &mut self,
+        facade: &F,
+        path: impl AsRef<std::path::Path>,
+    ) -> Result<(TextureId, [u32; 2]), RendererError> {
+        let bytes = std::fs::read(path).map_err(image::ImageError::IoError)?;
+        self.load_texture_from_bytes(facade, &bytes)
+    }
+    /// Removes a texture added with [`register_texture`](Self::register_texture), its ID must
+    /// not be drawn afterwards
+    pub fn unregister_texture(&mut self, texture_id: TextureId) -> Option<Texture> {
+        self.textures.remove(texture_id)
+    }
+    fn shader_features(&self) -> ShaderFeatures {
+        let mut features = ShaderFeatures::NONE;
+        if self.alpha_mode == AlphaMode::Premultiplied {
+            features = features | ShaderFeatures::PREMULTIPLY;
+        }
+        if self.color_mode == ColorMode::Linear {
+            features = features | ShaderFeatures::LINEAR;
+        }
+        features
+    }
+    fn lookup_texture<'a>(
+        &'a self,
+        gl: &'a GlResources,
+        texture_id: TextureId,
+    ) -> Result<&'a Texture, RendererError> {
         if texture_id.id() == usize::MAX {
-            Ok(&self.font_texture)
+            Ok(&gl.font_texture)
         } else if let Some(texture) = self.textures.get(texture_id) {
             Ok(texture)
         } else {
@@ -193,7 +1081,6 @@ impl Renderer {
         if !(fb_width > 0.0 && fb_height > 0.0) {
             return Ok(());
         }
-        let _ = self.ctx.insert_debug_marker("imgui-rs: starting rendering");
         let left = draw_data.display_pos[0];
         let right = draw_data.display_pos[0] + draw_data.display_size[0];
         let top = draw_data.display_pos[1];
@@ -209,17 +1096,151 @@ impl Renderer {
                 1.0,
             ],
         ];
-        let clip_off = draw_data.display_pos;
-        let clip_scale = draw_data.framebuffer_scale;
-        for draw_list in draw_data.draw_lists() {
-            let vtx_buffer = VertexBuffer::immutable(&self.ctx, unsafe {
-                draw_list.transmute_vtx_buffer::<GliumDrawVert>()
-            })?;
-            let idx_buffer = IndexBuffer::immutable(
-                &self.ctx,
-                PrimitiveType::TrianglesList,
+        match self.projection_override {
+            Some(transform) => {
+                let matrix = transform * Mat4::from_col_arrays(matrix);
+                self.render_pass(
+                    target,
+                    draw_data,
+                    matrix.into_col_arrays(),
+                    false,
+                    Depth::default(),
+                )
+            }
+            None => self.render_pass(target, draw_data, matrix, true, Depth::default()),
+        }
+    }
+    /// Renders into `texture` instead of the screen, e.g. to run the UI through post-processing.
+    ///
+    /// The texture isn't cleared first. It should have the framebuffer size of the draw data,
+    /// otherwise clipping is off. For compositing a transparent texture over the scene use
+    /// [`AlphaMode::Premultiplied`].
+    pub fn render_to_texture(
+        &mut self,
+        draw_data: &DrawData,
+        texture: &Texture2d,
+    ) -> Result<(), RendererError> {
+        let Some(gl) = &self.gl else {
+            return Ok(());
+        };
+        let ctx = Rc::clone(&gl.ctx);
+        let mut target = SimpleFrameBuffer::new(&ctx, texture)?;
+        self.render(&mut target, draw_data)
+    }
+    /// Draws the UI as a quad in the 3D scene, e.g. for in-world panels.
+    ///
+    /// Vertices are in UI coordinates (pixels, y down, starting at `draw_data.display_pos`) and
+    /// transformed by `world.transform`, which usually is projection * view * model where the
+    /// model matrix places the UI rectangle in the world. Clip rects are in screen space and
+    /// can't be applied, so scrolled or clipped content isn't cut off in this mode.
+    pub fn render_world<T: Surface>(
+        &mut self,
+        target: &mut T,
+        draw_data: &DrawData,
+        world: &WorldSpace,
+    ) -> Result<(), RendererError> {
+        let depth = if world.depth_test {
+            Depth {
+                test: DepthTest::IfLessOrEqual,
+                // The UI is flat, writing depth would only stop later UI layers from blending
+                write: false,
+                ..Depth::default()
+            }
+        } else {
+            Depth::default()
+        };
+        self.render_pass(
+            target,
+            draw_data,
+            world.transform.into_col_arrays(),
+            false,
+            depth,
+        )
+    }
+    fn render_pass<T: Surface>(
+        &mut self,
+        target: &mut T,
+        draw_data: &DrawData,
+        matrix: [[f32; 4]; 4],
+        scissor: bool,
+        depth: Depth,
+    ) -> Result<(), RendererError> {
+        let fb_width = draw_data.display_size[0] * draw_data.framebuffer_scale[0];
+        let fb_height = draw_data.display_size[1] * draw_data.framebuffer_scale[1];
+        let features = self.shader_features();
+        let Some(gl) = &mut self.gl else {
+            return Ok(());
+        };
+        // Callbacks may set an image view, which can't be known before running them
+        let callbacks = draw_data
+            .draw_lists()
+            .flat_map(|draw_list| draw_list.commands())
+            .any(|cmd| matches!(cmd, DrawCmd::RawCallback { .. }));
+        let mut needed = vec![features];
+        for cmd in draw_data
+            .draw_lists()
+            .flat_map(|draw_list| draw_list.commands())
+        {
+            if let DrawCmd::Elements { cmd_params, .. } = cmd {
+                if let Some(texture) = self.textures.get(cmd_params.texture_id) {
+                    let features = features | texture.features();
+                    let views = callbacks.then_some(features | ShaderFeatures::VIEW);
+                    for features in [features].into_iter().chain(views) {
+                        if !needed.contains(&features) {
+                            needed.push(features);
+                        }
+                    }
+                }
+            }
+        }
+        for features in needed {
+            if !gl.programs.contains_key(&features) {
+                let program = compile_program(&gl.ctx, features)?;
+                gl.programs.insert(features, program);
+            }
+        }
+        for (index, draw_list) in draw_data.draw_lists().enumerate() {
+            DrawListBuffers::upload(
+                &mut self.buffers,
+                index,
+                &gl.ctx,
+                unsafe { draw_list.transmute_vtx_buffer::<GliumDrawVert>() },
                 draw_list.idx_buffer(),
             )?;
+        }
+        let gl = self.gl.as_ref().expect("checked above");
+        let stats = FrameStats::new(draw_data, target.get_dimensions());
+        self.last_frame_stats = stats;
+        if let Some(hook) = &mut self.pre_render_hook {
+            hook(&gl.ctx, &stats);
+        }
+        let _ = gl.ctx.insert_debug_marker("imgui-rs: starting rendering");
+        let blend = match self.alpha_mode {
+            AlphaMode::Straight => Blend {
+                alpha: BlendingFunction::Addition {
+                    source: LinearBlendingFactor::One,
+                    destination: LinearBlendingFactor::OneMinusSourceAlpha,
+                },
+                ..Blend::alpha_blending()
+            },
+            AlphaMode::Premultiplied => {
+                let function = BlendingFunction::Addition {
+                    source: LinearBlendingFactor::One,
+                    destination: LinearBlendingFactor::OneMinusSourceAlpha,
+                };
+                Blend {
+                    color: function,
+                    alpha: function,
+                    ..Blend::alpha_blending()
+                }
+            }
+        };
+        let stencil = self.stencil_mode.to_stencil();
+        let clip_off = draw_data.display_pos;
+        let clip_scale = draw_data.framebuffer_scale;
+        IMAGE_VIEW.with(|view| view.set(None));
+        for (draw_list, buffers) in draw_data.draw_lists().zip(&self.buffers) {
+            let vtx_count = draw_list.vtx_buffer().len();
             for cmd in draw_list.commands() {
                 match cmd {
                     DrawCmd::Elements {
@@ -240,34 +1261,53 @@ impl Renderer {
                             (clip_rect[3] - clip_off[1]) * clip_scale[1],
                         ];
 
-                        if clip_rect[0] < fb_width
-                            && clip_rect[1] < fb_height
-                            && clip_rect[2] >= 0.0
-                            && clip_rect[3] >= 0.0
+                        if !scissor
+                            || clip_rect[0] < fb_width
+                                && clip_rect[1] < fb_height
+                                && clip_rect[2] >= 0.0
+                                && clip_rect[3] >= 0.0
                         {
-                            let texture = self.lookup_texture(texture_id)?;
+                            let texture = self.lookup_texture(gl, texture_id)?;
+                            let view = IMAGE_VIEW.with(Cell::get);
+                            let custom_program = texture
+                                .program
+                                .as_deref()
+                                .or(gl
+                                    .custom_program
+                                    .as_ref()
+                                    .filter(|_| texture.features() == ShaderFeatures::NONE))
+                                .filter(|_| view.is_none());
+                            let mut features = features | texture.features();
+                            if view.is_some() {
+                                features = features | ShaderFeatures::VIEW;
+                            }
+                            let program = custom_program.unwrap_or(&gl.programs[&features]);
+                            let extra = match custom_program {
+                                Some(_) => &self.program_uniforms[..],
+                                None => &[],
+                            };
 
                             target.draw(
-                                vtx_buffer
-                                    .slice(vtx_offset..)
+                                buffers
+                                    .vertices
+                                    .slice(vtx_offset..vtx_count)
                                     .expect("Invalid vertex buffer range"),
-                                idx_buffer
+                                buffers
+                                    .indices
                                     .slice(idx_offset..(idx_offset + count))
                                     .expect("Invalid index buffer range"),
-                                &self.program,
-                                &uniform! {
-                                    matrix: matrix,
-                                    tex: Sampler(texture.texture.as_ref(), texture.sampler)
+                                program,
+                                &DrawUniforms {
+                                    matrix,
+                                    texture,
+                                    view,
+                                    extra,
                                 },
                                 &DrawParameters {
-                                    blend: Blend {
-                                        alpha: BlendingFunction::Addition {
-                                            source: LinearBlendingFactor::One,
-                                            destination: LinearBlendingFactor::OneMinusSourceAlpha,
-                                        },
-                                        ..Blend::alpha_blending()
-                                    },
-                                    scissor: Some(Rect {
+                                    blend,
+                                    stencil,
+                                    depth,
+                                    scissor: scissor.then(|| Rect {
                                         left: f32::max(0.0, clip_rect[0]).floor() as u32,
                                         bottom: f32::max(0.0, fb_height - clip_rect[3]).floor()
                                             as u32,
@@ -286,26 +1326,51 @@ impl Renderer {
                 }
             }
         }
-        let _ = self.ctx.insert_debug_marker("imgui-rs: rendering finished");
+        IMAGE_VIEW.with(|view| view.set(None));
+        let _ = gl.ctx.insert_debug_marker("imgui-rs: rendering finished");
+        if let Some(hook) = &mut self.post_render_hook {
+            hook(&gl.ctx, &stats);
+        }
         Ok(())
     }
 }
 
+/// Sampler of textures loaded from image files
+#[cfg(feature = "image")]
+fn image_sampler() -> SamplerBehavior {
+    SamplerBehavior {
+        minify_filter: MinifySamplerFilter::LinearMipmapLinear,
+        magnify_filter: MagnifySamplerFilter::Linear,
+        wrap_function: (
+            SamplerWrapFunction::Clamp,
+            SamplerWrapFunction::Clamp,
+            SamplerWrapFunction::Clamp,
+        ),
+        ..Default::default()
+    }
+}
+
 fn upload_font_texture(
     fonts: &mut imgui::FontAtlas,
     ctx: &Rc<Context>,
+    alpha_mode: AlphaMode,
 ) -> Result<Texture, RendererError> {
     let texture = fonts.build_rgba32_texture();
+    let data = match alpha_mode {
+        AlphaMode::Straight => Cow::Borrowed(texture.data),
+        AlphaMode::Premultiplied => Cow::Owned(premultiply_rgba(texture.data)),
+    };
     let data = RawImage2d {
-        data: Cow::Borrowed(texture.data),
+        data,
         width: texture.width,
         height: texture.height,
         format: ClientFormat::U8U8U8U8,
     };
+    // Glyph coverage is linear in both color modes, so the atlas is never an sRGB texture
     let font_texture = Texture2d::with_mipmaps(ctx, data, MipmapsOption::NoMipmap)?;
     fonts.tex_id = TextureId::from(usize::MAX);
     Ok(Texture {
-        texture: Rc::new(font_texture),
+        source: TextureSource::Texture2d(Rc::new(font_texture)),
         sampler: SamplerBehavior {
             minify_filter: MinifySamplerFilter::Linear,
             magnify_filter: MagnifySamplerFilter::Linear,
@@ -316,41 +1381,97 @@ fn upload_font_texture(
             ),
             ..Default::default()
         },
+        hdr: None,
+        program: None,
     })
 }
 
-fn compile_default_program<F: Facade>(facade: &F) -> Result<Program, ProgramChooserCreationError> {
+/// Directory shaders are loaded from with the `shader-hot-reload` feature, can be overridden
+/// with the `BUGSYTH_IMGUI_SHADER_DIR` environment variable
+#[cfg(feature = "shader-hot-reload")]
+const SHADER_DIR: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/src/shader");
+
+/// Source of a shader in `src/shader`
+macro_rules! shader {
+    ($file:literal) => {
+        shader_source($file, include_str!(concat!("shader/", $file)))
+    };
+}
+
+#[cfg(feature = "shader-hot-reload")]
+fn shader_source(file: &str, embedded: &'static str) -> Cow<'static, str> {
+    let dir = std::env::var("BUGSYTH_IMGUI_SHADER_DIR").unwrap_or_else(|_| SHADER_DIR.to_string());
+    let path = std::path::Path::new(&dir).join(file);
+    match std::fs::read_to_string(&path) {
+        Ok(source) => Cow::Owned(source),
+        Err(e) => {
+            log!(
+                warn,
+                "Failed to read {}, using the embedded shader: {}",
+                path.display(),
+                e
+            );
+            Cow::Borrowed(embedded)
+        }
+    }
+}
+
+#[cfg(not(feature = "shader-hot-reload"))]
+fn shader_source(_file: &str, embedded: &'static str) -> Cow<'static, str> {
+    Cow::Borrowed(embedded)
+}
+
+fn compile_program<F: Facade>(
+    facade: &F,
+    features: ShaderFeatures,
+) -> Result<Program, ProgramChooserCreationError> {
+    // With `false` glium turns on the framebuffer's conversion to sRGB
+    let outputs_srgb = !features.contains(ShaderFeatures::LINEAR);
     program!(
         facade,
         400 => {
-            vertex: include_str!("shader/glsl_400.vert"),
-            fragment: include_str!("shader/glsl_400.frag"),
-            outputs_srgb: true,
+            vertex: &features.apply(&shader!("glsl_400.vert")),
+            fragment: &features.apply(&shader!("glsl_400.frag")),
+            outputs_srgb: outputs_srgb,
         },
         150 => {
-            vertex: include_str!("shader/glsl_150.vert"),
-            fragment: include_str!("shader/glsl_150.frag"),
-            outputs_srgb: true,
+            vertex: &features.apply(&shader!("glsl_150.vert")),
+            fragment: &features.apply(&shader!("glsl_150.frag")),
+            outputs_srgb: outputs_srgb,
         },
         130 => {
-            vertex: include_str!("shader/glsl_130.vert"),
-            fragment: include_str!("shader/glsl_130.frag"),
-            outputs_srgb: true,
+            vertex: &features.apply(&shader!("glsl_130.vert")),
+            fragment: &features.apply(&shader!("glsl_130.frag")),
+            outputs_srgb: outputs_srgb,
         },
         110 => {
-            vertex: include_str!("shader/glsl_110.vert"),
-            fragment: include_str!("shader/glsl_110.frag"),
-            outputs_srgb: true,
+            vertex: &features.apply(&shader!("glsl_110.vert")),
+            fragment: &features.apply(&shader!("glsl_110.frag")),
+            outputs_srgb: outputs_srgb,
         },
         300 es => {
-            vertex: include_str!("shader/glsles_300.vert"),
-            fragment: include_str!("shader/glsles_300.frag"),
-            outputs_srgb: true,
+            vertex: &features.apply(&shader!("glsles_300.vert")),
+            fragment: &features.apply(&shader!("glsles_300.frag")),
+            outputs_srgb: outputs_srgb,
         },
         100 es => {
-            vertex: include_str!("shader/glsles_100.vert"),
-            fragment: include_str!("shader/glsles_100.frag"),
-            outputs_srgb: true,
+            vertex: &features.apply(&shader!("glsles_100.vert")),
+            fragment: &features.apply(&shader!("glsles_100.frag")),
+            outputs_srgb: outputs_srgb,
         },
     )
 }
+
+fn premultiply_rgba(data: &[u8]) -> Vec<u8> {
+    data.chunks_exact(4)
+        .flat_map(|pixel| {
+            let premultiply = |c: u8| (c as u16 * pixel[3] as u16 / 255) as u8;
+            [
+                premultiply(pixel[0]),
+                premultiply(pixel[1]),
+                premultiply(pixel[2]),
+                pixel[3],
+            ]
+        })
+        .collect()
+}
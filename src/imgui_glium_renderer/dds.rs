@@ -0,0 +1,230 @@
+//! Reading block compressed DDS files for [`Renderer::load_texture_from_bytes`]
+//!
+//! [`Renderer::load_texture_from_bytes`]: super::Renderer::load_texture_from_bytes
+
+use bugsyth_engine::glium::texture::CompressedFormat;
+
+pub(super) const MAGIC: &[u8] = b"DDS ";
+const HEADER_END: usize = 128;
+const DX10_HEADER_END: usize = HEADER_END + 20;
+/// The pixel format has a four character code
+const FOURCC_FLAG: u32 = 0x4;
+
+/// BCn formats, the block size is 4x4 pixels for all of them
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub(super) enum BlockFormat {
+    Bc1,
+    Bc2,
+    Bc3,
+    Bc4,
+    Bc5,
+    Bc6Unsigned,
+    Bc6Signed,
+    Bc7,
+}
+
+impl BlockFormat {
+    fn from_fourcc(fourcc: &[u8]) -> Option<Self> {
+        Some(match fourcc {
+            b"DXT1" => BlockFormat::Bc1,
+            b"DXT3" => BlockFormat::Bc2,
+            b"DXT5" => BlockFormat::Bc3,
+            b"ATI1" | b"BC4U" => BlockFormat::Bc4,
+            b"ATI2" | b"BC5U" => BlockFormat::Bc5,
+            _ => return None,
+        })
+    }
+    fn from_dxgi(format: u32) -> Option<Self> {
+        Some(match format {
+            70..=72 => BlockFormat::Bc1,
+            73..=75 => BlockFormat::Bc2,
+            76..=78 => BlockFormat::Bc3,
+            79 | 80 => BlockFormat::Bc4,
+            82 | 83 => BlockFormat::Bc5,
+            94 | 95 => BlockFormat::Bc6Unsigned,
+            96 => BlockFormat::Bc6Signed,
+            97..=99 => BlockFormat::Bc7,
+            _ => return None,
+        })
+    }
+    fn block_bytes(self) -> usize {
+        match self {
+            BlockFormat::Bc1 | BlockFormat::Bc4 => 8,
+            _ => 16,
+        }
+    }
+    pub(super) fn compressed_format(self) -> CompressedFormat {
+        match self {
+            BlockFormat::Bc1 => CompressedFormat::S3tcDxt1Alpha,
+            BlockFormat::Bc2 => CompressedFormat::S3tcDxt3Alpha,
+            BlockFormat::Bc3 => CompressedFormat::S3tcDxt5Alpha,
+            BlockFormat::Bc4 => CompressedFormat::RgtcFormatU,
+            BlockFormat::Bc5 => CompressedFormat::RgtcFormatUU,
+            BlockFormat::Bc6Unsigned => CompressedFormat::BptcUnsignedFloat3,
+            BlockFormat::Bc6Signed => CompressedFormat::BptcSignedFloat3,
+            BlockFormat::Bc7 => CompressedFormat::BptcUnorm4,
+        }
+    }
+}
+
+/// Top mip level of a DDS file
+#[derive(Debug)]
+pub(super) struct DdsImage<'a> {
+    pub(super) format: BlockFormat,
+    pub(super) width: u32,
+    pub(super) height: u32,
+    pub(super) data: &'a [u8],
+}
+
+impl<'a> DdsImage<'a> {
+    pub(super) fn parse(bytes: &'a [u8]) -> Result<Self, &'static str> {
+        let read = |offset: usize| {
+            bytes
+                .get(offset..offset + 4)
+                .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+                .ok_or("DDS header is cut off")
+        };
+        if !bytes.starts_with(MAGIC) || read(4)? != 124 {
+            return Err("Not a DDS file");
+        }
+        let height = read(12)?;
+        let width = read(16)?;
+        if read(80)? & FOURCC_FLAG == 0 {
+            return Err("Uncompressed DDS files aren't supported");
+        }
+        let fourcc = bytes.get(84..88).ok_or("DDS header is cut off")?;
+        if fourcc == b"DXT2" || fourcc == b"DXT4" {
+            return Err("Premultiplied alpha DDS files (DXT2 and DXT4) aren't supported");
+        }
+        let (format, start) = if fourcc == b"DX10" {
+            let format = BlockFormat::from_dxgi(read(HEADER_END)?);
+            (format, DX10_HEADER_END)
+        } else {
+            (BlockFormat::from_fourcc(fourcc), HEADER_END)
+        };
+        let format = format.ok_or("DDS pixel format isn't BC1 to BC7")?;
+        let end = (width.div_ceil(4) as usize)
+            .checked_mul(height.div_ceil(4) as usize)
+            .and_then(|blocks| blocks.checked_mul(format.block_bytes()))
+            .and_then(|len| len.checked_add(start))
+            .ok_or("DDS size is too large")?;
+        let data = bytes
+            .get(start..end)
+            .ok_or("DDS file is shorter than its size says")?;
+        Ok(Self {
+            format,
+            width,
+            height,
+            data,
+        })
+    }
+
+    /// Decompresses into RGBA8 rows on the CPU, for contexts that can't sample the format
+    pub(super) fn decode(&self) -> Result<Vec<u8>, &'static str> {
+        let (width, height) = (self.width as usize, self.height as usize);
+        let mut pixels = vec![0u32; width * height];
+        match self.format {
+            BlockFormat::Bc1 => {
+                texture2ddecoder::decode_bc1a(self.data, width, height, &mut pixels)
+            }
+            BlockFormat::Bc2 => texture2ddecoder::decode_bc2(self.data, width, height, &mut pixels),
+            BlockFormat::Bc3 => texture2ddecoder::decode_bc3(self.data, width, height, &mut pixels),
+            BlockFormat::Bc4 => texture2ddecoder::decode_bc4(self.data, width, height, &mut pixels),
+            BlockFormat::Bc5 => texture2ddecoder::decode_bc5(self.data, width, height, &mut pixels),
+            BlockFormat::Bc6Unsigned => {
+                texture2ddecoder::decode_bc6(self.data, width, height, &mut pixels, false)
+            }
+            BlockFormat::Bc6Signed => {
+                texture2ddecoder::decode_bc6(self.data, width, height, &mut pixels, true)
+            }
+            BlockFormat::Bc7 => texture2ddecoder::decode_bc7(self.data, width, height, &mut pixels),
+        }?;
+        // The decoder packs pixels as BGRA
+        Ok(pixels
+            .into_iter()
+            .flat_map(|pixel| {
+                let [b, g, r, a] = pixel.to_le_bytes();
+                [r, g, b, a]
+            })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// DDS file of `width` by `height` pixels with a four character code, and `data_len` bytes
+    /// after the header
+    fn dds(width: u32, height: u32, fourcc: &[u8; 4], data_len: usize) -> Vec<u8> {
+        let mut bytes = vec![0; HEADER_END];
+        bytes[..4].copy_from_slice(MAGIC);
+        bytes[4..8].copy_from_slice(&124u32.to_le_bytes());
+        bytes[12..16].copy_from_slice(&height.to_le_bytes());
+        bytes[16..20].copy_from_slice(&width.to_le_bytes());
+        bytes[80..84].copy_from_slice(&FOURCC_FLAG.to_le_bytes());
+        bytes[84..88].copy_from_slice(fourcc);
+        bytes.resize(HEADER_END + data_len, 0xAB);
+        bytes
+    }
+
+    #[test]
+    fn header_is_parsed() {
+        // 5x3 pixels round up to 2x1 blocks
+        let bytes = dds(5, 3, b"DXT1", 16);
+        let image = DdsImage::parse(&bytes).unwrap();
+        assert_eq!(image.format, BlockFormat::Bc1);
+        assert_eq!((image.width, image.height), (5, 3));
+        assert_eq!(image.data.len(), 16);
+        assert_eq!(image.decode().unwrap().len(), 5 * 3 * 4);
+
+        let mut bytes = dds(4, 4, b"DX10", 20 + 16);
+        bytes[HEADER_END..HEADER_END + 4].copy_from_slice(&98u32.to_le_bytes());
+        let image = DdsImage::parse(&bytes).unwrap();
+        assert_eq!(image.format, BlockFormat::Bc7);
+        assert_eq!(image.data.len(), 16);
+    }
+
+    #[test]
+    fn bad_files_are_rejected() {
+        assert_eq!(
+            DdsImage::parse(b"PNG not a dds").unwrap_err(),
+            "Not a DDS file"
+        );
+        assert_eq!(
+            DdsImage::parse(&dds(4, 4, b"DXT5", 16)[..40]).unwrap_err(),
+            "DDS header is cut off"
+        );
+        assert_eq!(
+            DdsImage::parse(&dds(4, 4, b"DXT5", 16)[..86]).unwrap_err(),
+            "DDS header is cut off"
+        );
+        assert_eq!(
+            DdsImage::parse(&dds(8, 8, b"DXT5", 63)).unwrap_err(),
+            "DDS file is shorter than its size says"
+        );
+        assert_eq!(
+            DdsImage::parse(&dds(4, 4, b"RGBG", 16)).unwrap_err(),
+            "DDS pixel format isn't BC1 to BC7"
+        );
+        assert_eq!(
+            DdsImage::parse(&dds(4, 4, b"DXT2", 16)).unwrap_err(),
+            "Premultiplied alpha DDS files (DXT2 and DXT4) aren't supported"
+        );
+        let mut uncompressed = dds(4, 4, b"\0\0\0\0", 64);
+        uncompressed[80..84].copy_from_slice(&0u32.to_le_bytes());
+        assert_eq!(
+            DdsImage::parse(&uncompressed).unwrap_err(),
+            "Uncompressed DDS files aren't supported"
+        );
+    }
+
+    #[test]
+    fn huge_dimensions_are_rejected() {
+        let huge = dds(u32::MAX, u32::MAX, b"DXT5", 16);
+        assert_eq!(DdsImage::parse(&huge).unwrap_err(), "DDS size is too large");
+        let mut huge = dds(u32::MAX, u32::MAX, b"DX10", 20 + 16);
+        huge[HEADER_END..HEADER_END + 4].copy_from_slice(&98u32.to_le_bytes());
+        assert_eq!(DdsImage::parse(&huge).unwrap_err(), "DDS size is too large");
+    }
+}
@@ -0,0 +1,192 @@
+use super::{Renderer, RendererError};
+use bugsyth_engine::glium::backend::Facade;
+use bugsyth_engine::glium::texture::{ClientFormat, PixelValue, RawImage2d, Texture2d, TextureAny};
+use bugsyth_engine::glium::uniforms::{
+    MagnifySamplerFilter, MinifySamplerFilter, SamplerBehavior, SamplerWrapFunction,
+};
+use bugsyth_engine::glium::Rect;
+use imgui::{TextureId, Ui};
+use std::borrow::Cow;
+use std::rc::Rc;
+
+/// Reads the main level of a 2D texture, rows top to bottom
+pub(crate) fn read_pixels<P: PixelValue>(texture: &TextureAny) -> (u32, u32, Vec<P>) {
+    let rect = Rect {
+        left: 0,
+        bottom: 0,
+        width: texture.get_width(),
+        height: texture.get_height().unwrap_or(1),
+    };
+    let rows: Vec<Vec<P>> = texture
+        .main_level()
+        .first_layer()
+        .into_image(None)
+        .expect("2D textures have an image per layer")
+        .raw_read(&rect);
+    let height = rows.len() as u32;
+    let width = rows.first().map_or(0, |row| row.len() as u32);
+    // Rows come back bottom to top
+    let pixels = rows.into_iter().rev().flatten().collect();
+    (width, height, pixels)
+}
+
+/// CPU copy of a texture with a colored preview registered in the [`Renderer`], for the debug
+/// views that show a texture zoomed in and look up the pixel under the mouse.
+///
+/// The preview uses nearest filtering so single pixels stay visible when zoomed in.
+pub(crate) struct PixelPreview<P> {
+    width: u32,
+    height: u32,
+    /// Rows top to bottom
+    pixels: Vec<P>,
+    texture: Option<TextureId>,
+    dirty: bool,
+}
+
+impl<P> Default for PixelPreview<P> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<P> PixelPreview<P> {
+    pub fn new() -> Self {
+        Self {
+            width: 0,
+            height: 0,
+            pixels: Vec::new(),
+            texture: None,
+            dirty: false,
+        }
+    }
+
+    /// Replaces the pixels, rows top to bottom
+    pub fn set_pixels(&mut self, width: u32, height: u32, pixels: Vec<P>) {
+        assert_eq!(
+            pixels.len(),
+            width as usize * height as usize,
+            "a {}x{} texture needs width * height pixels",
+            width,
+            height
+        );
+        self.width = width;
+        self.height = height;
+        self.pixels = pixels;
+        self.dirty = true;
+    }
+    pub fn size(&self) -> [u32; 2] {
+        [self.width, self.height]
+    }
+    pub fn pixels(&self) -> &[P] {
+        &self.pixels
+    }
+    /// Pixel at `x`, `y` from the top left
+    pub fn pixel(&self, x: u32, y: u32) -> Option<&P> {
+        (x < self.width && y < self.height)
+            .then(|| &self.pixels[y as usize * self.width as usize + x as usize])
+    }
+    pub fn texture(&self) -> Option<TextureId> {
+        self.texture
+    }
+    /// Uploads the preview again on the next [`update`](Self::update), e.g. when the colors change
+    pub fn mark_dirty(&mut self) {
+        self.dirty = true;
+    }
+
+    /// Uploads the preview with each pixel turned into an RGBA color, if the pixels changed or
+    /// [`mark_dirty`](Self::mark_dirty) was called since the last call
+    pub fn update<F: Facade>(
+        &mut self,
+        facade: &F,
+        renderer: &mut Renderer,
+        color: impl Fn(&P) -> [u8; 4],
+    ) -> Result<(), RendererError> {
+        if !self.dirty || self.pixels.is_empty() {
+            return Ok(());
+        }
+        self.dirty = false;
+        let data: Vec<u8> = self.pixels.iter().flat_map(color).collect();
+        let raw = RawImage2d {
+            data: Cow::Owned(data),
+            width: self.width,
+            height: self.height,
+            format: ClientFormat::U8U8U8U8,
+        };
+        let texture = Texture2d::new(facade, raw)?;
+        let sampler = SamplerBehavior {
+            minify_filter: MinifySamplerFilter::Nearest,
+            magnify_filter: MagnifySamplerFilter::Nearest,
+            wrap_function: (
+                SamplerWrapFunction::Clamp,
+                SamplerWrapFunction::Clamp,
+                SamplerWrapFunction::Clamp,
+            ),
+            ..Default::default()
+        };
+        if let Some(old) = self.texture.take() {
+            renderer.unregister_texture(old);
+        }
+        self.texture = Some(renderer.register_texture(Rc::new(texture), sampler));
+        Ok(())
+    }
+    /// Removes the preview from the renderer, the next [`update`](Self::update) uploads it again
+    pub fn release(&mut self, renderer: &mut Renderer) {
+        if let Some(texture) = self.texture.take() {
+            renderer.unregister_texture(texture);
+        }
+        self.dirty = true;
+    }
+
+    /// Draws the preview scaled by `zoom` in a scrolling child region. Hovering it shows a tooltip
+    /// built by `tooltip` for the pixel under the mouse, whose position is returned.
+    ///
+    /// Draws nothing before the first [`update`](Self::update).
+    pub fn draw(
+        &self,
+        ui: &Ui,
+        id: &str,
+        zoom: f32,
+        tooltip: impl FnOnce([u32; 2], &P),
+    ) -> Option<[u32; 2]> {
+        let texture = self.texture?;
+        ui.child_window(id)
+            .horizontal_scrollbar(true)
+            .build(|| {
+                let size = [self.width as f32 * zoom, self.height as f32 * zoom];
+                let origin = ui.cursor_screen_pos();
+                imgui::Image::new(texture, size).build(ui);
+                if !ui.is_item_hovered() {
+                    return None;
+                }
+                let mouse = ui.io().mouse_pos;
+                let x = ((mouse[0] - origin[0]) / zoom) as u32;
+                let y = ((mouse[1] - origin[1]) / zoom) as u32;
+                let pixel = self.pixel(x, y)?;
+                ui.tooltip(|| tooltip([x, y], pixel));
+                Some([x, y])
+            })
+            .flatten()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pixels_are_looked_up_from_the_top_left() {
+        let mut preview = PixelPreview::new();
+        preview.set_pixels(3, 2, vec![1, 2, 3, 4, 5, 6]);
+        assert_eq!(preview.size(), [3, 2]);
+        assert_eq!(preview.pixel(0, 0), Some(&1));
+        assert_eq!(preview.pixel(2, 1), Some(&6));
+        assert_eq!(preview.pixel(3, 0), None);
+        assert_eq!(preview.pixel(0, 2), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "needs width * height pixels")]
+    fn wrong_pixel_count_panics() {
+        PixelPreview::new().set_pixels(2, 2, vec![0u32; 3]);
+    }
+}
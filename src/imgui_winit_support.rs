@@ -18,12 +18,19 @@ use winit::{
     window::{CursorIcon as MouseCursor, Window},
 };
 
+/// Window events kept per frame while recording, the rest of a flood is dropped
+const MAX_RECORDED_EVENTS: usize = 256;
+
 /// winit backend platform state
 #[derive(Debug)]
 pub struct WinitPlatform {
     hidpi_mode: ActiveHiDpiMode,
     hidpi_factor: f64,
     cursor_cache: Option<CursorSettings>,
+    record_events: bool,
+    /// Events received since the last `prepare_frame`
+    events: Vec<String>,
+    last_frame_events: Vec<String>,
 }
 
 impl WinitPlatform {
@@ -46,6 +53,9 @@ impl WinitPlatform {
             hidpi_mode: ActiveHiDpiMode::Default,
             hidpi_factor: 1.0,
             cursor_cache: None,
+            record_events: false,
+            events: Vec::new(),
+            last_frame_events: Vec::new(),
         }
     }
 
@@ -71,6 +81,26 @@ impl WinitPlatform {
         let logical_size = self.scale_size_from_winit(window, logical_size);
         io.display_size = [logical_size.width as f32, logical_size.height as f32];
     }
+    /// Turns keeping the window events of each frame on or off, for input debugging.
+    ///
+    /// Off by default. The events are formatted with `Debug`, see
+    /// [`recorded_events`](Self::recorded_events).
+    pub fn set_record_events(&mut self, record: bool) {
+        self.record_events = record;
+        if !record {
+            self.events.clear();
+            self.last_frame_events.clear();
+        }
+    }
+    /// Whether window events are being recorded
+    pub fn records_events(&self) -> bool {
+        self.record_events
+    }
+    /// Window events received before the current frame, empty unless
+    /// [`set_record_events`](Self::set_record_events) turned recording on
+    pub fn recorded_events(&self) -> &[String] {
+        &self.last_frame_events
+    }
     /// Returns the current DPI factor.
     ///
     /// The value might not be the same as the winit DPI factor (depends on the used DPI mode)
@@ -166,6 +196,9 @@ impl WinitPlatform {
     /// * keyboard state is updated
     /// * mouse state is updated
     pub fn handle_window_event(&mut self, io: &mut Io, window: &Window, event: &WindowEvent) {
+        if self.record_events && self.events.len() < MAX_RECORDED_EVENTS {
+            self.events.push(format!("{:?}", event));
+        }
         match *event {
             WindowEvent::Resized(physical_size) => {
                 let logical_size = physical_size.to_logical(window.scale_factor());
@@ -255,12 +288,10 @@ impl WinitPlatform {
                     io.add_mouse_button_event(mb, pressed);
                 }
             }
-            WindowEvent::Focused(newly_focused) => {
-                if !newly_focused {
-                    // Set focus-lost to avoid stuck keys (like 'alt'
-                    // when alt-tabbing)
-                    io.app_focus_lost = true;
-                }
+            // Set focus-lost to avoid stuck keys (like 'alt'
+            // when alt-tabbing)
+            WindowEvent::Focused(false) => {
+                io.app_focus_lost = true;
             }
             _ => (),
         }
@@ -271,7 +302,11 @@ impl WinitPlatform {
     /// This function performs the following actions:
     ///
     /// * mouse cursor is repositioned (if requested by imgui-rs)
-    pub fn prepare_frame(&self, io: &mut Io, window: &Window) -> Result<(), ExternalError> {
+    /// * recorded events move on to the new frame
+    pub fn prepare_frame(&mut self, io: &mut Io, window: &Window) -> Result<(), ExternalError> {
+        if self.record_events {
+            self.last_frame_events = std::mem::take(&mut self.events);
+        }
         if io.want_set_mouse_pos {
             let logical_pos = self.scale_pos_for_winit(
                 window,
@@ -290,13 +325,19 @@ impl WinitPlatform {
     ///
     /// * mouse cursor is changed and/or hidden (if requested by imgui-rs)
     pub fn prepare_render(&mut self, ui: &Ui, window: &Window) {
-        let io = ui.io();
+        self.update_cursor(ui.io(), ui.mouse_cursor(), window);
+    }
+    /// Same as [`prepare_render`](Self::prepare_render) for when the `Ui` isn't at hand
+    pub fn prepare_render_with_context(&mut self, ctx: &Context, window: &Window) {
+        self.update_cursor(ctx.io(), ctx.mouse_cursor(), window);
+    }
+    fn update_cursor(&mut self, io: &Io, cursor: Option<imgui::MouseCursor>, window: &Window) {
         if !io
             .config_flags
             .contains(ConfigFlags::NO_MOUSE_CURSOR_CHANGE)
         {
             let cursor = CursorSettings {
-                cursor: ui.mouse_cursor(),
+                cursor,
                 draw_cursor: io.mouse_draw_cursor,
             };
             if self.cursor_cache != Some(cursor) {
@@ -1,21 +1,100 @@
+/// Forwards to `log` with the `log` feature, e.g. `log!(warn, "...")`, otherwise the message is
+/// dropped
+macro_rules! log {
+    ($level:ident, $($arg:tt)+) => {{
+        #[cfg(feature = "log")]
+        ::log::$level!($($arg)+);
+        #[cfg(not(feature = "log"))]
+        let _ = format_args!($($arg)+);
+    }};
+}
+
+pub mod accessibility;
+pub mod annotations;
+#[cfg(feature = "bug-report")]
+pub mod bug_report;
+pub mod cache;
+pub mod capture;
+pub mod cheats;
+pub mod clipboard;
+pub mod console;
+pub mod coords;
+pub mod debug;
+pub mod display_settings;
+pub mod editor;
+pub mod fonts;
+mod frame_timer;
+pub mod gamepad;
+pub mod gizmo;
+#[cfg(any(feature = "bug-report-http", feature = "update-check"))]
+mod http;
 mod imgui_glium_renderer;
 mod imgui_winit_support;
+pub mod localization;
+pub mod logs;
+pub mod proxy;
+pub mod safe_mode;
+pub mod selection;
+pub mod settings;
+mod shortcut;
+pub mod sounds;
+pub mod telemetry;
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
+pub mod theme;
+pub mod watermark;
+pub mod whats_new;
+pub mod widgets;
 
-use std::time::Duration;
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+use std::rc::Rc;
+use std::time::{Duration, Instant};
 
+use accessibility::{InputTimings, LowVision};
 use bugsyth_engine::glium::{
     glutin::surface::WindowSurface,
-    winit::{event::WindowEvent, window::Window},
-    Display,
+    texture::Texture2d,
+    uniforms::SamplerBehavior,
+    winit::{
+        dpi::PhysicalPosition,
+        event::{MouseButton, Touch, TouchPhase, WindowEvent},
+        window::Window,
+    },
+    CapabilitiesSource, Display, Surface,
 };
-use imgui::{FontConfig, FontGlyphRanges, FontSource};
+use debug::{DrawnWindow, IdConflictDetector};
+use fonts::{FontAtlasInfo, FontBuilder};
+use imgui::{ConfigFlags, FontGlyphRanges, FontId, Style, TextureId, Ui};
 use imgui_winit_support::HiDpiMode;
+use logs::LogLevel;
+use proxy::{UiInbox, UiProxy};
+use settings::{Session, SettingsError, SettingsStore};
+use sounds::{SoundState, UiSounds};
+use theme::Theme;
+use watermark::Watermark;
+use widgets::VirtualKeyboard;
 
 pub use {
+    frame_timer::{FrameTimer, FRAME_TIMER_WINDOW},
     imgui::{Condition, Context},
+    imgui_glium_renderer::AlphaMode,
+    imgui_glium_renderer::ColorMode,
+    imgui_glium_renderer::CubemapView,
+    imgui_glium_renderer::FrameStats,
+    imgui_glium_renderer::HdrDisplay,
+    imgui_glium_renderer::RenderHook,
     imgui_glium_renderer::Renderer,
     imgui_glium_renderer::RendererError,
+    imgui_glium_renderer::StencilMode,
+    imgui_glium_renderer::Texture,
+    imgui_glium_renderer::TextureSource,
+    imgui_glium_renderer::Tonemap,
+    imgui_glium_renderer::WorldSpace,
     imgui_winit_support::WinitPlatform,
+    shortcut::Shortcut,
     winit::error::ExternalError,
 };
 
@@ -26,11 +105,95 @@ pub struct ImGui {
     pub context: Context,
     pub renderer: Renderer,
     pub platform: WinitPlatform,
+    /// Result of the last font atlas build
+    pub font_atlas: FontAtlasInfo,
+    fonts: FontBuilder,
+    font_ids: HashMap<String, FontId>,
+    /// GPU limit for the font atlas
+    max_texture_size: u32,
+    /// Drawn over everything each frame while set
+    pub watermark: Option<Watermark>,
+    /// Shown while a text field is active when set, for touch and controller-only setups
+    pub virtual_keyboard: Option<VirtualKeyboard>,
+    /// New ID conflicts are reported after every frame while set, keep a clone of the detector
+    /// for [tracking](debug::IdConflictDetector::track)
+    pub id_conflicts: Option<IdConflictDetector>,
+    /// Log lines, toasts and progress sent from other threads, see [`proxy`](Self::proxy)
+    pub inbox: UiInbox,
+    /// Frame times passed to [`update_dt`](Self::update_dt)
+    pub frame_timer: FrameTimer,
+    drawn_windows: Vec<DrawnWindow>,
+    /// Last cursor position in UI coordinates, tracked for the virtual keyboard
+    cursor_pos: [f32; 2],
+    sounds: Option<Box<dyn UiSounds>>,
+    sound_state: SoundState,
+    /// Saved and restored with the settings, see [`set_settings_store`](Self::set_settings_store)
+    pub session: Session,
+    /// Per-user state saved outside of the [settings scope](Self::set_settings_scope), so it
+    /// stays the same across projects, e.g. the accessibility settings
+    pub user_session: Session,
+    safe_mode: bool,
+    settings: Option<SettingsStore>,
+    scope_callbacks: Vec<ScopeCallback>,
+    save_callbacks: Vec<SaveCallback>,
+    settings_dirty: bool,
+    autosave_interval: Option<Duration>,
+    last_save: Instant,
+    /// Why the last autosave failed, cleared by the next successful save
+    save_error: Option<SettingsError>,
+    frame_state: FrameState,
+    resume_callbacks: Vec<ResumeCallback>,
+    /// Style before [`ImGui::scale_style`] was first called
+    base_style: Option<Style>,
+    style_scale: f32,
+    ui_scale: f32,
+    low_vision: Option<LowVision>,
+    /// Preset loaded from the settings during a frame, applied when the next one starts
+    pending_low_vision: Option<Option<LowVision>>,
+    /// Reloads the renderer's shaders from disk when pressed, F5 by default. Failures are logged
+    /// with the `log` feature, call [`Renderer::reload_shaders`] to handle them yourself.
+    #[cfg(feature = "shader-hot-reload")]
+    pub shader_reload_key: Option<winit::keyboard::KeyCode>,
+}
+
+type ResumeCallback = Box<dyn FnMut(&mut Renderer, &Display<WindowSurface>)>;
+type ScopeCallback = Box<dyn FnMut(&mut Context, &SettingsStore)>;
+type SaveCallback = Box<dyn FnMut(&SettingsStore) -> Result<(), SettingsError>>;
+
+/// Default for [`ImGui::set_autosave_interval`]
+pub const AUTOSAVE_INTERVAL: Duration = Duration::from_secs(30);
+/// How long autosaves wait after a failed one before trying again
+const AUTOSAVE_RETRY: Duration = Duration::from_secs(5);
+
+/// Returned by [`ImGui::filter_event`]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum EventOutcome {
+    /// The UI uses the event, the game should ignore it
+    Consumed,
+    /// The game should handle the event
+    Passed,
+}
+
+impl EventOutcome {
+    pub fn is_consumed(self) -> bool {
+        self == EventOutcome::Consumed
+    }
+}
+
+/// Where the current frame is in the prepare_ui -> get_ui -> prepare_render -> render sequence
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum FrameState {
+    Idle,
+    Prepared,
+    Building,
+    RenderPrepared,
 }
 
 impl ImGui {
-    /// Pass the engine's delta time into this function
+    /// Pass the engine's delta time into this function, it's also recorded in
+    /// [`frame_timer`](Self::frame_timer)
     pub fn update_dt(&mut self, dt: f32) {
+        self.frame_timer.record(dt);
         self.context
             .io_mut()
             .update_delta_time(Duration::from_secs_f32(dt));
@@ -43,70 +206,982 @@ impl ImGui {
     /// - keyboard state is updated
     /// - mouse state is updated
     pub fn event(&mut self, window: &Window, event: &WindowEvent) {
+        if let Some(keyboard) = &mut self.virtual_keyboard {
+            let to_ui = |position: PhysicalPosition<f64>| {
+                let position = self
+                    .platform
+                    .scale_pos_from_winit(window, position.to_logical(window.scale_factor()));
+                [position.x as f32, position.y as f32]
+            };
+            match *event {
+                WindowEvent::CursorMoved { position, .. } => self.cursor_pos = to_ui(position),
+                // Presses on the keyboard never reach imgui, that would deactivate the text field
+                WindowEvent::MouseInput {
+                    state,
+                    button: MouseButton::Left,
+                    ..
+                } if keyboard.contains(self.cursor_pos) => {
+                    if state.is_pressed() {
+                        keyboard.press(self.context.io_mut(), self.cursor_pos);
+                    }
+                    return;
+                }
+                WindowEvent::Touch(Touch {
+                    phase: TouchPhase::Started,
+                    location,
+                    ..
+                }) if keyboard.contains(to_ui(location)) => {
+                    keyboard.press(self.context.io_mut(), to_ui(location));
+                    return;
+                }
+                _ => (),
+            }
+        }
         self.platform
             .handle_window_event(self.context.io_mut(), window, event);
+        #[cfg(feature = "shader-hot-reload")]
+        if let WindowEvent::KeyboardInput { event, .. } = event {
+            if event.state.is_pressed()
+                && !event.repeat
+                && self
+                    .shader_reload_key
+                    .is_some_and(|key| event.physical_key == key)
+            {
+                match self.renderer.reload_shaders() {
+                    Ok(()) => log!(info, "Reloaded imgui shaders"),
+                    Err(e) => log!(warn, "Reloading imgui shaders failed: {}", e),
+                }
+            }
+        }
+    }
+
+    /// Starts a frame, call before [`get_ui`](Self::get_ui)
+    #[track_caller]
+    pub fn prepare_ui(&mut self, window: &Window) -> Result<(), ExternalError> {
+        match self.frame_state {
+            FrameState::Idle => (),
+            FrameState::Prepared => {
+                panic!("ImGui::prepare_ui called twice, call ImGui::get_ui to build the frame")
+            }
+            FrameState::Building | FrameState::RenderPrepared => panic!(
+                "ImGui::prepare_ui called while a frame is in progress, finish it with ImGui::prepare_render and ImGui::render first"
+            ),
+        }
+        if let Some(preset) = self.pending_low_vision.take() {
+            if let Err(e) = self.apply_low_vision(preset) {
+                log!(warn, "Applying the low vision preset failed: {}", e);
+            }
+        }
+        self.platform.prepare_frame(self.context.io_mut(), window)?;
+        self.frame_state = FrameState::Prepared;
+        Ok(())
+    }
+    /// Returns the `Ui` to build the frame with, call once per frame after
+    /// [`prepare_ui`](Self::prepare_ui)
+    #[track_caller]
+    pub fn get_ui(&mut self) -> &mut Ui {
+        match self.frame_state {
+            FrameState::Prepared => (),
+            FrameState::Idle => panic!("ImGui::get_ui called before ImGui::prepare_ui"),
+            FrameState::Building | FrameState::RenderPrepared => panic!(
+                "ImGui::get_ui called twice in one frame, keep the Ui from the first call until ImGui::render"
+            ),
+        }
+        self.frame_state = FrameState::Building;
+        let ui = self.context.new_frame();
+        self.inbox.poll();
+        self.inbox.draw(ui);
+        if let Some(watermark) = &self.watermark {
+            watermark.draw(ui);
+        }
+        if let Some(keyboard) = &mut self.virtual_keyboard {
+            keyboard.draw(ui);
+        }
+        ui
+    }
+    /// Handle for feeding log lines, toasts and progress bars from worker threads, they show up
+    /// from the next frame on
+    pub fn proxy(&self) -> UiProxy {
+        self.inbox.proxy()
+    }
+    /// Applies the mouse cursor requested by the frame, call after building the UI
+    #[track_caller]
+    pub fn prepare_render(&mut self, window: &Window) {
+        match self.frame_state {
+            FrameState::Building => (),
+            FrameState::Idle => panic!(
+                "ImGui::prepare_render called without a frame, call ImGui::prepare_ui and ImGui::get_ui first"
+            ),
+            FrameState::Prepared => {
+                panic!("ImGui::prepare_render called before ImGui::get_ui")
+            }
+            FrameState::RenderPrepared => {
+                panic!("ImGui::prepare_render called twice, call ImGui::render next")
+            }
+        }
+        if let Some(sounds) = &mut self.sounds {
+            self.sound_state.update(sounds.as_mut());
+        }
+        if let Some(detector) = &self.id_conflicts {
+            for conflict in detector.take_new_conflicts() {
+                log!(warn, "{}", conflict);
+                self.inbox.proxy().log(LogLevel::Warn, conflict.to_string());
+            }
+        }
+        self.platform
+            .prepare_render_with_context(&self.context, window);
+        self.frame_state = FrameState::RenderPrepared;
+    }
+    /// Windows drawn in the last rendered frame, for the debug tools, e.g.
+    /// [`Ruler::set_windows`](debug::Ruler::set_windows)
+    pub fn drawn_windows(&self) -> &[DrawnWindow] {
+        &self.drawn_windows
+    }
+    /// Coordinate conversions for the current window and DPI settings
+    pub fn coords(&self, window: &Window) -> coords::Coords {
+        coords::Coords::new(self.context.io(), &self.platform, window)
+    }
+
+    /// Turns moving focus between items with Tab and the arrow keys on or off
+    pub fn set_keyboard_nav(&mut self, enabled: bool) {
+        self.context
+            .io_mut()
+            .config_flags
+            .set(ConfigFlags::NAV_ENABLE_KEYBOARD, enabled);
+    }
+    pub fn keyboard_nav(&self) -> bool {
+        self.context
+            .io()
+            .config_flags
+            .contains(ConfigFlags::NAV_ENABLE_KEYBOARD)
+    }
+    /// Whether imgui uses the keyboard this frame, e.g. a text field is active or keyboard
+    /// navigation has focus, so the game should ignore key presses
+    pub fn wants_keyboard(&self) -> bool {
+        self.context.io().want_capture_keyboard
+    }
+    /// Whether the mouse is over the UI or dragging in it, so the game should ignore it
+    pub fn wants_mouse(&self) -> bool {
+        self.context.io().want_capture_mouse
+    }
+    /// Whether a text field is active, e.g. to show an on-screen keyboard
+    pub fn wants_text_input(&self) -> bool {
+        self.context.io().want_text_input
+    }
+    /// Whether the game should handle an event after it was passed to [`event`](Self::event).
+    ///
+    /// Mouse and touch events are consumed while imgui [wants the mouse](Self::wants_mouse) or
+    /// the cursor is on the virtual keyboard, keyboard and IME events while it
+    /// [wants the keyboard](Self::wants_keyboard). Everything else, e.g. resizes, focus changes
+    /// and modifier updates, is passed so the game's state stays in sync.
+    pub fn filter_event(&self, event: &WindowEvent) -> EventOutcome {
+        let consumed = match event {
+            WindowEvent::CursorMoved { .. }
+            | WindowEvent::MouseInput { .. }
+            | WindowEvent::MouseWheel { .. }
+            | WindowEvent::Touch(_) => {
+                self.wants_mouse()
+                    || self
+                        .virtual_keyboard
+                        .as_ref()
+                        .is_some_and(|keyboard| keyboard.contains(self.cursor_pos))
+            }
+            WindowEvent::KeyboardInput { .. } | WindowEvent::Ime(_) => self.wants_keyboard(),
+            _ => false,
+        };
+        if consumed {
+            EventOutcome::Consumed
+        } else {
+            EventOutcome::Passed
+        }
+    }
+    /// Builds a whole frame with `build_ui`, replacing the calls to
+    /// [`prepare_ui`](Self::prepare_ui), [`get_ui`](Self::get_ui) and
+    /// [`prepare_render`](Self::prepare_render). Call [`render`](Self::render) afterwards to draw
+    /// it.
+    #[track_caller]
+    pub fn frame<F>(&mut self, window: &Window, build_ui: F) -> Result<(), ExternalError>
+    where
+        F: FnOnce(&mut Ui),
+    {
+        self.prepare_ui(window)?;
+        build_ui(self.get_ui());
+        self.prepare_render(window);
+        Ok(())
+    }
+    /// Builds a frame with `build_ui` and draws it onto the engine's surface, everything a
+    /// [`GameState::draw`](bugsyth_engine::game_state::GameState::draw) needs in one call.
+    ///
+    /// `ctx` is only read, so `build_ui` can use it too.
+    #[track_caller]
+    pub fn draw<R, F>(
+        &mut self,
+        ctx: &bugsyth_engine::context::Context,
+        renderer: &mut R,
+        build_ui: F,
+    ) -> Result<(), FrameError>
+    where
+        R: bugsyth_engine::renderer::Renderer,
+        F: FnOnce(&Ui),
+    {
+        self.frame(&ctx.window, |ui| build_ui(ui))?;
+        self.render(renderer.get_surface_mut())?;
+        Ok(())
+    }
+    /// Draws the frame onto `target` and ends it
+    #[track_caller]
+    pub fn render<S: Surface>(&mut self, target: &mut S) -> Result<(), RendererError> {
+        self.end_frame();
+        let draw_data = self.context.render();
+        self.drawn_windows = debug::drawn_windows(draw_data);
+        let result = self.renderer.render(target, draw_data);
+        self.autosave();
+        result
+    }
+    /// Same as [`render`](Self::render) but draws into `texture`, see
+    /// [`Renderer::render_to_texture`]
+    #[track_caller]
+    pub fn render_to_texture(&mut self, texture: &Texture2d) -> Result<(), RendererError> {
+        self.end_frame();
+        let draw_data = self.context.render();
+        self.drawn_windows = debug::drawn_windows(draw_data);
+        let result = self.renderer.render_to_texture(draw_data, texture);
+        self.autosave();
+        result
+    }
+    #[track_caller]
+    fn end_frame(&mut self) {
+        match self.frame_state {
+            FrameState::RenderPrepared => (),
+            FrameState::Idle => panic!(
+                "ImGui::render called without a frame, the frame was already rendered or ImGui::prepare_ui is missing"
+            ),
+            FrameState::Prepared | FrameState::Building => {
+                panic!("ImGui::render called before ImGui::prepare_render")
+            }
+        }
+        self.frame_state = FrameState::Idle;
+    }
+
+    /// Releases the renderer's GL resources, call when winit delivers `Suspended`.
+    ///
+    /// Registered user textures are released as well, re-register them in an
+    /// [`on_resume`](Self::on_resume) callback.
+    pub fn suspend(&mut self) {
+        self.renderer.suspend();
+    }
+    /// Recreates the renderer's GL resources on the new display and runs the
+    /// [`on_resume`](Self::on_resume) callbacks, call when winit delivers `Resumed`
+    pub fn resume(&mut self, display: &Display<WindowSurface>) -> Result<(), RendererError> {
+        self.renderer.resume(&mut self.context, display)?;
+        for callback in &mut self.resume_callbacks {
+            callback(&mut self.renderer, display);
+        }
+        Ok(())
+    }
+    /// Adds a callback that runs after [`resume`](Self::resume), used to register user textures
+    /// again. Texture IDs from before the suspend are invalid, store the new ones.
+    pub fn on_resume<F>(&mut self, callback: F)
+    where
+        F: FnMut(&mut Renderer, &Display<WindowSurface>) + 'static,
+    {
+        self.resume_callbacks.push(Box::new(callback));
+    }
+
+    /// Registers an engine texture for `ui.image()`, see [`Renderer::register_texture`].
+    ///
+    /// IDs are invalid after a [`suspend`](Self::suspend), register again in
+    /// [`on_resume`](Self::on_resume).
+    pub fn register_texture(
+        &mut self,
+        texture: Rc<Texture2d>,
+        sampler: SamplerBehavior,
+    ) -> TextureId {
+        self.renderer.register_texture(texture, sampler)
+    }
+    /// Removes a texture added with [`register_texture`](Self::register_texture)
+    pub fn unregister_texture(&mut self, texture_id: TextureId) -> Option<Texture> {
+        self.renderer.unregister_texture(texture_id)
+    }
+
+    /// Plays UI sounds for hovered and clicked items and opened popups, see [`sounds`]
+    pub fn set_ui_sounds<S: UiSounds + 'static>(&mut self, sounds: S) {
+        self.sounds = Some(Box::new(sounds));
+    }
+    pub fn clear_ui_sounds(&mut self) {
+        self.sounds = None;
+    }
+    /// Plays [`UiSounds::error`], does nothing without sounds set
+    pub fn play_error_sound(&mut self) {
+        if let Some(sounds) = &mut self.sounds {
+            sounds.error();
+        }
+    }
+
+    /// Scales the style to `factor` times its unscaled sizes, e.g. after a DPI change.
+    ///
+    /// The factor is absolute, scaling to 1.5 and then 2.0 gives the same style as scaling to
+    /// 2.0 directly. Rounding isn't snapped to whole pixels and border sizes are rounded to whole
+    /// pixels but never drop below 1 if they were set. Edits to the style while it's scaled are
+    /// lost on the next call, call [`reset_style_scale`](Self::reset_style_scale) before editing.
+    ///
+    /// The [low vision preset](Self::set_low_vision) is applied on top while it's on.
+    pub fn scale_style(&mut self, factor: f32) {
+        self.style_scale = factor;
+        self.update_style();
+    }
+    /// Scales the unscaled style by the style and UI scales and applies the low vision preset,
+    /// restores the unscaled style when none of them changes it
+    fn update_style(&mut self) {
+        if self.style_scale == 1.0 && self.ui_scale == 1.0 && self.low_vision.is_none() {
+            if let Some(base) = self.base_style.take() {
+                *self.context.style_mut() = base;
+            }
+            return;
+        }
+        let factor = self.style_scale * self.ui_scale;
+        let base = *self.base_style.get_or_insert_with(|| *self.context.style());
+        let mut style = base;
+        let sizes = factor * self.low_vision.map_or(1.0, |preset| preset.style_scale);
+        style.scale_all_sizes(sizes);
+        for (rounding, base) in [
+            (&mut style.window_rounding, base.window_rounding),
+            (&mut style.child_rounding, base.child_rounding),
+            (&mut style.popup_rounding, base.popup_rounding),
+            (&mut style.frame_rounding, base.frame_rounding),
+            (&mut style.scrollbar_rounding, base.scrollbar_rounding),
+            (&mut style.grab_rounding, base.grab_rounding),
+            (&mut style.tab_rounding, base.tab_rounding),
+        ] {
+            *rounding = base * sizes;
+        }
+        for (border, base) in [
+            (&mut style.window_border_size, base.window_border_size),
+            (&mut style.child_border_size, base.child_border_size),
+            (&mut style.popup_border_size, base.popup_border_size),
+            (&mut style.frame_border_size, base.frame_border_size),
+            (&mut style.tab_border_size, base.tab_border_size),
+        ] {
+            *border = if base > 0.0 {
+                (base * sizes).round().max(1.0)
+            } else {
+                0.0
+            };
+        }
+        if let Some(preset) = &self.low_vision {
+            preset.apply(&mut style, factor);
+        }
+        *self.context.style_mut() = style;
+    }
+    /// Current factor set with [`scale_style`](Self::scale_style)
+    pub fn style_scale(&self) -> f32 {
+        self.style_scale
+    }
+    /// Colors and rounding of the unscaled style, without the scales and low vision preset, so
+    /// [`set_theme`](Self::set_theme) gives the same look back
+    pub fn theme(&self) -> Theme {
+        Theme::from_style(self.base_style.as_ref().unwrap_or(self.context.style()))
+    }
+    /// Replaces the colors and rounding. The style scale, UI scale and low vision preset stay
+    /// applied.
+    pub fn set_theme(&mut self, theme: &Theme) {
+        match &mut self.base_style {
+            Some(base) => {
+                theme.apply(base);
+                self.update_style();
+            }
+            None => theme.apply(self.context.style_mut()),
+        }
+    }
+    /// Restores the unscaled style, only the [UI scale](Self::set_ui_scale) and low vision preset
+    /// stay applied when they're set
+    pub fn reset_style_scale(&mut self) {
+        self.style_scale = 1.0;
+        self.update_style();
+    }
+
+    /// Sets the size of the UI font in logical pixels, rebuilds the atlas and uploads it. Call
+    /// outside of a frame.
+    #[track_caller]
+    pub fn set_font_size(&mut self, size: f32) -> Result<FontAtlasInfo, RendererError> {
+        assert!(
+            self.frame_state == FrameState::Idle,
+            "ImGui::set_font_size called while a frame is in progress, call it after ImGui::render"
+        );
+        self.fonts = std::mem::replace(&mut self.fonts, FontBuilder::empty()).size(size);
+        self.build_fonts()
+    }
+    /// Size set with [`set_font_size`](Self::set_font_size) or the [`FontBuilder`], without
+    /// the UI scale
+    pub fn font_size(&self) -> f32 {
+        self.fonts.font_size()
+    }
+    /// Scales the whole UI, e.g. from a UI scale slider in the settings menu. The fonts are
+    /// rebuilt at `scale` times their size so text stays sharp, and the style is scaled on top
+    /// of [`scale_style`](Self::scale_style). Call outside of a frame.
+    #[track_caller]
+    pub fn set_ui_scale(&mut self, scale: f32) -> Result<(), RendererError> {
+        assert!(
+            self.frame_state == FrameState::Idle,
+            "ImGui::set_ui_scale called while a frame is in progress, call it after ImGui::render"
+        );
+        if scale == self.ui_scale {
+            return Ok(());
+        }
+        self.ui_scale = scale;
+        self.update_style();
+        self.build_fonts()?;
+        Ok(())
+    }
+    pub fn ui_scale(&self) -> f32 {
+        self.ui_scale
+    }
+
+    /// Turns the [low vision preset](accessibility::LowVision) on, or off with `None`. Rebuilds
+    /// the fonts when their size changes, call outside of a frame.
+    ///
+    /// The preset is kept in the [`user_session`](Self::user_session) and turned back on when
+    /// the settings are loaded.
+    #[track_caller]
+    pub fn set_low_vision(&mut self, preset: Option<LowVision>) -> Result<(), RendererError> {
+        assert!(
+            self.frame_state == FrameState::Idle,
+            "ImGui::set_low_vision called while a frame is in progress, call it after ImGui::render"
+        );
+        match &preset {
+            Some(preset) => self
+                .user_session
+                .set_value(accessibility::LOW_VISION, preset),
+            None => {
+                self.user_session.remove(accessibility::LOW_VISION);
+            }
+        }
+        self.mark_settings_dirty();
+        self.pending_low_vision = None;
+        self.apply_low_vision(preset)
+    }
+    pub fn low_vision(&self) -> Option<LowVision> {
+        self.low_vision
+    }
+    /// Sets the global [reduced motion](accessibility::reduced_motion) flag and stops the text
+    /// cursor from blinking while it's on. Kept in the [`user_session`](Self::user_session) like
+    /// the low vision preset.
+    pub fn set_reduced_motion(&mut self, on: bool) {
+        self.user_session
+            .set_value(accessibility::REDUCED_MOTION, &on);
+        self.mark_settings_dirty();
+        self.apply_reduced_motion(on);
+    }
+    /// Sets the double click, key repeat and hover timings, kept in the
+    /// [`user_session`](Self::user_session) like the low vision preset
+    pub fn set_input_timings(&mut self, timings: InputTimings) {
+        self.user_session
+            .set_value(accessibility::INPUT_TIMINGS, &timings);
+        self.mark_settings_dirty();
+        timings.apply(self.context.io_mut());
+    }
+    pub fn input_timings(&self) -> InputTimings {
+        InputTimings::from_io(self.context.io())
+    }
+    fn apply_reduced_motion(&mut self, on: bool) {
+        accessibility::set_reduced_motion(on);
+        self.context.io_mut().config_input_text_cursor_blink = !on;
+    }
+    fn apply_low_vision(&mut self, preset: Option<LowVision>) -> Result<(), RendererError> {
+        let font_scale = |preset: Option<LowVision>| preset.map_or(1.0, |preset| preset.font_scale);
+        let rebuild = font_scale(preset) != font_scale(self.low_vision);
+        self.low_vision = preset;
+        self.update_style();
+        if rebuild {
+            self.build_fonts()?;
+        }
+        Ok(())
+    }
+
+    /// Started with [`init_safe_mode`], saved settings shouldn't be loaded
+    pub fn is_safe_mode(&self) -> bool {
+        self.safe_mode
+    }
+
+    /// Sets where settings are kept and loads the saved window layout and session, unless in
+    /// safe mode.
+    ///
+    /// The store is kept even if loading fails.
+    pub fn set_settings_store(&mut self, store: SettingsStore) -> Result<(), SettingsError> {
+        self.settings = Some(store);
+        self.load_settings()
+    }
+    /// Keeps the window layout and session in the per-user config directory of `game_name`,
+    /// see [`SettingsStore::for_game`]. Same as [`set_settings_store`](Self::set_settings_store)
+    /// otherwise.
+    pub fn enable_settings_persistence(&mut self, game_name: &str) -> Result<(), SettingsError> {
+        let store = SettingsStore::for_game(game_name).ok_or_else(|| {
+            SettingsError::Io(std::io::Error::new(
+                std::io::ErrorKind::NotFound,
+                "no config directory on this platform",
+            ))
+        })?;
+        self.set_settings_store(store)
+    }
+    /// Loads the layout and session of the current scope and the user session again, replacing
+    /// the current ones. Does nothing without a store or in safe mode.
+    pub fn load_settings(&mut self) -> Result<(), SettingsError> {
+        let Some(store) = &self.settings else {
+            return Ok(());
+        };
+        if self.safe_mode {
+            return Ok(());
+        }
+        if let Some(layout) = store.load(settings::LAYOUT)? {
+            self.context.load_ini_settings(&layout);
+        }
+        self.session.load(store)?;
+        self.user_session.load_user(store)?;
+        let reduced_motion = self
+            .user_session
+            .value(accessibility::REDUCED_MOTION)
+            .unwrap_or(false);
+        self.apply_reduced_motion(reduced_motion);
+        if let Some(timings) = self
+            .user_session
+            .value::<InputTimings>(accessibility::INPUT_TIMINGS)
+        {
+            timings.apply(self.context.io_mut());
+        }
+        let preset = self.user_session.value(accessibility::LOW_VISION);
+        if preset != self.low_vision {
+            if self.frame_state == FrameState::Idle {
+                if let Err(e) = self.apply_low_vision(preset) {
+                    log!(warn, "Applying the low vision preset failed: {}", e);
+                }
+            } else {
+                self.pending_low_vision = Some(preset);
+            }
+        }
+        Ok(())
+    }
+    pub fn settings_store(&self) -> Option<&SettingsStore> {
+        self.settings.as_ref()
+    }
+    pub fn settings_store_mut(&mut self) -> Option<&mut SettingsStore> {
+        self.settings.as_mut()
+    }
+    /// Saves the window layout and both sessions to the settings store and runs the
+    /// [`on_settings_save`](Self::on_settings_save) callbacks, does nothing without a store or in
+    /// safe mode.
+    ///
+    /// The settings stay dirty when it fails, so the next autosave tries again.
+    pub fn save_settings(&mut self) -> Result<(), SettingsError> {
+        let Some(store) = save_target(self.settings.as_ref(), self.safe_mode) else {
+            return Ok(());
+        };
+        self.last_save = Instant::now();
+        let mut layout = String::new();
+        self.context.save_ini_settings(&mut layout);
+        store.save(settings::LAYOUT, &layout)?;
+        self.session.save(store)?;
+        self.user_session.save_user(store)?;
+        for callback in &mut self.save_callbacks {
+            callback(store)?;
+        }
+        self.settings_dirty = false;
+        self.context.io_mut().want_save_ini_settings = false;
+        self.save_error = None;
+        Ok(())
+    }
+    /// Why the last autosave failed, `None` once a save succeeded again. Also logged with the
+    /// `log` feature.
+    pub fn save_error(&self) -> Option<&SettingsError> {
+        self.save_error.as_ref()
+    }
+
+    /// Requests a save at the end of the frame, e.g. after a panel got opened or closed
+    pub fn mark_settings_dirty(&mut self) {
+        self.settings_dirty = true;
+    }
+    /// Saves are also done after imgui noticed layout changes (a few seconds after a window was
+    /// moved or resized) and when settings are marked dirty. `None` turns the periodic saves off.
+    pub fn set_autosave_interval(&mut self, interval: Option<Duration>) {
+        self.autosave_interval = interval;
+    }
+    /// Adds a callback that runs on every save, used to persist app state like which panels are
+    /// open
+    pub fn on_settings_save<F>(&mut self, callback: F)
+    where
+        F: FnMut(&SettingsStore) -> Result<(), SettingsError> + 'static,
+    {
+        self.save_callbacks.push(Box::new(callback));
+    }
+    /// Saves when something changed or the interval passed, so a killed process loses little.
+    /// Nothing is saved in safe mode, see [`save_target`].
+    fn autosave(&mut self) {
+        if save_target(self.settings.as_ref(), self.safe_mode).is_none() {
+            return;
+        }
+        let elapsed = self.last_save.elapsed();
+        if self.save_error.is_some() && elapsed < AUTOSAVE_RETRY {
+            return;
+        }
+        let due = self
+            .autosave_interval
+            .is_some_and(|interval| elapsed >= interval);
+        if self.settings_dirty || self.context.io().want_save_ini_settings || due {
+            if let Err(e) = self.save_settings() {
+                log!(warn, "Saving settings failed: {}", e);
+                self.save_error = Some(e);
+            }
+        }
+    }
+
+    /// Switches the settings to another project, e.g. when the user opens a different one.
+    ///
+    /// The layout and session are saved into the old scope and the new scope's are loaded, the
+    /// [`user_session`](Self::user_session) is the same for all scopes.
+    /// The [`on_settings_scope_change`](Self::on_settings_scope_change) callbacks run afterwards
+    /// to swap the rest of the persisted state. Does nothing without a settings store. In safe
+    /// mode the old scope isn't saved over. Call outside of a frame.
+    #[track_caller]
+    pub fn set_settings_scope(&mut self, project_id: Option<&str>) -> Result<(), SettingsError> {
+        assert!(
+            self.frame_state == FrameState::Idle,
+            "ImGui::set_settings_scope called while a frame is in progress, call it after ImGui::render"
+        );
+        if self.settings.is_none() {
+            return Ok(());
+        }
+        self.save_settings()?;
+        if let Some(store) = &mut self.settings {
+            store.set_scope(project_id);
+        }
+        let loaded = self.load_settings();
+        let store = self.settings.as_ref().expect("checked above");
+        for callback in &mut self.scope_callbacks {
+            callback(&mut self.context, store);
+        }
+        loaded
+    }
+    /// Adds a callback that runs after [`set_settings_scope`](Self::set_settings_scope) switched
+    /// the scope, used to load app state like themes or watches from the new scope
+    pub fn on_settings_scope_change<F>(&mut self, callback: F)
+    where
+        F: FnMut(&mut Context, &SettingsStore) + 'static,
+    {
+        self.scope_callbacks.push(Box::new(callback));
+    }
+
+    /// Replaces the fonts and uploads the new atlas, call outside of a frame
+    #[track_caller]
+    pub fn rebuild_fonts(
+        &mut self,
+        display: &Display<WindowSurface>,
+        fonts: &FontBuilder,
+    ) -> Result<FontAtlasInfo, RendererError> {
+        assert!(
+            self.frame_state == FrameState::Idle,
+            "ImGui::rebuild_fonts called while a frame is in progress, call it after ImGui::render"
+        );
+        self.fonts = fonts.clone();
+        self.max_texture_size = display.get_capabilities().max_texture_size as u32;
+        self.build_fonts()
+    }
+    /// Adds a separate font at runtime, rebuilds the atlas and uploads it. Use the ID with
+    /// `ui.push_font`, a font with the same name is replaced. Call outside of a frame.
+    ///
+    /// Fonts added to the context by hand, e.g. in the `startup` closure, are lost, add them
+    /// through the [`FontBuilder`] instead.
+    #[track_caller]
+    pub fn add_font(
+        &mut self,
+        name: &str,
+        ttf_bytes: impl Into<Cow<'static, [u8]>>,
+        size: f32,
+        glyph_ranges: FontGlyphRanges,
+    ) -> Result<FontId, RendererError> {
+        assert!(
+            self.frame_state == FrameState::Idle,
+            "ImGui::add_font called while a frame is in progress, call it after ImGui::render"
+        );
+        self.fonts = std::mem::replace(&mut self.fonts, FontBuilder::empty()).add_named_font(
+            name,
+            ttf_bytes,
+            size,
+            glyph_ranges,
+        );
+        self.build_fonts()?;
+        Ok(self.font_ids[name])
+    }
+    /// ID of a font added with [`add_font`](Self::add_font) or
+    /// [`FontBuilder::add_named_font`]
+    pub fn font(&self, name: &str) -> Option<FontId> {
+        self.font_ids.get(name).copied()
+    }
+    fn build_fonts(&mut self) -> Result<FontAtlasInfo, RendererError> {
+        let scale = self.ui_scale * self.low_vision.map_or(1.0, |preset| preset.font_scale);
+        self.font_atlas = if scale == 1.0 {
+            self.fonts.build(&mut self.context, self.max_texture_size)
+        } else {
+            self.fonts
+                .scaled(scale)
+                .build(&mut self.context, self.max_texture_size)
+        };
+        self.font_ids = font_ids(&mut self.context, &self.fonts);
+        self.renderer.reload_font_texture(&mut self.context)?;
+        Ok(self.font_atlas)
+    }
+}
+
+/// Why [`try_init`] failed
+#[derive(Debug)]
+pub enum InitError {
+    /// Creating the shaders or buffers failed, usually an unsupported GL driver
+    Renderer(RendererError),
+    /// Uploading the font atlas texture failed
+    FontAtlas(RendererError),
+    /// The window can't be attached, e.g. it reports a broken scale factor
+    Platform(String),
+}
+
+impl Error for InitError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        use self::InitError::*;
+        match *self {
+            Renderer(ref e) => Some(e),
+            FontAtlas(ref e) => Some(e),
+            Platform(_) => None,
+        }
+    }
+}
+
+impl fmt::Display for InitError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use self::InitError::*;
+        match *self {
+            Renderer(ref e) => write!(f, "Failed to initialize renderer: {}", e),
+            FontAtlas(ref e) => write!(f, "Failed to upload font atlas: {}", e),
+            Platform(ref e) => write!(f, "Failed to attach window: {}", e),
+        }
+    }
+}
+
+impl From<RendererError> for InitError {
+    fn from(e: RendererError) -> InitError {
+        match e {
+            RendererError::Texture(_) => InitError::FontAtlas(e),
+            e => InitError::Renderer(e),
+        }
     }
 }
 
-/// Builds a context and winit backend
-pub fn init<FInit>(window: &Window, display: &Display<WindowSurface>, mut startup: FInit) -> ImGui
+/// Why [`ImGui::draw`] failed
+#[derive(Debug)]
+pub enum FrameError {
+    /// Preparing the frame for the window failed
+    Platform(ExternalError),
+    Renderer(RendererError),
+}
+
+impl Error for FrameError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        use self::FrameError::*;
+        match *self {
+            Platform(ref e) => Some(e),
+            Renderer(ref e) => Some(e),
+        }
+    }
+}
+
+impl fmt::Display for FrameError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use self::FrameError::*;
+        match *self {
+            Platform(ref e) => write!(f, "Failed to prepare frame: {}", e),
+            Renderer(ref e) => write!(f, "Failed to render frame: {}", e),
+        }
+    }
+}
+
+impl From<ExternalError> for FrameError {
+    fn from(e: ExternalError) -> FrameError {
+        FrameError::Platform(e)
+    }
+}
+
+impl From<RendererError> for FrameError {
+    fn from(e: RendererError) -> FrameError {
+        FrameError::Renderer(e)
+    }
+}
+
+/// Builds a context and winit backend.
+///
+/// # Panics
+///
+/// When initialization fails, see [`try_init`].
+pub fn init<FInit>(window: &Window, display: &Display<WindowSurface>, startup: FInit) -> ImGui
+where
+    FInit: FnMut(&mut Context, &mut Renderer, &Display<WindowSurface>) + 'static,
+{
+    init_with_fonts(window, display, &FontBuilder::default(), startup)
+}
+
+/// Same as [`init`] but returns an error instead of panicking, so the engine can fall back to
+/// running without the UI or show its own message
+pub fn try_init<FInit>(
+    window: &Window,
+    display: &Display<WindowSurface>,
+    startup: FInit,
+) -> Result<ImGui, InitError>
+where
+    FInit: FnMut(&mut Context, &mut Renderer, &Display<WindowSurface>) + 'static,
+{
+    try_init_with_fonts(window, display, &FontBuilder::default(), startup)
+}
+
+/// Same as [`init`] with custom fonts
+pub fn init_with_fonts<FInit>(
+    window: &Window,
+    display: &Display<WindowSurface>,
+    fonts: &FontBuilder,
+    startup: FInit,
+) -> ImGui
+where
+    FInit: FnMut(&mut Context, &mut Renderer, &Display<WindowSurface>) + 'static,
+{
+    try_init_with_fonts(window, display, fonts, startup).unwrap_or_else(|e| panic!("{}", e))
+}
+
+/// Same as [`try_init`] with custom fonts
+pub fn try_init_with_fonts<FInit>(
+    window: &Window,
+    display: &Display<WindowSurface>,
+    fonts: &FontBuilder,
+    mut startup: FInit,
+) -> Result<ImGui, InitError>
 where
     FInit: FnMut(&mut Context, &mut Renderer, &Display<WindowSurface>) + 'static,
 {
+    let scale_factor = window.scale_factor();
+    if !scale_factor.is_finite() || scale_factor <= 0.0 {
+        return Err(InitError::Platform(format!(
+            "invalid scale factor {}",
+            scale_factor
+        )));
+    }
     let mut imgui = create_context();
-    let mut renderer = Renderer::new(&mut imgui, display).expect("Failed to initialize renderer");
+    // Note imgui_winit_support uses "logical pixels", which are
+    // physical pixels scaled by the devices scaling factor. Meaning,
+    // 13.0 pixels should look the same size on two different screens,
+    // and thus we do not need to scale the font size (as the scaling
+    // is handled by winit)
+    let max_texture_size = display.get_capabilities().max_texture_size as u32;
+    let font_atlas = fonts.build(&mut imgui, max_texture_size);
+    let font_ids = font_ids(&mut imgui, fonts);
+    let mut renderer = Renderer::new(&mut imgui, display)?;
 
     let mut platform = WinitPlatform::new(&mut imgui);
     platform.attach_window(imgui.io_mut(), window, HiDpiMode::Default);
 
-    startup(&mut imgui, &mut renderer, &display);
-    ImGui {
+    startup(&mut imgui, &mut renderer, display);
+    Ok(ImGui {
         context: imgui,
         renderer,
         platform,
-    }
+        font_atlas,
+        fonts: fonts.clone(),
+        font_ids,
+        max_texture_size,
+        watermark: None,
+        virtual_keyboard: None,
+        id_conflicts: None,
+        inbox: UiInbox::new(),
+        frame_timer: FrameTimer::new(),
+        drawn_windows: Vec::new(),
+        cursor_pos: [0.0, 0.0],
+        sounds: None,
+        sound_state: SoundState::default(),
+        session: Session::new(),
+        user_session: Session::new(),
+        safe_mode: false,
+        settings: None,
+        scope_callbacks: Vec::new(),
+        save_callbacks: Vec::new(),
+        settings_dirty: false,
+        autosave_interval: Some(AUTOSAVE_INTERVAL),
+        last_save: Instant::now(),
+        save_error: None,
+        frame_state: FrameState::Idle,
+        resume_callbacks: Vec::new(),
+        base_style: None,
+        style_scale: 1.0,
+        ui_scale: 1.0,
+        low_vision: None,
+        pending_low_vision: None,
+        #[cfg(feature = "shader-hot-reload")]
+        shader_reload_key: Some(winit::keyboard::KeyCode::F5),
+    })
+}
+
+/// Same as [`init`] but ignores all saved settings and loads only imgui's built-in font, for
+/// starting after a crash (see [`safe_mode::CrashSentinel`]) so a broken settings or font file
+/// can't keep the tool from starting
+pub fn init_safe_mode<FInit>(
+    window: &Window,
+    display: &Display<WindowSurface>,
+    startup: FInit,
+) -> ImGui
+where
+    FInit: FnMut(&mut Context, &mut Renderer, &Display<WindowSurface>) + 'static,
+{
+    try_init_safe_mode(window, display, startup).unwrap_or_else(|e| panic!("{}", e))
+}
+
+/// Same as [`init_safe_mode`] but returns an error instead of panicking
+pub fn try_init_safe_mode<FInit>(
+    window: &Window,
+    display: &Display<WindowSurface>,
+    startup: FInit,
+) -> Result<ImGui, InitError>
+where
+    FInit: FnMut(&mut Context, &mut Renderer, &Display<WindowSurface>) + 'static,
+{
+    let mut imgui = try_init_with_fonts(window, display, &FontBuilder::empty(), startup)?;
+    imgui.safe_mode = true;
+    Ok(imgui)
+}
+
+/// Maps the named fonts of `fonts` to their IDs after a build
+fn font_ids(ctx: &mut Context, fonts: &FontBuilder) -> HashMap<String, FontId> {
+    let ids = ctx.fonts().fonts();
+    fonts
+        .named_fonts()
+        .zip(ids.into_iter().skip(1))
+        .map(|(name, id)| (name.to_string(), id))
+        .collect()
+}
+
+/// Store the settings are saved to, none in safe mode so the settings from before the crash stay
+/// around for the next normal start
+fn save_target(settings: Option<&SettingsStore>, safe_mode: bool) -> Option<&SettingsStore> {
+    settings.filter(|_| !safe_mode)
 }
 
 /// Creates the imgui context
 fn create_context() -> imgui::Context {
     let mut imgui = Context::create();
-    // Fixed font size. Note imgui_winit_support uses "logical
-    // pixels", which are physical pixels scaled by the devices
-    // scaling factor. Meaning, 13.0 pixels should look the same size
-    // on two different screens, and thus we do not need to scale this
-    // value (as the scaling is handled by winit)
-    imgui.fonts().add_font(&[
-        FontSource::TtfData {
-            data: include_bytes!("../resources/Roboto-Regular.ttf"),
-            size_pixels: FONT_SIZE,
-            config: Some(FontConfig {
-                // As imgui-glium-renderer isn't gamma-correct with
-                // it's font rendering, we apply an arbitrary
-                // multiplier to make the font a bit "heavier". With
-                // default imgui-glow-renderer this is unnecessary.
-                rasterizer_multiply: 1.5,
-                // Oversampling font helps improve text rendering at
-                // expense of larger font atlas texture.
-                oversample_h: 4,
-                oversample_v: 4,
-                ..FontConfig::default()
-            }),
-        },
-        FontSource::TtfData {
-            data: include_bytes!("../resources/mplus-1p-regular.ttf"),
-            size_pixels: FONT_SIZE,
-            config: Some(FontConfig {
-                // Oversampling font helps improve text rendering at
-                // expense of larger font atlas texture.
-                oversample_h: 4,
-                oversample_v: 4,
-                // Range of glyphs to rasterize
-                glyph_ranges: FontGlyphRanges::japanese(),
-                ..FontConfig::default()
-            }),
-        },
-    ]);
     imgui.set_ini_filename(None);
 
     imgui
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn safe_mode_saves_nothing() {
+        let store = SettingsStore::new(std::env::temp_dir().join("imgui_support_unused"));
+        assert!(save_target(Some(&store), false).is_some());
+        assert!(save_target(Some(&store), true).is_none());
+        assert!(save_target(None, false).is_none());
+    }
+}
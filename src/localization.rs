@@ -0,0 +1,687 @@
+//! Translation tables and an editor for them.
+//!
+//! [`StringTable`] maps keys to one translation per language and reads and writes CSV, and JSON
+//! with the `serde` feature. [`LocalizationPanel`] edits it with the languages side by side and
+//! asks for a font rebuild when translations use characters the UI font can't show, see
+//! [`fonts::glyph_ranges_for`](crate::fonts::glyph_ranges_for).
+
+use crate::fonts::{glyph_ranges_for, missing_glyphs};
+use imgui::{Condition, FontGlyphRanges, ListClipper, StyleColor, Ui};
+use std::collections::{BTreeMap, HashSet};
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+const MISSING_COLOR: [f32; 4] = [0.55, 0.15, 0.15, 0.6];
+const WARNING_COLOR: [f32; 4] = [1.0, 0.7, 0.2, 1.0];
+const ERROR_COLOR: [f32; 4] = [0.9, 0.35, 0.35, 1.0];
+/// Missing characters listed in the panel, the rest are counted
+const LISTED_GLYPHS: usize = 32;
+
+/// Why a string table couldn't be read or written
+#[derive(Debug)]
+pub enum LocalizationError {
+    Io(io::Error),
+    /// Line of the CSV text, counted from 1, and what's wrong with it
+    Csv(usize, String),
+    #[cfg(feature = "serde")]
+    Json(serde_json::Error),
+}
+
+impl Error for LocalizationError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            LocalizationError::Io(e) => Some(e),
+            LocalizationError::Csv(..) => None,
+            #[cfg(feature = "serde")]
+            LocalizationError::Json(e) => Some(e),
+        }
+    }
+}
+
+impl fmt::Display for LocalizationError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LocalizationError::Io(e) => write!(f, "String table file error: {}", e),
+            LocalizationError::Csv(line, e) => write!(f, "Invalid CSV on line {}: {}", line, e),
+            #[cfg(feature = "serde")]
+            LocalizationError::Json(e) => write!(f, "Invalid string table JSON: {}", e),
+        }
+    }
+}
+
+impl From<io::Error> for LocalizationError {
+    fn from(e: io::Error) -> LocalizationError {
+        LocalizationError::Io(e)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl From<serde_json::Error> for LocalizationError {
+    fn from(e: serde_json::Error) -> LocalizationError {
+        LocalizationError::Json(e)
+    }
+}
+
+/// Translations by key and language. Empty translations count as missing.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct StringTable {
+    languages: Vec<String>,
+    /// Translations of each key by language
+    entries: BTreeMap<String, BTreeMap<String, String>>,
+}
+
+impl StringTable {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Language names in column order, e.g. "en" or "ja"
+    pub fn languages(&self) -> &[String] {
+        &self.languages
+    }
+    /// Returns `false` if the language is already there
+    pub fn add_language(&mut self, language: impl Into<String>) -> bool {
+        let language = language.into();
+        if self.languages.contains(&language) {
+            return false;
+        }
+        self.languages.push(language);
+        true
+    }
+    /// Removes the language with all its translations
+    pub fn remove_language(&mut self, language: &str) {
+        self.languages.retain(|name| name != language);
+        for translations in self.entries.values_mut() {
+            translations.remove(language);
+        }
+    }
+
+    /// Keys in sorted order
+    pub fn keys(&self) -> impl Iterator<Item = &str> {
+        self.entries.keys().map(String::as_str)
+    }
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+    /// Adds a key without translations, returns `false` if it's already there
+    pub fn add_key(&mut self, key: impl Into<String>) -> bool {
+        let key = key.into();
+        if self.entries.contains_key(&key) {
+            return false;
+        }
+        self.entries.insert(key, BTreeMap::new());
+        true
+    }
+    pub fn remove_key(&mut self, key: &str) {
+        self.entries.remove(key);
+    }
+    /// Returns `false` if `key` doesn't exist or `new_key` already does
+    pub fn rename_key(&mut self, key: &str, new_key: impl Into<String>) -> bool {
+        let new_key = new_key.into();
+        if self.entries.contains_key(&new_key) {
+            return false;
+        }
+        match self.entries.remove(key) {
+            Some(translations) => {
+                self.entries.insert(new_key, translations);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// The translation of `key`, `None` when it's missing
+    pub fn get(&self, key: &str, language: &str) -> Option<&str> {
+        self.entries
+            .get(key)?
+            .get(language)
+            .map(String::as_str)
+            .filter(|text| !text.is_empty())
+    }
+    /// Sets a translation, adding the key and the language if needed. An empty `text` removes
+    /// it.
+    pub fn set(&mut self, key: &str, language: &str, text: impl Into<String>) {
+        let text = text.into();
+        self.add_language(language);
+        let translations = self.entries.entry(key.to_string()).or_default();
+        if text.is_empty() {
+            translations.remove(language);
+        } else {
+            translations.insert(language.to_string(), text);
+        }
+    }
+    pub fn is_missing(&self, key: &str, language: &str) -> bool {
+        self.get(key, language).is_none()
+    }
+    /// Keys without a translation into `language`
+    pub fn missing_count(&self, language: &str) -> usize {
+        self.keys()
+            .filter(|key| self.is_missing(key, language))
+            .count()
+    }
+    /// Every translation joined, e.g. for [`glyph_ranges_for`]
+    pub fn all_text(&self) -> String {
+        self.entries
+            .values()
+            .flat_map(|translations| translations.values())
+            .map(String::as_str)
+            .collect()
+    }
+
+    /// Reads a table with a header row of `key` and the language names, then one row per key.
+    /// Cells are separated by commas, quoted cells may hold commas, quotes as `""` and newlines.
+    pub fn from_csv(text: &str) -> Result<Self, LocalizationError> {
+        let mut rows = parse_csv(text.strip_prefix('\u{feff}').unwrap_or(text))?.into_iter();
+        let Some((_, header)) = rows.next() else {
+            return Ok(Self::new());
+        };
+        let mut table = Self::new();
+        let languages: Vec<String> = header.into_iter().skip(1).collect();
+        for language in &languages {
+            table.add_language(language.clone());
+        }
+        for (line, row) in rows {
+            let mut cells = row.into_iter();
+            let Some(key) = cells.next().filter(|key| !key.is_empty()) else {
+                continue;
+            };
+            if table.entries.contains_key(&key) {
+                return Err(LocalizationError::Csv(
+                    line,
+                    format!("duplicate key {:?}", key),
+                ));
+            }
+            table.add_key(key.clone());
+            for (index, text) in cells.enumerate() {
+                let Some(language) = languages.get(index) else {
+                    return Err(LocalizationError::Csv(
+                        line,
+                        format!("more cells than the {} languages", languages.len()),
+                    ));
+                };
+                table.set(&key, language, text);
+            }
+        }
+        Ok(table)
+    }
+    /// Writes the table in the format [`from_csv`](Self::from_csv) reads
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::new();
+        let header = std::iter::once("key").chain(self.languages.iter().map(String::as_str));
+        write_csv_row(&mut csv, header);
+        for key in self.keys() {
+            let cells = self
+                .languages
+                .iter()
+                .map(|language| self.get(key, language).unwrap_or_default());
+            write_csv_row(&mut csv, std::iter::once(key).chain(cells));
+        }
+        csv
+    }
+
+    /// Reads an object of languages holding objects of keys and translations, e.g.
+    /// `{"en": {"menu.start": "Start"}}`
+    #[cfg(feature = "serde")]
+    pub fn from_json(json: &str) -> Result<Self, LocalizationError> {
+        let languages: BTreeMap<String, BTreeMap<String, String>> = serde_json::from_str(json)?;
+        let mut table = Self::new();
+        for (language, translations) in languages {
+            table.add_language(language.clone());
+            for (key, text) in translations {
+                table.add_key(key.clone());
+                table.set(&key, &language, text);
+            }
+        }
+        Ok(table)
+    }
+    /// Writes the table in the format [`from_json`](Self::from_json) reads, missing
+    /// translations are left out
+    #[cfg(feature = "serde")]
+    pub fn to_json(&self) -> Result<String, LocalizationError> {
+        let languages: BTreeMap<&str, BTreeMap<&str, &str>> = self
+            .languages
+            .iter()
+            .map(|language| {
+                let translations = self
+                    .keys()
+                    .filter_map(|key| Some((key, self.get(key, language)?)))
+                    .collect();
+                (language.as_str(), translations)
+            })
+            .collect();
+        Ok(serde_json::to_string_pretty(&languages)?)
+    }
+
+    /// Reads a `.json` file as JSON with the `serde` feature, anything else as CSV
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, LocalizationError> {
+        let path = path.as_ref();
+        let text = fs::read_to_string(path)?;
+        #[cfg(feature = "serde")]
+        if is_json(path) {
+            return Self::from_json(&text);
+        }
+        Self::from_csv(&text)
+    }
+    /// Writes a `.json` file as JSON with the `serde` feature, anything else as CSV
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), LocalizationError> {
+        let path = path.as_ref();
+        #[cfg(feature = "serde")]
+        if is_json(path) {
+            fs::write(path, self.to_json()?)?;
+            return Ok(());
+        }
+        fs::write(path, self.to_csv())?;
+        Ok(())
+    }
+}
+
+#[cfg(feature = "serde")]
+fn is_json(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("json"))
+}
+
+/// Rows of CSV text with the line each starts on
+fn parse_csv(text: &str) -> Result<Vec<(usize, Vec<String>)>, LocalizationError> {
+    let mut rows = Vec::new();
+    let mut row = Vec::new();
+    let mut cell = String::new();
+    let mut quoted = false;
+    let mut line = 1;
+    let mut row_line = 1;
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                chars.next();
+                cell.push('"');
+            }
+            '"' if quoted => quoted = false,
+            '"' if cell.is_empty() => quoted = true,
+            ',' if !quoted => row.push(std::mem::take(&mut cell)),
+            '\r' if !quoted && chars.peek() == Some(&'\n') => (),
+            '\n' if !quoted => {
+                row.push(std::mem::take(&mut cell));
+                rows.push((row_line, std::mem::take(&mut row)));
+                line += 1;
+                row_line = line;
+            }
+            c => {
+                if c == '\n' {
+                    line += 1;
+                }
+                cell.push(c);
+            }
+        }
+    }
+    if quoted {
+        return Err(LocalizationError::Csv(row_line, "unclosed quote".into()));
+    }
+    if !cell.is_empty() || !row.is_empty() {
+        row.push(cell);
+        rows.push((row_line, row));
+    }
+    Ok(rows)
+}
+
+fn write_csv_row<'a>(csv: &mut String, cells: impl Iterator<Item = &'a str>) {
+    for (index, cell) in cells.enumerate() {
+        if index > 0 {
+            csv.push(',');
+        }
+        let quote = cell.contains([',', '"', '\n', '\r']) || cell.trim() != cell;
+        if quote {
+            csv.push('"');
+            csv.push_str(&cell.replace('"', "\"\""));
+            csv.push('"');
+        } else {
+            csv.push_str(cell);
+        }
+    }
+    csv.push('\n');
+}
+
+/// Editor for a [`StringTable`]: the languages side by side with missing translations
+/// highlighted, search, adding keys and languages, and importing and exporting files.
+///
+/// The UI font only has the glyphs it was built with, so the panel checks new translations
+/// against it. [`draw`](Self::draw) returns `true` when the fonts should be rebuilt with a font
+/// covering [`glyph_ranges`](Self::glyph_ranges), e.g. with
+/// [`ImGui::rebuild_fonts`](crate::ImGui::rebuild_fonts) after rendering, so new scripts show up
+/// right away.
+#[derive(Clone, Debug, Default)]
+pub struct LocalizationPanel {
+    pub table: StringTable,
+    /// Matches keys and translations, ignoring case
+    pub search: String,
+    /// Shows only keys missing a translation into a shown language
+    pub missing_only: bool,
+    hidden_languages: HashSet<String>,
+    /// Asks for a font rebuild as soon as translations need glyphs the font doesn't have,
+    /// otherwise only from the panel's button
+    pub auto_rebuild: bool,
+    new_key: String,
+    new_language: String,
+    path: String,
+    /// Result of the last import or export
+    status: Option<Result<String, String>>,
+    /// Characters the UI font can't show, `None` until checked after a change
+    missing_glyphs: Option<Vec<char>>,
+    /// Missing characters of the last automatic rebuild, so it isn't asked for again
+    requested_glyphs: Vec<char>,
+}
+
+impl LocalizationPanel {
+    pub fn new(table: StringTable) -> Self {
+        Self {
+            table,
+            auto_rebuild: true,
+            ..Self::default()
+        }
+    }
+    /// Glyph ranges of every translation, for the font merged in on a rebuild. Leaks them,
+    /// see [`glyph_ranges_for`].
+    pub fn glyph_ranges(&self) -> FontGlyphRanges {
+        glyph_ranges_for(&self.table.all_text())
+    }
+    /// Characters of the translations the UI font couldn't show when last checked
+    pub fn missing_glyphs(&self) -> &[char] {
+        self.missing_glyphs.as_deref().unwrap_or_default()
+    }
+    /// Checks the glyphs again on the next draw, e.g. after the table was changed elsewhere
+    pub fn invalidate(&mut self) {
+        self.missing_glyphs = None;
+    }
+
+    fn matches(&self, key: &str) -> bool {
+        let visible = || {
+            self.table
+                .languages()
+                .iter()
+                .filter(|language| !self.hidden_languages.contains(*language))
+        };
+        if self.missing_only && !visible().any(|language| self.table.is_missing(key, language)) {
+            return false;
+        }
+        if self.search.is_empty() {
+            return true;
+        }
+        let search = self.search.to_lowercase();
+        key.to_lowercase().contains(&search)
+            || visible().any(|language| {
+                self.table
+                    .get(key, language)
+                    .is_some_and(|text| text.to_lowercase().contains(&search))
+            })
+    }
+
+    /// Draws the panel, returns `true` when the fonts should be rebuilt
+    pub fn draw(&mut self, ui: &Ui, opened: &mut bool) -> bool {
+        let mut rebuild = false;
+        ui.window("Localization")
+            .opened(opened)
+            .size([720.0, 480.0], Condition::FirstUseEver)
+            .build(|| {
+                if self.missing_glyphs.is_none() {
+                    let missing = missing_glyphs(ui, &self.table.all_text());
+                    if self.auto_rebuild && !missing.is_empty() && missing != self.requested_glyphs
+                    {
+                        self.requested_glyphs = missing.clone();
+                        rebuild = true;
+                    }
+                    self.missing_glyphs = Some(missing);
+                }
+                self.draw_files(ui);
+                rebuild |= self.draw_glyph_warning(ui);
+                ui.separator();
+                self.draw_toolbar(ui);
+                ui.separator();
+                self.draw_table(ui);
+            });
+        if rebuild {
+            // The new font is in place by the next draw
+            self.missing_glyphs = None;
+        }
+        rebuild
+    }
+
+    fn draw_files(&mut self, ui: &Ui) {
+        ui.set_next_item_width(ui.content_region_avail()[0] * 0.5);
+        ui.input_text("##path", &mut self.path)
+            .hint("strings.csv")
+            .build();
+        ui.same_line();
+        if ui.button("Import") {
+            self.status = Some(match StringTable::load(&self.path) {
+                Ok(table) => {
+                    self.table = table;
+                    self.missing_glyphs = None;
+                    Ok(format!("Imported {} keys", self.table.len()))
+                }
+                Err(e) => Err(e.to_string()),
+            });
+        }
+        ui.same_line();
+        if ui.button("Export") {
+            self.status = Some(match self.table.save(&self.path) {
+                Ok(()) => Ok(format!("Exported {} keys", self.table.len())),
+                Err(e) => Err(e.to_string()),
+            });
+        }
+        if ui.is_item_hovered() {
+            ui.tooltip_text(if cfg!(feature = "serde") {
+                ".json files are JSON, others CSV"
+            } else {
+                "Files are CSV"
+            });
+        }
+        match &self.status {
+            Some(Ok(message)) => ui.text_disabled(message),
+            Some(Err(message)) => ui.text_colored(ERROR_COLOR, message),
+            None => (),
+        }
+    }
+
+    /// Returns `true` when Rebuild fonts was pressed
+    fn draw_glyph_warning(&self, ui: &Ui) -> bool {
+        let missing = self.missing_glyphs();
+        if missing.is_empty() {
+            return false;
+        }
+        let listed: String = missing.iter().take(LISTED_GLYPHS).collect();
+        let more = missing.len().saturating_sub(LISTED_GLYPHS);
+        ui.text_colored(
+            WARNING_COLOR,
+            format!("{} characters the font can't show", missing.len()),
+        );
+        if ui.is_item_hovered() {
+            ui.tooltip_text(if more > 0 {
+                format!("{} and {} more", listed, more)
+            } else {
+                listed
+            });
+        }
+        ui.same_line();
+        ui.button("Rebuild fonts")
+    }
+
+    fn draw_toolbar(&mut self, ui: &Ui) {
+        ui.set_next_item_width(200.0);
+        ui.input_text("##search", &mut self.search)
+            .hint("Search")
+            .build();
+        ui.same_line();
+        ui.checkbox("Missing only", &mut self.missing_only);
+        let mut remove = None;
+        for language in self.table.languages() {
+            let missing = self.table.missing_count(language);
+            let mut shown = !self.hidden_languages.contains(language);
+            if ui.checkbox(format!("{} ({} missing)", language, missing), &mut shown) {
+                if shown {
+                    self.hidden_languages.remove(language);
+                } else {
+                    self.hidden_languages.insert(language.clone());
+                }
+            }
+            if let Some(_popup) = ui.begin_popup_context_item() {
+                if ui.menu_item("Remove language") {
+                    remove = Some(language.clone());
+                }
+            }
+            ui.same_line();
+        }
+        ui.new_line();
+        if let Some(language) = remove {
+            self.table.remove_language(&language);
+            self.missing_glyphs = None;
+        }
+
+        ui.set_next_item_width(200.0);
+        let add_key = ui
+            .input_text("##new_key", &mut self.new_key)
+            .hint("New key")
+            .enter_returns_true(true)
+            .build();
+        ui.same_line();
+        if (ui.button("Add key") || add_key)
+            && !self.new_key.is_empty()
+            && self.table.add_key(self.new_key.clone())
+        {
+            self.search = std::mem::take(&mut self.new_key);
+        }
+        ui.same_line();
+        ui.set_next_item_width(120.0);
+        let add_language = ui
+            .input_text("##new_language", &mut self.new_language)
+            .hint("New language")
+            .enter_returns_true(true)
+            .build();
+        ui.same_line();
+        if (ui.button("Add language") || add_language) && !self.new_language.is_empty() {
+            self.table
+                .add_language(std::mem::take(&mut self.new_language));
+        }
+    }
+
+    fn draw_table(&mut self, ui: &Ui) {
+        let languages: Vec<String> = self
+            .table
+            .languages()
+            .iter()
+            .filter(|language| !self.hidden_languages.contains(*language))
+            .cloned()
+            .collect();
+        let keys: Vec<String> = self
+            .table
+            .keys()
+            .filter(|key| self.matches(key))
+            .map(String::from)
+            .collect();
+        ui.text_disabled(format!("{} of {} keys", keys.len(), self.table.len()));
+        ui.child_window("##strings").build(|| {
+            ui.columns(languages.len() as i32 + 1, "##string_table", true);
+            ui.text_disabled("Key");
+            ui.next_column();
+            for language in &languages {
+                ui.text_disabled(language);
+                ui.next_column();
+            }
+            ui.separator();
+            let mut removed = None;
+            let clipper = ListClipper::new(keys.len() as i32).begin(ui);
+            for row in clipper.iter() {
+                let key = &keys[row as usize];
+                let _id = ui.push_id(key);
+                if ui.small_button("x") {
+                    removed = Some(key.clone());
+                }
+                if ui.is_item_hovered() {
+                    ui.tooltip_text("Remove key");
+                }
+                ui.same_line();
+                ui.text(key);
+                ui.next_column();
+                for language in &languages {
+                    let _id = ui.push_id(language);
+                    let mut text = self
+                        .table
+                        .get(key, language)
+                        .unwrap_or_default()
+                        .to_string();
+                    let missing = text.is_empty();
+                    let _color =
+                        missing.then(|| ui.push_style_color(StyleColor::FrameBg, MISSING_COLOR));
+                    ui.set_next_item_width(-1.0);
+                    if ui.input_text("##text", &mut text).hint("missing").build() {
+                        self.table.set(key, language, text);
+                        self.missing_glyphs = None;
+                    }
+                    ui.next_column();
+                }
+            }
+            ui.columns(1, "##string_table_end", false);
+            if let Some(key) = removed {
+                self.table.remove_key(&key);
+                self.missing_glyphs = None;
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn csv_round_trips() {
+        let mut table = StringTable::new();
+        table.add_language("en");
+        table.add_language("de");
+        table.add_key("menu.start");
+        table.set("menu.start", "en", "Start");
+        table.set("menu.start", "de", "Starten");
+        table.add_key("dialog.quote");
+        table.set("dialog.quote", "en", "He said \"hi\", then left");
+        table.add_key("dialog.lines");
+        table.set("dialog.lines", "en", "First\nsecond");
+        table.set("dialog.lines", "de", " padded ");
+
+        let csv = table.to_csv();
+        let read = StringTable::from_csv(&csv).unwrap();
+        assert_eq!(read.to_csv(), csv);
+        assert_eq!(
+            read.get("dialog.quote", "en"),
+            Some("He said \"hi\", then left")
+        );
+        assert_eq!(read.get("dialog.lines", "en"), Some("First\nsecond"));
+        assert_eq!(read.get("dialog.lines", "de"), Some(" padded "));
+    }
+
+    #[test]
+    fn csv_errors_name_the_line() {
+        let duplicate = "key,en\na,A\n\"multi\nline\",B\na,C\n";
+        assert!(matches!(
+            StringTable::from_csv(duplicate),
+            Err(LocalizationError::Csv(5, _))
+        ));
+        assert!(matches!(
+            StringTable::from_csv("key,en\na,A,extra\n"),
+            Err(LocalizationError::Csv(2, _))
+        ));
+        assert!(matches!(
+            StringTable::from_csv("key,en\na,\"open\n"),
+            Err(LocalizationError::Csv(2, _))
+        ));
+    }
+
+    #[test]
+    fn csv_accepts_bom_and_crlf() {
+        let table = StringTable::from_csv("\u{feff}key,en\r\nhello,Hello\r\n").unwrap();
+        assert_eq!(table.get("hello", "en"), Some("Hello"));
+    }
+}
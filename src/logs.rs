@@ -0,0 +1,238 @@
+//! Log viewing panels.
+//!
+//! [`LogFilter`] is the level and text filter shared by the log panels, [`LogTail`] follows a log
+//! file on disk. Lines are split into level, module, message and `key=value` fields by
+//! [`ParsedLine`], which understands tracing-subscriber's default format. [`ModuleVerbosity`]
+//! changes the log level per module at runtime.
+//!
+//! With the `log` feature [`LogCapture`] is a `log` crate logger keeping the records in a
+//! [`LogBuffer`] for [`log_window`](crate::widgets::log_window).
+
+#[cfg(feature = "log")]
+mod capture;
+mod tail;
+mod verbosity;
+
+#[cfg(feature = "log")]
+pub use capture::{LogBuffer, LogCapture, LogRecord, LOG_CAPACITY};
+pub use tail::LogTail;
+pub use verbosity::{ModuleVerbosity, VerbosityHandle};
+
+use imgui::Ui;
+use std::borrow::Cow;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum LogLevel {
+    Error,
+    Warn,
+    Info,
+    Debug,
+    Trace,
+}
+
+impl LogLevel {
+    pub const ALL: [LogLevel; 5] = [
+        LogLevel::Error,
+        LogLevel::Warn,
+        LogLevel::Info,
+        LogLevel::Debug,
+        LogLevel::Trace,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            LogLevel::Error => "Error",
+            LogLevel::Warn => "Warn",
+            LogLevel::Info => "Info",
+            LogLevel::Debug => "Debug",
+            LogLevel::Trace => "Trace",
+        }
+    }
+    pub fn color(self) -> [f32; 4] {
+        match self {
+            LogLevel::Error => [1.0, 0.35, 0.35, 1.0],
+            LogLevel::Warn => [1.0, 0.75, 0.3, 1.0],
+            LogLevel::Info => [0.85, 0.85, 0.85, 1.0],
+            LogLevel::Debug => [0.55, 0.7, 1.0, 1.0],
+            LogLevel::Trace => [0.6, 0.6, 0.6, 1.0],
+        }
+    }
+    /// Finds the level in a formatted line by its first level word, e.g. `ERROR` or `[warn]`
+    pub fn parse_line(line: &str) -> Option<LogLevel> {
+        line.split(|c: char| !c.is_ascii_alphabetic())
+            .take(8)
+            .find_map(LogLevel::from_word)
+    }
+    fn from_word(word: &str) -> Option<LogLevel> {
+        match word.to_ascii_uppercase().as_str() {
+            "ERROR" | "ERR" => Some(LogLevel::Error),
+            "WARN" | "WARNING" => Some(LogLevel::Warn),
+            "INFO" => Some(LogLevel::Info),
+            "DEBUG" => Some(LogLevel::Debug),
+            "TRACE" => Some(LogLevel::Trace),
+            _ => None,
+        }
+    }
+    /// Name used in filter directives, e.g. `debug`
+    pub fn directive(self) -> &'static str {
+        match self {
+            LogLevel::Error => "error",
+            LogLevel::Warn => "warn",
+            LogLevel::Info => "info",
+            LogLevel::Debug => "debug",
+            LogLevel::Trace => "trace",
+        }
+    }
+}
+
+/// Log line split into its parts.
+///
+/// Expects lines like `2024-05-01T12:00:00Z  INFO engine::assets: loaded path="a b.png" ms=3`,
+/// parts that aren't found are left empty and the whole line becomes the message.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParsedLine {
+    pub level: Option<LogLevel>,
+    /// Module path, i.e. the tracing target
+    pub module: Option<String>,
+    pub message: String,
+    /// Trailing `key=value` pairs, quotes around values are removed
+    pub fields: Vec<(String, String)>,
+}
+
+impl ParsedLine {
+    pub fn parse(line: &str) -> ParsedLine {
+        let tokens = tokenize(line);
+        let mut body = 0;
+        let mut level = None;
+        let mut module = None;
+        if let Some(i) = tokens.iter().take(8).position(|&(_, token)| {
+            LogLevel::from_word(token.trim_matches(|c: char| !c.is_ascii_alphabetic())).is_some()
+        }) {
+            level =
+                LogLevel::from_word(tokens[i].1.trim_matches(|c: char| !c.is_ascii_alphabetic()));
+            body = i + 1;
+            if let Some(&(_, token)) = tokens.get(body) {
+                if token.len() > 1 && token.ends_with(':') && !token.contains('=') {
+                    module = Some(token.trim_end_matches(':').to_string());
+                    body += 1;
+                }
+            }
+        }
+        let mut end = tokens.len();
+        let mut fields = Vec::new();
+        while end > body {
+            let Some(field) = parse_field(tokens[end - 1].1) else {
+                break;
+            };
+            fields.push(field);
+            end -= 1;
+        }
+        fields.reverse();
+        let message = match (tokens.get(body), end > body) {
+            (Some(&(start, _)), true) => {
+                let (last_start, last) = tokens[end - 1];
+                line[start..last_start + last.len()].to_string()
+            }
+            _ => String::new(),
+        };
+        ParsedLine {
+            level,
+            module,
+            message,
+            fields,
+        }
+    }
+}
+
+/// Splits at whitespace outside of double quotes, with the byte offset of each token
+fn tokenize(line: &str) -> Vec<(usize, &str)> {
+    let mut tokens = Vec::new();
+    let mut start = None;
+    let mut quoted = false;
+    let mut escaped = false;
+    for (i, c) in line.char_indices() {
+        if c.is_whitespace() && !quoted {
+            if let Some(start) = start.take() {
+                tokens.push((start, &line[start..i]));
+            }
+            continue;
+        }
+        start.get_or_insert(i);
+        match c {
+            _ if escaped => escaped = false,
+            '\\' => escaped = true,
+            '"' => quoted = !quoted,
+            _ => (),
+        }
+    }
+    if let Some(start) = start {
+        tokens.push((start, &line[start..]));
+    }
+    tokens
+}
+
+fn parse_field(token: &str) -> Option<(String, String)> {
+    let (key, value) = token.split_once('=')?;
+    let valid_key = !key.is_empty()
+        && key
+            .chars()
+            .all(|c| c.is_alphanumeric() || matches!(c, '_' | '.' | '-'));
+    if !valid_key {
+        return None;
+    }
+    let value = match value.strip_prefix('"').and_then(|v| v.strip_suffix('"')) {
+        Some(quoted) => quoted.replace("\\\"", "\"").replace("\\\\", "\\"),
+        None => value.to_string(),
+    };
+    Some((key.to_string(), value))
+}
+
+/// Level and text search filter drawn above a log
+#[derive(Clone, Debug)]
+pub struct LogFilter {
+    /// Most verbose level shown
+    pub max_level: LogLevel,
+    /// Case-insensitive substring
+    pub search: String,
+}
+
+impl Default for LogFilter {
+    fn default() -> Self {
+        Self {
+            max_level: LogLevel::Trace,
+            search: String::new(),
+        }
+    }
+}
+
+impl LogFilter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Lines without a recognized level are only hidden by the search
+    pub fn matches(&self, level: Option<LogLevel>, text: &str) -> bool {
+        if level.is_some_and(|level| level > self.max_level) {
+            return false;
+        }
+        self.search.is_empty() || text.to_lowercase().contains(&self.search.to_lowercase())
+    }
+    /// Draws the level combo and search field, returns `true` when the filter changed
+    pub fn draw(&mut self, ui: &Ui) -> bool {
+        let mut level = LogLevel::ALL
+            .iter()
+            .position(|&level| level == self.max_level)
+            .unwrap_or(LogLevel::ALL.len() - 1);
+        ui.set_next_item_width(ui.calc_text_size("Warn")[0] * 3.0);
+        let mut changed = ui.combo("##level", &mut level, &LogLevel::ALL, |level| {
+            Cow::Borrowed(level.name())
+        });
+        self.max_level = LogLevel::ALL[level];
+        ui.same_line();
+        ui.set_next_item_width(-1.0);
+        changed |= ui
+            .input_text("##search", &mut self.search)
+            .hint("Search")
+            .build();
+        changed
+    }
+}
@@ -0,0 +1,260 @@
+use super::{LogFilter, LogLevel};
+use std::collections::VecDeque;
+use std::fmt;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+/// Records kept by default, see [`LogCapture::with_capacity`]
+pub const LOG_CAPACITY: usize = 5000;
+
+/// A captured `log` record
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LogRecord {
+    pub level: LogLevel,
+    /// Usually the module path of the call site
+    pub target: String,
+    pub message: String,
+}
+
+impl From<log::Level> for LogLevel {
+    fn from(level: log::Level) -> Self {
+        match level {
+            log::Level::Error => LogLevel::Error,
+            log::Level::Warn => LogLevel::Warn,
+            log::Level::Info => LogLevel::Info,
+            log::Level::Debug => LogLevel::Debug,
+            log::Level::Trace => LogLevel::Trace,
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+pub(crate) struct LogView {
+    pub(crate) filter: LogFilter,
+    pub(crate) follow: bool,
+}
+
+#[derive(Debug)]
+struct Shared {
+    records: VecDeque<LogRecord>,
+    capacity: usize,
+    /// Records dropped because the buffer was full
+    dropped: u64,
+}
+
+/// Ring buffer the [`LogCapture`] logger writes to, cheap to clone and share between threads.
+/// Draw it with [`log_window`](crate::widgets::log_window).
+#[derive(Clone, Debug)]
+pub struct LogBuffer {
+    shared: Arc<Mutex<Shared>>,
+    view: Arc<Mutex<LogView>>,
+}
+
+impl LogBuffer {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            shared: Arc::new(Mutex::new(Shared {
+                records: VecDeque::with_capacity(capacity.min(LOG_CAPACITY)),
+                capacity: capacity.max(1),
+                dropped: 0,
+            })),
+            view: Arc::new(Mutex::new(LogView {
+                follow: true,
+                ..LogView::default()
+            })),
+        }
+    }
+    fn lock(&self) -> MutexGuard<'_, Shared> {
+        // A thread panicking while logging leaves the records intact
+        self.shared.lock().unwrap_or_else(|e| e.into_inner())
+    }
+    pub(crate) fn view(&self) -> MutexGuard<'_, LogView> {
+        self.view.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Adds a record, dropping the oldest one when full
+    pub fn push(&self, record: LogRecord) {
+        let mut shared = self.lock();
+        if shared.records.len() >= shared.capacity {
+            shared.records.pop_front();
+            shared.dropped += 1;
+        }
+        shared.records.push_back(record);
+    }
+    pub fn len(&self) -> usize {
+        self.lock().records.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.lock().records.is_empty()
+    }
+    /// Records dropped since the start because the buffer was full
+    pub fn dropped(&self) -> u64 {
+        self.lock().dropped
+    }
+    pub fn clear(&self) {
+        self.lock().records.clear();
+    }
+    /// Copies of the records, oldest first
+    pub fn records(&self) -> Vec<LogRecord> {
+        self.lock().records.iter().cloned().collect()
+    }
+    /// Runs `f` on the records while the buffer is locked, loggers block until it returns
+    pub fn with_records<R>(&self, f: impl FnOnce(&VecDeque<LogRecord>) -> R) -> R {
+        f(&self.lock().records)
+    }
+}
+
+/// `log` crate logger that keeps the records in a [`LogBuffer`] so they can be read in game,
+/// e.g. in fullscreen where stdout can't be seen.
+///
+/// Records only go to the buffer unless they're [echoed to stderr](Self::echo_to_stderr) or
+/// [forwarded](Self::forward_to) to the logger this one replaces.
+///
+/// ```no_run
+/// # use bugsyth_engine_imgui_support::logs::LogCapture;
+/// let buffer = LogCapture::new().echo_to_stderr(true).install().unwrap();
+/// log::info!("shows up in the log window");
+/// ```
+pub struct LogCapture {
+    buffer: LogBuffer,
+    max_level: log::LevelFilter,
+    echo: bool,
+    inner: Option<Box<dyn log::Log>>,
+}
+
+impl fmt::Debug for LogCapture {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LogCapture")
+            .field("buffer", &self.buffer)
+            .field("max_level", &self.max_level)
+            .field("echo", &self.echo)
+            .field("forwarding", &self.inner.is_some())
+            .finish_non_exhaustive()
+    }
+}
+
+impl Default for LogCapture {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LogCapture {
+    /// Captures everything up to `Debug` into a buffer of [`LOG_CAPACITY`] records
+    pub fn new() -> Self {
+        Self::with_capacity(LOG_CAPACITY)
+    }
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            buffer: LogBuffer::new(capacity),
+            max_level: log::LevelFilter::Debug,
+            echo: false,
+            inner: None,
+        }
+    }
+    pub fn max_level(mut self, max_level: log::LevelFilter) -> Self {
+        self.max_level = max_level;
+        self
+    }
+    /// Also prints the records to stderr, like the logger it replaces would have. Off by default.
+    pub fn echo_to_stderr(mut self, echo: bool) -> Self {
+        self.echo = echo;
+        self
+    }
+    /// Also passes the records to `inner`, e.g. the logger the game set up before, which keeps
+    /// its own filtering and formatting
+    pub fn forward_to<L: log::Log + 'static>(mut self, inner: L) -> Self {
+        self.inner = Some(Box::new(inner));
+        self
+    }
+    pub fn buffer(&self) -> &LogBuffer {
+        &self.buffer
+    }
+
+    /// Makes this the global logger and returns its buffer. Fails if a logger is already set.
+    pub fn install(self) -> Result<LogBuffer, log::SetLoggerError> {
+        let buffer = self.buffer.clone();
+        let max_level = self.max_level;
+        log::set_boxed_logger(Box::new(self))?;
+        log::set_max_level(max_level);
+        Ok(buffer)
+    }
+}
+
+impl log::Log for LogCapture {
+    fn enabled(&self, metadata: &log::Metadata) -> bool {
+        metadata.level() <= self.max_level
+    }
+    fn log(&self, record: &log::Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+        if let Some(inner) = &self.inner {
+            if inner.enabled(record.metadata()) {
+                inner.log(record);
+            }
+        }
+        let message = record.args().to_string();
+        if self.echo {
+            eprintln!("[{} {}] {}", record.level(), record.target(), message);
+        }
+        self.buffer.push(LogRecord {
+            level: record.level().into(),
+            target: record.target().to_string(),
+            message,
+        });
+    }
+    fn flush(&self) {
+        if let Some(inner) = &self.inner {
+            inner.flush();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use log::Log;
+
+    /// Counts the records it's given
+    struct Counter(Arc<Mutex<usize>>);
+
+    impl Log for Counter {
+        fn enabled(&self, metadata: &log::Metadata) -> bool {
+            metadata.level() <= log::Level::Warn
+        }
+        fn log(&self, _: &log::Record) {
+            *self.0.lock().unwrap() += 1;
+        }
+        fn flush(&self) {}
+    }
+
+    #[test]
+    fn records_are_buffered_and_forwarded() {
+        let count = Arc::new(Mutex::new(0));
+        // Not installed, `log` allows one global logger per process
+        let capture = LogCapture::with_capacity(2).forward_to(Counter(count.clone()));
+        for (level, message) in [
+            (log::Level::Warn, "first"),
+            (log::Level::Info, "second"),
+            (log::Level::Trace, "filtered"),
+            (log::Level::Error, "third"),
+        ] {
+            capture.log(
+                &log::Record::builder()
+                    .level(level)
+                    .target("test")
+                    .args(format_args!("{}", message))
+                    .build(),
+            );
+        }
+        let messages: Vec<_> = capture
+            .buffer()
+            .records()
+            .into_iter()
+            .map(|record| record.message)
+            .collect();
+        assert_eq!(messages, ["second", "third"]);
+        assert_eq!(capture.buffer().dropped(), 1);
+        assert_eq!(*count.lock().unwrap(), 2);
+    }
+}
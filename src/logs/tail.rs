@@ -0,0 +1,359 @@
+use super::{LogFilter, LogLevel, ModuleVerbosity, ParsedLine, VerbosityHandle};
+use imgui::{Condition, ListClipper, Ui};
+use std::collections::VecDeque;
+use std::fmt;
+use std::fs::{self, File};
+use std::io::{self, Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+
+/// Seconds between checks of the file for new lines
+const POLL_INTERVAL: f64 = 0.25;
+/// Height of the pane showing the fields of the selected line
+const DETAILS_HEIGHT: f32 = 120.0;
+
+#[derive(Clone, Debug)]
+struct TailLine {
+    /// Counts up over all lines read, unlike the index it stays the same as old lines are dropped
+    seq: usize,
+    text: String,
+    parsed: ParsedLine,
+}
+
+/// Panel following a log file, like `tail -f`.
+///
+/// Rotation is detected when the file shrinks or, on Unix, gets replaced by a new file. The
+/// panel then starts over with the new file. Lines with `key=value` fields can be selected to
+/// show the fields in a pane below the list.
+pub struct LogTail {
+    path: PathBuf,
+    filter: LogFilter,
+    lines: VecDeque<TailLine>,
+    max_lines: usize,
+    /// Indices into `lines` passing the filter
+    visible: Vec<usize>,
+    next_seq: usize,
+    /// Sequence number of the line whose fields are shown
+    selected: Option<usize>,
+    offset: u64,
+    /// Bytes after the last newline
+    partial: Vec<u8>,
+    #[cfg(unix)]
+    inode: Option<u64>,
+    last_poll: Option<f64>,
+    follow: bool,
+    error: Option<String>,
+    verbosity: Option<(ModuleVerbosity, Box<dyn VerbosityHandle>)>,
+}
+
+impl fmt::Debug for LogTail {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LogTail")
+            .field("path", &self.path)
+            .field("lines", &self.lines.len())
+            .field("offset", &self.offset)
+            .finish_non_exhaustive()
+    }
+}
+
+impl LogTail {
+    /// Reads the whole file first, keeping the last 10000 lines
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            filter: LogFilter::new(),
+            lines: VecDeque::new(),
+            max_lines: 10_000,
+            visible: Vec::new(),
+            next_seq: 0,
+            selected: None,
+            offset: 0,
+            partial: Vec::new(),
+            #[cfg(unix)]
+            inode: None,
+            last_poll: None,
+            follow: true,
+            error: None,
+            verbosity: None,
+        }
+    }
+    /// Shows per-module level dropdowns applied through `handle`, for a log written by this
+    /// process. Modules are added as they show up in the log.
+    pub fn with_verbosity<H: VerbosityHandle + 'static>(
+        mut self,
+        default: LogLevel,
+        handle: H,
+    ) -> Self {
+        self.verbosity = Some((ModuleVerbosity::new(default), Box::new(handle)));
+        self
+    }
+    /// Limits how many lines are kept, oldest lines are dropped first
+    pub fn with_max_lines(mut self, max_lines: usize) -> Self {
+        self.max_lines = max_lines.max(1);
+        self
+    }
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+    pub fn filter_mut(&mut self) -> &mut LogFilter {
+        &mut self.filter
+    }
+    /// Starts over with the beginning of the file
+    pub fn reload(&mut self) {
+        self.lines.clear();
+        self.visible.clear();
+        self.partial.clear();
+        self.offset = 0;
+    }
+
+    /// Reads lines appended since the last poll, done by [`draw`](Self::draw) a few times per
+    /// second
+    pub fn poll(&mut self) -> io::Result<()> {
+        let metadata = fs::metadata(&self.path)?;
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::MetadataExt;
+            if self
+                .inode
+                .replace(metadata.ino())
+                .is_some_and(|inode| inode != metadata.ino())
+            {
+                self.reload();
+            }
+        }
+        if metadata.len() < self.offset {
+            self.reload();
+        }
+        if metadata.len() == self.offset {
+            return Ok(());
+        }
+        let mut file = File::open(&self.path)?;
+        file.seek(SeekFrom::Start(self.offset))?;
+        let mut data = Vec::new();
+        file.read_to_end(&mut data)?;
+        self.offset += data.len() as u64;
+
+        self.partial.extend_from_slice(&data);
+        let Some(end) = self.partial.iter().rposition(|&b| b == b'\n') else {
+            return Ok(());
+        };
+        let rest = self.partial.split_off(end + 1);
+        let complete = std::mem::replace(&mut self.partial, rest);
+        for line in String::from_utf8_lossy(&complete).lines() {
+            self.push_line(line.to_string());
+        }
+        Ok(())
+    }
+
+    fn push_line(&mut self, text: String) {
+        let parsed = ParsedLine::parse(&text);
+        if let (Some((verbosity, _)), Some(module)) = (&mut self.verbosity, &parsed.module) {
+            verbosity.add_module(module);
+        }
+        let line = TailLine {
+            seq: self.next_seq,
+            text,
+            parsed,
+        };
+        self.next_seq += 1;
+        if self.lines.len() == self.max_lines {
+            self.lines.pop_front();
+            self.visible.retain_mut(|index| match index.checked_sub(1) {
+                Some(new) => {
+                    *index = new;
+                    true
+                }
+                None => false,
+            });
+        }
+        if self.filter.matches(line.parsed.level, &line.text) {
+            self.visible.push(self.lines.len());
+        }
+        self.lines.push_back(line);
+    }
+
+    /// The line with sequence number `seq`, if it wasn't dropped yet
+    fn line(&self, seq: usize) -> Option<&TailLine> {
+        let first = self.lines.front()?.seq;
+        self.lines.get(seq.checked_sub(first)?)
+    }
+
+    fn refilter(&mut self) {
+        self.visible = self
+            .lines
+            .iter()
+            .enumerate()
+            .filter(|(_, line)| self.filter.matches(line.parsed.level, &line.text))
+            .map(|(i, _)| i)
+            .collect();
+    }
+
+    pub fn draw(&mut self, ui: &Ui, opened: &mut bool) {
+        let now = ui.time();
+        if self
+            .last_poll
+            .is_none_or(|last| now - last >= POLL_INTERVAL || now < last)
+        {
+            self.last_poll = Some(now);
+            self.error = self.poll().err().map(|e| e.to_string());
+        }
+        let title = format!(
+            "Log: {}###log_tail_{}",
+            self.path.file_name().map_or_else(
+                || self.path.to_string_lossy(),
+                |name| name.to_string_lossy()
+            ),
+            self.path.display()
+        );
+        ui.window(title)
+            .opened(opened)
+            .size([600.0, 400.0], Condition::FirstUseEver)
+            .build(|| {
+                if self.filter.draw(ui) {
+                    self.refilter();
+                }
+                ui.checkbox("Follow", &mut self.follow);
+                ui.same_line();
+                if ui.button("Reload") {
+                    self.reload();
+                    self.last_poll = None;
+                }
+                ui.same_line();
+                ui.text_disabled(format!("{}/{} lines", self.visible.len(), self.lines.len()));
+                if let Some(error) = &self.error {
+                    ui.text_colored(LogLevel::Error.color(), error);
+                }
+                if let Some((verbosity, handle)) = &mut self.verbosity {
+                    if ui.collapsing_header("Verbosity", imgui::TreeNodeFlags::empty()) {
+                        verbosity.draw(ui, handle.as_mut());
+                    }
+                }
+                ui.separator();
+                let selected = self.selected.and_then(|seq| self.line(seq)).cloned();
+                let list_height = if selected.is_some() {
+                    -DETAILS_HEIGHT
+                } else {
+                    0.0
+                };
+                ui.child_window("##log_tail_lines")
+                    .size([0.0, list_height])
+                    .horizontal_scrollbar(true)
+                    .build(|| {
+                        // Every row is one line high, the clipper measures the first one
+                        let clipper = ListClipper::new(self.visible.len() as i32).begin(ui);
+                        for row in clipper.iter() {
+                            let line = &self.lines[self.visible[row as usize]];
+                            if draw_line(ui, line, self.selected == Some(line.seq)) {
+                                self.selected =
+                                    (self.selected != Some(line.seq)).then_some(line.seq);
+                            }
+                        }
+                        if self.follow && ui.scroll_y() >= ui.scroll_max_y() {
+                            ui.set_scroll_here_y_with_ratio(1.0);
+                        }
+                    });
+                // Clicks in the list apply on the next frame, when the list makes room for the pane
+                match selected {
+                    Some(line) => {
+                        ui.child_window("##log_tail_details")
+                            .border(true)
+                            .build(|| draw_fields(ui, &line));
+                    }
+                    // Dropped lines can't be shown anymore
+                    None if self.selected.is_some_and(|seq| self.line(seq).is_none()) => {
+                        self.selected = None;
+                    }
+                    None => (),
+                }
+            });
+    }
+}
+
+/// Plain text, or a selectable row when the line has fields. Returns whether it was clicked.
+fn draw_line(ui: &Ui, line: &TailLine, selected: bool) -> bool {
+    let color = line.parsed.level.map(LogLevel::color);
+    let _color = color.map(|color| ui.push_style_color(imgui::StyleColor::Text, color));
+    if line.parsed.fields.is_empty() {
+        ui.text(&line.text);
+        return false;
+    }
+    let _id = ui.push_id_usize(line.seq);
+    let label = format!(
+        "{}{} ({} fields)",
+        line.parsed
+            .module
+            .as_ref()
+            .map_or_else(String::new, |module| format!("{}: ", module)),
+        line.parsed.message,
+        line.parsed.fields.len()
+    );
+    ui.selectable_config(&label).selected(selected).build()
+}
+
+/// The selected line and its fields
+fn draw_fields(ui: &Ui, line: &TailLine) {
+    ui.text_wrapped(&line.text);
+    ui.separator();
+    for (key, value) in &line.parsed.fields {
+        ui.text_disabled(key);
+        ui.same_line();
+        ui.text(value);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::Harness;
+    use std::io::Write;
+
+    #[test]
+    fn selection_follows_the_line_until_dropped() {
+        let dir = std::env::temp_dir().join(format!("imgui_support_tail_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("game.log");
+        let write = |lines: &[&str]| {
+            let mut file = fs::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&path)
+                .unwrap();
+            for line in lines {
+                writeln!(file, "{}", line).unwrap();
+            }
+        };
+        write(&["INFO app: start n=0", "INFO app: load n=1"]);
+        let mut tail = LogTail::new(&path).with_max_lines(2);
+        let mut harness = Harness::new();
+        let mut opened = true;
+        // The lines and the details pane are the only child regions
+        let details_shown = |harness: &Harness| {
+            harness
+                .drawn_windows()
+                .iter()
+                .filter(|window| window.is_child())
+                .count()
+                == 2
+        };
+        harness.run(2, |ui| tail.draw(ui, &mut opened));
+        assert!(!details_shown(&harness));
+
+        tail.selected = Some(1);
+        harness.run(2, |ui| tail.draw(ui, &mut opened));
+        assert!(details_shown(&harness));
+
+        // Shifts the kept lines, the selected one stays selected until it's dropped
+        write(&["INFO app: play n=2"]);
+        tail.poll().unwrap();
+        assert_eq!(tail.line(1).unwrap().text, "INFO app: load n=1");
+        harness.run(2, |ui| tail.draw(ui, &mut opened));
+        assert_eq!(tail.selected, Some(1));
+        write(&["INFO app: quit n=3"]);
+        tail.poll().unwrap();
+        assert!(tail.line(1).is_none());
+        assert_eq!(tail.line(3).unwrap().text, "INFO app: quit n=3");
+        harness.run(2, |ui| tail.draw(ui, &mut opened));
+        assert_eq!(tail.selected, None);
+        assert!(!details_shown(&harness));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,100 @@
+use super::LogLevel;
+use imgui::Ui;
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+
+/// Applies filter directives to the logger, e.g. by calling `reload::Handle::modify` with a
+/// new `EnvFilter` when using tracing-subscriber.
+///
+/// Implemented for closures taking the directives.
+pub trait VerbosityHandle {
+    /// `directives` are in `EnvFilter` syntax, e.g. `info,engine::render=debug`
+    fn set_directives(&mut self, directives: &str) -> Result<(), String>;
+}
+
+impl<F> VerbosityHandle for F
+where
+    F: FnMut(&str) -> Result<(), String>,
+{
+    fn set_directives(&mut self, directives: &str) -> Result<(), String> {
+        self(directives)
+    }
+}
+
+/// Per-module level dropdowns, for raising the verbosity of one subsystem at runtime
+#[derive(Clone, Debug)]
+pub struct ModuleVerbosity {
+    default: LogLevel,
+    /// `None` uses the default level
+    modules: BTreeMap<String, Option<LogLevel>>,
+    error: Option<String>,
+}
+
+impl ModuleVerbosity {
+    pub fn new(default: LogLevel) -> Self {
+        Self {
+            default,
+            modules: BTreeMap::new(),
+            error: None,
+        }
+    }
+    /// Adds a module to the list, e.g. when it shows up in the log
+    pub fn add_module(&mut self, module: &str) {
+        if !self.modules.contains_key(module) {
+            self.modules.insert(module.to_string(), None);
+        }
+    }
+    pub fn set_level(&mut self, module: &str, level: Option<LogLevel>) {
+        self.modules.insert(module.to_string(), level);
+    }
+    /// The current settings in `EnvFilter` syntax
+    pub fn directives(&self) -> String {
+        let mut directives = self.default.directive().to_string();
+        for (module, level) in &self.modules {
+            if let Some(level) = level {
+                directives.push(',');
+                directives.push_str(module);
+                directives.push('=');
+                directives.push_str(level.directive());
+            }
+        }
+        directives
+    }
+
+    /// Draws the dropdowns, changes are applied through `handle` right away
+    pub fn draw(&mut self, ui: &Ui, handle: &mut dyn VerbosityHandle) {
+        let mut changed = false;
+        let width = ui.calc_text_size("Default")[0] * 2.5;
+        let mut default = LogLevel::ALL
+            .iter()
+            .position(|&level| level == self.default)
+            .unwrap_or(0);
+        ui.set_next_item_width(width);
+        if ui.combo("Default", &mut default, &LogLevel::ALL, |level| {
+            Cow::Borrowed(level.name())
+        }) {
+            self.default = LogLevel::ALL[default];
+            changed = true;
+        }
+        // Index 0 is "Default", the levels follow
+        let choices: Vec<Option<LogLevel>> = std::iter::once(None)
+            .chain(LogLevel::ALL.map(Some))
+            .collect();
+        for (module, level) in &mut self.modules {
+            let mut index = choices.iter().position(|c| c == level).unwrap_or(0);
+            ui.set_next_item_width(width);
+            if ui.combo(module.as_str(), &mut index, &choices, |level| {
+                Cow::Borrowed(level.map_or("Default", LogLevel::name))
+            }) {
+                *level = choices[index];
+                changed = true;
+            }
+        }
+        if changed {
+            self.error = handle.set_directives(&self.directives()).err();
+        }
+        if let Some(error) = &self.error {
+            ui.text_colored(LogLevel::Error.color(), error);
+        }
+    }
+}
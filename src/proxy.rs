@@ -0,0 +1,328 @@
+//! Feeding the UI from worker threads.
+//!
+//! [`UiProxy`] is a cheap `Send + Sync` handle that queues log lines, toasts and task progress
+//! for the main thread, so workers never touch the imgui context. Get one from
+//! [`ImGui::proxy`](crate::ImGui::proxy) and clone it into every thread; the [`UiInbox`] behind
+//! it is drained at the start of each frame, toasts and progress bars are then drawn over the
+//! UI and the log lines kept for the log window, opened with [`UiInbox::set_logs_open`].
+
+use crate::accessibility;
+use crate::logs::{LogFilter, LogLevel};
+use imgui::{Condition, ListClipper, Ui};
+use std::collections::VecDeque;
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::time::{Duration, Instant};
+
+/// How long a toast stays up
+pub const TOAST_DURATION: Duration = Duration::from_secs(4);
+/// Log lines kept by default, see [`UiInbox::set_max_logs`]
+pub const MAX_LOGS: usize = 1000;
+
+const FADE: Duration = Duration::from_millis(500);
+const MARGIN: f32 = 8.0;
+const PADDING: f32 = 6.0;
+const PROGRESS_WIDTH: f32 = 220.0;
+
+/// Sent from a [`UiProxy`] to the [`UiInbox`]
+#[derive(Clone, Debug, PartialEq)]
+pub enum UiMessage {
+    Log {
+        level: LogLevel,
+        text: String,
+    },
+    Toast {
+        level: LogLevel,
+        text: String,
+    },
+    /// Starts or updates the task with this ID
+    Progress {
+        id: String,
+        label: String,
+        /// 0.0 to 1.0
+        fraction: f32,
+    },
+    /// Removes the task's progress bar
+    Finished {
+        id: String,
+    },
+}
+
+/// Handle for sending log lines, toasts and progress from any thread
+#[derive(Clone, Debug)]
+pub struct UiProxy {
+    sender: Sender<UiMessage>,
+}
+
+impl UiProxy {
+    /// Queues a message, returns `false` when the UI is gone
+    pub fn send(&self, message: UiMessage) -> bool {
+        self.sender.send(message).is_ok()
+    }
+    pub fn log(&self, level: LogLevel, text: impl Into<String>) -> bool {
+        self.send(UiMessage::Log {
+            level,
+            text: text.into(),
+        })
+    }
+    /// Short notification in the bottom right corner, shown for [`TOAST_DURATION`]
+    pub fn toast(&self, level: LogLevel, text: impl Into<String>) -> bool {
+        self.send(UiMessage::Toast {
+            level,
+            text: text.into(),
+        })
+    }
+    /// Shows a progress bar until [`finish`](Self::finish) is called with the same ID
+    pub fn progress(&self, id: impl Into<String>, label: impl Into<String>, fraction: f32) -> bool {
+        self.send(UiMessage::Progress {
+            id: id.into(),
+            label: label.into(),
+            fraction: fraction.clamp(0.0, 1.0),
+        })
+    }
+    pub fn finish(&self, id: impl Into<String>) -> bool {
+        self.send(UiMessage::Finished { id: id.into() })
+    }
+}
+
+#[derive(Clone, Debug)]
+struct Toast {
+    level: LogLevel,
+    text: String,
+    shown: Instant,
+}
+
+#[derive(Clone, Debug)]
+struct Task {
+    id: String,
+    label: String,
+    fraction: f32,
+}
+
+/// Main thread end of the [`UiProxy`] handles
+#[derive(Debug)]
+pub struct UiInbox {
+    sender: Sender<UiMessage>,
+    receiver: Receiver<UiMessage>,
+    logs: VecDeque<(LogLevel, String)>,
+    max_logs: usize,
+    log_filter: LogFilter,
+    toasts: Vec<Toast>,
+    tasks: Vec<Task>,
+    logs_open: bool,
+}
+
+impl Default for UiInbox {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl UiInbox {
+    pub fn new() -> Self {
+        let (sender, receiver) = mpsc::channel();
+        Self {
+            sender,
+            receiver,
+            logs: VecDeque::new(),
+            max_logs: MAX_LOGS,
+            log_filter: LogFilter::new(),
+            toasts: Vec::new(),
+            tasks: Vec::new(),
+            logs_open: false,
+        }
+    }
+    pub fn proxy(&self) -> UiProxy {
+        UiProxy {
+            sender: self.sender.clone(),
+        }
+    }
+    /// Drops the oldest log lines beyond `max_logs`
+    pub fn set_max_logs(&mut self, max_logs: usize) {
+        self.max_logs = max_logs;
+        self.trim_logs();
+    }
+    pub fn logs(&self) -> impl Iterator<Item = (LogLevel, &str)> {
+        self.logs
+            .iter()
+            .map(|(level, text)| (*level, text.as_str()))
+    }
+    pub fn clear_logs(&mut self) {
+        self.logs.clear();
+    }
+    /// Shows or hides the "Logs" window drawn with the toasts, it has a close button too
+    pub fn set_logs_open(&mut self, open: bool) {
+        self.logs_open = open;
+    }
+    pub fn logs_open(&self) -> bool {
+        self.logs_open
+    }
+    fn trim_logs(&mut self) {
+        let excess = self.logs.len().saturating_sub(self.max_logs);
+        self.logs.drain(..excess);
+    }
+
+    /// Handles the queued messages, done by [`ImGui`](crate::ImGui) when a frame starts
+    pub fn poll(&mut self) {
+        for message in self.receiver.try_iter() {
+            match message {
+                UiMessage::Log { level, text } => self.logs.push_back((level, text)),
+                UiMessage::Toast { level, text } => self.toasts.push(Toast {
+                    level,
+                    text,
+                    shown: Instant::now(),
+                }),
+                UiMessage::Progress {
+                    id,
+                    label,
+                    fraction,
+                } => match self.tasks.iter_mut().find(|task| task.id == id) {
+                    Some(task) => {
+                        task.label = label;
+                        task.fraction = fraction;
+                    }
+                    None => self.tasks.push(Task {
+                        id,
+                        label,
+                        fraction,
+                    }),
+                },
+                UiMessage::Finished { id } => self.tasks.retain(|task| task.id != id),
+            }
+        }
+        self.trim_logs();
+        self.toasts
+            .retain(|toast| toast.shown.elapsed() < TOAST_DURATION);
+    }
+
+    /// Draws the progress bars and toasts stacked up from the bottom right corner on the
+    /// foreground draw list and the log window while it's open, done by
+    /// [`ImGui`](crate::ImGui) every frame
+    pub fn draw(&mut self, ui: &Ui) {
+        if self.logs_open {
+            let mut open = true;
+            ui.window("Logs")
+                .size([500.0, 300.0], Condition::FirstUseEver)
+                .opened(&mut open)
+                .build(|| self.draw_logs(ui));
+            self.logs_open = open;
+        }
+        let display_size = ui.io().display_size;
+        let draw_list = ui.get_foreground_draw_list();
+        let line_height = ui.text_line_height();
+        let mut bottom = display_size[1] - MARGIN;
+        for task in self.tasks.iter().rev() {
+            let text = format!("{} {:.0}%", task.label, task.fraction * 100.0);
+            let width = ui.calc_text_size(&text)[0].max(PROGRESS_WIDTH) + PADDING * 2.0;
+            let min = [
+                display_size[0] - MARGIN - width,
+                bottom - line_height * 2.0 - PADDING * 3.0,
+            ];
+            let max = [display_size[0] - MARGIN, bottom];
+            draw_list
+                .add_rect(min, max, ui.style_color(imgui::StyleColor::PopupBg))
+                .filled(true)
+                .build();
+            draw_list.add_text(
+                [min[0] + PADDING, min[1] + PADDING],
+                ui.style_color(imgui::StyleColor::Text),
+                &text,
+            );
+            let bar_min = [min[0] + PADDING, max[1] - PADDING - line_height];
+            let bar_max = [max[0] - PADDING, max[1] - PADDING];
+            draw_list
+                .add_rect(bar_min, bar_max, ui.style_color(imgui::StyleColor::FrameBg))
+                .filled(true)
+                .build();
+            draw_list
+                .add_rect(
+                    bar_min,
+                    [
+                        bar_min[0] + (bar_max[0] - bar_min[0]) * task.fraction,
+                        bar_max[1],
+                    ],
+                    ui.style_color(imgui::StyleColor::PlotHistogram),
+                )
+                .filled(true)
+                .build();
+            bottom = min[1] - MARGIN;
+        }
+        for toast in self.toasts.iter().rev() {
+            let left = TOAST_DURATION.saturating_sub(toast.shown.elapsed());
+            let alpha = accessibility::fade_out(left, FADE);
+            let size = ui.calc_text_size(&toast.text);
+            let min = [
+                display_size[0] - MARGIN - size[0] - PADDING * 2.0,
+                bottom - size[1] - PADDING * 2.0,
+            ];
+            let max = [display_size[0] - MARGIN, bottom];
+            let mut background = ui.style_color(imgui::StyleColor::PopupBg);
+            background[3] *= alpha;
+            let mut accent = toast.level.color();
+            accent[3] *= alpha;
+            let mut color = ui.style_color(imgui::StyleColor::Text);
+            color[3] *= alpha;
+            draw_list
+                .add_rect(min, max, background)
+                .filled(true)
+                .build();
+            draw_list
+                .add_rect(min, [min[0] + 3.0, max[1]], accent)
+                .filled(true)
+                .build();
+            draw_list.add_text([min[0] + PADDING, min[1] + PADDING], color, &toast.text);
+            bottom = min[1] - MARGIN;
+        }
+    }
+
+    /// The received log lines with a level and search filter, inside the current window. Used by
+    /// the log window, or for showing them in a window of your own when using the inbox without
+    /// [`ImGui`](crate::ImGui).
+    pub fn draw_logs(&mut self, ui: &Ui) {
+        self.log_filter.draw(ui);
+        if ui.button("Clear") {
+            self.clear_logs();
+        }
+        ui.separator();
+        let visible: Vec<usize> = self
+            .logs
+            .iter()
+            .enumerate()
+            .filter(|(_, (level, text))| self.log_filter.matches(Some(*level), text))
+            .map(|(i, _)| i)
+            .collect();
+        ui.child_window("##inbox_logs")
+            .horizontal_scrollbar(true)
+            .build(|| {
+                let clipper = ListClipper::new(visible.len() as i32).begin(ui);
+                for row in clipper.iter() {
+                    let (level, text) = &self.logs[visible[row as usize]];
+                    ui.text_colored(level.color(), text);
+                }
+                if ui.scroll_y() >= ui.scroll_max_y() {
+                    ui.set_scroll_here_y_with_ratio(1.0);
+                }
+            });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::Harness;
+
+    #[test]
+    fn log_window_shows_received_lines() {
+        let mut inbox = UiInbox::new();
+        inbox.proxy().log(LogLevel::Warn, "ID conflict");
+        inbox.poll();
+        assert_eq!(inbox.logs().count(), 1);
+
+        let mut harness = Harness::new();
+        let closed = harness.frame(|ui| inbox.draw(ui));
+        assert_eq!(closed.vertices, 0);
+        inbox.set_logs_open(true);
+        let open = harness.run(2, |ui| inbox.draw(ui));
+        assert!(open.vertices > 0);
+        assert!(inbox.logs_open());
+    }
+}
@@ -0,0 +1,51 @@
+//! Crash detection for starting in safe mode.
+//!
+//! A [`CrashSentinel`] is a file that exists while the tool runs and is removed on a clean exit.
+//! Finding it at startup means the last run didn't exit cleanly, possibly because of a broken
+//! settings file, so the app can offer [`init_safe_mode`](crate::init_safe_mode), which skips all
+//! saved settings and loads only the default font.
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Marker file present while the tool is running
+#[derive(Debug)]
+pub struct CrashSentinel {
+    path: PathBuf,
+    previous_run_crashed: bool,
+}
+
+impl CrashSentinel {
+    /// Checks for a sentinel left by the last run and creates the one for this run
+    pub fn create(path: impl AsRef<Path>) -> io::Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let previous_run_crashed = path.exists();
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(&path, std::process::id().to_string())?;
+        Ok(Self {
+            path,
+            previous_run_crashed,
+        })
+    }
+    /// The sentinel already existed, i.e. the last run didn't call
+    /// [`clean_exit`](Self::clean_exit)
+    pub fn previous_run_crashed(&self) -> bool {
+        self.previous_run_crashed
+    }
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+    /// Removes the sentinel, call when the tool shuts down normally.
+    ///
+    /// Dropping the sentinel leaves the file in place on purpose, a panic unwinding past it still
+    /// counts as a crash.
+    pub fn clean_exit(self) -> io::Result<()> {
+        match fs::remove_file(&self.path) {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e),
+            _ => Ok(()),
+        }
+    }
+}
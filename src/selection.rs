@@ -0,0 +1,119 @@
+//! RTS-style drag selection over the game viewport.
+
+use crate::coords::{Coords, Ray};
+use bugsyth_engine::context::camera::CameraState;
+use bugsyth_engine::glium::winit::dpi::PhysicalPosition;
+use bugsyth_engine::math::Vec3;
+use imgui::{Key, MouseButton, Ui};
+
+/// Finished drag selection
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Selection {
+    /// Rect in UI coordinates, min is the top left corner
+    pub min: [f32; 2],
+    pub max: [f32; 2],
+    /// Rect in physical window pixels
+    pub window_min: PhysicalPosition<f64>,
+    pub window_max: PhysicalPosition<f64>,
+    /// Camera rays through the corners, top left, top right, bottom right, bottom left. Together
+    /// they bound the selected part of the view frustum.
+    pub rays: [Ray; 4],
+    /// Where the rays hit the ground plane, `None` if any corner misses it, e.g. above the
+    /// horizon
+    pub ground: Option<[Vec3<f32>; 4]>,
+}
+
+impl Selection {
+    /// Whether a world position is inside the selection as seen by the camera
+    pub fn contains(&self, coords: &Coords, camera: &CameraState, pos: Vec3<f32>) -> bool {
+        coords.world_to_ui(camera, pos).is_some_and(|[x, y]| {
+            x >= self.min[0] && x <= self.max[0] && y >= self.min[1] && y <= self.max[1]
+        })
+    }
+}
+
+/// Drag selection rectangle over the area not covered by the UI.
+///
+/// Drags only start where imgui doesn't want the mouse, the rectangle is drawn on the background
+/// draw list so windows stay on top of it. Right click or Escape cancels a drag.
+#[derive(Debug)]
+pub struct DragSelect {
+    start: Option<[f32; 2]>,
+    /// Drags shorter than this in UI pixels count as clicks and select nothing
+    pub min_drag: f32,
+    pub border_color: [f32; 4],
+    pub fill_color: [f32; 4],
+    /// A point on the ground plane and its normal, y = 0 by default
+    pub ground_plane: (Vec3<f32>, Vec3<f32>),
+}
+
+impl Default for DragSelect {
+    fn default() -> Self {
+        Self {
+            start: None,
+            min_drag: 4.0,
+            border_color: [0.3, 0.9, 0.3, 1.0],
+            fill_color: [0.3, 0.9, 0.3, 0.15],
+            ground_plane: (Vec3::zero(), Vec3::unit_y()),
+        }
+    }
+}
+
+impl DragSelect {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    pub fn is_dragging(&self) -> bool {
+        self.start.is_some()
+    }
+    pub fn cancel(&mut self) {
+        self.start = None;
+    }
+
+    /// Tracks the drag and draws the rectangle, call once per frame. Returns the selection on
+    /// the frame the mouse is released.
+    pub fn update(&mut self, ui: &Ui, coords: &Coords, camera: &CameraState) -> Option<Selection> {
+        let mouse = ui.io().mouse_pos;
+        let Some(start) = self.start else {
+            if ui.is_mouse_clicked(MouseButton::Left) && !ui.io().want_capture_mouse {
+                self.start = Some(mouse);
+            }
+            return None;
+        };
+        if ui.is_key_pressed(Key::Escape) || ui.is_mouse_clicked(MouseButton::Right) {
+            self.cancel();
+            return None;
+        }
+        let min = [start[0].min(mouse[0]), start[1].min(mouse[1])];
+        let max = [start[0].max(mouse[0]), start[1].max(mouse[1])];
+        if ui.is_mouse_down(MouseButton::Left) {
+            let draw_list = ui.get_background_draw_list();
+            draw_list
+                .add_rect(min, max, self.fill_color)
+                .filled(true)
+                .build();
+            draw_list.add_rect(min, max, self.border_color).build();
+            return None;
+        }
+        self.start = None;
+        if max[0] - min[0] < self.min_drag && max[1] - min[1] < self.min_drag {
+            return None;
+        }
+        let rays = [min, [max[0], min[1]], max, [min[0], max[1]]]
+            .map(|corner| coords.screen_ray(camera, corner));
+        let (point, normal) = self.ground_plane;
+        let hits = rays.map(|ray| ray.intersect_plane(point, normal));
+        let ground = match hits {
+            [Some(a), Some(b), Some(c), Some(d)] => Some([a, b, c, d]),
+            _ => None,
+        };
+        Some(Selection {
+            min,
+            max,
+            window_min: coords.ui_to_physical(min),
+            window_max: coords.ui_to_physical(max),
+            rays,
+            ground,
+        })
+    }
+}
@@ -0,0 +1,422 @@
+//! Versioned settings files.
+//!
+//! [`SettingsStore`] keeps named entries (the window layout, themes, app data) as text files in a
+//! directory. Every file starts with a header holding [`SETTINGS_VERSION`], files written by an
+//! older version of the crate go through the store's [`Migrator`] before they're handed out, so a
+//! crate upgrade doesn't reset the user's layout.
+//!
+//! A store can be scoped to a project with [`SettingsStore::set_scope`], entries are then kept
+//! per project under `<dir>/projects/<scope>/`. Per-user entries that don't belong to a project,
+//! like accessibility settings, go through [`SettingsStore::load_unscoped`] and
+//! [`SettingsStore::save_unscoped`]. The [`Session`] holds state restored on the next launch,
+//! like open documents, and an [`Mru`] the recently opened files or projects.
+
+mod recent;
+mod session;
+
+pub use recent::{Mru, MruEntry};
+pub use session::{Session, SessionState, SESSION, USER_SESSION};
+
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Version of the settings files written by this crate
+pub const SETTINGS_VERSION: u32 = 1;
+
+const HEADER: &str = "; bugsyth_engine_imgui_support settings v";
+
+/// Name of the entry holding imgui's window layout
+pub const LAYOUT: &str = "layout";
+
+#[derive(Debug)]
+pub enum SettingsError {
+    Io(io::Error),
+    /// Written by a newer version of the crate, the file is left untouched
+    TooNew {
+        name: String,
+        version: u32,
+    },
+    /// Written by an older version and no migrator is set
+    NoMigrator {
+        name: String,
+        version: u32,
+    },
+    /// The migrator failed to upgrade the file
+    Migration {
+        name: String,
+        version: u32,
+        message: String,
+    },
+}
+
+impl Error for SettingsError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            SettingsError::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for SettingsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use self::SettingsError::*;
+        match self {
+            Io(e) => write!(f, "Settings IO failed: {}", e),
+            TooNew { name, version } => write!(
+                f,
+                "Settings \"{}\" are version {}, newer than the supported version {}",
+                name, version, SETTINGS_VERSION
+            ),
+            NoMigrator { name, version } => write!(
+                f,
+                "Settings \"{}\" are version {} and no migrator is set to upgrade them",
+                name, version
+            ),
+            Migration {
+                name,
+                version,
+                message,
+            } => write!(
+                f,
+                "Migrating settings \"{}\" from version {} failed: {}",
+                name, version, message
+            ),
+        }
+    }
+}
+
+impl From<io::Error> for SettingsError {
+    fn from(e: io::Error) -> SettingsError {
+        SettingsError::Io(e)
+    }
+}
+
+/// Upgrades settings written by older versions of the crate.
+///
+/// Implemented for closures with the same signature as [`migrate`](Self::migrate).
+pub trait Migrator {
+    /// Converts the contents of entry `name` from `version` to `version + 1`. It's called
+    /// repeatedly until the contents reach [`SETTINGS_VERSION`]. Files without a header are
+    /// version 0.
+    fn migrate(&self, name: &str, version: u32, contents: String) -> Result<String, String>;
+}
+
+impl<F> Migrator for F
+where
+    F: Fn(&str, u32, String) -> Result<String, String>,
+{
+    fn migrate(&self, name: &str, version: u32, contents: String) -> Result<String, String> {
+        self(name, version, contents)
+    }
+}
+
+/// Directory of settings entries
+pub struct SettingsStore {
+    dir: PathBuf,
+    scope: Option<String>,
+    migrator: Option<Box<dyn Migrator>>,
+}
+
+impl fmt::Debug for SettingsStore {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SettingsStore")
+            .field("dir", &self.dir)
+            .field("scope", &self.scope)
+            .field("migrator", &self.migrator.is_some())
+            .finish()
+    }
+}
+
+impl SettingsStore {
+    /// Entries are stored as `<dir>/<name>.ini`, the directory is created on the first save
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self {
+            dir: dir.into(),
+            scope: None,
+            migrator: None,
+        }
+    }
+    /// Store in the per-user config directory of a game, e.g. `~/.config/<game_name>` on Linux.
+    /// `None` when the platform has no config directory.
+    pub fn for_game(game_name: &str) -> Option<Self> {
+        Some(Self::new(config_dir()?.join(sanitize(game_name))))
+    }
+    pub fn set_migrator<M: Migrator + 'static>(&mut self, migrator: M) {
+        self.migrator = Some(Box::new(migrator));
+    }
+    /// Base directory, entries of a scope are in a subdirectory
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+    pub fn scope(&self) -> Option<&str> {
+        self.scope.as_deref()
+    }
+    /// Namespaces the entries by project, `None` goes back to the unscoped entries.
+    ///
+    /// Characters other than ASCII letters, digits, `-` and `_` are replaced in the directory
+    /// name, use IDs that stay distinct after that.
+    pub fn set_scope(&mut self, scope: Option<&str>) {
+        self.scope = scope.map(str::to_string);
+    }
+    /// Directory the entries of the current scope are in
+    pub fn scope_dir(&self) -> PathBuf {
+        match &self.scope {
+            Some(scope) => self.dir.join("projects").join(sanitize(scope)),
+            None => self.dir.clone(),
+        }
+    }
+    pub fn path(&self, name: &str) -> PathBuf {
+        self.scope_dir().join(format!("{}.ini", name))
+    }
+    /// Path of an entry outside of any scope, see [`load_unscoped`](Self::load_unscoped)
+    pub fn unscoped_path(&self, name: &str) -> PathBuf {
+        self.dir.join(format!("{}.ini", name))
+    }
+
+    /// Reads an entry, migrating it to the current version. `None` if it was never saved.
+    pub fn load(&self, name: &str) -> Result<Option<String>, SettingsError> {
+        self.load_file(name, &self.path(name))
+    }
+    /// Same as [`load`](Self::load) for an entry shared by all scopes, e.g. per-user settings
+    /// that shouldn't change with the project
+    pub fn load_unscoped(&self, name: &str) -> Result<Option<String>, SettingsError> {
+        self.load_file(name, &self.unscoped_path(name))
+    }
+    fn load_file(&self, name: &str, path: &Path) -> Result<Option<String>, SettingsError> {
+        let file = match fs::read_to_string(path) {
+            Ok(file) => file,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e.into()),
+        };
+        let (mut version, mut contents) = parse_header(&file);
+        if version > SETTINGS_VERSION {
+            return Err(SettingsError::TooNew {
+                name: name.to_string(),
+                version,
+            });
+        }
+        while version < SETTINGS_VERSION {
+            let Some(migrator) = &self.migrator else {
+                return Err(SettingsError::NoMigrator {
+                    name: name.to_string(),
+                    version,
+                });
+            };
+            contents = migrator
+                .migrate(name, version, contents)
+                .map_err(|message| SettingsError::Migration {
+                    name: name.to_string(),
+                    version,
+                    message,
+                })?;
+            version += 1;
+        }
+        Ok(Some(contents))
+    }
+    /// Writes an entry with the current version header.
+    ///
+    /// The file is written next to the entry and renamed over it, so a process killed while
+    /// saving leaves the previous version intact.
+    pub fn save(&self, name: &str, contents: &str) -> Result<(), SettingsError> {
+        save_file(&self.path(name), contents)
+    }
+    /// Same as [`save`](Self::save) for an entry shared by all scopes, see
+    /// [`load_unscoped`](Self::load_unscoped)
+    pub fn save_unscoped(&self, name: &str, contents: &str) -> Result<(), SettingsError> {
+        save_file(&self.unscoped_path(name), contents)
+    }
+}
+
+fn save_file(path: &Path, contents: &str) -> Result<(), SettingsError> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let temp = path.with_extension("ini.tmp");
+    fs::write(
+        &temp,
+        format!("{}{}\n{}", HEADER, SETTINGS_VERSION, contents),
+    )?;
+    fs::rename(&temp, path)?;
+    Ok(())
+}
+
+/// Splits a file into its version and contents
+fn parse_header(file: &str) -> (u32, String) {
+    let (first, rest) = file.split_once('\n').unwrap_or((file, ""));
+    match first
+        .trim_end()
+        .strip_prefix(HEADER)
+        .and_then(|version| version.parse().ok())
+    {
+        Some(version) => (version, rest.to_string()),
+        None => (0, file.to_string()),
+    }
+}
+
+/// Per-user config directory: `$XDG_CONFIG_HOME` or `~/.config` on Linux, `%APPDATA%` on
+/// Windows and `~/Library/Application Support` on macOS
+pub fn config_dir() -> Option<PathBuf> {
+    let env_dir = |name: &str| {
+        std::env::var_os(name)
+            .map(PathBuf::from)
+            .filter(|dir| dir.is_absolute())
+    };
+    if cfg!(windows) {
+        env_dir("APPDATA")
+    } else if cfg!(target_os = "macos") {
+        env_dir("HOME").map(|home| home.join("Library/Application Support"))
+    } else {
+        env_dir("XDG_CONFIG_HOME").or_else(|| env_dir("HOME").map(|home| home.join(".config")))
+    }
+}
+
+/// Escapes `s` for line-based files: backslashes, newlines, carriage returns, tabs and `=`, so
+/// the latter two can separate fields. Undone by [`unescape`].
+pub(crate) fn escape(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            '=' => escaped.push_str("\\e"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+pub(crate) fn unescape(s: &str) -> String {
+    let mut unescaped = String::with_capacity(s.len());
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            unescaped.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => unescaped.push('\n'),
+            Some('r') => unescaped.push('\r'),
+            Some('t') => unescaped.push('\t'),
+            Some('e') => unescaped.push('='),
+            Some(c) => unescaped.push(c),
+            None => unescaped.push('\\'),
+        }
+    }
+    unescaped
+}
+
+/// Keeps names usable as a single path component
+fn sanitize(name: &str) -> String {
+    name.chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Fresh directory under the system temp dir, removed again by the test
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("imgui_support_{}_{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    #[test]
+    fn escape_round_trips() {
+        for s in [
+            "",
+            "plain",
+            "a=b",
+            "tab\there",
+            "multi\nline\r\n",
+            "back\\slash\\",
+            "\\e",
+        ] {
+            let escaped = escape(s);
+            assert!(!escaped.contains(['\n', '\r', '\t', '=']));
+            assert_eq!(unescape(&escaped), s);
+        }
+    }
+
+    #[test]
+    fn old_files_are_migrated() {
+        let dir = temp_dir("migration");
+        fs::create_dir_all(&dir).unwrap();
+        let mut store = SettingsStore::new(&dir);
+        fs::write(store.path("headerless"), "width=1").unwrap();
+        assert!(matches!(
+            store.load("headerless"),
+            Err(SettingsError::NoMigrator { version: 0, .. })
+        ));
+
+        store.set_migrator(|name: &str, version: u32, contents: String| {
+            assert_eq!((name, version), ("headerless", 0));
+            Ok(contents.replace("width", "size"))
+        });
+        assert_eq!(store.load("headerless").unwrap().as_deref(), Some("size=1"));
+        assert_eq!(store.load("missing").unwrap(), None);
+
+        store.save("current", "a\nb").unwrap();
+        let file = fs::read_to_string(store.path("current")).unwrap();
+        assert_eq!(parse_header(&file), (SETTINGS_VERSION, "a\nb".to_string()));
+        assert_eq!(store.load("current").unwrap().as_deref(), Some("a\nb"));
+
+        let newer = format!("{}{}\nsize=1", HEADER, SETTINGS_VERSION + 1);
+        fs::write(store.path("newer"), &newer).unwrap();
+        assert!(matches!(
+            store.load("newer"),
+            Err(SettingsError::TooNew { .. })
+        ));
+        assert_eq!(fs::read_to_string(store.path("newer")).unwrap(), newer);
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn failed_migration_is_reported() {
+        let dir = temp_dir("failed_migration");
+        fs::create_dir_all(&dir).unwrap();
+        let mut store = SettingsStore::new(&dir);
+        fs::write(store.path("layout"), "broken").unwrap();
+        store.set_migrator(|_: &str, _: u32, _: String| Err("unreadable".to_string()));
+        assert!(matches!(
+            store.load("layout"),
+            Err(SettingsError::Migration { message, .. }) if message == "unreadable"
+        ));
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn user_session_is_shared_by_scopes() {
+        let dir = temp_dir("user_session");
+        let mut store = SettingsStore::new(&dir);
+        let mut user = Session::new();
+        user.set("accessibility/reduced_motion", "true");
+        user.save_user(&store).unwrap();
+        let mut project = Session::new();
+        project.set("open", "a.map");
+        project.save(&store).unwrap();
+
+        store.set_scope(Some("other"));
+        let mut loaded = Session::new();
+        loaded.load_user(&store).unwrap();
+        assert_eq!(loaded, user);
+        loaded.load(&store).unwrap();
+        assert_eq!(loaded, Session::new());
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,191 @@
+use super::{SessionState, SettingsError, SettingsStore};
+use imgui::Ui;
+use std::path::{Path, PathBuf};
+
+/// A recently opened file or project
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MruEntry {
+    pub path: PathBuf,
+    /// Pinned entries stay at the top and aren't dropped or cleared
+    pub pinned: bool,
+}
+
+/// Most recently used list of files or projects for an "Open Recent" menu.
+///
+/// Saved as its own settings entry with [`save`](Self::save), or into the
+/// [`Session`](super::Session) as a [`SessionState`]. A store scoped to a project keeps a list
+/// per project, load and save a list of projects while the store is unscoped.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Mru {
+    /// Name of the settings entry
+    name: String,
+    capacity: usize,
+    /// Pinned entries first, then the rest from the most recent
+    entries: Vec<MruEntry>,
+}
+
+impl Mru {
+    /// Keeps up to `capacity` unpinned entries
+    pub fn new(name: impl Into<String>, capacity: usize) -> Self {
+        Self {
+            name: name.into(),
+            capacity,
+            entries: Vec::new(),
+        }
+    }
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+    pub fn entries(&self) -> &[MruEntry] {
+        &self.entries
+    }
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Moves `path` to the top of the unpinned entries, dropping the oldest one when full.
+    /// Pinned paths keep their place.
+    pub fn add(&mut self, path: impl Into<PathBuf>) {
+        let path = path.into();
+        if let Some(index) = self.position(&path) {
+            if self.entries[index].pinned {
+                return;
+            }
+            self.entries.remove(index);
+        }
+        let first_unpinned = self.entries.iter().filter(|entry| entry.pinned).count();
+        self.entries.insert(
+            first_unpinned,
+            MruEntry {
+                path,
+                pinned: false,
+            },
+        );
+        self.entries.truncate(first_unpinned + self.capacity);
+    }
+    pub fn remove(&mut self, path: &Path) {
+        if let Some(index) = self.position(path) {
+            self.entries.remove(index);
+        }
+    }
+    /// Pinning moves the entry to the end of the pinned ones, unpinning to the top of the rest
+    pub fn set_pinned(&mut self, path: &Path, pinned: bool) {
+        let Some(index) = self.position(path) else {
+            return;
+        };
+        let mut entry = self.entries.remove(index);
+        entry.pinned = pinned;
+        let first_unpinned = self.entries.iter().filter(|entry| entry.pinned).count();
+        self.entries.insert(first_unpinned, entry);
+        let pinned_count = self.entries.iter().filter(|entry| entry.pinned).count();
+        self.entries.truncate(pinned_count + self.capacity);
+    }
+    /// Removes the unpinned entries
+    pub fn clear(&mut self) {
+        self.entries.retain(|entry| entry.pinned);
+    }
+    /// Removes entries whose files no longer exist, e.g. after loading
+    pub fn retain_existing(&mut self) {
+        self.entries.retain(|entry| entry.path.exists());
+    }
+    fn position(&self, path: &Path) -> Option<usize> {
+        self.entries.iter().position(|entry| entry.path == path)
+    }
+
+    /// Replaces the entries with the ones saved in `store`
+    pub fn load(&mut self, store: &SettingsStore) -> Result<(), SettingsError> {
+        match store.load(&self.name)? {
+            Some(contents) => self.restore_session(&contents),
+            None => self.entries.clear(),
+        }
+        Ok(())
+    }
+    pub fn save(&self, store: &SettingsStore) -> Result<(), SettingsError> {
+        store.save(&self.name, &self.save_session())
+    }
+
+    /// "Open Recent" submenu with the pinned entries, the recent ones and a Clear action.
+    /// Right click an entry to pin or unpin it. Returns the path picked to open, call
+    /// [`add`](Self::add) once it opened or [`remove`](Self::remove) if it's gone.
+    pub fn menu(&mut self, ui: &Ui, label: &str) -> Option<PathBuf> {
+        let _menu = ui.begin_menu_with_enabled(label, !self.entries.is_empty())?;
+        let mut picked = None;
+        let mut toggle_pin = None;
+        let mut was_pinned = true;
+        for (index, entry) in self.entries.iter().enumerate() {
+            if was_pinned && !entry.pinned && index > 0 {
+                ui.separator();
+            }
+            was_pinned = entry.pinned;
+            let _id = ui.push_id_usize(index);
+            let name = entry.path.file_name().map_or_else(
+                || entry.path.to_string_lossy(),
+                |name| name.to_string_lossy(),
+            );
+            let label = if entry.pinned {
+                format!("* {}", name)
+            } else {
+                name.into_owned()
+            };
+            if ui.menu_item(label) {
+                picked = Some(entry.path.clone());
+            }
+            if ui.is_item_clicked_with_button(imgui::MouseButton::Right) {
+                toggle_pin = Some(index);
+            }
+            if ui.is_item_hovered() {
+                ui.tooltip_text(format!(
+                    "{}\nRight click to {}",
+                    entry.path.display(),
+                    if entry.pinned { "unpin" } else { "pin" }
+                ));
+            }
+        }
+        if let Some(index) = toggle_pin {
+            let entry = &self.entries[index];
+            self.set_pinned(&entry.path.clone(), !entry.pinned);
+        }
+        ui.separator();
+        let has_unpinned = self.entries.iter().any(|entry| !entry.pinned);
+        if ui
+            .menu_item_config("Clear recent")
+            .enabled(has_unpinned)
+            .build()
+        {
+            self.clear();
+        }
+        picked
+    }
+}
+
+/// One entry per line, pinned ones start with `*`
+impl SessionState for Mru {
+    fn save_session(&self) -> String {
+        let mut contents = String::new();
+        for entry in &self.entries {
+            contents.push(if entry.pinned { '*' } else { ' ' });
+            contents.push_str(&entry.path.to_string_lossy());
+            contents.push('\n');
+        }
+        contents
+    }
+    fn restore_session(&mut self, data: &str) {
+        self.entries.clear();
+        for line in data.lines() {
+            let (pinned, path) = match line.strip_prefix('*') {
+                Some(path) => (true, path),
+                None => (false, line.strip_prefix(' ').unwrap_or(line)),
+            };
+            if path.is_empty() || self.position(Path::new(path)).is_some() {
+                continue;
+            }
+            self.entries.push(MruEntry {
+                path: PathBuf::from(path),
+                pinned,
+            });
+        }
+        self.entries.sort_by_key(|entry| !entry.pinned);
+        let pinned_count = self.entries.iter().filter(|entry| entry.pinned).count();
+        self.entries.truncate(pinned_count + self.capacity);
+    }
+}
@@ -0,0 +1,154 @@
+use super::{escape, unescape, SettingsError, SettingsStore};
+use std::collections::BTreeMap;
+use std::fmt::Display;
+use std::str::FromStr;
+
+/// Name of the entry holding the [`Session`]
+pub const SESSION: &str = "session";
+/// Name of the unscoped entry holding [`ImGui::user_session`](crate::ImGui::user_session)
+pub const USER_SESSION: &str = "user";
+
+/// State a panel saves into the [`Session`], e.g. its open files or scroll positions
+pub trait SessionState {
+    fn save_session(&self) -> String;
+    /// Called with what [`save_session`](Self::save_session) returned on the last run
+    fn restore_session(&mut self, data: &str);
+}
+
+/// Per-launch state restored on the next launch, e.g. open documents and their selections.
+///
+/// Entries are strings under keys, per-document state is kept apart with a key per document,
+/// see [`document_key`](Self::document_key). Saved and loaded with the rest of the settings by
+/// [`ImGui`](crate::ImGui), so it's per project when the settings are scoped.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Session {
+    entries: BTreeMap<String, String>,
+}
+
+impl Session {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// `document/<document>/<key>`, remove all of a document's state with
+    /// [`remove_document`](Self::remove_document)
+    pub fn document_key(document: &str, key: &str) -> String {
+        format!("document/{}/{}", document, key)
+    }
+
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.entries.get(key).map(String::as_str)
+    }
+    pub fn set(&mut self, key: impl Into<String>, value: impl Into<String>) {
+        self.entries.insert(key.into(), value.into());
+    }
+    pub fn remove(&mut self, key: &str) -> Option<String> {
+        self.entries.remove(key)
+    }
+    /// Parses an entry, `None` if it's missing or doesn't parse
+    pub fn value<T: FromStr>(&self, key: &str) -> Option<T> {
+        self.get(key)?.parse().ok()
+    }
+    pub fn set_value<T: Display>(&mut self, key: impl Into<String>, value: &T) {
+        self.set(key, value.to_string());
+    }
+    pub fn save_state<T: SessionState + ?Sized>(&mut self, key: impl Into<String>, state: &T) {
+        self.set(key, state.save_session());
+    }
+    /// Restores `state` from the entry, returns `false` if there is none
+    pub fn restore_state<T: SessionState + ?Sized>(&self, key: &str, state: &mut T) -> bool {
+        match self.get(key) {
+            Some(data) => {
+                state.restore_session(data);
+                true
+            }
+            None => false,
+        }
+    }
+    /// Keys of one document's entries
+    pub fn document_keys<'a>(&'a self, document: &str) -> impl Iterator<Item = &'a str> + 'a {
+        let prefix = Self::document_key(document, "");
+        self.entries
+            .keys()
+            .filter(move |key| key.starts_with(&prefix))
+            .map(String::as_str)
+    }
+    /// Forgets a closed document
+    pub fn remove_document(&mut self, document: &str) {
+        let prefix = Self::document_key(document, "");
+        self.entries.retain(|key, _| !key.starts_with(&prefix));
+    }
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.entries
+            .iter()
+            .map(|(key, value)| (key.as_str(), value.as_str()))
+    }
+
+    /// Replaces the entries with the ones saved in `store`
+    pub fn load(&mut self, store: &SettingsStore) -> Result<(), SettingsError> {
+        let contents = store.load(SESSION)?;
+        self.parse(contents.as_deref());
+        Ok(())
+    }
+    pub fn save(&self, store: &SettingsStore) -> Result<(), SettingsError> {
+        store.save(SESSION, &self.serialize())
+    }
+    /// Same as [`load`](Self::load) from the [`USER_SESSION`] entry shared by all scopes
+    pub fn load_user(&mut self, store: &SettingsStore) -> Result<(), SettingsError> {
+        let contents = store.load_unscoped(USER_SESSION)?;
+        self.parse(contents.as_deref());
+        Ok(())
+    }
+    pub fn save_user(&self, store: &SettingsStore) -> Result<(), SettingsError> {
+        store.save_unscoped(USER_SESSION, &self.serialize())
+    }
+
+    fn parse(&mut self, contents: Option<&str>) {
+        self.entries.clear();
+        for line in contents.unwrap_or_default().lines() {
+            if let Some((key, value)) = line.split_once('=') {
+                self.entries.insert(unescape(key), unescape(value));
+            }
+        }
+    }
+    fn serialize(&self) -> String {
+        let mut contents = String::new();
+        for (key, value) in &self.entries {
+            contents.push_str(&escape(key));
+            contents.push('=');
+            contents.push_str(&escape(value));
+            contents.push('\n');
+        }
+        contents
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn entries_round_trip_through_the_store() {
+        let dir =
+            std::env::temp_dir().join(format!("imgui_support_session_{}", std::process::id()));
+        let store = SettingsStore::new(&dir);
+        let mut session = Session::new();
+        session.set("a=b", "c=d");
+        session.set(
+            Session::document_key("C:\\maps\\new\tmap", "scroll"),
+            "12.5",
+        );
+        session.set("notes", "line one\nline two\r\n\\n");
+        session.set("empty", "");
+        session.save(&store).unwrap();
+
+        let mut loaded = Session::new();
+        loaded.set("stale", "gone after loading");
+        loaded.load(&store).unwrap();
+        assert_eq!(loaded, session);
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
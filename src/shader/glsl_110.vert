@@ -2,6 +2,13 @@
 
 uniform mat4 matrix;
 
+#ifdef LINEAR
+// imgui's colors are sRGB, the framebuffer encodes the output back
+vec3 srgb_to_linear(vec3 c) {
+  return mix(c / 12.92, pow((c + 0.055) / 1.055, vec3(2.4)), step(0.04045, c));
+}
+#endif
+
 attribute vec2 pos;
 attribute vec2 uv;
 attribute vec4 col;
@@ -15,5 +22,8 @@ varying vec4 f_color;
 void main() {
   f_uv = uv;
   f_color = col / 255.0;
+#ifdef LINEAR
+  f_color.rgb = srgb_to_linear(f_color.rgb);
+#endif
   gl_Position = matrix * vec4(pos.xy, 0, 1);
 }
@@ -1,12 +1,118 @@
 #version 400
 
+#ifdef DEPTH
+// Depth shown from black to white over this range
+uniform vec2 depth_range;
+#endif
+
+#ifdef VIEW
+// Set by the texture viewer: 0 shows all channels, 1 to 4 one of R, G, B, A as grey
+uniform int view_channel;
+// Mip level to sample, negative for the usual filtering
+uniform float view_lod;
+#endif
+
+#if defined(CUBEMAP)
+uniform samplerCube tex;
+// 0 to 5 show a face in the +X, -X, +Y, -Y, +Z, -Z order, 6 a sphere reflecting the map
+uniform int cube_face;
+
+vec4 sample_texture(vec2 uv) {
+  vec2 p = uv * 2.0 - 1.0;
+  vec3 dir;
+  if (cube_face == 6) {
+    float r2 = dot(p, p);
+    if (r2 > 1.0) {
+      return vec4(0.0);
+    }
+    dir = reflect(vec3(0.0, 0.0, -1.0), vec3(p.x, -p.y, sqrt(1.0 - r2)));
+  } else if (cube_face == 0) {
+    dir = vec3(1.0, -p.y, -p.x);
+  } else if (cube_face == 1) {
+    dir = vec3(-1.0, -p.y, p.x);
+  } else if (cube_face == 2) {
+    dir = vec3(p.x, 1.0, p.y);
+  } else if (cube_face == 3) {
+    dir = vec3(p.x, -1.0, -p.y);
+  } else if (cube_face == 4) {
+    dir = vec3(p.x, -p.y, 1.0);
+  } else {
+    dir = vec3(-p.x, -p.y, -1.0);
+  }
+  return texture(tex, dir);
+}
+#elif defined(ARRAY)
+uniform sampler2DArray tex;
+uniform float layer;
+
+vec4 sample_texture(vec2 uv) {
+  return texture(tex, vec3(uv, layer));
+}
+#else
 uniform sampler2D tex;
 
+vec4 sample_texture(vec2 uv) {
+#ifdef VIEW
+  if (view_lod >= 0.0) {
+    return textureLod(tex, uv, view_lod);
+  }
+#endif
+  return texture(tex, uv);
+}
+#endif
+
+#ifdef HDR
+// 2^exposure, applied before the tonemap
+uniform float exposure;
+// 0 clamps, 1 is Reinhard, 2 is the ACES fit
+uniform int tonemap;
+uniform float inv_gamma;
+
+vec3 display(vec3 c) {
+  c = max(c * exposure, 0.0);
+  if (tonemap == 1) {
+    c = c / (1.0 + c);
+  } else if (tonemap == 2) {
+    c = (c * (2.51 * c + 0.03)) / (c * (2.43 * c + 0.59) + 0.14);
+  }
+#ifdef LINEAR
+  // The framebuffer applies the gamma
+  return clamp(c, 0.0, 1.0);
+#else
+  return pow(clamp(c, 0.0, 1.0), vec3(inv_gamma));
+#endif
+}
+#endif
+
 in vec2 f_uv;
 in vec4 f_color;
 
 out vec4 out_color;
 
 void main() {
-  out_color = f_color * texture(tex, f_uv.st);
+  vec4 color = f_color;
+#ifdef PREMULTIPLY
+  color.rgb *= color.a;
+#endif
+  vec4 texel = sample_texture(f_uv.st);
+#ifdef DEPTH
+  // Depth textures sample as (depth, 0, 0, 1)
+  float depth = (texel.r - depth_range.x) / max(depth_range.y - depth_range.x, 0.0001);
+  texel = vec4(vec3(clamp(depth, 0.0, 1.0)), 1.0);
+#endif
+#ifdef VIEW
+  if (view_channel == 1) {
+    texel = vec4(texel.rrr, 1.0);
+  } else if (view_channel == 2) {
+    texel = vec4(texel.ggg, 1.0);
+  } else if (view_channel == 3) {
+    texel = vec4(texel.bbb, 1.0);
+  } else if (view_channel == 4) {
+    texel = vec4(texel.aaa, 1.0);
+  }
+#endif
+#ifdef HDR
+  texel.rgb = display(texel.rgb);
+#endif
+  out_color = color * texel;
 }
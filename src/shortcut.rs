@@ -0,0 +1,67 @@
+use imgui::{Key, Ui};
+use std::fmt;
+
+/// Keyboard shortcut, a key plus the modifiers that have to be held with it
+#[derive(Copy, Clone, Debug, Hash, Eq, PartialEq)]
+pub struct Shortcut {
+    pub key: Key,
+    pub ctrl: bool,
+    pub shift: bool,
+    pub alt: bool,
+}
+
+impl Shortcut {
+    /// Shortcut without any modifiers
+    pub fn new(key: Key) -> Self {
+        Self {
+            key,
+            ctrl: false,
+            shift: false,
+            alt: false,
+        }
+    }
+    /// Shortcut with Ctrl held, e.g. `Shortcut::ctrl(Key::S)` for Ctrl+S
+    pub fn ctrl(key: Key) -> Self {
+        Self::new(key).with_ctrl()
+    }
+    pub fn with_ctrl(mut self) -> Self {
+        self.ctrl = true;
+        self
+    }
+    pub fn with_shift(mut self) -> Self {
+        self.shift = true;
+        self
+    }
+    pub fn with_alt(mut self) -> Self {
+        self.alt = true;
+        self
+    }
+    /// Whether the shortcut was pressed this frame, modifiers have to match exactly
+    pub fn is_pressed(&self, ui: &Ui) -> bool {
+        let io = ui.io();
+        io.key_ctrl == self.ctrl
+            && io.key_shift == self.shift
+            && io.key_alt == self.alt
+            && ui.is_key_pressed_no_repeat(self.key)
+    }
+}
+
+impl fmt::Display for Shortcut {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.ctrl {
+            write!(f, "Ctrl+")?;
+        }
+        if self.shift {
+            write!(f, "Shift+")?;
+        }
+        if self.alt {
+            write!(f, "Alt+")?;
+        }
+        let name = format!("{:?}", self.key);
+        let name = name
+            .strip_prefix("Alpha")
+            .or_else(|| name.strip_prefix("Keypad"))
+            .unwrap_or(&name);
+        write!(f, "{}", name)
+    }
+}
@@ -0,0 +1,58 @@
+//! Audible feedback for the UI.
+//!
+//! Set a [`UiSounds`] with [`ImGui::set_ui_sounds`](crate::ImGui::set_ui_sounds) and it's called
+//! from [`ImGui::prepare_render`](crate::ImGui::prepare_render) when the frame hovered or
+//! activated an item or opened a popup, so menus get feedback without sound calls in UI code.
+
+use imgui::sys;
+
+/// Plays the UI sounds, every method does nothing by default
+pub trait UiSounds {
+    /// The mouse moved onto an item. Moving straight from one item to a touching one isn't
+    /// reported, imgui doesn't expose which item is hovered.
+    fn hover(&mut self) {}
+    /// An item was clicked or activated with the keyboard or gamepad
+    fn click(&mut self) {}
+    /// A popup, menu or combo opened
+    fn open(&mut self) {}
+    /// Played by [`ImGui::play_error_sound`](crate::ImGui::play_error_sound), e.g. when
+    /// validation failed
+    fn error(&mut self) {}
+}
+
+/// What the previous frame had hovered, active and open
+#[derive(Copy, Clone, Debug, Default)]
+pub(crate) struct SoundState {
+    hovered: bool,
+    active: bool,
+    popup_open: bool,
+}
+
+impl SoundState {
+    /// Compares the current frame against the last one and plays the sounds, call between
+    /// `new_frame` and `render`
+    pub(crate) fn update(&mut self, sounds: &mut dyn UiSounds) {
+        // SAFETY: only reads the state of the current frame, the caller guarantees a frame is
+        // in progress
+        let current = unsafe {
+            SoundState {
+                hovered: sys::igIsAnyItemHovered(),
+                active: sys::igIsAnyItemActive(),
+                popup_open: sys::igIsPopupOpen(
+                    c"".as_ptr(),
+                    (sys::ImGuiPopupFlags_AnyPopupId | sys::ImGuiPopupFlags_AnyPopupLevel) as i32,
+                ),
+            }
+        };
+        if current.hovered && !self.hovered {
+            sounds.hover();
+        }
+        if current.active && !self.active {
+            sounds.click();
+        }
+        if current.popup_open && !self.popup_open {
+            sounds.open();
+        }
+        *self = current;
+    }
+}
@@ -0,0 +1,418 @@
+//! Opt-in usage telemetry.
+//!
+//! Nothing is recorded until the user agreed in the consent panel. Events are recorded with
+//! [`event`] from anywhere once a [`Telemetry`] is [`install`]ed, they're buffered in a file on
+//! disk and handed to a [`TelemetryUploader`] by [`upload`], so no event is lost when the tool
+//! closes before an upload. Denying consent deletes everything buffered.
+//!
+//! Writing to disk while recording or from the consent panel can't report failures to the
+//! caller, they're logged with the `log` feature. [`flush`] and [`set_consent`] return them.
+
+use crate::settings::{escape, unescape};
+use imgui::{Condition, Ui};
+use std::fmt;
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const EVENTS_FILE: &str = "events.log";
+/// Events handed to the uploader, kept until it succeeded
+const SENDING_FILE: &str = "events.sending";
+const CONSENT_FILE: &str = "consent";
+/// Events kept in memory before they're written to disk
+const FLUSH_AT: usize = 32;
+
+static TELEMETRY: Mutex<Option<Telemetry>> = Mutex::new(None);
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Consent {
+    /// The user wasn't asked yet, the consent panel is shown
+    Unknown,
+    Granted,
+    Denied,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Event {
+    /// Milliseconds since the Unix epoch
+    pub timestamp: u64,
+    pub name: String,
+    pub props: Vec<(String, String)>,
+}
+
+/// Sends buffered events somewhere, e.g. to the team's analytics endpoint
+pub trait TelemetryUploader: Send {
+    fn upload(&mut self, events: &[Event]) -> Result<(), String>;
+}
+
+/// Telemetry state, see the [module docs](self)
+pub struct Telemetry {
+    dir: PathBuf,
+    consent: Consent,
+    /// What's collected, shown in the consent panel
+    description: String,
+    buffer: Vec<Event>,
+    uploader: Option<Box<dyn TelemetryUploader>>,
+}
+
+impl fmt::Debug for Telemetry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Telemetry")
+            .field("dir", &self.dir)
+            .field("consent", &self.consent)
+            .field("buffered", &self.buffer.len())
+            .finish_non_exhaustive()
+    }
+}
+
+impl Telemetry {
+    /// Events and the consent decision are kept in `dir`. `description` tells the user what's
+    /// collected and why.
+    pub fn new(dir: impl Into<PathBuf>, description: impl Into<String>) -> Self {
+        let dir = dir.into();
+        let consent = match fs::read_to_string(dir.join(CONSENT_FILE)).as_deref() {
+            Ok("granted") => Consent::Granted,
+            Ok("denied") => Consent::Denied,
+            _ => Consent::Unknown,
+        };
+        Self {
+            dir,
+            consent,
+            description: description.into(),
+            buffer: Vec::new(),
+            uploader: None,
+        }
+    }
+    pub fn with_uploader<U: TelemetryUploader + 'static>(mut self, uploader: U) -> Self {
+        self.uploader = Some(Box::new(uploader));
+        self
+    }
+
+    fn set_consent(&mut self, consent: Consent) -> io::Result<()> {
+        self.consent = consent;
+        fs::create_dir_all(&self.dir)?;
+        let value = match consent {
+            Consent::Granted => "granted",
+            Consent::Denied => "denied",
+            Consent::Unknown => {
+                return match fs::remove_file(self.dir.join(CONSENT_FILE)) {
+                    Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e),
+                    _ => Ok(()),
+                };
+            }
+        };
+        fs::write(self.dir.join(CONSENT_FILE), value)?;
+        if consent == Consent::Denied {
+            self.buffer.clear();
+            remove_if_exists(&self.dir.join(EVENTS_FILE))?;
+            remove_if_exists(&self.dir.join(SENDING_FILE))?;
+        }
+        Ok(())
+    }
+
+    fn record(&mut self, name: &str, props: &[(&str, &str)]) {
+        if self.consent != Consent::Granted {
+            return;
+        }
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |time| time.as_millis() as u64);
+        self.buffer.push(Event {
+            timestamp,
+            name: name.to_string(),
+            props: props
+                .iter()
+                .map(|&(k, v)| (k.to_string(), v.to_string()))
+                .collect(),
+        });
+        if self.buffer.len() >= FLUSH_AT {
+            if let Err(e) = self.flush() {
+                log!(warn, "Writing telemetry failed: {}", e);
+            }
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        if self.buffer.is_empty() {
+            return Ok(());
+        }
+        fs::create_dir_all(&self.dir)?;
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(self.dir.join(EVENTS_FILE))?;
+        let mut lines = String::new();
+        for event in self.buffer.drain(..) {
+            lines.push_str(&format!("{}\t{}", event.timestamp, escape(&event.name)));
+            for (key, value) in &event.props {
+                lines.push_str(&format!("\t{}\t{}", escape(key), escape(value)));
+            }
+            lines.push('\n');
+        }
+        file.write_all(lines.as_bytes())
+    }
+
+    /// Moves the events on disk to the sending file and takes the uploader, so the upload can
+    /// run without holding the lock. `None` when there's nothing to upload.
+    fn start_upload(&mut self) -> Result<Option<Upload>, String> {
+        if self.consent != Consent::Granted || self.uploader.is_none() {
+            return Ok(None);
+        }
+        self.flush().map_err(|e| e.to_string())?;
+        let sending = self.dir.join(SENDING_FILE);
+        // Events of a failed upload are still in the sending file, new ones go after them
+        match fs::read_to_string(self.dir.join(EVENTS_FILE)) {
+            Ok(contents) => {
+                OpenOptions::new()
+                    .create(true)
+                    .append(true)
+                    .open(&sending)
+                    .and_then(|mut file| file.write_all(contents.as_bytes()))
+                    .and_then(|_| fs::remove_file(self.dir.join(EVENTS_FILE)))
+                    .map_err(|e| e.to_string())?;
+            }
+            Err(e) if e.kind() == io::ErrorKind::NotFound => (),
+            Err(e) => return Err(e.to_string()),
+        }
+        let events = match fs::read_to_string(&sending) {
+            Ok(contents) => parse_events(&contents),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e.to_string()),
+        };
+        if events.is_empty() {
+            return Ok(None);
+        }
+        Ok(self.uploader.take().map(|uploader| Upload {
+            uploader,
+            events,
+            sending,
+        }))
+    }
+}
+
+/// Events being uploaded outside of the lock, see [`Telemetry::start_upload`]
+struct Upload {
+    uploader: Box<dyn TelemetryUploader>,
+    events: Vec<Event>,
+    sending: PathBuf,
+}
+
+fn remove_if_exists(path: &Path) -> io::Result<()> {
+    match fs::remove_file(path) {
+        Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e),
+        _ => Ok(()),
+    }
+}
+
+/// Makes `telemetry` the one [`event`] records into, replacing any earlier one
+pub fn install(telemetry: Telemetry) {
+    let mut global = TELEMETRY.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some(old) = global.as_mut() {
+        let _ = old.flush();
+    }
+    *global = Some(telemetry);
+}
+
+/// Records an event, does nothing without consent or an installed [`Telemetry`]
+pub fn event(name: &str, props: &[(&str, &str)]) {
+    if let Some(telemetry) = TELEMETRY.lock().unwrap_or_else(|e| e.into_inner()).as_mut() {
+        telemetry.record(name, props);
+    }
+}
+
+/// Writes buffered events to disk, call on shutdown
+pub fn flush() -> io::Result<()> {
+    match TELEMETRY.lock().unwrap_or_else(|e| e.into_inner()).as_mut() {
+        Some(telemetry) => telemetry.flush(),
+        None => Ok(()),
+    }
+}
+
+/// Hands all buffered events to the uploader and deletes them once it succeeded, returns how
+/// many were uploaded. Blocks while the uploader runs, [`event`] can be called from other threads
+/// meanwhile.
+pub fn upload() -> Result<usize, String> {
+    let upload = match TELEMETRY.lock().unwrap_or_else(|e| e.into_inner()).as_mut() {
+        Some(telemetry) => telemetry.start_upload()?,
+        None => None,
+    };
+    let Some(Upload {
+        mut uploader,
+        events,
+        sending,
+    }) = upload
+    else {
+        return Ok(0);
+    };
+    let result = uploader.upload(&events);
+    if let Some(telemetry) = TELEMETRY.lock().unwrap_or_else(|e| e.into_inner()).as_mut() {
+        telemetry.uploader.get_or_insert(uploader);
+    }
+    result?;
+    remove_if_exists(&sending).map_err(|e| e.to_string())?;
+    Ok(events.len())
+}
+
+pub fn consent() -> Consent {
+    TELEMETRY
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .as_ref()
+        .map_or(Consent::Denied, |telemetry| telemetry.consent)
+}
+
+/// Changes the consent, e.g. from a settings page. Denying deletes the buffered events.
+pub fn set_consent(consent: Consent) -> io::Result<()> {
+    match TELEMETRY.lock().unwrap_or_else(|e| e.into_inner()).as_mut() {
+        Some(telemetry) => telemetry.set_consent(consent),
+        None => Ok(()),
+    }
+}
+
+/// Asks for consent while the user hasn't decided yet, call every frame
+pub fn draw_consent(ui: &Ui) {
+    // The window is built without the lock, so recording on other threads doesn't wait for it
+    let description = match TELEMETRY.lock().unwrap_or_else(|e| e.into_inner()).as_ref() {
+        Some(telemetry) if telemetry.consent == Consent::Unknown => telemetry.description.clone(),
+        _ => return,
+    };
+    let mut decision = None;
+    let display_size = ui.io().display_size;
+    ui.window("Help improve this tool?")
+        .always_auto_resize(true)
+        .collapsible(false)
+        .position(
+            [display_size[0] * 0.5, display_size[1] * 0.5],
+            Condition::Appearing,
+        )
+        .position_pivot([0.5, 0.5])
+        .build(|| {
+            ui.text_wrapped(&description);
+            ui.text_disabled("Data is only sent with your permission, you can change this later.");
+            if ui.button("Allow") {
+                decision = Some(Consent::Granted);
+            }
+            ui.same_line();
+            if ui.button("Don't allow") {
+                decision = Some(Consent::Denied);
+            }
+        });
+    if let Some(consent) = decision {
+        if let Err(e) = set_consent(consent) {
+            log!(warn, "Saving telemetry consent failed: {}", e);
+        }
+    }
+}
+
+/// Checkbox for a settings page, `true` when it was toggled
+pub fn consent_checkbox(ui: &Ui, label: &str) -> bool {
+    let mut granted = consent() == Consent::Granted;
+    if ui.checkbox(label, &mut granted) {
+        let consent = if granted {
+            Consent::Granted
+        } else {
+            Consent::Denied
+        };
+        if let Err(e) = set_consent(consent) {
+            log!(warn, "Saving telemetry consent failed: {}", e);
+        }
+        return true;
+    }
+    false
+}
+
+fn parse_events(contents: &str) -> Vec<Event> {
+    contents
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split('\t').map(unescape);
+            let timestamp = fields.next()?.parse().ok()?;
+            let name = fields.next()?;
+            let mut props = Vec::new();
+            while let (Some(key), Some(value)) = (fields.next(), fields.next()) {
+                props.push((key, value));
+            }
+            Some(Event {
+                timestamp,
+                name,
+                props,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    /// Keeps the uploaded events, fails the first upload and records an event while uploading
+    struct Collect {
+        uploaded: Arc<Mutex<Vec<Event>>>,
+        attempts: usize,
+    }
+
+    impl TelemetryUploader for Collect {
+        fn upload(&mut self, events: &[Event]) -> Result<(), String> {
+            self.attempts += 1;
+            // Would deadlock if the upload held the lock
+            event("during upload", &[]);
+            if self.attempts == 1 {
+                return Err("offline".to_string());
+            }
+            self.uploaded.lock().unwrap().extend_from_slice(events);
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn flushed_events_parse_back() {
+        let dir =
+            std::env::temp_dir().join(format!("imgui_support_telemetry_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let uploaded = Arc::new(Mutex::new(Vec::new()));
+        let mut telemetry = Telemetry::new(&dir, "Test").with_uploader(Collect {
+            uploaded: Arc::clone(&uploaded),
+            attempts: 0,
+        });
+        telemetry.record("ignored", &[]);
+        telemetry.set_consent(Consent::Granted).unwrap();
+        telemetry.record("opened", &[("path", "C:\\maps\\a\tb.map")]);
+        telemetry.record("tricky\tname", &[("k=1", "multi\nline"), ("empty", "")]);
+        let recorded = telemetry.buffer.clone();
+        telemetry.flush().unwrap();
+
+        let contents = fs::read_to_string(dir.join(EVENTS_FILE)).unwrap();
+        assert_eq!(contents.lines().count(), 2);
+        assert_eq!(parse_events(&contents), recorded);
+
+        install(telemetry);
+        assert_eq!(upload(), Err("offline".to_string()));
+        assert!(dir.join(SENDING_FILE).exists());
+        // The failed batch goes again, followed by the event recorded during the first upload
+        assert_eq!(upload(), Ok(3));
+        let uploaded = uploaded.lock().unwrap();
+        assert_eq!(uploaded[..2], recorded);
+        assert_eq!(uploaded[2].name, "during upload");
+        assert!(!dir.join(SENDING_FILE).exists());
+        flush().unwrap();
+        let contents = fs::read_to_string(dir.join(EVENTS_FILE)).unwrap();
+        assert_eq!(parse_events(&contents)[0].name, "during upload");
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn malformed_lines_are_skipped() {
+        let events = parse_events("not a timestamp\tname\n\n5\tok\tdangling\n");
+        assert_eq!(
+            events,
+            [Event {
+                timestamp: 5,
+                name: "ok".to_string(),
+                props: Vec::new(),
+            }]
+        );
+    }
+}
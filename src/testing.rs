@@ -0,0 +1,270 @@
+//! Running UI code without a window or a GL context, for unit tests of widgets in CI.
+//!
+//! [`Harness`] drives a bare imgui context: each frame builds the UI and ends with the draw data,
+//! which is summed up in a [`FrameStats`] instead of being rendered. Widgets that need the
+//! [`Renderer`](crate::Renderer) or a display can't run in it.
+//!
+//! For golden tests a frame is kept as a [`DrawSnapshot`] and compared against one stored from an
+//! earlier run, with `serde` as JSON files through [`check_snapshot`].
+
+mod snapshot;
+
+use crate::debug::{drawn_windows, DrawnWindow};
+use crate::fonts::FontBuilder;
+use crate::FrameStats;
+use imgui::{Context, Io, MouseButton, Ui};
+use std::sync::{Mutex, MutexGuard};
+use std::time::Duration;
+
+#[cfg(feature = "serde")]
+pub use snapshot::{check_snapshot, SnapshotError, UPDATE_SNAPSHOTS_VAR};
+pub use snapshot::{DrawCmdSnapshot, DrawListSnapshot, DrawSnapshot};
+
+/// Display size of [`Harness::new`], in UI pixels
+pub const HARNESS_DISPLAY_SIZE: [f32; 2] = [1280.0, 720.0];
+/// Largest font atlas the harness builds, what most GPUs allow
+const MAX_TEXTURE_SIZE: u32 = 8192;
+
+/// Held by the living [`Harness`], imgui allows one context per process
+static CONTEXT_LOCK: Mutex<()> = Mutex::new(());
+
+/// Headless imgui context running frames of a UI closure.
+///
+/// Input is fed through [`io_mut`](Self::io_mut) or the mouse helpers and applies to the next
+/// frame, the same as with a window.
+///
+/// imgui allows one context in the whole process, so creating a harness waits until no other
+/// one is alive. Tests running on parallel threads take turns, keep a harness only as long as
+/// the test needs it. Creating an [`ImGui`](crate::ImGui) or any other context while a harness
+/// lives panics.
+pub struct Harness {
+    // Dropped before the lock is released
+    context: Context,
+    frames: u64,
+    drawn_windows: Vec<DrawnWindow>,
+    _lock: MutexGuard<'static, ()>,
+}
+
+impl std::fmt::Debug for Harness {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Harness")
+            .field("frames", &self.frames)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Default for Harness {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Harness {
+    /// Uses imgui's built-in font, which builds faster than the default UI fonts
+    pub fn new() -> Self {
+        Self::with_fonts(&FontBuilder::empty())
+    }
+    /// Builds the font atlas from `fonts`, for widgets that look up named fonts
+    pub fn with_fonts(fonts: &FontBuilder) -> Self {
+        // A test that panicked with a harness still dropped its context
+        let lock = CONTEXT_LOCK
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let mut context = Context::create();
+        context.set_ini_filename(None);
+        context.set_log_filename(None);
+        fonts.build(&mut context, MAX_TEXTURE_SIZE);
+        let io = context.io_mut();
+        io.display_size = HARNESS_DISPLAY_SIZE;
+        io.display_framebuffer_scale = [1.0, 1.0];
+        io.update_delta_time(Duration::from_secs_f32(1.0 / 60.0));
+        Self {
+            context,
+            frames: 0,
+            drawn_windows: Vec::new(),
+            _lock: lock,
+        }
+    }
+    pub fn with_display_size(mut self, size: [f32; 2]) -> Self {
+        self.context.io_mut().display_size = size;
+        self
+    }
+    /// Time passed to imgui between frames, 1/60 of a second by default
+    pub fn with_delta_time(mut self, dt: f32) -> Self {
+        self.context
+            .io_mut()
+            .update_delta_time(Duration::from_secs_f32(dt));
+        self
+    }
+
+    pub fn context(&mut self) -> &mut Context {
+        &mut self.context
+    }
+    pub fn io_mut(&mut self) -> &mut Io {
+        self.context.io_mut()
+    }
+    /// Frames run so far
+    pub fn frames(&self) -> u64 {
+        self.frames
+    }
+    /// Windows drawn in the last frame, see [`ImGui::drawn_windows`](crate::ImGui::drawn_windows)
+    pub fn drawn_windows(&self) -> &[DrawnWindow] {
+        &self.drawn_windows
+    }
+
+    /// Runs one frame of `build` and returns the size of its draw data
+    pub fn frame<F: FnOnce(&Ui)>(&mut self, build: F) -> FrameStats {
+        let ui = self.context.new_frame();
+        build(ui);
+        let [width, height] = self.context.io().display_size;
+        let draw_data = self.context.render();
+        self.drawn_windows = drawn_windows(draw_data);
+        self.frames += 1;
+        FrameStats::new(draw_data, (width as u32, height as u32))
+    }
+    /// Runs one frame of `build` and keeps what it drew
+    pub fn snapshot<F: FnOnce(&Ui)>(&mut self, build: F) -> DrawSnapshot {
+        let ui = self.context.new_frame();
+        build(ui);
+        let draw_data = self.context.render();
+        self.drawn_windows = drawn_windows(draw_data);
+        let snapshot = DrawSnapshot::new(draw_data);
+        self.frames += 1;
+        snapshot
+    }
+    /// Runs `frames` frames of `build` and returns the stats of the last one, e.g. to let
+    /// windows size themselves, which takes imgui a couple of frames
+    pub fn run<F: FnMut(&Ui)>(&mut self, frames: usize, mut build: F) -> FrameStats {
+        let mut stats = FrameStats::default();
+        for _ in 0..frames {
+            stats = self.frame(&mut build);
+        }
+        stats
+    }
+
+    pub fn move_mouse(&mut self, pos: [f32; 2]) {
+        self.io_mut().add_mouse_pos_event(pos);
+    }
+    /// Moves the mouse to `pos` and clicks `button` there: a frame with the button down and one
+    /// with it up. Returns the stats of the second frame.
+    pub fn click<F: FnMut(&Ui)>(
+        &mut self,
+        pos: [f32; 2],
+        button: MouseButton,
+        mut build: F,
+    ) -> FrameStats {
+        self.move_mouse(pos);
+        self.io_mut().add_mouse_button_event(button, true);
+        self.frame(&mut build);
+        self.io_mut().add_mouse_button_event(button, false);
+        self.frame(&mut build)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+
+    #[test]
+    fn frame_counts_draw_data() {
+        let mut harness = Harness::new();
+        let empty = harness.frame(|_| ());
+        assert_eq!(empty.vertices, 0);
+        let stats = harness.run(2, |ui| {
+            ui.window("Test").build(|| ui.text("Hello"));
+        });
+        assert!(stats.vertices > 0);
+        assert_eq!(harness.frames(), 3);
+    }
+
+    #[test]
+    fn click_reaches_button() {
+        let mut harness = Harness::new();
+        let mut clicks = 0;
+        let mut build = |ui: &Ui| {
+            ui.window("Test")
+                .position([0.0, 0.0], imgui::Condition::Always)
+                .build(|| {
+                    if ui.button("Click") {
+                        clicks += 1;
+                    }
+                });
+        };
+        harness.run(2, &mut build);
+        harness.click([20.0, 30.0], MouseButton::Left, &mut build);
+        assert_eq!(clicks, 1);
+    }
+
+    fn panel(ui: &Ui, lines: usize) {
+        ui.window("Panel")
+            .position([10.0, 10.0], imgui::Condition::Always)
+            .size([200.0, 150.0], imgui::Condition::Always)
+            .build(|| {
+                for line in 0..lines {
+                    ui.text(format!("Line {}", line));
+                }
+            });
+    }
+
+    #[test]
+    fn snapshots_of_the_same_ui_are_equal() {
+        let mut harness = Harness::new();
+        harness.run(2, |ui| panel(ui, 2));
+        let first = harness.snapshot(|ui| panel(ui, 2));
+        let second = harness.snapshot(|ui| panel(ui, 2));
+        assert_eq!(first.display_size, HARNESS_DISPLAY_SIZE);
+        assert!(!first.lists.is_empty());
+        assert!(second.differences(&first).is_empty());
+
+        let changed = harness.snapshot(|ui| panel(ui, 3));
+        let differences = changed.differences(&first);
+        assert!(
+            differences.iter().any(|line| line.contains("vertices")),
+            "{:?}",
+            differences
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn snapshot_file_is_written_then_checked() {
+        let path = std::env::temp_dir().join(format!(
+            "imgui_support_snapshot_{}.json",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_file(&path);
+        let mut harness = Harness::new();
+        harness.run(2, |ui| panel(ui, 2));
+        let snapshot = harness.snapshot(|ui| panel(ui, 2));
+        check_snapshot(&path, &snapshot).unwrap();
+        assert_eq!(
+            DrawSnapshot::from_json(&std::fs::read_to_string(&path).unwrap()).unwrap(),
+            snapshot
+        );
+        check_snapshot(&path, &harness.snapshot(|ui| panel(ui, 2))).unwrap();
+        if std::env::var_os(UPDATE_SNAPSHOTS_VAR).is_none() {
+            let changed = harness.snapshot(|ui| panel(ui, 3));
+            assert!(matches!(
+                check_snapshot(&path, &changed),
+                Err(SnapshotError::Mismatch(_))
+            ));
+        }
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn harnesses_on_parallel_threads_take_turns() {
+        let threads: Vec<_> = (0..4)
+            .map(|_| {
+                thread::spawn(|| {
+                    let mut harness = Harness::new();
+                    harness.run(3, |ui| ui.text("Hello")).vertices
+                })
+            })
+            .collect();
+        for thread in threads {
+            assert!(thread.join().unwrap() > 0);
+        }
+    }
+}
@@ -0,0 +1,221 @@
+use imgui::{DrawCmd, DrawData};
+#[cfg(feature = "serde")]
+use std::{error::Error, fmt, fs, io, path::Path};
+
+/// Environment variable making [`check_snapshot`] overwrite the snapshot files instead of
+/// comparing against them
+#[cfg(feature = "serde")]
+pub const UPDATE_SNAPSHOTS_VAR: &str = "UPDATE_SNAPSHOTS";
+
+/// What a frame drew: the draw lists with their vertex and index counts, and each command's clip
+/// rect and texture. Vertex data itself isn't kept, layout changes show up in the counts and clip
+/// rects.
+///
+/// Taken with [`Harness::snapshot`](super::Harness::snapshot) and compared with
+/// [`differences`](Self::differences). Frames built from the same UI code, fonts and display size
+/// give equal snapshots.
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DrawSnapshot {
+    pub display_pos: [f32; 2],
+    pub display_size: [f32; 2],
+    pub lists: Vec<DrawListSnapshot>,
+}
+
+/// One draw list of a [`DrawSnapshot`]
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DrawListSnapshot {
+    pub vertices: usize,
+    pub indices: usize,
+    pub commands: Vec<DrawCmdSnapshot>,
+}
+
+/// One command of a [`DrawListSnapshot`]
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum DrawCmdSnapshot {
+    Elements {
+        count: usize,
+        /// Left, top, right, bottom in UI pixels
+        clip_rect: [f32; 4],
+        texture_id: usize,
+        vtx_offset: usize,
+        idx_offset: usize,
+    },
+    ResetRenderState,
+    /// A draw list callback, only its position is kept
+    Callback,
+}
+
+impl DrawSnapshot {
+    pub fn new(draw_data: &DrawData) -> Self {
+        let mut snapshot = Self {
+            display_pos: draw_data.display_pos,
+            display_size: draw_data.display_size,
+            lists: Vec::new(),
+        };
+        // The list pointer is null without draw lists, which `draw_lists` can't take
+        if draw_data.draw_lists_count() == 0 {
+            return snapshot;
+        }
+        snapshot.lists = draw_data
+            .draw_lists()
+            .map(|draw_list| DrawListSnapshot {
+                vertices: draw_list.vtx_buffer().len(),
+                indices: draw_list.idx_buffer().len(),
+                commands: draw_list
+                    .commands()
+                    .map(|command| match command {
+                        DrawCmd::Elements { count, cmd_params } => DrawCmdSnapshot::Elements {
+                            count,
+                            clip_rect: cmd_params.clip_rect,
+                            texture_id: cmd_params.texture_id.id(),
+                            vtx_offset: cmd_params.vtx_offset,
+                            idx_offset: cmd_params.idx_offset,
+                        },
+                        DrawCmd::ResetRenderState => DrawCmdSnapshot::ResetRenderState,
+                        DrawCmd::RawCallback { .. } => DrawCmdSnapshot::Callback,
+                    })
+                    .collect(),
+            })
+            .collect();
+        snapshot
+    }
+
+    /// How `self` differs from `expected`, one line each, empty when they're equal
+    pub fn differences(&self, expected: &DrawSnapshot) -> Vec<String> {
+        let mut differences = Vec::new();
+        if self.display_pos != expected.display_pos {
+            differences.push(format!(
+                "display position is {:?}, expected {:?}",
+                self.display_pos, expected.display_pos
+            ));
+        }
+        if self.display_size != expected.display_size {
+            differences.push(format!(
+                "display size is {:?}, expected {:?}",
+                self.display_size, expected.display_size
+            ));
+        }
+        if self.lists.len() != expected.lists.len() {
+            differences.push(format!(
+                "{} draw lists, expected {}",
+                self.lists.len(),
+                expected.lists.len()
+            ));
+        }
+        for (index, (list, expected)) in self.lists.iter().zip(&expected.lists).enumerate() {
+            if list.vertices != expected.vertices || list.indices != expected.indices {
+                differences.push(format!(
+                    "draw list {}: {} vertices and {} indices, expected {} and {}",
+                    index, list.vertices, list.indices, expected.vertices, expected.indices
+                ));
+            }
+            if list.commands.len() != expected.commands.len() {
+                differences.push(format!(
+                    "draw list {}: {} commands, expected {}",
+                    index,
+                    list.commands.len(),
+                    expected.commands.len()
+                ));
+            }
+            for (command_index, (command, expected)) in
+                list.commands.iter().zip(&expected.commands).enumerate()
+            {
+                if command != expected {
+                    differences.push(format!(
+                        "draw list {} command {}: {:?}, expected {:?}",
+                        index, command_index, command, expected
+                    ));
+                }
+            }
+        }
+        differences
+    }
+
+    #[cfg(feature = "serde")]
+    pub fn to_json(&self) -> Result<String, serde_json::Error> {
+        serde_json::to_string_pretty(self)
+    }
+    #[cfg(feature = "serde")]
+    pub fn from_json(json: &str) -> Result<Self, serde_json::Error> {
+        serde_json::from_str(json)
+    }
+}
+
+/// Why [`check_snapshot`] failed
+#[cfg(feature = "serde")]
+#[derive(Debug)]
+pub enum SnapshotError {
+    Io(io::Error),
+    Json(serde_json::Error),
+    /// The frame differs from the stored snapshot, see [`DrawSnapshot::differences`]
+    Mismatch(Vec<String>),
+}
+
+#[cfg(feature = "serde")]
+impl Error for SnapshotError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            SnapshotError::Io(e) => Some(e),
+            SnapshotError::Json(e) => Some(e),
+            SnapshotError::Mismatch(_) => None,
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl fmt::Display for SnapshotError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SnapshotError::Io(e) => write!(f, "Snapshot file error: {}", e),
+            SnapshotError::Json(e) => write!(f, "Invalid snapshot JSON: {}", e),
+            SnapshotError::Mismatch(differences) => {
+                write!(f, "Frame differs from the snapshot:")?;
+                for difference in differences {
+                    write!(f, "\n  {}", difference)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl From<io::Error> for SnapshotError {
+    fn from(e: io::Error) -> SnapshotError {
+        SnapshotError::Io(e)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl From<serde_json::Error> for SnapshotError {
+    fn from(e: serde_json::Error) -> SnapshotError {
+        SnapshotError::Json(e)
+    }
+}
+
+/// Golden test against the JSON snapshot at `path`. Writes the file when it doesn't exist yet or
+/// [`UPDATE_SNAPSHOTS_VAR`] is set, otherwise fails with the differences.
+#[cfg(feature = "serde")]
+pub fn check_snapshot(
+    path: impl AsRef<Path>,
+    snapshot: &DrawSnapshot,
+) -> Result<(), SnapshotError> {
+    let path = path.as_ref();
+    if !path.exists() || std::env::var_os(UPDATE_SNAPSHOTS_VAR).is_some() {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, snapshot.to_json()?)?;
+        return Ok(());
+    }
+    let expected = DrawSnapshot::from_json(&fs::read_to_string(path)?)?;
+    let differences = snapshot.differences(&expected);
+    if differences.is_empty() {
+        Ok(())
+    } else {
+        Err(SnapshotError::Mismatch(differences))
+    }
+}
@@ -0,0 +1,365 @@
+//! Color and rounding presets.
+//!
+//! A [`Theme`] starts from one of imgui's color sets and overrides single colors and the
+//! rounding on top. Apply it with [`ImGui::set_theme`](crate::ImGui::set_theme), which keeps the
+//! style scale and the low vision preset working.
+//!
+//! With the `serde` feature themes load from and save to JSON, and with `toml` to TOML, so
+//! colors can be tweaked without recompiling. Colors are keyed by imgui's names, e.g.
+//! `WindowBg`:
+//!
+//! ```toml
+//! base = "Dark"
+//! window_rounding = 6.0
+//! rounding = 4.0
+//!
+//! [colors]
+//! WindowBg = [0.1, 0.11, 0.14, 0.96]
+//! ```
+
+use imgui::{Style, StyleColor};
+#[cfg(feature = "serde")]
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fmt;
+
+/// imgui's built-in color sets
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ThemeBase {
+    #[default]
+    Dark,
+    Light,
+    Classic,
+}
+
+/// Colors and rounding of the UI
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Theme {
+    pub base: ThemeBase,
+    /// Applied over the base colors in order
+    pub colors: Vec<(StyleColor, [f32; 4])>,
+    pub window_rounding: f32,
+    /// Rounding of frames, child regions, popups, grabs, scrollbars and tabs
+    pub rounding: f32,
+}
+
+impl Theme {
+    /// imgui's default colors with square corners
+    pub fn dark() -> Self {
+        Self::default()
+    }
+    pub fn light() -> Self {
+        Self {
+            base: ThemeBase::Light,
+            ..Self::default()
+        }
+    }
+    /// The look of older imgui versions
+    pub fn classic() -> Self {
+        Self {
+            base: ThemeBase::Classic,
+            ..Self::default()
+        }
+    }
+    /// Dark blue-grey with orange accents and rounded corners
+    pub fn bugsyth() -> Self {
+        const ACCENT: [f32; 4] = [0.95, 0.55, 0.15, 1.0];
+        const ACCENT_HOVERED: [f32; 4] = [1.0, 0.65, 0.3, 1.0];
+        const ACCENT_DIM: [f32; 4] = [0.95, 0.55, 0.15, 0.4];
+        Self {
+            base: ThemeBase::Dark,
+            colors: vec![
+                (StyleColor::WindowBg, [0.1, 0.11, 0.14, 0.96]),
+                (StyleColor::ChildBg, [0.0, 0.0, 0.0, 0.0]),
+                (StyleColor::PopupBg, [0.12, 0.13, 0.17, 0.98]),
+                (StyleColor::Border, [0.25, 0.27, 0.33, 0.6]),
+                (StyleColor::FrameBg, [0.17, 0.19, 0.24, 1.0]),
+                (StyleColor::FrameBgHovered, [0.23, 0.25, 0.31, 1.0]),
+                (StyleColor::FrameBgActive, [0.28, 0.3, 0.37, 1.0]),
+                (StyleColor::TitleBg, [0.08, 0.09, 0.11, 1.0]),
+                (StyleColor::TitleBgActive, [0.14, 0.15, 0.19, 1.0]),
+                (StyleColor::MenuBarBg, [0.12, 0.13, 0.17, 1.0]),
+                (StyleColor::CheckMark, ACCENT),
+                (StyleColor::SliderGrab, ACCENT),
+                (StyleColor::SliderGrabActive, ACCENT_HOVERED),
+                (StyleColor::Button, [0.2, 0.22, 0.28, 1.0]),
+                (StyleColor::ButtonHovered, ACCENT_DIM),
+                (StyleColor::ButtonActive, ACCENT),
+                (StyleColor::Header, [0.2, 0.22, 0.28, 1.0]),
+                (StyleColor::HeaderHovered, ACCENT_DIM),
+                (StyleColor::HeaderActive, ACCENT),
+                (StyleColor::SeparatorHovered, ACCENT_DIM),
+                (StyleColor::SeparatorActive, ACCENT),
+                (StyleColor::ResizeGripHovered, ACCENT_DIM),
+                (StyleColor::ResizeGripActive, ACCENT),
+                (StyleColor::Tab, [0.14, 0.15, 0.19, 1.0]),
+                (StyleColor::TabHovered, ACCENT_DIM),
+                (StyleColor::TabActive, [0.25, 0.22, 0.2, 1.0]),
+                (StyleColor::TextSelectedBg, ACCENT_DIM),
+                (StyleColor::NavHighlight, ACCENT),
+            ],
+            window_rounding: 6.0,
+            rounding: 4.0,
+        }
+    }
+
+    /// Every color and the rounding of an unscaled style, e.g. to export what was made in the
+    /// style editor.
+    ///
+    /// The live style of an [`ImGui`](crate::ImGui) has its rounding scaled with the DPI and
+    /// style scale, and applying a theme taken from it would scale it twice. Use
+    /// [`ImGui::theme`](crate::ImGui::theme) for that one.
+    pub fn from_style(style: &Style) -> Self {
+        Self {
+            base: ThemeBase::Dark,
+            colors: StyleColor::VARIANTS
+                .into_iter()
+                .map(|color| (color, style[color]))
+                .collect(),
+            window_rounding: style.window_rounding,
+            rounding: style.frame_rounding,
+        }
+    }
+
+    pub fn with_color(mut self, color: StyleColor, value: [f32; 4]) -> Self {
+        self.colors.push((color, value));
+        self
+    }
+    pub fn with_rounding(mut self, window_rounding: f32, rounding: f32) -> Self {
+        self.window_rounding = window_rounding;
+        self.rounding = rounding;
+        self
+    }
+
+    /// Replaces the colors and rounding of an unscaled style
+    pub fn apply(&self, style: &mut Style) {
+        match self.base {
+            ThemeBase::Dark => style.use_dark_colors(),
+            ThemeBase::Light => style.use_light_colors(),
+            ThemeBase::Classic => style.use_classic_colors(),
+        };
+        for &(color, value) in &self.colors {
+            style[color] = value;
+        }
+        style.window_rounding = self.window_rounding;
+        for rounding in [
+            &mut style.child_rounding,
+            &mut style.popup_rounding,
+            &mut style.frame_rounding,
+            &mut style.scrollbar_rounding,
+            &mut style.grab_rounding,
+            &mut style.tab_rounding,
+        ] {
+            *rounding = self.rounding;
+        }
+    }
+}
+
+/// Why a theme file couldn't be read or written
+#[derive(Debug)]
+pub enum ThemeError {
+    /// A color name imgui doesn't know
+    UnknownColor(String),
+    #[cfg(feature = "serde")]
+    Json(serde_json::Error),
+    #[cfg(feature = "toml")]
+    TomlRead(toml::de::Error),
+    #[cfg(feature = "toml")]
+    TomlWrite(toml::ser::Error),
+}
+
+impl Error for ThemeError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        use self::ThemeError::*;
+        match self {
+            UnknownColor(_) => None,
+            #[cfg(feature = "serde")]
+            Json(e) => Some(e),
+            #[cfg(feature = "toml")]
+            TomlRead(e) => Some(e),
+            #[cfg(feature = "toml")]
+            TomlWrite(e) => Some(e),
+        }
+    }
+}
+
+impl fmt::Display for ThemeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use self::ThemeError::*;
+        match self {
+            UnknownColor(name) => write!(f, "Unknown style color {:?}", name),
+            #[cfg(feature = "serde")]
+            Json(e) => write!(f, "Invalid theme JSON: {}", e),
+            #[cfg(feature = "toml")]
+            TomlRead(e) => write!(f, "Invalid theme TOML: {}", e),
+            #[cfg(feature = "toml")]
+            TomlWrite(e) => write!(f, "Writing theme TOML failed: {}", e),
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl From<serde_json::Error> for ThemeError {
+    fn from(e: serde_json::Error) -> ThemeError {
+        ThemeError::Json(e)
+    }
+}
+
+#[cfg(feature = "toml")]
+impl From<toml::de::Error> for ThemeError {
+    fn from(e: toml::de::Error) -> ThemeError {
+        ThemeError::TomlRead(e)
+    }
+}
+
+#[cfg(feature = "toml")]
+impl From<toml::ser::Error> for ThemeError {
+    fn from(e: toml::ser::Error) -> ThemeError {
+        ThemeError::TomlWrite(e)
+    }
+}
+
+/// [`Theme`] as stored in files, colors by name
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+struct ThemeFile {
+    base: ThemeBase,
+    window_rounding: f32,
+    rounding: f32,
+    colors: BTreeMap<String, [f32; 4]>,
+}
+
+#[cfg(feature = "serde")]
+impl Default for ThemeFile {
+    fn default() -> Self {
+        ThemeFile::from(&Theme::default())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl From<&Theme> for ThemeFile {
+    fn from(theme: &Theme) -> Self {
+        Self {
+            base: theme.base,
+            window_rounding: theme.window_rounding,
+            rounding: theme.rounding,
+            colors: theme
+                .colors
+                .iter()
+                .map(|(color, value)| (color.name().to_string(), *value))
+                .collect(),
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<ThemeFile> for Theme {
+    type Error = ThemeError;
+
+    fn try_from(file: ThemeFile) -> Result<Self, ThemeError> {
+        let colors = file
+            .colors
+            .into_iter()
+            .map(|(name, value)| {
+                StyleColor::VARIANTS
+                    .into_iter()
+                    .find(|color| color.name() == name)
+                    .map(|color| (color, value))
+                    .ok_or(ThemeError::UnknownColor(name))
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Self {
+            base: file.base,
+            colors,
+            window_rounding: file.window_rounding,
+            rounding: file.rounding,
+        })
+    }
+}
+
+#[cfg(feature = "serde")]
+impl Theme {
+    /// Missing fields keep the [`dark`](Self::dark) theme's values
+    pub fn from_json(json: &str) -> Result<Self, ThemeError> {
+        serde_json::from_str::<ThemeFile>(json)?.try_into()
+    }
+    pub fn to_json(&self) -> Result<String, ThemeError> {
+        Ok(serde_json::to_string_pretty(&ThemeFile::from(self))?)
+    }
+}
+
+#[cfg(feature = "toml")]
+impl Theme {
+    /// Missing fields keep the [`dark`](Self::dark) theme's values
+    pub fn from_toml(toml: &str) -> Result<Self, ThemeError> {
+        toml::from_str::<ThemeFile>(toml)?.try_into()
+    }
+    pub fn to_toml(&self) -> Result<String, ThemeError> {
+        Ok(toml::to_string_pretty(&ThemeFile::from(self))?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::Harness;
+
+    /// Colors by name with the later of repeated colors, how they end up in a style
+    #[cfg(feature = "serde")]
+    fn colors(theme: &Theme) -> BTreeMap<&'static str, [f32; 4]> {
+        theme
+            .colors
+            .iter()
+            .map(|(color, value)| (color.name(), *value))
+            .collect()
+    }
+
+    #[test]
+    fn style_round_trip_keeps_the_theme() {
+        let mut harness = Harness::new();
+        let mut style = *harness.context().style();
+        Theme::bugsyth().apply(&mut style);
+        let theme = Theme::from_style(&style);
+        assert_eq!((theme.window_rounding, theme.rounding), (6.0, 4.0));
+        let mut again = *harness.context().style();
+        theme.apply(&mut again);
+        assert_eq!(again.colors, style.colors);
+        assert_eq!(again.frame_rounding, style.frame_rounding);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn json_round_trip_keeps_the_theme() {
+        let theme = Theme::bugsyth().with_color(StyleColor::Text, [0.9, 0.9, 0.8, 1.0]);
+        let loaded = Theme::from_json(&theme.to_json().unwrap()).unwrap();
+        assert_eq!(loaded.base, theme.base);
+        assert_eq!(colors(&loaded), colors(&theme));
+        assert_eq!(
+            (loaded.window_rounding, loaded.rounding),
+            (theme.window_rounding, theme.rounding)
+        );
+        assert!(matches!(
+            Theme::from_json(r#"{"colors": {"Nope": [0, 0, 0, 1]}}"#),
+            Err(ThemeError::UnknownColor(name)) if name == "Nope"
+        ));
+    }
+
+    #[cfg(feature = "toml")]
+    #[test]
+    fn toml_round_trip_keeps_the_theme() {
+        let theme = Theme::light().with_rounding(3.0, 2.0);
+        let loaded = Theme::from_toml(&theme.to_toml().unwrap()).unwrap();
+        assert_eq!(loaded, theme);
+        let theme = Theme::bugsyth();
+        let loaded = Theme::from_toml(&theme.to_toml().unwrap()).unwrap();
+        assert_eq!(colors(&loaded), colors(&theme));
+        // Missing fields keep the dark theme's values
+        let partial =
+            Theme::from_toml("window_rounding = 6.0\n[colors]\nWindowBg = [0.1, 0.1, 0.1, 1.0]\n")
+                .unwrap();
+        assert_eq!(partial.base, ThemeBase::Dark);
+        assert_eq!((partial.window_rounding, partial.rounding), (6.0, 0.0));
+    }
+}
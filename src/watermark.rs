@@ -0,0 +1,120 @@
+//! Text or image stamped over everything, e.g. "PREVIEW build 1234" on QA builds.
+//!
+//! Set [`ImGui::watermark`](crate::ImGui::watermark) and it's drawn every frame on the foreground
+//! draw list, above all windows.
+
+use imgui::{TextureId, Ui};
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum Corner {
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    #[default]
+    BottomRight,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum WatermarkContent {
+    /// `{name}` placeholders are replaced with the values from [`Watermark::var`], `{fps}` with the
+    /// current framerate
+    Text(String),
+    /// Texture drawn at `size` logical pixels
+    Texture { id: TextureId, size: [f32; 2] },
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Watermark {
+    pub content: WatermarkContent,
+    pub corner: Corner,
+    /// 0.0 to 1.0
+    pub opacity: f32,
+    /// Distance from the corner in logical pixels
+    pub margin: f32,
+    vars: Vec<(String, String)>,
+}
+
+impl Watermark {
+    pub fn text(text: impl Into<String>) -> Self {
+        Self::with_content(WatermarkContent::Text(text.into()))
+    }
+    pub fn texture(id: TextureId, size: [f32; 2]) -> Self {
+        Self::with_content(WatermarkContent::Texture { id, size })
+    }
+    fn with_content(content: WatermarkContent) -> Self {
+        Self {
+            content,
+            corner: Corner::default(),
+            opacity: 0.5,
+            margin: 8.0,
+            vars: Vec::new(),
+        }
+    }
+    pub fn corner(mut self, corner: Corner) -> Self {
+        self.corner = corner;
+        self
+    }
+    pub fn opacity(mut self, opacity: f32) -> Self {
+        self.opacity = opacity.clamp(0.0, 1.0);
+        self
+    }
+    pub fn margin(mut self, margin: f32) -> Self {
+        self.margin = margin;
+        self
+    }
+    /// Replaces `{name}` in the text with `value`, e.g. `var("build", env!("BUILD_ID"))`
+    pub fn var(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        let name = name.into();
+        let value = value.into();
+        match self.vars.iter_mut().find(|(n, _)| *n == name) {
+            Some((_, v)) => *v = value,
+            None => self.vars.push((name, value)),
+        }
+        self
+    }
+
+    /// Text with the placeholders substituted
+    pub fn resolve_text(&self, ui: &Ui) -> Option<String> {
+        let WatermarkContent::Text(text) = &self.content else {
+            return None;
+        };
+        let mut text = text.replace("{fps}", &format!("{:.0}", ui.io().framerate));
+        for (name, value) in &self.vars {
+            text = text.replace(&format!("{{{}}}", name), value);
+        }
+        Some(text)
+    }
+
+    /// Draws the watermark on the foreground draw list, done by [`ImGui`](crate::ImGui) when set
+    pub fn draw(&self, ui: &Ui) {
+        let text = self.resolve_text(ui);
+        let size = match (&self.content, &text) {
+            (WatermarkContent::Texture { size, .. }, _) => *size,
+            (_, Some(text)) => ui.calc_text_size(text),
+            _ => return,
+        };
+        let display_size = ui.io().display_size;
+        let x = match self.corner {
+            Corner::TopLeft | Corner::BottomLeft => self.margin,
+            Corner::TopRight | Corner::BottomRight => display_size[0] - self.margin - size[0],
+        };
+        let y = match self.corner {
+            Corner::TopLeft | Corner::TopRight => self.margin,
+            Corner::BottomLeft | Corner::BottomRight => display_size[1] - self.margin - size[1],
+        };
+        let pos = [x.floor(), y.floor()];
+        let draw_list = ui.get_foreground_draw_list();
+        match (&self.content, text) {
+            (WatermarkContent::Texture { id, .. }, _) => draw_list
+                .add_image(*id, pos, [pos[0] + size[0], pos[1] + size[1]])
+                .col([1.0, 1.0, 1.0, self.opacity])
+                .build(),
+            (_, Some(text)) => {
+                let mut color = ui.style_color(imgui::StyleColor::Text);
+                color[3] *= self.opacity;
+                draw_list.add_text(pos, color, text);
+            }
+            _ => (),
+        }
+    }
+}
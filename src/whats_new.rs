@@ -0,0 +1,259 @@
+//! "What's new" popup and update notification.
+//!
+//! [`WhatsNew`] shows the release notes of the running version once, the first time it starts,
+//! and remembers that in the [`Session`]. With the `update-check` feature it can also fetch a
+//! version manifest and show a badge in the menu bar when a newer version is out.
+//!
+//! Release notes come from a markdown changelog with a `## <version>` heading per release.
+//! The manifest is plain text, `key = value` lines for `version` and optionally `url`, then a
+//! blank line and the release notes of that version as markdown.
+
+use crate::settings::Session;
+use crate::widgets::markdown;
+use imgui::{Condition, Ui};
+use std::cmp::Ordering;
+use std::fs;
+use std::io;
+use std::path::Path;
+#[cfg(feature = "update-check")]
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+
+const SEEN_KEY: &str = "whats_new/seen_version";
+
+/// Latest release as described by a version manifest
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Release {
+    pub version: String,
+    /// Download page
+    pub url: Option<String>,
+    pub notes: String,
+}
+
+impl Release {
+    /// Parses a version manifest, `None` without a `version` line
+    pub fn parse_manifest(manifest: &str) -> Option<Release> {
+        let (head, notes) = manifest
+            .split_once("\n\n")
+            .or_else(|| manifest.split_once("\r\n\r\n"))
+            .unwrap_or((manifest, ""));
+        let mut release = Release {
+            notes: notes.trim().to_string(),
+            ..Release::default()
+        };
+        for line in head.lines() {
+            match line.split_once('=').map(|(k, v)| (k.trim(), v.trim())) {
+                Some(("version", version)) => release.version = version.to_string(),
+                Some(("url", url)) => release.url = Some(url.to_string()),
+                _ => (),
+            }
+        }
+        (!release.version.is_empty()).then_some(release)
+    }
+}
+
+/// Compares dotted versions numerically, e.g. `0.10.0` > `0.9.3`. A leading `v` and
+/// pre-release suffixes (`-beta`) are ignored.
+pub fn compare_versions(a: &str, b: &str) -> Ordering {
+    fn parts(version: &str) -> Vec<u64> {
+        version
+            .trim()
+            .trim_start_matches('v')
+            .split(['-', '+'])
+            .next()
+            .unwrap_or_default()
+            .split('.')
+            .map(|part| part.parse().unwrap_or(0))
+            .collect()
+    }
+    let (a, b) = (parts(a), parts(b));
+    (0..a.len().max(b.len()))
+        .map(|i| {
+            a.get(i)
+                .copied()
+                .unwrap_or(0)
+                .cmp(&b.get(i).copied().unwrap_or(0))
+        })
+        .find(|ordering| ordering.is_ne())
+        .unwrap_or(Ordering::Equal)
+}
+
+/// Section of a markdown changelog under the `## <version>` heading, brackets and a leading
+/// `v` around the version are allowed
+pub fn changelog_section(changelog: &str, version: &str) -> Option<String> {
+    let mut section: Option<Vec<&str>> = None;
+    for line in changelog.lines() {
+        if let Some(heading) = line.strip_prefix("## ") {
+            if section.is_some() {
+                break;
+            }
+            let heading_version = heading
+                .split_whitespace()
+                .next()
+                .unwrap_or_default()
+                .trim_matches(|c| c == '[' || c == ']')
+                .trim_start_matches('v');
+            if heading_version == version.trim_start_matches('v') {
+                section = Some(Vec::new());
+            }
+        } else if let Some(section) = &mut section {
+            section.push(line);
+        }
+    }
+    section.map(|lines| lines.join("\n").trim().to_string())
+}
+
+/// Release notes popup and update badge
+#[derive(Debug)]
+pub struct WhatsNew {
+    current_version: String,
+    notes: Option<String>,
+    show_notes: bool,
+    available: Option<Release>,
+    show_update: bool,
+    #[cfg(feature = "update-check")]
+    pending: Option<Receiver<Result<String, String>>>,
+    error: Option<String>,
+}
+
+impl WhatsNew {
+    /// `current_version` is the running version, e.g. `env!("CARGO_PKG_VERSION")`
+    pub fn new(current_version: impl Into<String>) -> Self {
+        Self {
+            current_version: current_version.into(),
+            notes: None,
+            show_notes: false,
+            available: None,
+            show_update: false,
+            #[cfg(feature = "update-check")]
+            pending: None,
+            error: None,
+        }
+    }
+    /// Takes the notes of the current version from a markdown changelog
+    pub fn with_changelog(mut self, changelog: &str) -> Self {
+        self.notes = changelog_section(changelog, &self.current_version);
+        self
+    }
+    /// Same as [`with_changelog`](Self::with_changelog) with a changelog file
+    pub fn with_changelog_file(self, path: impl AsRef<Path>) -> io::Result<Self> {
+        let changelog = fs::read_to_string(path)?;
+        Ok(self.with_changelog(&changelog))
+    }
+    /// Compares a fetched manifest against the current version
+    pub fn set_manifest(&mut self, manifest: &str) {
+        self.available = Release::parse_manifest(manifest).filter(|release| {
+            compare_versions(&release.version, &self.current_version) == Ordering::Greater
+        });
+    }
+    /// Fetches the manifest from an `http://` or `https://` URL on a background thread, the
+    /// result is picked up by the draw functions
+    #[cfg(feature = "update-check")]
+    pub fn check_for_updates(&mut self, manifest_url: impl Into<String>) {
+        let url = manifest_url.into();
+        let (sender, receiver) = mpsc::channel();
+        std::thread::spawn(move || {
+            let result = crate::http::request("GET", &url, &[], &[])
+                .map(|body| String::from_utf8_lossy(&body).into_owned());
+            let _ = sender.send(result);
+        });
+        self.pending = Some(receiver);
+    }
+    /// Newer release found by the last check
+    pub fn update_available(&self) -> Option<&Release> {
+        self.available.as_ref()
+    }
+    /// Error of the last update check
+    pub fn error(&self) -> Option<&str> {
+        self.error.as_deref()
+    }
+    /// Shows the release notes again, e.g. from a Help menu
+    pub fn show_notes(&mut self) {
+        self.show_notes = self.notes.is_some();
+    }
+
+    #[cfg(feature = "update-check")]
+    fn poll(&mut self) {
+        let Some(receiver) = &self.pending else {
+            return;
+        };
+        match receiver.try_recv() {
+            Ok(Ok(manifest)) => {
+                self.error = None;
+                self.set_manifest(&manifest);
+                self.pending = None;
+            }
+            Ok(Err(e)) => {
+                self.error = Some(e);
+                self.pending = None;
+            }
+            Err(TryRecvError::Disconnected) => self.pending = None,
+            Err(TryRecvError::Empty) => (),
+        }
+    }
+
+    /// Draws the popups, the release notes open by themselves once per version.
+    ///
+    /// `session` remembers the last version whose notes were shown.
+    pub fn draw(&mut self, ui: &Ui, session: &mut Session) {
+        #[cfg(feature = "update-check")]
+        self.poll();
+        if self.notes.is_some() && session.get(SEEN_KEY) != Some(&self.current_version) {
+            session.set(SEEN_KEY, self.current_version.clone());
+            self.show_notes = true;
+        }
+        if self.show_notes {
+            let mut opened = true;
+            let title = format!("What's new in {}", self.current_version);
+            ui.window(title)
+                .opened(&mut opened)
+                .size([480.0, 360.0], Condition::FirstUseEver)
+                .position_pivot([0.5, 0.5])
+                .position(
+                    [ui.io().display_size[0] * 0.5, ui.io().display_size[1] * 0.5],
+                    Condition::FirstUseEver,
+                )
+                .build(|| {
+                    if let Some(notes) = &self.notes {
+                        markdown(ui, notes);
+                    }
+                });
+            self.show_notes = opened;
+        }
+        if self.show_update {
+            let mut opened = true;
+            if let Some(release) = &self.available {
+                ui.window("Update available")
+                    .opened(&mut opened)
+                    .size([480.0, 360.0], Condition::FirstUseEver)
+                    .build(|| {
+                        ui.text(format!(
+                            "Version {} is available, you have {}",
+                            release.version, self.current_version
+                        ));
+                        if let Some(url) = &release.url {
+                            ui.text_disabled(url);
+                            ui.same_line();
+                            if ui.small_button("Copy link") {
+                                ui.set_clipboard_text(url);
+                            }
+                        }
+                        ui.separator();
+                        markdown(ui, &release.notes);
+                    });
+            }
+            self.show_update = opened && self.available.is_some();
+        }
+    }
+
+    /// Draws the update badge, call inside a menu bar. Clicking it shows the new release.
+    pub fn draw_menu_bar_badge(&mut self, ui: &Ui) {
+        let Some(release) = &self.available else {
+            return;
+        };
+        let label = format!("Update {} available", release.version);
+        let _color = ui.push_style_color(imgui::StyleColor::Text, [0.4, 1.0, 0.4, 1.0]);
+        if ui.menu_item(label) {
+            self.show_update = true;
+        }
+    }
+}
@@ -0,0 +1,43 @@
+//! Extra widgets built on top of imgui-rs
+
+mod autocomplete;
+mod camera;
+mod context_menu;
+mod debug_overlay;
+mod dial;
+mod diff;
+mod eyedropper;
+mod history;
+mod inline_edit;
+mod input_debug;
+#[cfg(feature = "log")]
+mod log_window;
+mod markdown;
+mod math;
+mod shader_editor;
+mod texture_preview;
+mod texture_viewer;
+#[cfg(feature = "serde")]
+mod value_editor;
+mod virtual_keyboard;
+
+pub use autocomplete::{input_text_autocomplete, Autocomplete};
+pub use camera::camera_inspector;
+pub use context_menu::{ContextMenu, ContextMenuItem};
+pub use debug_overlay::{debug_overlay, DebugOverlay, FRAME_TIME_SAMPLES};
+pub use dial::{angle_dial, direction_picker};
+pub use diff::{diff_view, DiffAction, DiffRow, TextDiff};
+pub use eyedropper::{color_edit_eyedropper, ColorSpace, Eyedropper};
+pub use history::{input_history, input_history_multiline, InputHistory};
+pub use inline_edit::{selectable_rename, InlineEdit, RenameEvent};
+pub use input_debug::input_debug;
+#[cfg(feature = "log")]
+pub use log_window::log_window;
+pub use markdown::markdown;
+pub use math::{drag_quat, drag_vec2, drag_vec3, drag_vec4, mat4_table};
+pub use shader_editor::{ShaderEditor, ShaderSource};
+pub use texture_preview::{ArrayPreview, CubemapPreview};
+pub use texture_viewer::{texture_viewer, TextureViewer};
+#[cfg(feature = "serde")]
+pub use value_editor::value_editor;
+pub use virtual_keyboard::{KeyboardTheme, VirtualKeyboard};
@@ -0,0 +1,323 @@
+use super::InputHistory;
+use imgui::{
+    sys, HistoryDirection, InputTextCallback, InputTextCallbackHandler, ItemHoveredFlags,
+    MouseButton, TextCallbackData, Ui, WindowFlags, WindowHoveredFlags,
+};
+use std::ffi::CStr;
+use std::ptr;
+
+/// Max height of the suggestion list before it starts scrolling
+const POPUP_MAX_HEIGHT: f32 = 200.0;
+/// Popup of the suggestions, made unique by the label pushed on the ID stack
+const POPUP_ID: &CStr = c"##autocomplete";
+
+/// State kept between frames by [`input_text_autocomplete`]
+#[derive(Debug, Default)]
+pub struct Autocomplete {
+    history: InputHistory,
+    suggestions: Vec<String>,
+    selected: Option<usize>,
+    query: Option<String>,
+    dismissed: bool,
+    popup_hovered: bool,
+    refocus: bool,
+    cursor_to_end: bool,
+}
+
+impl Autocomplete {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Uses an existing history, e.g. one loaded from disk
+    pub fn with_history(mut self, history: InputHistory) -> Self {
+        self.history = history;
+        self
+    }
+    /// Submitted entries
+    pub fn history(&self) -> &InputHistory {
+        &self.history
+    }
+    pub fn history_mut(&mut self) -> &mut InputHistory {
+        &mut self.history
+    }
+    /// Suggestions currently shown in the popup
+    pub fn suggestions(&self) -> &[String] {
+        &self.suggestions
+    }
+    /// Forces the provider to be queried again on the next frame
+    pub fn refresh(&mut self) {
+        self.query = None;
+    }
+
+    fn popup_open(&self) -> bool {
+        !self.dismissed && !self.suggestions.is_empty()
+    }
+    fn accept(&mut self, index: usize) -> Option<String> {
+        let suggestion = self.suggestions.get(index)?.clone();
+        self.dismissed = true;
+        self.selected = None;
+        Some(suggestion)
+    }
+}
+
+struct Callbacks<'a> {
+    state: &'a mut Autocomplete,
+    popup_open: bool,
+}
+
+fn replace_text(data: &mut TextCallbackData, text: &str) {
+    data.clear();
+    data.push_str(text);
+}
+
+impl InputTextCallbackHandler for Callbacks<'_> {
+    fn on_completion(&mut self, mut data: TextCallbackData) {
+        if !self.popup_open {
+            return;
+        }
+        if let Some(suggestion) = self.state.accept(self.state.selected.unwrap_or(0)) {
+            replace_text(&mut data, &suggestion);
+        }
+    }
+
+    fn on_history(&mut self, dir: HistoryDirection, mut data: TextCallbackData) {
+        let state = &mut *self.state;
+        if self.popup_open {
+            let last = state.suggestions.len() - 1;
+            state.selected = Some(match (dir, state.selected) {
+                (HistoryDirection::Down, None) => 0,
+                (HistoryDirection::Down, Some(i)) => (i + 1).min(last),
+                (HistoryDirection::Up, None) | (HistoryDirection::Up, Some(0)) => 0,
+                (HistoryDirection::Up, Some(i)) => i - 1,
+            });
+            return;
+        }
+        if let Some(entry) = state.history.navigate(dir) {
+            replace_text(&mut data, entry);
+        }
+        // Recalled entries shouldn't immediately pop the suggestion list up
+        state.dismissed = true;
+    }
+
+    fn on_edit(&mut self, _: TextCallbackData) {
+        self.state.history.reset_cursor();
+        self.state.dismissed = false;
+    }
+
+    fn on_always(&mut self, mut data: TextCallbackData) {
+        if self.state.cursor_to_end {
+            self.state.cursor_to_end = false;
+            data.clear_selection();
+            let end = data.str().len();
+            data.set_cursor_pos(end);
+        }
+    }
+}
+
+/// Text input with a suggestion popup fed by `provider`.
+///
+/// The provider is called with the current text whenever it changes and returns the suggestions
+/// to show. Up/Down move through the suggestions while the popup is open, or through the history
+/// otherwise. Tab accepts the highlighted (or first) suggestion, Enter accepts the highlighted one.
+///
+/// Returns `true` when the text was submitted with Enter, the submitted text is also added to the
+/// history.
+pub fn input_text_autocomplete<F>(
+    ui: &Ui,
+    label: &str,
+    buf: &mut String,
+    state: &mut Autocomplete,
+    mut provider: F,
+) -> bool
+where
+    F: FnMut(&str) -> Vec<String>,
+{
+    if state.refocus {
+        state.refocus = false;
+        state.cursor_to_end = true;
+        ui.set_keyboard_focus_here();
+    }
+
+    let popup_open = state.popup_open();
+    let entered = ui
+        .input_text(label, buf)
+        .enter_returns_true(true)
+        .callback(
+            InputTextCallback::COMPLETION
+                | InputTextCallback::HISTORY
+                | InputTextCallback::EDIT
+                | InputTextCallback::ALWAYS,
+            Callbacks { state, popup_open },
+        )
+        .build();
+    let active = ui.is_item_active();
+
+    if state.query.as_deref() != Some(buf.as_str()) {
+        state.suggestions = if buf.is_empty() {
+            Vec::new()
+        } else {
+            provider(buf)
+        };
+        state.selected = None;
+        state.query = Some(buf.clone());
+    }
+
+    if entered {
+        if let Some(suggestion) = state.selected.and_then(|i| state.accept(i)) {
+            *buf = suggestion;
+            state.query = Some(buf.clone());
+            state.refocus = true;
+            return false;
+        }
+        state.history.push(buf.clone());
+        state.dismissed = true;
+        return true;
+    }
+
+    let _id = ui.push_id(label);
+    let show = (active || state.popup_hovered) && state.popup_open();
+    if show && !unsafe { sys::igIsPopupOpen(POPUP_ID.as_ptr(), 0) } {
+        unsafe { sys::igOpenPopup_Str(POPUP_ID.as_ptr(), 0) };
+    }
+    let min = ui.item_rect_min();
+    let max = ui.item_rect_max();
+    // Not focusing the popup keeps the keyboard on the text input
+    let flags = WindowFlags::NO_DECORATION
+        | WindowFlags::ALWAYS_AUTO_RESIZE
+        | WindowFlags::NO_SAVED_SETTINGS
+        | WindowFlags::NO_FOCUS_ON_APPEARING;
+    let begun = unsafe {
+        sys::igSetNextWindowPos(
+            sys::ImVec2::new(min[0], max[1]),
+            sys::ImGuiCond_Always as i32,
+            sys::ImVec2::zero(),
+        );
+        sys::igSetNextWindowSizeConstraints(
+            sys::ImVec2::new(max[0] - min[0], 0.0),
+            sys::ImVec2::new(f32::MAX, POPUP_MAX_HEIGHT),
+            None,
+            ptr::null_mut(),
+        );
+        sys::igBeginPopup(POPUP_ID.as_ptr(), flags.bits() as i32)
+    };
+    if !begun {
+        state.popup_hovered = false;
+        return false;
+    }
+    let mut clicked = None;
+    if show {
+        for (i, suggestion) in state.suggestions.iter().enumerate() {
+            let selected = state.selected == Some(i);
+            // The text input stays active until the click lands, which blocks the usual hover
+            let pressed = ui.selectable_config(suggestion).selected(selected).build()
+                || (ui.is_item_hovered_with_flags(
+                    ItemHoveredFlags::ALLOW_WHEN_BLOCKED_BY_ACTIVE_ITEM,
+                ) && ui.is_mouse_clicked(MouseButton::Left));
+            if pressed {
+                clicked = Some(i);
+            }
+            if selected {
+                ui.set_scroll_here_y();
+            }
+        }
+        state.popup_hovered =
+            ui.is_window_hovered_with_flags(WindowHoveredFlags::ALLOW_WHEN_BLOCKED_BY_ACTIVE_ITEM);
+    } else {
+        state.popup_hovered = false;
+        ui.close_current_popup();
+    }
+    unsafe { sys::igEndPopup() };
+
+    if let Some(suggestion) = clicked.and_then(|i| state.accept(i)) {
+        *buf = suggestion;
+        state.query = Some(buf.clone());
+        state.popup_hovered = false;
+        state.refocus = true;
+    }
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::Harness;
+    use imgui::{Condition, MouseButton};
+
+    fn fruits(text: &str) -> Vec<String> {
+        ["apple", "apricot", "banana"]
+            .into_iter()
+            .filter(|fruit| fruit.starts_with(text))
+            .map(String::from)
+            .collect()
+    }
+
+    #[test]
+    fn list_leaves_the_keyboard_on_the_input() {
+        let mut harness = Harness::new();
+        let mut buf = String::new();
+        let mut state = Autocomplete::new();
+        let mut build = |ui: &Ui| {
+            ui.window("Owner")
+                .position([0.0, 0.0], Condition::Always)
+                .build(|| {
+                    input_text_autocomplete(ui, "Fruit", &mut buf, &mut state, fruits);
+                });
+        };
+        harness.run(2, &mut build);
+        harness.click([20.0, 30.0], MouseButton::Left, &mut build);
+        harness.io_mut().add_input_character('a');
+        harness.run(2, &mut build);
+        harness.io_mut().add_input_character('p');
+        harness.run(2, &mut build);
+        assert_eq!(buf, "ap");
+        assert_eq!(state.suggestions(), ["apple", "apricot"]);
+    }
+
+    #[test]
+    fn label_with_nul_opens_the_list() {
+        let mut harness = Harness::new();
+        let mut buf = String::new();
+        let mut state = Autocomplete::new();
+        let mut build = |ui: &Ui| {
+            ui.window("Owner")
+                .position([0.0, 0.0], Condition::Always)
+                .build(|| {
+                    input_text_autocomplete(ui, "Fr\0uit", &mut buf, &mut state, fruits);
+                });
+        };
+        harness.run(2, &mut build);
+        harness.click([20.0, 30.0], MouseButton::Left, &mut build);
+        harness.io_mut().add_input_character('b');
+        harness.run(2, &mut build);
+        assert_eq!(buf, "b");
+        assert_eq!(state.suggestions(), ["banana"]);
+    }
+
+    #[test]
+    fn clicked_suggestion_fills_the_input() {
+        let mut harness = Harness::new();
+        let mut buf = String::new();
+        let mut state = Autocomplete::new();
+        let mut build = |ui: &Ui| {
+            ui.window("Owner")
+                .position([0.0, 0.0], Condition::Always)
+                .size([300.0, 200.0], Condition::Always)
+                .build(|| {
+                    input_text_autocomplete(ui, "Fruit", &mut buf, &mut state, fruits);
+                });
+        };
+        harness.run(2, &mut build);
+        harness.click([20.0, 30.0], MouseButton::Left, &mut build);
+        harness.io_mut().add_input_character('a');
+        harness.run(2, &mut build);
+        // Clicking the input again focuses its window, the list has to stay on top of it
+        harness.click([40.0, 30.0], MouseButton::Left, &mut build);
+        harness.run(2, &mut build);
+        // Hovering the list first keeps it open while the click takes focus from the input
+        harness.move_mouse([20.0, 60.0]);
+        harness.frame(&mut build);
+        harness.click([20.0, 60.0], MouseButton::Left, &mut build);
+        harness.run(2, &mut build);
+        assert_eq!(buf, "apple");
+    }
+}
@@ -0,0 +1,98 @@
+use super::drag_vec3;
+use bugsyth_engine::context::camera::CameraState;
+use bugsyth_engine::math::Vec3;
+use imgui::{Drag, Ui};
+
+/// Pitch is kept just short of straight up or down, where the view's up vector breaks down
+const PITCH_LIMIT: f32 = 89.0;
+
+/// Direction the camera looks in, the same way [`CameraState::update`] places the target
+fn forward(camera: &CameraState) -> Vec3<f32> {
+    Vec3::new(
+        camera.yaw.cos() * camera.pitch.cos(),
+        camera.pitch.sin(),
+        camera.yaw.sin() * camera.pitch.cos(),
+    )
+}
+
+/// Rust that recreates the camera, for pasting a tweaked view back into the game
+fn to_code(camera: &CameraState) -> String {
+    let vec3 = |v: Vec3<f32>| format!("Vec3::new({:?}, {:?}, {:?})", v.x, v.y, v.z);
+    format!(
+        "let mut camera = CameraState::new(\n    {},\n    {},\n    {},\n    {:?},\n    {:?},\n    {:?},\n    {:?},\n);\ncamera.yaw = {:?};\ncamera.pitch = {:?};\n",
+        vec3(camera.position),
+        vec3(camera.position + forward(camera)),
+        vec3(camera.up),
+        camera.fov,
+        camera.aspect_ratio,
+        camera.near,
+        camera.far,
+        camera.yaw,
+        camera.pitch,
+    )
+}
+
+/// Shows and edits the engine camera: position, yaw and pitch, field of view and clip planes.
+/// "Copy as code" puts Rust that recreates the camera on the clipboard.
+///
+/// Angles are shown in degrees. The camera's target is moved along with the edits, so the view
+/// changes even if the game doesn't call [`CameraState::update`]. Returns `true` when something
+/// changed.
+pub fn camera_inspector(ui: &Ui, camera: &mut CameraState) -> bool {
+    let _id = ui.push_id("camera_inspector");
+    let mut changed = drag_vec3(ui, "Position", &mut camera.position);
+
+    let mut yaw = camera.yaw.to_degrees();
+    if Drag::new("Yaw")
+        .speed(0.5)
+        .display_format("%.1f\u{b0}")
+        .build(ui, &mut yaw)
+    {
+        camera.yaw = yaw.to_radians();
+        changed = true;
+    }
+    let mut pitch = camera.pitch.to_degrees();
+    if ui
+        .slider_config("Pitch", -PITCH_LIMIT, PITCH_LIMIT)
+        .display_format("%.1f\u{b0}")
+        .build(&mut pitch)
+    {
+        camera.pitch = pitch.to_radians();
+        changed = true;
+    }
+    let mut fov = camera.fov.to_degrees();
+    if ui
+        .slider_config("FOV", 10.0, 150.0)
+        .display_format("%.1f\u{b0}")
+        .build(&mut fov)
+    {
+        camera.fov = fov.to_radians();
+        changed = true;
+    }
+    changed |= Drag::new("Near")
+        .speed(0.001)
+        .range(0.0001, camera.far)
+        .build(ui, &mut camera.near);
+    changed |= Drag::new("Far")
+        .speed(1.0)
+        .range(camera.near, f32::MAX)
+        .build(ui, &mut camera.far);
+    changed |= Drag::new("Aspect ratio")
+        .speed(0.01)
+        .range(0.01, 100.0)
+        .build(ui, &mut camera.aspect_ratio);
+    changed |= drag_vec3(ui, "Up", &mut camera.up);
+
+    let direction = forward(camera);
+    ui.text_disabled(format!(
+        "Looking at {:.2}, {:.2}, {:.2}",
+        direction.x, direction.y, direction.z
+    ));
+    if ui.button("Copy as code") {
+        ui.set_clipboard_text(to_code(camera));
+    }
+    if changed {
+        camera.update();
+    }
+    changed
+}
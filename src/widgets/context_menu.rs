@@ -0,0 +1,172 @@
+use crate::Shortcut;
+use imgui::{MouseButton, Ui};
+
+/// Item of a [`ContextMenu`], reports `value` when activated
+#[derive(Debug, Clone)]
+pub struct ContextMenuItem<T> {
+    label: String,
+    value: T,
+    shortcut: Option<Shortcut>,
+    enabled: bool,
+    checked: Option<bool>,
+}
+
+impl<T> ContextMenuItem<T> {
+    pub fn new(label: impl Into<String>, value: T) -> Self {
+        Self {
+            label: label.into(),
+            value,
+            shortcut: None,
+            enabled: true,
+            checked: None,
+        }
+    }
+    /// Shows the shortcut next to the label, see [`ContextMenu::handle_shortcuts`]
+    pub fn shortcut(mut self, shortcut: Shortcut) -> Self {
+        self.shortcut = Some(shortcut);
+        self
+    }
+    pub fn enabled(mut self, enabled: bool) -> Self {
+        self.enabled = enabled;
+        self
+    }
+    /// Draws a check mark next to the item
+    pub fn checked(mut self, checked: bool) -> Self {
+        self.checked = Some(checked);
+        self
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Entry<T> {
+    Item(ContextMenuItem<T>),
+    Separator,
+    Submenu {
+        label: String,
+        enabled: bool,
+        menu: ContextMenu<T>,
+    },
+}
+
+/// Declarative right-click menu.
+///
+/// Items carry a value that's returned from the `show_*` functions when they're activated, so
+/// handling the result doesn't need closures borrowing the surrounding state.
+#[derive(Debug, Clone)]
+pub struct ContextMenu<T> {
+    id: String,
+    entries: Vec<Entry<T>>,
+}
+
+impl<T: Clone> ContextMenu<T> {
+    /// `id` is the popup id, it only has to be unique within the current ID scope
+    pub fn new(id: impl Into<String>) -> Self {
+        Self {
+            id: id.into(),
+            entries: Vec::new(),
+        }
+    }
+    pub fn item(self, label: impl Into<String>, value: T) -> Self {
+        self.item_with(ContextMenuItem::new(label, value))
+    }
+    pub fn item_with(mut self, item: ContextMenuItem<T>) -> Self {
+        self.entries.push(Entry::Item(item));
+        self
+    }
+    pub fn separator(mut self) -> Self {
+        self.entries.push(Entry::Separator);
+        self
+    }
+    pub fn submenu(self, label: impl Into<String>, menu: ContextMenu<T>) -> Self {
+        self.submenu_with_enabled(label, true, menu)
+    }
+    pub fn submenu_with_enabled(
+        mut self,
+        label: impl Into<String>,
+        enabled: bool,
+        menu: ContextMenu<T>,
+    ) -> Self {
+        self.entries.push(Entry::Submenu {
+            label: label.into(),
+            enabled,
+            menu,
+        });
+        self
+    }
+
+    /// Opens the menu when the last item is right-clicked
+    pub fn show_for_item(&self, ui: &Ui) -> Option<T> {
+        let _popup = ui.begin_popup_context_with_label(&self.id)?;
+        self.draw_entries(ui)
+    }
+    /// Opens the menu when the current window is right-clicked
+    pub fn show_for_window(&self, ui: &Ui) -> Option<T> {
+        let _popup = ui.begin_popup_context_window_with_label(&self.id)?;
+        self.draw_entries(ui)
+    }
+    /// Opens the menu when empty space outside of any window is right-clicked
+    pub fn show_for_void(&self, ui: &Ui) -> Option<T> {
+        let _popup = ui.begin_popup_context_void_with_label(&self.id)?;
+        self.draw_entries(ui)
+    }
+    /// Opens the menu when a screen-space region of the current window is right-clicked, e.g. a
+    /// game viewport panel
+    pub fn show_for_rect(&self, ui: &Ui, min: [f32; 2], max: [f32; 2]) -> Option<T> {
+        if ui.is_mouse_clicked(MouseButton::Right)
+            && ui.is_mouse_hovering_rect(min, max)
+            && ui.is_window_hovered()
+        {
+            ui.open_popup(&self.id);
+        }
+        let _popup = ui.begin_popup(&self.id)?;
+        self.draw_entries(ui)
+    }
+
+    /// Returns the value of the first enabled item whose shortcut was pressed this frame, also
+    /// looking into submenus
+    pub fn handle_shortcuts(&self, ui: &Ui) -> Option<T> {
+        self.entries.iter().find_map(|entry| match entry {
+            Entry::Item(item) if item.enabled => item
+                .shortcut
+                .filter(|shortcut| shortcut.is_pressed(ui))
+                .map(|_| item.value.clone()),
+            Entry::Submenu {
+                enabled: true,
+                menu,
+                ..
+            } => menu.handle_shortcuts(ui),
+            _ => None,
+        })
+    }
+
+    fn draw_entries(&self, ui: &Ui) -> Option<T> {
+        let mut activated = None;
+        for entry in &self.entries {
+            match entry {
+                Entry::Item(item) => {
+                    let shortcut = item.shortcut.map(|s| s.to_string()).unwrap_or_default();
+                    if ui
+                        .menu_item_config(&item.label)
+                        .shortcut(shortcut)
+                        .selected(item.checked.unwrap_or(false))
+                        .enabled(item.enabled)
+                        .build()
+                    {
+                        activated = Some(item.value.clone());
+                    }
+                }
+                Entry::Separator => ui.separator(),
+                Entry::Submenu {
+                    label,
+                    enabled,
+                    menu,
+                } => {
+                    if let Some(_menu) = ui.begin_menu_with_enabled(label, *enabled) {
+                        activated = activated.or(menu.draw_entries(ui));
+                    }
+                }
+            }
+        }
+        activated
+    }
+}
@@ -0,0 +1,115 @@
+use crate::watermark::Corner;
+use crate::{FrameStats, FrameTimer};
+use bugsyth_engine::context::Context;
+use imgui::{Condition, Ui, WindowFlags};
+
+/// Frame times kept by [`DebugOverlay::new`], two seconds at 60 FPS
+pub const FRAME_TIME_SAMPLES: usize = 120;
+
+/// State of a [`debug_overlay`], the recent frame times and the renderer's stats
+#[derive(Clone, Debug)]
+pub struct DebugOverlay {
+    pub corner: Corner,
+    /// Distance from the corner in logical pixels
+    pub margin: f32,
+    timer: FrameTimer,
+    stats: Option<FrameStats>,
+}
+
+impl Default for DebugOverlay {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl DebugOverlay {
+    pub fn new() -> Self {
+        Self::with_samples(FRAME_TIME_SAMPLES)
+    }
+    /// Keeps the last `samples` frame times for the graph
+    pub fn with_samples(samples: usize) -> Self {
+        Self {
+            corner: Corner::TopRight,
+            margin: 8.0,
+            timer: FrameTimer::with_window(samples),
+            stats: None,
+        }
+    }
+    pub fn corner(mut self, corner: Corner) -> Self {
+        self.corner = corner;
+        self
+    }
+
+    /// Adds a frame time in seconds, done by [`debug_overlay`] with the engine's `dt`
+    pub fn record(&mut self, dt: f32) {
+        self.timer.record(dt);
+    }
+    /// The recorded frame times
+    pub fn timer(&self) -> &FrameTimer {
+        &self.timer
+    }
+    /// Draw calls of the last frame, pass
+    /// [`Renderer::last_frame_stats`](crate::Renderer::last_frame_stats) before building the UI
+    pub fn set_frame_stats(&mut self, stats: FrameStats) {
+        self.stats = Some(stats);
+    }
+    /// Average and worst frame time of the recorded frames in milliseconds
+    pub fn frame_time(&self) -> (f32, f32) {
+        (self.timer.average(), self.timer.worst())
+    }
+}
+
+/// Corner overlay with the FPS, a frame time graph and imgui's vertex, index and draw call
+/// counts of the last frame. Call once per frame.
+pub fn debug_overlay(ui: &Ui, overlay: &mut DebugOverlay, ctx: &Context) {
+    overlay.record(ctx.dt);
+    let display_size = ui.io().display_size;
+    let (pivot, x, y) = match overlay.corner {
+        Corner::TopLeft => ([0.0, 0.0], overlay.margin, overlay.margin),
+        Corner::TopRight => ([1.0, 0.0], display_size[0] - overlay.margin, overlay.margin),
+        Corner::BottomLeft => ([0.0, 1.0], overlay.margin, display_size[1] - overlay.margin),
+        Corner::BottomRight => (
+            [1.0, 1.0],
+            display_size[0] - overlay.margin,
+            display_size[1] - overlay.margin,
+        ),
+    };
+    ui.window("##debug_overlay")
+        .position([x, y], Condition::Always)
+        .position_pivot(pivot)
+        .bg_alpha(0.35)
+        .flags(
+            WindowFlags::NO_DECORATION
+                | WindowFlags::ALWAYS_AUTO_RESIZE
+                | WindowFlags::NO_SAVED_SETTINGS
+                | WindowFlags::NO_FOCUS_ON_APPEARING
+                | WindowFlags::NO_NAV
+                | WindowFlags::NO_INPUTS,
+        )
+        .build(|| {
+            let (average, worst) = overlay.frame_time();
+            ui.text(format!(
+                "{:.0} FPS, 1% low {:.0}",
+                overlay.timer.fps(),
+                overlay.timer.one_percent_low()
+            ));
+            ui.text(format!("{:.2} ms avg, {:.2} ms worst", average, worst));
+            ui.plot_lines("##frame_times", overlay.timer.history())
+                .scale_min(0.0)
+                .scale_max(worst.max(1000.0 / 30.0))
+                .graph_size([200.0, 40.0])
+                .build();
+            let io = ui.io();
+            ui.text(format!(
+                "{} vertices, {} indices",
+                io.metrics_render_vertices, io.metrics_render_indices
+            ));
+            match overlay.stats {
+                Some(stats) => ui.text(format!(
+                    "{} draw calls in {} lists",
+                    stats.draw_calls, stats.draw_lists
+                )),
+                None => ui.text(format!("{} windows", io.metrics_render_windows)),
+            }
+        });
+}
@@ -0,0 +1,145 @@
+use bugsyth_engine::math::Vec2;
+use imgui::{Key, MouseButton, StyleColor, Ui};
+use std::f32::consts::PI;
+
+/// Angle step while Shift is held, 15 degrees
+const SNAP: f32 = PI / 12.0;
+
+/// Center and radius of a round widget, the label goes to the right like other widgets
+fn disc(ui: &Ui, label: &str) -> ([f32; 2], f32, bool) {
+    let radius = ui.frame_height() * 1.25;
+    let pos = ui.cursor_screen_pos();
+    ui.invisible_button(label, [radius * 2.0, radius * 2.0]);
+    let center = [pos[0] + radius, pos[1] + radius];
+    let draw_list = ui.get_window_draw_list();
+    let background = if ui.is_item_active() {
+        StyleColor::FrameBgActive
+    } else if ui.is_item_hovered() {
+        StyleColor::FrameBgHovered
+    } else {
+        StyleColor::FrameBg
+    };
+    draw_list
+        .add_circle(center, radius, ui.style_color(background))
+        .filled(true)
+        .build();
+    let dragging = ui.is_item_active() && ui.is_mouse_down(MouseButton::Left);
+    (center, radius, dragging)
+}
+
+/// Mouse position relative to `center` with y pointing up
+fn mouse_offset(ui: &Ui, center: [f32; 2]) -> [f32; 2] {
+    let mouse = ui.io().mouse_pos;
+    [mouse[0] - center[0], center[1] - mouse[1]]
+}
+
+fn draw_label(ui: &Ui, label: &str, value: &str) {
+    let label = label.split("##").next().unwrap_or_default();
+    ui.same_line();
+    ui.group(|| {
+        if !label.is_empty() {
+            ui.text(label);
+        }
+        ui.text_disabled(value);
+    });
+}
+
+/// Rotary dial for an angle in radians, counter-clockwise from the positive x axis like the
+/// engine's math. Drag around the dial to turn it, hold Shift to snap to 15 degrees.
+///
+/// The angle is kept in -PI to PI once it's changed. Returns `true` when it changed.
+pub fn angle_dial(ui: &Ui, label: &str, radians: &mut f32) -> bool {
+    let (center, radius, dragging) = disc(ui, label);
+    let mut changed = false;
+    if dragging {
+        let [x, y] = mouse_offset(ui, center);
+        if x != 0.0 || y != 0.0 {
+            let mut angle = y.atan2(x);
+            if ui.is_key_down(Key::LeftShift) || ui.is_key_down(Key::RightShift) {
+                angle = (angle / SNAP).round() * SNAP;
+                if angle > PI {
+                    angle -= 2.0 * PI;
+                }
+            }
+            if angle != *radians {
+                *radians = angle;
+                changed = true;
+            }
+        }
+    }
+    let draw_list = ui.get_window_draw_list();
+    let tip = [
+        center[0] + radians.cos() * radius * 0.85,
+        center[1] - radians.sin() * radius * 0.85,
+    ];
+    draw_list
+        .add_line(center, tip, ui.style_color(StyleColor::SliderGrabActive))
+        .thickness(2.0)
+        .build();
+    draw_list
+        .add_circle(center, 2.5, ui.style_color(StyleColor::Text))
+        .filled(true)
+        .build();
+    draw_label(ui, label, &format!("{:.1}\u{b0}", radians.to_degrees()));
+    changed
+}
+
+/// Picker for a 2D unit vector, drag the point around the disc. Up on screen is positive y.
+///
+/// A zero vector is shown pointing along x until it's dragged. Returns `true` when it changed,
+/// the new value is always normalized.
+pub fn direction_picker(ui: &Ui, label: &str, direction: &mut Vec2<f32>) -> bool {
+    let (center, radius, dragging) = disc(ui, label);
+    let mut changed = false;
+    if dragging {
+        let [x, y] = mouse_offset(ui, center);
+        let new = Vec2::new(x, y);
+        if new.magnitude_squared() > 0.0 {
+            let new = new.normalized();
+            if new != *direction {
+                *direction = new;
+                changed = true;
+            }
+        }
+    }
+    let shown = if direction.magnitude_squared() > 0.0 {
+        direction.normalized()
+    } else {
+        Vec2::unit_x()
+    };
+    let draw_list = ui.get_window_draw_list();
+    let border = ui.style_color(StyleColor::Border);
+    draw_list
+        .add_line(
+            [center[0] - radius, center[1]],
+            [center[0] + radius, center[1]],
+            border,
+        )
+        .build();
+    draw_list
+        .add_line(
+            [center[0], center[1] - radius],
+            [center[0], center[1] + radius],
+            border,
+        )
+        .build();
+    let point = [
+        center[0] + shown.x * radius * 0.85,
+        center[1] - shown.y * radius * 0.85,
+    ];
+    let accent = ui.style_color(StyleColor::SliderGrabActive);
+    draw_list
+        .add_line(center, point, accent)
+        .thickness(2.0)
+        .build();
+    draw_list
+        .add_circle(point, 4.0, accent)
+        .filled(true)
+        .build();
+    draw_label(
+        ui,
+        label,
+        &format!("{:.2}, {:.2}", direction.x, direction.y),
+    );
+    changed
+}
@@ -0,0 +1,253 @@
+use imgui::{ListClipper, Ui};
+
+/// Above this many line pairs the changed middle isn't diffed further and shows up as removed
+/// and added as a whole, the LCS table would get too big
+const MAX_DIFF_CELLS: usize = 4_000_000;
+
+const REMOVED_LINE: [f32; 4] = [0.6, 0.15, 0.15, 0.35];
+const ADDED_LINE: [f32; 4] = [0.15, 0.55, 0.15, 0.35];
+const REMOVED_CHARS: [f32; 4] = [0.8, 0.2, 0.2, 0.6];
+const ADDED_CHARS: [f32; 4] = [0.2, 0.75, 0.2, 0.6];
+
+/// Button pressed under a [`diff_view`]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum DiffAction {
+    /// Keep the new text
+    Accept,
+    /// Go back to the old text
+    Revert,
+}
+
+/// One row of the side-by-side view, line indices into the old and new text. Changed lines are
+/// paired up so the changed characters can be highlighted.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct DiffRow {
+    pub old: Option<usize>,
+    pub new: Option<usize>,
+    /// The lines differ, or one side is missing
+    pub changed: bool,
+}
+
+/// Line diff of two texts, build it when the texts change and draw it with [`diff_view`]
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct TextDiff {
+    old: Vec<String>,
+    new: Vec<String>,
+    rows: Vec<DiffRow>,
+}
+
+impl TextDiff {
+    pub fn new(old: &str, new: &str) -> Self {
+        let old: Vec<String> = old.lines().map(str::to_string).collect();
+        let new: Vec<String> = new.lines().map(str::to_string).collect();
+        let rows = diff_rows(&old, &new);
+        Self { old, new, rows }
+    }
+    /// Diff of the pretty JSON of two values, e.g. saved and edited settings
+    #[cfg(feature = "serde")]
+    pub fn from_values<T: serde::Serialize>(old: &T, new: &T) -> Result<Self, serde_json::Error> {
+        Ok(Self::new(
+            &serde_json::to_string_pretty(old)?,
+            &serde_json::to_string_pretty(new)?,
+        ))
+    }
+
+    pub fn rows(&self) -> &[DiffRow] {
+        &self.rows
+    }
+    pub fn has_changes(&self) -> bool {
+        self.rows.iter().any(|row| row.changed)
+    }
+    /// Lines only in the old text and lines only in the new one, changed lines count for both
+    pub fn counts(&self) -> (usize, usize) {
+        self.rows
+            .iter()
+            .filter(|row| row.changed)
+            .fold((0, 0), |(removed, added), row| {
+                (
+                    removed + row.old.is_some() as usize,
+                    added + row.new.is_some() as usize,
+                )
+            })
+    }
+}
+
+/// Pairs the lines of the longest common subsequence, with the lines between them paired up as
+/// changed rows
+fn diff_rows(old: &[String], new: &[String]) -> Vec<DiffRow> {
+    let prefix = old
+        .iter()
+        .zip(new)
+        .take_while(|(old, new)| old == new)
+        .count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(old, new)| old == new)
+        .count();
+    let old_middle = &old[prefix..old.len() - suffix];
+    let new_middle = &new[prefix..new.len() - suffix];
+
+    let equal = |old: usize, new: usize| DiffRow {
+        old: Some(old),
+        new: Some(new),
+        changed: false,
+    };
+    let mut rows: Vec<DiffRow> = (0..prefix).map(|i| equal(i, i)).collect();
+    // Matched pairs of the middle, offset by the prefix
+    let mut matches = Vec::new();
+    let (n, m) = (old_middle.len(), new_middle.len());
+    if n > 0 && m > 0 && n * m <= MAX_DIFF_CELLS {
+        // lengths[i][j] = LCS length of old_middle[i..] and new_middle[j..]
+        let mut lengths = vec![0u32; (n + 1) * (m + 1)];
+        for i in (0..n).rev() {
+            for j in (0..m).rev() {
+                lengths[i * (m + 1) + j] = if old_middle[i] == new_middle[j] {
+                    lengths[(i + 1) * (m + 1) + j + 1] + 1
+                } else {
+                    lengths[(i + 1) * (m + 1) + j].max(lengths[i * (m + 1) + j + 1])
+                };
+            }
+        }
+        let (mut i, mut j) = (0, 0);
+        while i < n && j < m {
+            if old_middle[i] == new_middle[j] {
+                matches.push((prefix + i, prefix + j));
+                i += 1;
+                j += 1;
+            } else if lengths[(i + 1) * (m + 1) + j] >= lengths[i * (m + 1) + j + 1] {
+                i += 1;
+            } else {
+                j += 1;
+            }
+        }
+    }
+    matches.push((old.len() - suffix, new.len() - suffix));
+    let (mut old_line, mut new_line) = (prefix, prefix);
+    for (old_match, new_match) in matches {
+        let removed = old_line..old_match;
+        let added = new_line..new_match;
+        for k in 0..removed.len().max(added.len()) {
+            rows.push(DiffRow {
+                old: (k < removed.len()).then(|| removed.start + k),
+                new: (k < added.len()).then(|| added.start + k),
+                changed: true,
+            });
+        }
+        if old_match < old.len() - suffix {
+            rows.push(equal(old_match, new_match));
+        }
+        old_line = old_match + 1;
+        new_line = new_match + 1;
+    }
+    let (old_end, new_end) = (old.len() - suffix, new.len() - suffix);
+    rows.extend((0..suffix).map(|k| equal(old_end + k, new_end + k)));
+    rows
+}
+
+/// Byte ranges of the common start and end of two lines, on char boundaries
+fn common_ends(old: &str, new: &str) -> (usize, usize) {
+    let prefix: usize = old
+        .chars()
+        .zip(new.chars())
+        .take_while(|(old, new)| old == new)
+        .map(|(c, _)| c.len_utf8())
+        .sum();
+    let suffix: usize = old[prefix..]
+        .chars()
+        .rev()
+        .zip(new[prefix..].chars().rev())
+        .take_while(|(old, new)| old == new)
+        .map(|(c, _)| c.len_utf8())
+        .sum();
+    (prefix, suffix)
+}
+
+/// Line text with the changed middle highlighted
+fn draw_line(ui: &Ui, line: &str, other: Option<&str>, highlight: [f32; 4]) {
+    let Some(other) = other else {
+        ui.text(line);
+        return;
+    };
+    let (prefix, suffix) = common_ends(line, other);
+    let middle_end = line.len() - suffix;
+    if prefix >= middle_end {
+        ui.text(line);
+        return;
+    }
+    ui.text(&line[..prefix]);
+    ui.same_line_with_spacing(0.0, 0.0);
+    let middle = &line[prefix..middle_end];
+    let pos = ui.cursor_screen_pos();
+    let size = ui.calc_text_size(middle);
+    ui.get_window_draw_list()
+        .add_rect(pos, [pos[0] + size[0], pos[1] + size[1]], highlight)
+        .filled(true)
+        .build();
+    ui.text(middle);
+    ui.same_line_with_spacing(0.0, 0.0);
+    ui.text(&line[middle_end..]);
+}
+
+/// Side-by-side view of a [`TextDiff`], old text on the left. Changed lines are tinted and the
+/// changed characters within them highlighted. Returns the action when Accept or Revert was
+/// pressed, the buttons are disabled without changes.
+pub fn diff_view(ui: &Ui, label: &str, diff: &TextDiff) -> Option<DiffAction> {
+    let _id = ui.push_id(label);
+    let mut action = None;
+    let (removed, added) = diff.counts();
+    ui.text(format!("-{} +{}", removed, added));
+    ui.same_line();
+    ui.disabled(!diff.has_changes(), || {
+        if ui.button("Accept") {
+            action = Some(DiffAction::Accept);
+        }
+        ui.same_line();
+        if ui.button("Revert") {
+            action = Some(DiffAction::Revert);
+        }
+    });
+    ui.child_window("##diff")
+        .horizontal_scrollbar(true)
+        .build(|| {
+            ui.columns(2, "##diff_columns", true);
+            let clipper = ListClipper::new(diff.rows.len() as i32).begin(ui);
+            for row in clipper.iter() {
+                let row = diff.rows[row as usize];
+                let old = row.old.map(|i| diff.old[i].as_str());
+                let new = row.new.map(|i| diff.new[i].as_str());
+                for (index, line, other, line_color, chars_color) in [
+                    (row.old, old, new, REMOVED_LINE, REMOVED_CHARS),
+                    (row.new, new, old, ADDED_LINE, ADDED_CHARS),
+                ] {
+                    if let (true, Some(line)) = (row.changed, line) {
+                        let pos = ui.cursor_screen_pos();
+                        ui.get_window_draw_list()
+                            .add_rect(
+                                pos,
+                                [
+                                    pos[0] + ui.current_column_width(),
+                                    pos[1] + ui.text_line_height_with_spacing(),
+                                ],
+                                line_color,
+                            )
+                            .filled(true)
+                            .build();
+                        ui.text_disabled(format!("{:>4}", index.map_or(0, |i| i + 1)));
+                        ui.same_line();
+                        draw_line(ui, line, other, chars_color);
+                    } else if let Some(line) = line {
+                        ui.text_disabled(format!("{:>4}", index.map_or(0, |i| i + 1)));
+                        ui.same_line();
+                        ui.text(line);
+                    } else {
+                        ui.new_line();
+                    }
+                    ui.next_column();
+                }
+            }
+            ui.columns(1, "##diff_columns", false);
+        });
+    action
+}
@@ -0,0 +1,138 @@
+use crate::capture::{self, CaptureError, CaptureRegion};
+use bugsyth_engine::glium::backend::Facade;
+use imgui::{Condition, Id, Key, MouseButton, MouseCursor, Ui};
+
+/// Encoding of the colors returned by the eyedropper
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum ColorSpace {
+    /// As stored in the framebuffer, the default
+    #[default]
+    Srgb,
+    /// Decoded to linear, for colors fed to lighting or shaders
+    Linear,
+}
+
+/// Picks colors from the screen for [`color_edit_eyedropper`].
+///
+/// One eyedropper can serve any number of color widgets. The pixel is read back from the front
+/// buffer, call [`take_sample`](Self::take_sample) at the start of each frame before anything is
+/// drawn.
+#[derive(Debug, Default)]
+pub struct Eyedropper {
+    color_space: ColorSpace,
+    /// ID of the widget picking a color
+    active: Option<Id>,
+    pending: Option<(Id, CaptureRegion)>,
+    sampled: Option<(Id, [f32; 4])>,
+}
+
+impl Eyedropper {
+    pub fn new(color_space: ColorSpace) -> Self {
+        Self {
+            color_space,
+            ..Self::default()
+        }
+    }
+    pub fn color_space(&self) -> ColorSpace {
+        self.color_space
+    }
+    pub fn set_color_space(&mut self, color_space: ColorSpace) {
+        self.color_space = color_space;
+    }
+    pub fn is_picking(&self) -> bool {
+        self.active.is_some()
+    }
+    pub fn cancel(&mut self) {
+        self.active = None;
+    }
+
+    /// Reads the clicked pixel, the widget that started picking receives it while it's drawn.
+    ///
+    /// Returns the sampled color, or `None` if nothing was clicked since the last call.
+    pub fn take_sample<F: Facade>(&mut self, facade: &F) -> Option<Result<[f32; 4], CaptureError>> {
+        let (id, region) = self.pending.take()?;
+        let result = capture::read_region(facade, region).map(|image| {
+            let [r, g, b, _] = [0, 1, 2, 3].map(|i| image.pixels[i] as f32 / 255.0);
+            let rgb = match self.color_space {
+                ColorSpace::Srgb => [r, g, b],
+                ColorSpace::Linear => [r, g, b].map(srgb_to_linear),
+            };
+            [rgb[0], rgb[1], rgb[2], 1.0]
+        });
+        if let Ok(color) = result {
+            self.sampled = Some((id, color));
+        }
+        Some(result)
+    }
+
+    /// Draws the picking overlay while active, the next left click anywhere picks the pixel
+    fn draw_overlay(&mut self, ui: &Ui) {
+        if ui.is_key_pressed(Key::Escape) || ui.is_mouse_clicked(MouseButton::Right) {
+            self.active = None;
+            return;
+        }
+        let display_size = ui.io().display_size;
+        // Invisible window covering everything so the click doesn't reach other widgets
+        ui.window("##eyedropper")
+            .position([0.0, 0.0], Condition::Always)
+            .size(display_size, Condition::Always)
+            .no_decoration()
+            .draw_background(false)
+            .save_settings(false)
+            .focused(true)
+            .build(|| {
+                ui.invisible_button("##eyedropper_area", display_size);
+                ui.set_mouse_cursor(Some(MouseCursor::Hand));
+                if ui.is_item_clicked() {
+                    let mouse = ui.io().mouse_pos;
+                    let region =
+                        CaptureRegion::from_ui_rect(ui, mouse, [mouse[0] + 1.0, mouse[1] + 1.0]);
+                    if let Some(id) = self.active.take() {
+                        self.pending = Some((id, region));
+                    }
+                } else {
+                    // Not drawn on the click frame so it can't end up under the cursor
+                    ui.tooltip_text("Click to pick a color, right click to cancel");
+                }
+            });
+    }
+}
+
+/// [`Ui::color_edit4`] with a button picking the color from the screen.
+///
+/// Returns `true` when the color changed, including when a picked color arrived.
+pub fn color_edit_eyedropper(
+    ui: &Ui,
+    label: &str,
+    color: &mut [f32; 4],
+    eyedropper: &mut Eyedropper,
+) -> bool {
+    let id = ui.new_id_str(label);
+    let mut changed = false;
+    if let Some((_, sampled)) = eyedropper
+        .sampled
+        .take_if(|(sampled_id, _)| *sampled_id == id)
+    {
+        color[..3].copy_from_slice(&sampled[..3]);
+        changed = true;
+    }
+    changed |= ui.color_edit4(label, color);
+    ui.same_line();
+    let picking = eyedropper.active == Some(id);
+    let _id = ui.push_id(label);
+    if ui.small_button(if picking { "Picking..." } else { "Pick" }) {
+        eyedropper.active = if picking { None } else { Some(id) };
+    }
+    if picking {
+        eyedropper.draw_overlay(ui);
+    }
+    changed
+}
+
+fn srgb_to_linear(c: f32) -> f32 {
+    if c <= 0.04045 {
+        c / 12.92
+    } else {
+        ((c + 0.055) / 1.055).powf(2.4)
+    }
+}
@@ -0,0 +1,323 @@
+use crate::settings::{escape, unescape, SettingsError, SettingsStore};
+use imgui::{
+    HistoryDirection, InputTextCallback, InputTextCallbackHandler, InputTextFlags,
+    InputTextMultilineCallback, Key, TextCallbackData, Ui,
+};
+
+/// Previously submitted entries of a text input, shared by the REPL style widgets
+#[derive(Debug, Default, Clone)]
+pub struct InputHistory {
+    entries: Vec<String>,
+    pos: Option<usize>,
+    max_entries: Option<usize>,
+    search: Option<ReverseSearch>,
+    refocus: bool,
+    cursor_to_end: bool,
+    cursor_on_first_line: bool,
+    cursor_on_last_line: bool,
+}
+
+#[derive(Debug, Default, Clone)]
+struct ReverseSearch {
+    query: String,
+    found: Option<usize>,
+    focus: bool,
+}
+
+impl InputHistory {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Limits how many entries are kept, oldest entries are dropped first
+    pub fn with_max_entries(mut self, max_entries: usize) -> Self {
+        self.max_entries = Some(max_entries);
+        self.truncate();
+        self
+    }
+    /// Entries, oldest first
+    pub fn entries(&self) -> &[String] {
+        &self.entries
+    }
+    /// Adds an entry, empty entries and consecutive duplicates are ignored
+    pub fn push(&mut self, entry: impl Into<String>) {
+        let entry = entry.into();
+        self.pos = None;
+        if entry.is_empty() || self.entries.last() == Some(&entry) {
+            return;
+        }
+        self.entries.push(entry);
+        self.truncate();
+    }
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.pos = None;
+    }
+    /// Moves the recall cursor and returns the text that should replace the input.
+    ///
+    /// Moving down past the newest entry returns an empty string, `None` means there's nothing to
+    /// recall.
+    pub fn navigate(&mut self, dir: HistoryDirection) -> Option<&str> {
+        if self.entries.is_empty() {
+            return None;
+        }
+        self.pos = match (dir, self.pos) {
+            (HistoryDirection::Up, None) => Some(self.entries.len() - 1),
+            (HistoryDirection::Up, Some(pos)) => Some(pos.saturating_sub(1)),
+            (HistoryDirection::Down, Some(pos)) if pos + 1 < self.entries.len() => Some(pos + 1),
+            (HistoryDirection::Down, Some(_)) => None,
+            (HistoryDirection::Down, None) => return None,
+        };
+        Some(self.pos.map_or("", |pos| self.entries[pos].as_str()))
+    }
+    /// Forgets where the recall cursor is, the next `Up` starts from the newest entry again
+    pub fn reset_cursor(&mut self) {
+        self.pos = None;
+    }
+    /// Finds the newest entry older than `before` that contains `query`
+    pub fn search(&self, query: &str, before: Option<usize>) -> Option<usize> {
+        let end = before.unwrap_or(self.entries.len()).min(self.entries.len());
+        self.entries[..end]
+            .iter()
+            .rposition(|entry| entry.contains(query))
+    }
+
+    /// Serializes the entries, one per line with newlines and backslashes escaped
+    pub fn to_text(&self) -> String {
+        let mut text = String::new();
+        for entry in &self.entries {
+            text.push_str(&escape(entry));
+            text.push('\n');
+        }
+        text
+    }
+    /// Replaces the entries with ones produced by [`to_text`](Self::to_text)
+    pub fn load_text(&mut self, text: &str) {
+        self.clear();
+        for line in text.lines() {
+            self.push(unescape(line));
+        }
+    }
+    /// Replaces the entries with the ones saved under `name` in `store`, e.g. `"console_history"`
+    pub fn load(&mut self, store: &SettingsStore, name: &str) -> Result<(), SettingsError> {
+        match store.load(name)? {
+            Some(text) => self.load_text(&text),
+            None => self.clear(),
+        }
+        Ok(())
+    }
+    /// Saves the entries under their own `name` in `store`, next to the rest of the settings
+    pub fn save(&self, store: &SettingsStore, name: &str) -> Result<(), SettingsError> {
+        store.save(name, &self.to_text())
+    }
+
+    fn truncate(&mut self) {
+        if let Some(max) = self.max_entries {
+            let excess = self.entries.len().saturating_sub(max);
+            self.entries.drain(..excess);
+        }
+    }
+
+    /// Applies a pending refocus, call right before building the input
+    fn before_input(&mut self, ui: &Ui) {
+        if self.refocus {
+            self.refocus = false;
+            self.cursor_to_end = true;
+            ui.set_keyboard_focus_here();
+        }
+    }
+
+    /// Moves the cursor to the end after a refocus, call from the input's `on_always`
+    fn after_refocus(&mut self, data: &mut TextCallbackData) {
+        if self.cursor_to_end {
+            self.cursor_to_end = false;
+            data.clear_selection();
+            let end = data.str().len();
+            data.set_cursor_pos(end);
+        }
+    }
+
+    /// Draws the Ctrl+R search line in place of the input.
+    ///
+    /// Returns the accepted entry once the search is finished with Enter.
+    fn draw_search(&mut self, ui: &Ui, label: &str) -> Option<String> {
+        let mut search = self.search.take()?;
+        if search.focus {
+            search.focus = false;
+            ui.set_keyboard_focus_here();
+        }
+        let entered = ui
+            .input_text(format!("##reverse_search_{}", label), &mut search.query)
+            .hint("reverse search")
+            .enter_returns_true(true)
+            .build();
+        if ui.is_item_edited() {
+            search.found = self.search(&search.query, None);
+        } else if ui.is_item_active() && ui.io().key_ctrl && ui.is_key_pressed(Key::R) {
+            search.found = self.search(&search.query, search.found).or(search.found);
+        }
+        let cancelled = ui.is_item_deactivated();
+        ui.same_line();
+        match search.found {
+            Some(i) => ui.text(&self.entries[i]),
+            None => ui.text_disabled("no match"),
+        }
+
+        if entered || cancelled {
+            // Escape or clicking away cancels the search and keeps the old text
+            self.refocus = true;
+            return search
+                .found
+                .filter(|_| entered)
+                .map(|i| self.entries[i].clone());
+        }
+        self.search = Some(search);
+        None
+    }
+
+    fn start_search(&mut self, ui: &Ui, active: bool) {
+        if active && ui.io().key_ctrl && ui.is_key_pressed(Key::R) {
+            self.search = Some(ReverseSearch {
+                focus: true,
+                ..Default::default()
+            });
+        }
+    }
+}
+
+struct SingleLine<'a> {
+    history: &'a mut InputHistory,
+}
+
+impl InputTextCallbackHandler for SingleLine<'_> {
+    fn on_history(&mut self, dir: HistoryDirection, mut data: TextCallbackData) {
+        if let Some(entry) = self.history.navigate(dir) {
+            data.clear();
+            data.push_str(entry);
+        }
+    }
+    fn on_always(&mut self, mut data: TextCallbackData) {
+        self.history.after_refocus(&mut data);
+    }
+}
+
+struct MultiLine<'a> {
+    history: &'a mut InputHistory,
+    dir: Option<HistoryDirection>,
+}
+
+impl InputTextCallbackHandler for MultiLine<'_> {
+    fn on_always(&mut self, mut data: TextCallbackData) {
+        self.history.after_refocus(&mut data);
+        // Up/Down only recall entries once the cursor already sat on the first/last line, so they
+        // keep moving between lines otherwise
+        let recall = match self.dir {
+            Some(HistoryDirection::Up) => self.history.cursor_on_first_line,
+            Some(HistoryDirection::Down) => self.history.cursor_on_last_line,
+            None => false,
+        };
+        if recall {
+            if let Some(entry) = self.history.navigate(self.dir.unwrap()) {
+                let entry = entry.to_owned();
+                data.clear();
+                data.push_str(&entry);
+            }
+        }
+        let (before, after) = data.str().split_at(data.cursor_pos());
+        self.history.cursor_on_first_line = !before.contains('\n');
+        self.history.cursor_on_last_line = !after.contains('\n');
+    }
+    fn on_edit(&mut self, _: TextCallbackData) {
+        self.history.reset_cursor();
+    }
+}
+
+/// Single line text input with Up/Down history recall and Ctrl+R reverse search.
+///
+/// Returns `true` when the text was submitted with Enter, the text is added to `history` but the
+/// buffer is left for the caller to clear.
+pub fn input_history(ui: &Ui, label: &str, buf: &mut String, history: &mut InputHistory) -> bool {
+    if let Some(found) = history.draw_search(ui, label) {
+        *buf = found;
+    }
+    if history.search.is_some() {
+        return false;
+    }
+    history.before_input(ui);
+    let entered = ui
+        .input_text(label, buf)
+        .enter_returns_true(true)
+        .callback(
+            InputTextCallback::HISTORY | InputTextCallback::ALWAYS,
+            SingleLine { history },
+        )
+        .build();
+    history.start_search(ui, ui.is_item_active());
+    if entered {
+        history.push(buf.clone());
+    }
+    entered
+}
+
+/// Multi-line variant of [`input_history`] with console semantics.
+///
+/// Enter submits and Ctrl+Enter inserts a new line. Up/Down recall history when the cursor is on
+/// the first/last line and move between lines otherwise.
+pub fn input_history_multiline(
+    ui: &Ui,
+    label: &str,
+    buf: &mut String,
+    size: [f32; 2],
+    history: &mut InputHistory,
+) -> bool {
+    if let Some(found) = history.draw_search(ui, label) {
+        *buf = found;
+    }
+    if history.search.is_some() {
+        return false;
+    }
+    history.before_input(ui);
+    let dir = if ui.is_key_pressed(Key::UpArrow) {
+        Some(HistoryDirection::Up)
+    } else if ui.is_key_pressed(Key::DownArrow) {
+        Some(HistoryDirection::Down)
+    } else {
+        None
+    };
+    let entered = ui
+        .input_text_multiline(label, buf, size)
+        .flags(InputTextFlags::CALLBACK_RESIZE | InputTextFlags::CTRL_ENTER_FOR_NEW_LINE)
+        .enter_returns_true(true)
+        .callback(
+            InputTextMultilineCallback::ALWAYS | InputTextMultilineCallback::EDIT,
+            MultiLine { history, dir },
+        )
+        .build();
+    history.start_search(ui, ui.is_item_active());
+    if entered {
+        history.push(buf.clone());
+    }
+    entered
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn entries_round_trip_through_the_store() {
+        let dir =
+            std::env::temp_dir().join(format!("imgui_support_history_{}", std::process::id()));
+        let store = SettingsStore::new(&dir);
+        let mut history = InputHistory::new();
+        for entry in ["help", "spawn x=1\ny=2", "path C:\\games", "tab\there\r"] {
+            history.push(entry);
+        }
+        history.save(&store, "console_history").unwrap();
+        let mut loaded = InputHistory::new();
+        loaded.load(&store, "console_history").unwrap();
+        assert_eq!(loaded.entries(), history.entries());
+        loaded.load(&store, "missing").unwrap();
+        assert!(loaded.entries().is_empty());
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,130 @@
+use imgui::{Key, MouseButton, SelectableFlags, Ui};
+
+const ERROR_COLOR: [f32; 4] = [1.0, 0.4, 0.4, 1.0];
+
+/// What happened to an item drawn with [`selectable_rename`] this frame
+#[derive(Debug, Clone, PartialEq)]
+pub enum RenameEvent {
+    /// The label was clicked while not being edited
+    Clicked,
+    /// Editing finished with Enter and the new text passed validation
+    Renamed(String),
+    /// Editing was cancelled with Escape or by clicking away
+    Cancelled,
+}
+
+/// Tracks which item of a list or tree is being renamed, only one item can be edited at a time
+#[derive(Debug, Default)]
+pub struct InlineEdit {
+    editing: Option<Editing>,
+}
+
+#[derive(Debug)]
+struct Editing {
+    id: String,
+    buf: String,
+    focus: bool,
+    error: Option<String>,
+}
+
+impl InlineEdit {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Starts editing the item `id` with `text` as the initial value
+    pub fn start(&mut self, id: impl Into<String>, text: impl Into<String>) {
+        self.editing = Some(Editing {
+            id: id.into(),
+            buf: text.into(),
+            focus: true,
+            error: None,
+        });
+    }
+    pub fn cancel(&mut self) {
+        self.editing = None;
+    }
+    pub fn is_editing(&self, id: &str) -> bool {
+        self.editing
+            .as_ref()
+            .is_some_and(|editing| editing.id == id)
+    }
+
+    /// Draws the text field for the item being edited.
+    ///
+    /// Call this in place of the item's label when [`is_editing`](Self::is_editing) is `true`,
+    /// e.g. when drawing tree nodes by hand. `validate` runs on Enter, an `Err` keeps the field open
+    /// and shows the message below it.
+    pub fn draw_editor<V>(&mut self, ui: &Ui, mut validate: V) -> Option<RenameEvent>
+    where
+        V: FnMut(&str) -> Result<(), String>,
+    {
+        let editing = self.editing.as_mut()?;
+        if editing.focus {
+            editing.focus = false;
+            ui.set_keyboard_focus_here();
+        }
+        ui.set_next_item_width(ui.content_region_avail()[0]);
+        let entered = ui
+            .input_text(format!("##inline_edit_{}", editing.id), &mut editing.buf)
+            .auto_select_all(true)
+            .enter_returns_true(true)
+            .build();
+        let deactivated = ui.is_item_deactivated();
+        if let Some(error) = &editing.error {
+            ui.text_colored(ERROR_COLOR, error);
+        }
+
+        if entered {
+            match validate(&editing.buf) {
+                Ok(()) => {
+                    let text = std::mem::take(&mut editing.buf);
+                    self.editing = None;
+                    return Some(RenameEvent::Renamed(text));
+                }
+                Err(error) => {
+                    editing.error = Some(error);
+                    editing.focus = true;
+                    return None;
+                }
+            }
+        }
+        if deactivated {
+            self.editing = None;
+            return Some(RenameEvent::Cancelled);
+        }
+        None
+    }
+}
+
+/// Selectable label that turns into a text field on double-click or F2.
+///
+/// `id` must be unique among the items sharing `state`. Enter commits the edit once `validate`
+/// accepts it and Escape cancels it.
+pub fn selectable_rename<V>(
+    ui: &Ui,
+    id: &str,
+    label: &str,
+    selected: bool,
+    state: &mut InlineEdit,
+    validate: V,
+) -> Option<RenameEvent>
+where
+    V: FnMut(&str) -> Result<(), String>,
+{
+    if state.is_editing(id) {
+        return state.draw_editor(ui, validate);
+    }
+
+    let clicked = ui
+        .selectable_config(format!("{}##{}", label, id))
+        .selected(selected)
+        .flags(SelectableFlags::ALLOW_DOUBLE_CLICK)
+        .build();
+    let rename_key =
+        (ui.is_item_focused() || selected && ui.is_window_focused()) && ui.is_key_pressed(Key::F2);
+    if clicked && ui.is_mouse_double_clicked(MouseButton::Left) || rename_key {
+        state.start(id, label);
+        return None;
+    }
+    clicked.then_some(RenameEvent::Clicked)
+}
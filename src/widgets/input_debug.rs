@@ -0,0 +1,79 @@
+use imgui::{Io, Key, MouseButton, Ui};
+
+/// Labelled yes or no, green when set
+fn flag(ui: &Ui, label: &str, value: bool) {
+    let color = if value {
+        [0.4, 0.9, 0.4, 1.0]
+    } else {
+        [0.5, 0.5, 0.5, 1.0]
+    };
+    ui.text_colored(
+        color,
+        format!("{}: {}", label, if value { "yes" } else { "no" }),
+    );
+}
+
+/// Shows what imgui sees of the input: the mouse position, pressed buttons, held keys, keyboard
+/// navigation and whether imgui wants the mouse and keyboard, which is what the game should
+/// check before reacting to them.
+///
+/// `events` are the raw window events of the last frame, e.g. a copy of
+/// [`WinitPlatform::recorded_events`](crate::WinitPlatform::recorded_events) taken before the
+/// frame with recording turned on. Pass an empty slice to leave them out.
+pub fn input_debug(ui: &Ui, io: &Io, events: &[String]) {
+    let _id = ui.push_id("input_debug");
+    if ui.collapsing_header("Mouse", imgui::TreeNodeFlags::DEFAULT_OPEN) {
+        let [x, y] = io.mouse_pos;
+        if x.is_finite() && y.is_finite() {
+            ui.text(format!("Position: {:.1}, {:.1}", x, y));
+        } else {
+            ui.text_disabled("Position: outside the window");
+        }
+        ui.text(format!(
+            "Delta: {:.1}, {:.1}  Wheel: {:.2}, {:.2}",
+            io.mouse_delta[0], io.mouse_delta[1], io.mouse_wheel_h, io.mouse_wheel
+        ));
+        let pressed: Vec<String> = MouseButton::VARIANTS
+            .iter()
+            .zip(io.mouse_down)
+            .filter(|&(_, down)| down)
+            .map(|(button, _)| format!("{:?}", button))
+            .collect();
+        ui.text(format!("Buttons: {}", pressed.join(", ")));
+        flag(ui, "Captured by the UI", io.want_capture_mouse);
+    }
+    if ui.collapsing_header("Keyboard", imgui::TreeNodeFlags::DEFAULT_OPEN) {
+        let modifiers = [
+            ("Ctrl", io.key_ctrl),
+            ("Shift", io.key_shift),
+            ("Alt", io.key_alt),
+            ("Super", io.key_super),
+        ]
+        .into_iter()
+        .filter(|&(_, down)| down)
+        .map(|(name, _)| name)
+        .collect::<Vec<_>>();
+        ui.text(format!("Modifiers: {}", modifiers.join(", ")));
+        let held: Vec<String> = Key::VARIANTS
+            .iter()
+            .filter(|&&key| ui.is_key_down(key))
+            .map(|key| format!("{:?}", key))
+            .collect();
+        ui.text_wrapped(format!("Held: {}", held.join(", ")));
+        flag(ui, "Captured by the UI", io.want_capture_keyboard);
+        flag(ui, "Text input", io.want_text_input);
+    }
+    if ui.collapsing_header("Navigation", imgui::TreeNodeFlags::empty()) {
+        flag(ui, "Active", io.nav_active);
+        flag(ui, "Highlight visible", io.nav_visible);
+        flag(ui, "Moves the mouse", io.want_set_mouse_pos);
+    }
+    if ui.collapsing_header("Events", imgui::TreeNodeFlags::empty()) {
+        if events.is_empty() {
+            ui.text_disabled("No events recorded last frame");
+        }
+        for event in events {
+            ui.text_wrapped(event);
+        }
+    }
+}
@@ -0,0 +1,54 @@
+use crate::logs::LogBuffer;
+use imgui::{Condition, ListClipper, Ui};
+
+/// Window showing the records of a [`LogBuffer`] with a level and search filter. Follow keeps it
+/// scrolled to the newest record, scrolling up pauses it.
+pub fn log_window(ui: &Ui, buffer: &LogBuffer, opened: &mut bool) {
+    ui.window("Log")
+        .opened(opened)
+        .size([640.0, 360.0], Condition::FirstUseEver)
+        .build(|| {
+            let mut view = buffer.view();
+            view.filter.draw(ui);
+            ui.checkbox("Follow", &mut view.follow);
+            ui.same_line();
+            if ui.button("Clear") {
+                buffer.clear();
+            }
+            ui.same_line();
+            let dropped = buffer.dropped();
+            buffer.with_records(|records| {
+                let visible: Vec<usize> = records
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, record)| {
+                        view.filter.matches(Some(record.level), &record.message)
+                            || view.filter.matches(Some(record.level), &record.target)
+                    })
+                    .map(|(i, _)| i)
+                    .collect();
+                ui.text_disabled(format!("{}/{} records", visible.len(), records.len()));
+                if dropped > 0 {
+                    ui.same_line();
+                    ui.text_disabled(format!("({} dropped)", dropped));
+                }
+                ui.separator();
+                ui.child_window("##log_records")
+                    .horizontal_scrollbar(true)
+                    .build(|| {
+                        let clipper = ListClipper::new(visible.len() as i32).begin(ui);
+                        for row in clipper.iter() {
+                            let record = &records[visible[row as usize]];
+                            ui.text_colored(record.level.color(), record.level.name());
+                            ui.same_line();
+                            ui.text_disabled(&record.target);
+                            ui.same_line();
+                            ui.text(&record.message);
+                        }
+                        if view.follow && ui.scroll_y() >= ui.scroll_max_y() {
+                            ui.set_scroll_here_y_with_ratio(1.0);
+                        }
+                    });
+            });
+        });
+}
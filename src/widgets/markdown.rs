@@ -0,0 +1,43 @@
+use imgui::{StyleColor, Ui};
+
+/// Draws a small subset of markdown: `#` headings, `-`/`*` bullets, fenced code blocks and
+/// wrapped paragraphs. Inline `**bold**` and `` `code` `` markers are stripped.
+pub fn markdown(ui: &Ui, text: &str) {
+    let heading_color = ui.style_color(StyleColor::CheckMark);
+    let mut in_code = false;
+    for line in text.lines() {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("```") {
+            in_code = !in_code;
+            continue;
+        }
+        if in_code {
+            ui.text_disabled(line);
+            continue;
+        }
+        if trimmed.is_empty() {
+            ui.spacing();
+        } else if let Some(heading) = trimmed.strip_prefix('#') {
+            let level = heading.chars().take_while(|&c| c == '#').count() + 1;
+            ui.text_colored(
+                heading_color,
+                strip_inline(heading.trim_start_matches('#').trim()),
+            );
+            if level == 1 {
+                ui.separator();
+            }
+        } else if let Some(item) = trimmed
+            .strip_prefix("- ")
+            .or_else(|| trimmed.strip_prefix("* "))
+        {
+            ui.bullet();
+            ui.text_wrapped(strip_inline(item));
+        } else {
+            ui.text_wrapped(strip_inline(trimmed));
+        }
+    }
+}
+
+fn strip_inline(text: &str) -> String {
+    text.replace("**", "").replace('`', "")
+}
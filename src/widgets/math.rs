@@ -0,0 +1,71 @@
+use bugsyth_engine::math::{Mat4, Quaternion, Vec2, Vec3, Vec4};
+use imgui::{Drag, Ui};
+
+/// Change per pixel dragged
+const DRAG_SPEED: f32 = 0.01;
+
+/// Drag fields for each component on one line, like `ui.slider` but without a fixed range.
+/// Returns `true` when a component changed.
+pub fn drag_vec2(ui: &Ui, label: &str, value: &mut Vec2<f32>) -> bool {
+    Drag::new(label)
+        .speed(DRAG_SPEED)
+        .build_array(ui, value.as_mut_slice())
+}
+/// See [`drag_vec2`]
+pub fn drag_vec3(ui: &Ui, label: &str, value: &mut Vec3<f32>) -> bool {
+    Drag::new(label)
+        .speed(DRAG_SPEED)
+        .build_array(ui, value.as_mut_slice())
+}
+/// See [`drag_vec2`]
+pub fn drag_vec4(ui: &Ui, label: &str, value: &mut Vec4<f32>) -> bool {
+    Drag::new(label)
+        .speed(DRAG_SPEED)
+        .build_array(ui, value.as_mut_slice())
+}
+
+/// Edits a rotation as Euler angles in degrees, applied in x, y, z order.
+///
+/// The angles are worked out from the quaternion every frame, so they can jump near 90 degrees
+/// of y where several angles give the same rotation. Returns `true` when it changed, the new
+/// value is always normalized.
+pub fn drag_quat(ui: &Ui, label: &str, value: &mut Quaternion<f32>) -> bool {
+    let mut degrees = euler_angles(*value).map(f32::to_degrees);
+    if !Drag::new(label)
+        .speed(0.5)
+        .display_format("%.1f\u{b0}")
+        .build_array(ui, &mut degrees)
+    {
+        return false;
+    }
+    let [x, y, z] = degrees.map(f32::to_radians);
+    *value = (Quaternion::rotation_z(z) * Quaternion::rotation_y(y) * Quaternion::rotation_x(x))
+        .normalized();
+    true
+}
+
+/// Angles around x, y and z in radians of a rotation built as z * y * x
+fn euler_angles(q: Quaternion<f32>) -> [f32; 3] {
+    let q = q.normalized();
+    let x = (2.0 * (q.w * q.x + q.y * q.z)).atan2(1.0 - 2.0 * (q.x * q.x + q.y * q.y));
+    let y = (2.0 * (q.w * q.y - q.z * q.x)).clamp(-1.0, 1.0).asin();
+    let z = (2.0 * (q.w * q.z + q.x * q.y)).atan2(1.0 - 2.0 * (q.y * q.y + q.z * q.z));
+    [x, y, z]
+}
+
+/// Read-only 4x4 grid of a matrix, rows as in the math (the translation is the last column)
+pub fn mat4_table(ui: &Ui, label: &str, value: &Mat4<f32>) {
+    let _id = ui.push_id(label);
+    let label = label.split("##").next().unwrap_or_default();
+    if !label.is_empty() {
+        ui.text(label);
+    }
+    ui.columns(4, "##mat4", true);
+    for row in value.into_row_arrays() {
+        for element in row {
+            ui.text(format!("{:.3}", element));
+            ui.next_column();
+        }
+    }
+    ui.columns(1, "##mat4_end", false);
+}
@@ -0,0 +1,231 @@
+use bugsyth_engine::context::Context;
+use imgui::{Condition, Ui};
+
+type RecompileFn = Box<dyn FnMut(&mut Context, &str, &ShaderSource) -> Result<(), String>>;
+
+/// GLSL of one program, as passed to [`Context::new_program`]
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ShaderSource {
+    pub vertex: String,
+    pub fragment: String,
+    pub geometry: Option<String>,
+}
+
+impl ShaderSource {
+    pub fn new(vertex: impl Into<String>, fragment: impl Into<String>) -> Self {
+        Self {
+            vertex: vertex.into(),
+            fragment: fragment.into(),
+            geometry: None,
+        }
+    }
+    pub fn with_geometry(mut self, geometry: impl Into<String>) -> Self {
+        self.geometry = Some(geometry.into());
+        self
+    }
+}
+
+#[derive(Debug)]
+struct ShaderEntry {
+    name: String,
+    /// Source the running program was built from
+    applied: ShaderSource,
+    editing: ShaderSource,
+    /// Compile error of the last apply
+    error: Option<String>,
+    /// Apply was pressed and [`ShaderEditor::apply_pending`] hasn't run yet
+    pending: bool,
+}
+
+/// Window listing the game's programs by name with their GLSL in editable text boxes. Apply
+/// recompiles the program, so shaders can be changed while the game runs.
+///
+/// The engine doesn't keep the source of its programs, add them with [`add`](Self::add) next to
+/// [`Context::new_program`]. Apply only queues the program, [`apply_pending`](Self::apply_pending)
+/// compiles it outside of the frame with the recompile callback. The default one calls
+/// [`Context::new_program`], which keeps the old program if compiling fails. Errors are shown
+/// under the source.
+pub struct ShaderEditor {
+    entries: Vec<ShaderEntry>,
+    selected: usize,
+    recompile: RecompileFn,
+}
+
+impl std::fmt::Debug for ShaderEditor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ShaderEditor")
+            .field("entries", &self.entries)
+            .field("selected", &self.selected)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Default for ShaderEditor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ShaderEditor {
+    pub fn new() -> Self {
+        Self {
+            entries: Vec::new(),
+            selected: 0,
+            recompile: Box::new(|ctx, name, source| {
+                ctx.new_program(
+                    name,
+                    &source.vertex,
+                    &source.fragment,
+                    source.geometry.as_deref(),
+                )
+                .map_err(|error| error.to_string())
+            }),
+        }
+    }
+    /// Replaces how programs are rebuilt, e.g. to add defines or build them with
+    /// [`Context::add_program`]. Return the compile error to show it in the editor.
+    pub fn with_recompile(
+        mut self,
+        recompile: impl FnMut(&mut Context, &str, &ShaderSource) -> Result<(), String> + 'static,
+    ) -> Self {
+        self.recompile = Box::new(recompile);
+        self
+    }
+
+    /// Lists a program, replacing the source of one with the same name
+    pub fn add(&mut self, name: impl Into<String>, source: ShaderSource) {
+        let name = name.into();
+        match self.entries.iter_mut().find(|entry| entry.name == name) {
+            Some(entry) => {
+                entry.applied = source.clone();
+                entry.editing = source;
+                entry.error = None;
+            }
+            None => self.entries.push(ShaderEntry {
+                name,
+                applied: source.clone(),
+                editing: source,
+                error: None,
+                pending: false,
+            }),
+        }
+    }
+    pub fn remove(&mut self, name: &str) {
+        self.entries.retain(|entry| entry.name != name);
+    }
+    /// Source of the running program, i.e. without unapplied edits
+    pub fn source(&self, name: &str) -> Option<&ShaderSource> {
+        self.entries
+            .iter()
+            .find(|entry| entry.name == name)
+            .map(|entry| &entry.applied)
+    }
+    pub fn has_pending(&self) -> bool {
+        self.entries.iter().any(|entry| entry.pending)
+    }
+
+    /// Recompiles the programs applied since the last call, call it outside of the frame.
+    /// Returns the names of the programs that compiled.
+    pub fn apply_pending(&mut self, ctx: &mut Context) -> Vec<String> {
+        let mut compiled = Vec::new();
+        for entry in self.entries.iter_mut().filter(|entry| entry.pending) {
+            entry.pending = false;
+            match (self.recompile)(ctx, &entry.name, &entry.editing) {
+                Ok(()) => {
+                    entry.applied = entry.editing.clone();
+                    entry.error = None;
+                    compiled.push(entry.name.clone());
+                }
+                Err(error) => entry.error = Some(error),
+            }
+        }
+        compiled
+    }
+
+    pub fn draw(&mut self, ui: &Ui, opened: &mut bool) {
+        ui.window("Shaders")
+            .opened(opened)
+            .size([720.0, 480.0], Condition::FirstUseEver)
+            .build(|| {
+                if self.entries.is_empty() {
+                    ui.text_disabled("No programs added");
+                    return;
+                }
+                self.selected = self.selected.min(self.entries.len() - 1);
+                ui.child_window("##programs")
+                    .size([160.0, 0.0])
+                    .border(true)
+                    .build(|| {
+                        for (index, entry) in self.entries.iter().enumerate() {
+                            let label = if entry.editing != entry.applied {
+                                format!("{}*##{}", entry.name, index)
+                            } else {
+                                format!("{}##{}", entry.name, index)
+                            };
+                            if ui
+                                .selectable_config(label)
+                                .selected(index == self.selected)
+                                .build()
+                            {
+                                self.selected = index;
+                            }
+                        }
+                    });
+                ui.same_line();
+                ui.group(|| self.draw_entry(ui));
+            });
+    }
+
+    fn draw_entry(&mut self, ui: &Ui) {
+        let entry = &mut self.entries[self.selected];
+        let _id = ui.push_id(entry.name.as_str());
+        let modified = entry.editing != entry.applied;
+        ui.disabled(!modified || entry.pending, || {
+            if ui.button("Apply") {
+                entry.pending = true;
+            }
+            ui.same_line();
+            if ui.button("Revert") {
+                entry.editing = entry.applied.clone();
+                entry.error = None;
+            }
+        });
+        ui.same_line();
+        if entry.pending {
+            ui.text_disabled("Compiling...");
+        } else if entry.error.is_some() {
+            ui.text_colored([0.9, 0.3, 0.3, 1.0], "Compile failed");
+        } else if modified {
+            ui.text_disabled("Modified");
+        }
+
+        // Room for up to 8 lines of the compile error under the source
+        let error_height = entry.error.as_ref().map_or(0.0, |error| {
+            (error.lines().count().min(8) as f32 + 1.0) * ui.text_line_height_with_spacing()
+        });
+        let height = (ui.content_region_avail()[1] - error_height).max(80.0);
+        if let Some(_tabs) = ui.tab_bar("##stages") {
+            let source = &mut entry.editing;
+            let stages = [
+                ("Vertex", Some(&mut source.vertex)),
+                ("Fragment", Some(&mut source.fragment)),
+                ("Geometry", source.geometry.as_mut()),
+            ];
+            for (stage, text) in stages {
+                let Some(text) = text else {
+                    continue;
+                };
+                if let Some(_tab) = ui.tab_item(stage) {
+                    ui.input_text_multiline(format!("##{}", stage), text, [-1.0, height])
+                        .allow_tab_input(true)
+                        .build();
+                }
+            }
+        }
+        if let Some(error) = &entry.error {
+            ui.child_window("##error").build(|| {
+                ui.text_colored([0.9, 0.3, 0.3, 1.0], error);
+            });
+        }
+    }
+}
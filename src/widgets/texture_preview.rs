@@ -0,0 +1,149 @@
+use crate::{CubemapView, Renderer, TextureSource};
+use bugsyth_engine::glium::texture::{CubeLayer, Cubemap, Texture2dArray};
+use bugsyth_engine::glium::uniforms::SamplerBehavior;
+use imgui::{TextureId, Ui};
+use std::rc::Rc;
+
+/// Faces in the order of [`CubemapPreview`]'s IDs, with their place in the cross in face sizes
+const CROSS: [(CubeLayer, &str, [f32; 2]); 6] = [
+    (CubeLayer::PositiveX, "+X", [2.0, 1.0]),
+    (CubeLayer::NegativeX, "-X", [0.0, 1.0]),
+    (CubeLayer::PositiveY, "+Y", [1.0, 0.0]),
+    (CubeLayer::NegativeY, "-Y", [1.0, 2.0]),
+    (CubeLayer::PositiveZ, "+Z", [1.0, 1.0]),
+    (CubeLayer::NegativeZ, "-Z", [3.0, 1.0]),
+];
+
+/// Preview of a cube map, e.g. an environment map, as an unfolded cross or a mirror ball.
+///
+/// Every face and the sphere are registered with the renderer when the preview is created,
+/// call [`unregister`](Self::unregister) before dropping it.
+#[derive(Debug)]
+pub struct CubemapPreview {
+    faces: [TextureId; 6],
+    sphere: TextureId,
+    pub show_sphere: bool,
+}
+
+impl CubemapPreview {
+    pub fn new(renderer: &mut Renderer, texture: Rc<Cubemap>, sampler: SamplerBehavior) -> Self {
+        let mut register = |view: CubemapView| {
+            renderer.register_texture_source(
+                TextureSource::Cubemap {
+                    texture: Rc::clone(&texture),
+                    view,
+                },
+                sampler,
+            )
+        };
+        Self {
+            faces: CROSS.map(|(face, _, _)| register(CubemapView::Face(face))),
+            sphere: register(CubemapView::Sphere),
+            show_sphere: false,
+        }
+    }
+    pub fn unregister(self, renderer: &mut Renderer) {
+        for id in self.faces.into_iter().chain([self.sphere]) {
+            renderer.unregister_texture(id);
+        }
+    }
+    /// ID of one face, to draw it with `ui.image()`
+    pub fn face(&self, face: CubeLayer) -> TextureId {
+        let index = CROSS
+            .iter()
+            .position(|&(layer, _, _)| layer == face)
+            .expect("every face is in the cross");
+        self.faces[index]
+    }
+
+    /// Draws the cross or the sphere `width` wide, with a toggle between them. Hovering a face
+    /// names it.
+    pub fn draw(&mut self, ui: &Ui, width: f32) {
+        let _id = ui.push_id_ptr(self);
+        ui.checkbox("Sphere", &mut self.show_sphere);
+        if self.show_sphere {
+            let size = (width * 0.5).max(16.0);
+            imgui::Image::new(self.sphere, [size, size]).build(ui);
+            return;
+        }
+        let face_size = (width / 4.0).max(4.0);
+        let origin = ui.cursor_screen_pos();
+        ui.dummy([face_size * 4.0, face_size * 3.0]);
+        let hovered = ui.is_item_hovered();
+        let mouse = ui.io().mouse_pos;
+        let draw_list = ui.get_window_draw_list();
+        for (&id, (_, name, [column, row])) in self.faces.iter().zip(CROSS) {
+            let min = [origin[0] + column * face_size, origin[1] + row * face_size];
+            let max = [min[0] + face_size, min[1] + face_size];
+            draw_list.add_image(id, min, max).build();
+            if hovered
+                && (min[0]..max[0]).contains(&mouse[0])
+                && (min[1]..max[1]).contains(&mouse[1])
+            {
+                ui.tooltip_text(name);
+            }
+        }
+    }
+}
+
+/// Preview of an array texture with a layer slider, e.g. for shadow cascades.
+///
+/// Every layer is registered with the renderer when the preview is created, call
+/// [`unregister`](Self::unregister) before dropping it.
+#[derive(Debug)]
+pub struct ArrayPreview {
+    layers: Vec<TextureId>,
+    pub layer: u32,
+    height_per_width: f32,
+}
+
+impl ArrayPreview {
+    pub fn new(
+        renderer: &mut Renderer,
+        texture: Rc<Texture2dArray>,
+        sampler: SamplerBehavior,
+    ) -> Self {
+        let height_per_width = texture.height() as f32 / texture.width().max(1) as f32;
+        let layers = (0..texture.array_size())
+            .map(|layer| {
+                renderer.register_texture_source(
+                    TextureSource::Array {
+                        texture: Rc::clone(&texture),
+                        layer,
+                    },
+                    sampler,
+                )
+            })
+            .collect();
+        Self {
+            layers,
+            layer: 0,
+            height_per_width,
+        }
+    }
+    pub fn unregister(self, renderer: &mut Renderer) {
+        for id in self.layers {
+            renderer.unregister_texture(id);
+        }
+    }
+    /// ID of one layer, to draw it with `ui.image()`
+    pub fn layer_id(&self, layer: u32) -> Option<TextureId> {
+        self.layers.get(layer as usize).copied()
+    }
+
+    /// Draws the current layer `width` wide with a slider to pick it
+    pub fn draw(&mut self, ui: &Ui, width: f32) {
+        let _id = ui.push_id_ptr(self);
+        let Some(last) = self.layers.len().checked_sub(1) else {
+            ui.text_disabled("No layers");
+            return;
+        };
+        ui.slider("Layer", 0, last as u32, &mut self.layer);
+        self.layer = self.layer.min(last as u32);
+        imgui::Image::new(
+            self.layers[self.layer as usize],
+            [width, width * self.height_per_width],
+        )
+        .build(ui);
+    }
+}
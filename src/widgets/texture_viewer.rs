@@ -0,0 +1,172 @@
+use crate::debug::InspectChannel;
+use crate::imgui_glium_renderer::{set_image_view, ImageView};
+use crate::{Renderer, TextureSource};
+use bugsyth_engine::glium::{Rect, Texture2d};
+use imgui::{SliderFlags, TextureId, Ui};
+use std::rc::Rc;
+
+/// Zoom range of the slider and Ctrl + mouse wheel, in screen pixels per texel of the base level
+const ZOOM_RANGE: (f32, f32) = (0.125, 32.0);
+
+/// State of a [`texture_viewer`]: zoom, isolated channel, mip level and the texel last read back
+/// under the mouse.
+#[derive(Debug)]
+pub struct TextureViewer {
+    texture_id: TextureId,
+    texture: Rc<Texture2d>,
+    pub zoom: f32,
+    pub channel: InspectChannel,
+    pub mip: u32,
+    /// Shows the last row on top, the way render targets are laid out
+    pub flip_y: bool,
+    /// Mip level, x and y of the texel in `value`
+    hovered: Option<([u32; 3], [f32; 4])>,
+}
+
+impl TextureViewer {
+    /// Views the texture registered as `texture_id`, e.g. a shadow map or a G-buffer target.
+    /// `None` unless it was registered as a [`TextureSource::Texture2d`].
+    pub fn new(renderer: &mut Renderer, texture_id: TextureId) -> Option<Self> {
+        let TextureSource::Texture2d(texture) = &renderer.textures().get(texture_id)?.source else {
+            return None;
+        };
+        Some(Self {
+            texture_id,
+            texture: Rc::clone(texture),
+            zoom: 1.0,
+            channel: InspectChannel::Rgb,
+            mip: 0,
+            flip_y: false,
+            hovered: None,
+        })
+    }
+    pub fn texture_id(&self) -> TextureId {
+        self.texture_id
+    }
+
+    /// RGBA of texel `x`, `y` of `mip`, counted from the bottom left like GL does. Reads the
+    /// texture back, which waits for the GPU.
+    fn read_texel(&self, mip: u32, x: u32, y: u32) -> Option<[f32; 4]> {
+        let rect = Rect {
+            left: x,
+            bottom: y,
+            width: 1,
+            height: 1,
+        };
+        let rows: Vec<Vec<(f32, f32, f32, f32)>> = self
+            .texture
+            .mipmap(mip)?
+            .first_layer()
+            .into_image(None)?
+            .raw_read(&rect);
+        let &(r, g, b, a) = rows.first()?.first()?;
+        Some([r, g, b, a])
+    }
+}
+
+/// Zoomable view of a texture with the channels shown one at a time as grey, a mip level picker
+/// and the texel under the mouse in a tooltip. Ctrl + mouse wheel over the image zooms.
+///
+/// The channel and mip level are applied when the renderer draws the image, so the texture
+/// doesn't have to be copied. Mip selection needs GLSL 1.30 or GLSL ES 3.00.
+pub fn texture_viewer(ui: &Ui, viewer: &mut TextureViewer) {
+    let _id = ui.push_id_ptr(viewer);
+    let levels = viewer.texture.get_mipmap_levels();
+    viewer.mip = viewer.mip.min(levels.saturating_sub(1));
+
+    ui.slider_config("Zoom", ZOOM_RANGE.0, ZOOM_RANGE.1)
+        .flags(SliderFlags::LOGARITHMIC)
+        .display_format("%.2fx")
+        .build(&mut viewer.zoom);
+    for (index, channel) in InspectChannel::ALL.into_iter().enumerate() {
+        if index > 0 {
+            ui.same_line();
+        }
+        ui.radio_button(channel.name(), &mut viewer.channel, channel);
+    }
+    ui.same_line();
+    ui.checkbox("Flip Y", &mut viewer.flip_y);
+    if levels > 1 {
+        ui.slider("Mip", 0, levels - 1, &mut viewer.mip);
+    }
+
+    let mip_width = (viewer.texture.width() >> viewer.mip).max(1);
+    let mip_height = (viewer.texture.height() >> viewer.mip).max(1);
+    ui.text_disabled(format!(
+        "{}x{} of {}x{}, {} mip levels",
+        mip_width,
+        mip_height,
+        viewer.texture.width(),
+        viewer.texture.height(),
+        levels
+    ));
+
+    ui.child_window("##texture_viewer")
+        .horizontal_scrollbar(true)
+        .build(|| {
+            let size = [
+                viewer.texture.width() as f32 * viewer.zoom,
+                viewer.texture.height() as f32 * viewer.zoom,
+            ];
+            let min = ui.cursor_screen_pos();
+            let max = [min[0] + size[0], min[1] + size[1]];
+            ui.invisible_button("##image", size);
+            let hovered = ui.is_item_hovered();
+            if hovered && ui.io().key_ctrl && ui.io().mouse_wheel != 0.0 {
+                viewer.zoom = (viewer.zoom * 1.25f32.powf(ui.io().mouse_wheel))
+                    .clamp(ZOOM_RANGE.0, ZOOM_RANGE.1);
+            }
+
+            let (uv0, uv1) = if viewer.flip_y {
+                ([0.0, 1.0], [1.0, 0.0])
+            } else {
+                ([0.0, 0.0], [1.0, 1.0])
+            };
+            let view = ImageView {
+                channel: match viewer.channel {
+                    InspectChannel::Rgb => 0,
+                    InspectChannel::Red => 1,
+                    InspectChannel::Green => 2,
+                    InspectChannel::Blue => 3,
+                    InspectChannel::Alpha => 4,
+                },
+                mip: (levels > 1).then_some(viewer.mip),
+                depth_range: [0.0, 1.0],
+            };
+            let draw_list = ui.get_window_draw_list();
+            set_image_view(&draw_list, Some(view));
+            draw_list
+                .add_image(viewer.texture_id, min, max)
+                .uv_min(uv0)
+                .uv_max(uv1)
+                .build();
+            set_image_view(&draw_list, None);
+            drop(draw_list);
+
+            if !hovered {
+                return;
+            }
+            let mouse = ui.io().mouse_pos;
+            let column = ((mouse[0] - min[0]) / size[0] * mip_width as f32) as u32;
+            let row = ((mouse[1] - min[1]) / size[1] * mip_height as f32) as u32;
+            let x = column.min(mip_width - 1);
+            let mut y = row.min(mip_height - 1);
+            if viewer.flip_y {
+                y = mip_height - 1 - y;
+            }
+            let key = [viewer.mip, x, y];
+            if viewer.hovered.map(|(hovered, _)| hovered) != Some(key) {
+                viewer.hovered = viewer
+                    .read_texel(viewer.mip, x, y)
+                    .map(|value| (key, value));
+            }
+            ui.tooltip(|| {
+                ui.text(format!("Texel {}, {} of mip {}", x, y, viewer.mip));
+                if let Some((_, [r, g, b, a])) = viewer.hovered {
+                    ui.color_button("##texel", [r, g, b, a]);
+                    ui.same_line();
+                    ui.text(format!("R {:.4}\nG {:.4}\nB {:.4}\nA {:.4}", r, g, b, a));
+                }
+            });
+        });
+}
@@ -0,0 +1,98 @@
+use imgui::{TreeNodeFlags, Ui};
+use serde_json::{Number, Value};
+
+/// Tree editor for any serde value, turned into a [`Value`] with `serde_json::to_value`.
+///
+/// Objects and arrays are tree nodes, array items can be removed with "-" and appended with "+"
+/// as a copy of the last one. The types of existing values can't be changed, deserializing the
+/// result back tells if the edits still fit the original type. Returns `true` when something
+/// changed.
+pub fn value_editor(ui: &Ui, label: &str, value: &mut Value) -> bool {
+    let _id = ui.push_id(label);
+    edit(ui, label, value)
+}
+
+fn edit(ui: &Ui, label: &str, value: &mut Value) -> bool {
+    match value {
+        Value::Null => {
+            ui.text_disabled(format!("{}: null", label));
+            false
+        }
+        Value::Bool(value) => ui.checkbox(label, value),
+        Value::Number(number) => edit_number(ui, label, number),
+        Value::String(text) => ui.input_text(label, text).build(),
+        Value::Array(items) => {
+            let Some(_node) = container(ui, label, format!("[{}]", items.len())) else {
+                return false;
+            };
+            let mut changed = false;
+            let mut remove = None;
+            for (index, item) in items.iter_mut().enumerate() {
+                let _id = ui.push_id_usize(index);
+                if ui.small_button("-") {
+                    remove = Some(index);
+                }
+                ui.same_line();
+                changed |= edit(ui, &index.to_string(), item);
+            }
+            if let Some(index) = remove {
+                items.remove(index);
+                changed = true;
+            }
+            if ui.small_button("+") {
+                items.push(items.last().cloned().unwrap_or(Value::Null));
+                changed = true;
+            }
+            changed
+        }
+        Value::Object(fields) => {
+            let Some(_node) = container(ui, label, format!("{{{}}}", fields.len())) else {
+                return false;
+            };
+            let mut changed = false;
+            for (key, field) in fields.iter_mut() {
+                let _id = ui.push_id(key);
+                changed |= edit(ui, key, field);
+            }
+            changed
+        }
+    }
+}
+
+/// Tree node of an object or array, with the size after the label
+fn container<'ui>(ui: &'ui Ui, label: &str, size: String) -> Option<imgui::TreeNodeToken<'ui>> {
+    // The ID ignores the size, so the node stays open while items are added
+    ui.tree_node_config(format!("{} {}###{}", label, size, label))
+        .flags(TreeNodeFlags::SPAN_AVAIL_WIDTH)
+        .push()
+}
+
+/// Integers stay integers, so values deserialize back into integer fields. Only those past
+/// `i64::MAX` are edited unsigned.
+fn edit_number(ui: &Ui, label: &str, number: &mut Number) -> bool {
+    if let Some(mut value) = number.as_i64() {
+        if ui.input_scalar(label, &mut value).build() {
+            *number = value.into();
+            return true;
+        }
+    } else if let Some(mut value) = number.as_u64() {
+        if ui.input_scalar(label, &mut value).build() {
+            *number = value.into();
+            return true;
+        }
+    } else {
+        let mut value = number.as_f64().unwrap_or_default();
+        if ui
+            .input_scalar(label, &mut value)
+            .display_format("%.6g")
+            .build()
+        {
+            // NaN and infinity have no JSON number
+            if let Some(value) = Number::from_f64(value) {
+                *number = value;
+                return true;
+            }
+        }
+    }
+    false
+}
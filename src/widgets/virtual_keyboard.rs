@@ -0,0 +1,246 @@
+use crate::accessibility;
+use imgui::{Io, Key, Ui};
+use std::time::{Duration, Instant};
+
+/// How long a pressed key stays highlighted, keys don't flash with reduced motion
+const PRESS_HIGHLIGHT: Duration = Duration::from_millis(120);
+
+/// Colors and sizes of the [`VirtualKeyboard`]
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct KeyboardTheme {
+    pub background: [f32; 4],
+    pub key: [f32; 4],
+    pub key_pressed: [f32; 4],
+    /// Shift while it's on
+    pub key_active: [f32; 4],
+    pub text: [f32; 4],
+    /// Keyboard height as a fraction of the display height
+    pub height: f32,
+    pub key_spacing: f32,
+    pub key_rounding: f32,
+}
+
+impl Default for KeyboardTheme {
+    fn default() -> Self {
+        Self {
+            background: [0.08, 0.08, 0.1, 0.94],
+            key: [0.22, 0.24, 0.28, 1.0],
+            key_pressed: [0.26, 0.59, 0.98, 1.0],
+            key_active: [0.2, 0.4, 0.7, 1.0],
+            text: [1.0, 1.0, 1.0, 1.0],
+            height: 0.35,
+            key_spacing: 4.0,
+            key_rounding: 4.0,
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum VirtualKey {
+    /// Character without and with shift
+    Char(char, char),
+    Shift,
+    Backspace,
+    Space,
+    Enter,
+    Left,
+    Right,
+}
+
+impl VirtualKey {
+    fn label(self, shift: bool) -> String {
+        match self {
+            VirtualKey::Char(lower, upper) => if shift { upper } else { lower }.to_string(),
+            VirtualKey::Shift => "Shift".into(),
+            VirtualKey::Backspace => "<-".into(),
+            VirtualKey::Space => "Space".into(),
+            VirtualKey::Enter => "Enter".into(),
+            VirtualKey::Left => "<".into(),
+            VirtualKey::Right => ">".into(),
+        }
+    }
+    /// Width relative to a character key
+    fn width(self) -> f32 {
+        match self {
+            VirtualKey::Char(..) | VirtualKey::Left | VirtualKey::Right => 1.0,
+            VirtualKey::Shift | VirtualKey::Backspace | VirtualKey::Enter => 1.5,
+            VirtualKey::Space => 5.0,
+        }
+    }
+}
+
+fn layout() -> Vec<Vec<VirtualKey>> {
+    let chars = |lower: &str, upper: &str| -> Vec<VirtualKey> {
+        lower
+            .chars()
+            .zip(upper.chars())
+            .map(|(lower, upper)| VirtualKey::Char(lower, upper))
+            .collect()
+    };
+    let mut third = vec![VirtualKey::Shift];
+    third.extend(chars("zxcvbnm", "ZXCVBNM"));
+    third.push(VirtualKey::Backspace);
+    let mut fourth = chars(",", "<");
+    fourth.extend([VirtualKey::Left, VirtualKey::Space, VirtualKey::Right]);
+    fourth.extend(chars(".", ">"));
+    fourth.push(VirtualKey::Enter);
+    vec![
+        chars("1234567890", "!@#$%^&*()"),
+        chars("qwertyuiop", "QWERTYUIOP"),
+        chars("asdfghjkl-", "ASDFGHJKL_"),
+        third,
+        fourth,
+    ]
+}
+
+/// On-screen keyboard for touch and controller-only setups.
+///
+/// Shows itself at the bottom of the screen while imgui wants text input. Set it as
+/// [`ImGui::virtual_keyboard`](crate::ImGui::virtual_keyboard) and [`ImGui::event`] feeds
+/// presses on it to the focused text field instead of letting them reach imgui, which would
+/// deactivate the field.
+///
+/// [`ImGui::event`]: crate::ImGui::event
+#[derive(Debug)]
+pub struct VirtualKeyboard {
+    /// Show the keyboard when a text field is active, on by default
+    pub enabled: bool,
+    pub theme: KeyboardTheme,
+    layout: Vec<Vec<VirtualKey>>,
+    shift: bool,
+    /// Keys as drawn in the last frame, empty while hidden
+    keys: Vec<(VirtualKey, [f32; 2], [f32; 2])>,
+    /// Keyboard rect of the last frame
+    rect: Option<([f32; 2], [f32; 2])>,
+    pressed: Option<(VirtualKey, Instant)>,
+}
+
+impl Default for VirtualKeyboard {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            theme: KeyboardTheme::default(),
+            layout: layout(),
+            shift: false,
+            keys: Vec::new(),
+            rect: None,
+            pressed: None,
+        }
+    }
+}
+
+impl VirtualKeyboard {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    pub fn with_theme(mut self, theme: KeyboardTheme) -> Self {
+        self.theme = theme;
+        self
+    }
+    /// Whether the keyboard was drawn in the last frame
+    pub fn is_visible(&self) -> bool {
+        self.rect.is_some()
+    }
+
+    /// Draws the keyboard while imgui wants text input, [`ImGui::get_ui`](crate::ImGui::get_ui)
+    /// calls this when it's set on [`ImGui`](crate::ImGui)
+    pub fn draw(&mut self, ui: &Ui) {
+        self.keys.clear();
+        if !self.enabled || !ui.io().want_text_input {
+            self.rect = None;
+            self.shift = false;
+            return;
+        }
+        let theme = self.theme;
+        let display_size = ui.io().display_size;
+        let height = (display_size[1] * theme.height).round();
+        let min = [0.0, display_size[1] - height];
+        let max = display_size;
+        self.rect = Some((min, max));
+
+        let draw_list = ui.get_foreground_draw_list();
+        draw_list
+            .add_rect(min, max, theme.background)
+            .filled(true)
+            .build();
+        let spacing = theme.key_spacing;
+        let row_height = (height - spacing) / self.layout.len() as f32;
+        let pressed = self
+            .pressed
+            .filter(|(_, at)| accessibility::flashing(at.elapsed(), PRESS_HIGHLIGHT))
+            .map(|(key, _)| key);
+        for (row_index, row) in self.layout.iter().enumerate() {
+            let units: f32 = row.iter().map(|key| key.width()).sum();
+            let unit = (display_size[0] - spacing) / units.max(10.0);
+            // Short rows are centered
+            let mut x = (display_size[0] - unit * units) * 0.5 + spacing;
+            let y = min[1] + spacing + row_index as f32 * row_height;
+            for &key in row {
+                let key_min = [x, y];
+                let key_max = [x + unit * key.width() - spacing, y + row_height - spacing];
+                x += unit * key.width();
+                let color = if pressed == Some(key) {
+                    theme.key_pressed
+                } else if key == VirtualKey::Shift && self.shift {
+                    theme.key_active
+                } else {
+                    theme.key
+                };
+                draw_list
+                    .add_rect(key_min, key_max, color)
+                    .filled(true)
+                    .rounding(theme.key_rounding)
+                    .build();
+                let label = key.label(self.shift);
+                let text_size = ui.calc_text_size(&label);
+                draw_list.add_text(
+                    [
+                        (key_min[0] + key_max[0] - text_size[0]) * 0.5,
+                        (key_min[1] + key_max[1] - text_size[1]) * 0.5,
+                    ],
+                    theme.text,
+                    label,
+                );
+                self.keys.push((key, key_min, key_max));
+            }
+        }
+    }
+
+    /// Whether `pos` in UI coordinates is on the keyboard
+    pub fn contains(&self, pos: [f32; 2]) -> bool {
+        self.rect.is_some_and(|(min, max)| {
+            pos[0] >= min[0] && pos[1] >= min[1] && pos[0] < max[0] && pos[1] < max[1]
+        })
+    }
+
+    /// Handles a press at `pos`, returns `true` when it hit the keyboard so it shouldn't reach
+    /// imgui
+    pub fn press(&mut self, io: &mut Io, pos: [f32; 2]) -> bool {
+        if !self.contains(pos) {
+            return false;
+        }
+        let Some(&(key, _, _)) = self.keys.iter().find(|(_, min, max)| {
+            pos[0] >= min[0] && pos[1] >= min[1] && pos[0] < max[0] && pos[1] < max[1]
+        }) else {
+            return true;
+        };
+        self.pressed = Some((key, Instant::now()));
+        let tap = |io: &mut Io, key: Key| {
+            io.add_key_event(key, true);
+            io.add_key_event(key, false);
+        };
+        match key {
+            VirtualKey::Char(lower, upper) => {
+                io.add_input_character(if self.shift { upper } else { lower });
+                self.shift = false;
+            }
+            VirtualKey::Shift => self.shift = !self.shift,
+            VirtualKey::Backspace => tap(io, Key::Backspace),
+            VirtualKey::Space => io.add_input_character(' '),
+            VirtualKey::Enter => tap(io, Key::Enter),
+            VirtualKey::Left => tap(io, Key::LeftArrow),
+            VirtualKey::Right => tap(io, Key::RightArrow),
+        }
+        true
+    }
+}